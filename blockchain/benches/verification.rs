@@ -0,0 +1,94 @@
+//! Vérification d'un bloc de 100 transactions
+//!
+//! Compare la vérification une à une (avec l'ancien recalcul du hash par
+//! clonage) à la vérification par lot de `Block::verify`. Avec
+//! `--features parallel`, `Block::verify` répartit le travail sur les cœurs.
+//!
+//! ```text
+//! cargo bench -p mahala-blockchain --bench verification
+//! cargo bench -p mahala-blockchain --bench verification --features parallel
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use mahala_blockchain::crypto::{hash_data, sign, verify_signature, KeyPair};
+use mahala_blockchain::storage::merkle::MerkleTree;
+use mahala_blockchain::transaction::TransactionMetadata;
+use mahala_blockchain::{Block, Transaction};
+
+const TRANSACTIONS: usize = 100;
+const VALIDATORS: usize = 10;
+
+fn signed_block() -> (Block, mahala_blockchain::Hash) {
+    let producer = KeyPair::new();
+    let previous_hash = hash_data(b"previous");
+    let transactions = (0..TRANSACTIONS)
+        .map(|i| {
+            let sender = KeyPair::new();
+            let mut tx = Transaction::new(
+                *sender.public_key(),
+                *producer.public_key(),
+                1.0 + i as f64,
+                0.01,
+                TransactionMetadata::default(),
+            );
+            tx.sign(sender.private_key()).unwrap();
+            tx.hash = None; // comme un bloc reçu du réseau
+            tx
+        })
+        .collect();
+
+    let mut block = Block::new(1, previous_hash, transactions, *producer.public_key());
+    let block_hash = block.calculate_hash();
+    let commit_hash = block.commit_signing_hash(&block_hash);
+    for _ in 0..VALIDATORS {
+        let validator = KeyPair::new();
+        block.add_validator_signature(*validator.public_key(), sign(&commit_hash, validator.private_key()), [0u8; 80]);
+    }
+    (block, previous_hash)
+}
+
+/// Ancienne vérification: signatures une à une, hash recalculé sur une copie
+/// pour chaque signature puis pour la racine de Merkle
+fn verify_one_by_one_with_clones(block: &Block) -> bool {
+    let commit_hash = block.commit_signing_hash(&block.get_hash().unwrap());
+    let signatures_ok = block.transactions.iter().all(|tx| {
+        let mut copy = tx.clone();
+        copy.signature = None;
+        let hash = copy.calculate_hash();
+        tx.signature_bytes().is_some_and(|signature| verify_signature(&hash, &signature, &tx.from))
+    });
+    let leaves = block.transactions.iter().map(|tx| tx.clone().calculate_hash()).collect();
+    let validators_ok = block.validator_signatures
+        .iter()
+        .all(|vs| verify_signature(&commit_hash, &vs.signature, &vs.validator));
+
+    signatures_ok && validators_ok && MerkleTree::from_hashes(leaves).root() == block.header.merkle_root
+}
+
+fn bench_block_verification(c: &mut Criterion) {
+    let (block, previous_hash) = signed_block();
+    assert!(verify_one_by_one_with_clones(&block));
+    assert!(block.is_valid(Some(previous_hash)));
+
+    let mut group = c.benchmark_group("block_100_tx");
+    group.bench_function("one_by_one_with_clones", |b| {
+        b.iter(|| verify_one_by_one_with_clones(black_box(&block)))
+    });
+    group.bench_function("one_by_one", |b| {
+        b.iter(|| {
+            let block = black_box(&block);
+            let commit_hash = block.commit_signing_hash(&block.get_hash().unwrap());
+            block.transactions.iter().all(Transaction::is_valid)
+                && block.validator_signatures
+                    .iter()
+                    .all(|vs| verify_signature(&commit_hash, &vs.signature, &vs.validator))
+        })
+    });
+    group.bench_function("batch", |b| {
+        b.iter(|| black_box(&block).verify(Some(previous_hash)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_block_verification);
+criterion_main!(benches);
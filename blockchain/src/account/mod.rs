@@ -0,0 +1,192 @@
+//! Comptes: identité stable et clé de contrôle
+//!
+//! L'identité d'un compte est l'adresse sous laquelle il a été créé (sa
+//! première clé publique). Tout l'état (balance, NFTs, séquestres, ordres,
+//! canaux, validateur) reste rattaché à cette identité, tandis que la clé
+//! autorisée à signer pour le compte peut changer par rotation (voir
+//! `rotation`), y compris sur décision de gardiens si la clé est perdue
+//! (voir `recovery`). Les fonds envoyés à une ancienne clé ou à la clé
+//! courante arrivent sur le compte; seules les signatures de la clé courante
+//! sont acceptées.
+
+pub mod recovery;
+pub mod rotation;
+
+pub use recovery::{RecoveryApproval, RecoveryConfig, RecoveryOperation, MAX_GUARDIANS};
+pub use rotation::{
+    KeyRotation, KeyRotationOperation, PendingDelay, PendingRotation, MAX_ROTATION_DELAY_SECS,
+};
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::PublicKey;
+use crate::storage::snapshot::sorted_map;
+
+/// Compte dont la clé ou la politique diffère des valeurs par défaut
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    /// Identité du compte (adresse de création)
+    pub id: PublicKey,
+    /// Clé autorisée à signer pour le compte
+    pub controller: PublicKey,
+    /// Nombre de transactions émises par le compte
+    pub nonce: u64,
+    /// Délai imposé aux rotations (0: immédiate)
+    pub rotation_delay_secs: i64,
+    /// Rotation en attente (annulable par la clé courante)
+    pub pending_rotation: Option<PendingRotation>,
+    /// Réduction du délai en attente
+    pub pending_delay: Option<PendingDelay>,
+    /// Configuration de récupération sociale
+    #[serde(default)]
+    pub recovery: Option<RecoveryConfig>,
+    /// Approbations des gardiens en cours de collecte
+    #[serde(default)]
+    pub recovery_approvals: Vec<RecoveryApproval>,
+    /// Récupération approuvée, en attente d'exécution (annulable par la clé courante)
+    #[serde(default)]
+    pub scheduled_recovery: Option<PendingRotation>,
+}
+
+impl Account {
+    fn new(id: PublicKey) -> Self {
+        Self {
+            id,
+            controller: id,
+            nonce: 0,
+            rotation_delay_secs: 0,
+            pending_rotation: None,
+            pending_delay: None,
+            recovery: None,
+            recovery_approvals: Vec::new(),
+            scheduled_recovery: None,
+        }
+    }
+}
+
+/// Erreurs de compte
+#[derive(Debug, Error)]
+pub enum AccountError {
+    #[error("Key is no longer authorized for this account")]
+    RetiredKey,
+    #[error("Key is already bound to an account")]
+    KeyInUse,
+    #[error("New key must differ from the current key")]
+    SameKey,
+    #[error("Invalid proof of possession of the new key")]
+    InvalidProof,
+    #[error("A key rotation is already pending")]
+    RotationPending,
+    #[error("No pending key rotation")]
+    NoPendingRotation,
+    #[error("Invalid rotation delay")]
+    InvalidDelay,
+    #[error("Invalid recovery configuration: {0}")]
+    InvalidRecoveryConfig(String),
+    #[error("Account has no recovery configuration")]
+    NoRecoveryConfig,
+    #[error("Signer is not a guardian of this account")]
+    NotGuardian,
+    #[error("A recovery is already scheduled")]
+    RecoveryPending,
+    #[error("No recovery in progress")]
+    NoPendingRecovery,
+}
+
+/// Registre des comptes (clés de contrôle, nonces, rotations)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccountRegistry {
+    #[serde(with = "sorted_map")]
+    accounts: HashMap<PublicKey, Account>,
+    /// Clés liées par rotation (nouvelles clés et clés retirées) -> compte
+    #[serde(with = "sorted_map")]
+    key_index: HashMap<PublicKey, PublicKey>,
+    /// Historique des rotations effectuées
+    rotations: Vec<KeyRotation>,
+}
+
+impl AccountRegistry {
+    /// Créer un registre vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Identité du compte désigné par une adresse (clé courante, ancienne
+    /// clé ou identité elle-même)
+    pub fn resolve(&self, address: &PublicKey) -> PublicKey {
+        self.key_index.get(address).copied().unwrap_or(*address)
+    }
+
+    /// Clé de contrôle courante d'un compte
+    pub fn controller(&self, account: &PublicKey) -> PublicKey {
+        self.accounts.get(account).map_or(*account, |a| a.controller)
+    }
+
+    /// Compte pour lequel une clé peut signer
+    pub fn authorize(&self, signer: &PublicKey) -> Result<PublicKey, AccountError> {
+        let account = self.resolve(signer);
+        if &self.controller(&account) != signer {
+            return Err(AccountError::RetiredKey);
+        }
+        Ok(account)
+    }
+
+    /// La clé a-t-elle déjà été liée à un compte (identité, clé courante ou retirée) ?
+    pub fn is_bound(&self, key: &PublicKey) -> bool {
+        self.key_index.contains_key(key) || self.accounts.contains_key(key)
+    }
+
+    /// Compter une transaction émise par le compte
+    pub fn record_transaction(&mut self, account: &PublicKey) {
+        self.entry(account).nonce += 1;
+    }
+
+    /// Remettre un compte dans un état antérieur (annulation d'un bloc refusé)
+    pub(crate) fn restore(&mut self, id: PublicKey, account: Option<Account>) {
+        match account {
+            Some(account) => self.accounts.insert(id, account),
+            None => self.accounts.remove(&id),
+        };
+    }
+
+    /// Nonce du compte (nombre de transactions émises)
+    pub fn nonce(&self, account: &PublicKey) -> u64 {
+        self.accounts.get(account).map_or(0, |a| a.nonce)
+    }
+
+    /// Obtenir un compte (absent s'il n'a jamais émis ni changé de clé)
+    pub fn get(&self, account: &PublicKey) -> Option<&Account> {
+        self.accounts.get(account)
+    }
+
+    /// Rotations d'un compte, de la plus ancienne à la plus récente
+    pub fn rotations_of(&self, account: &PublicKey) -> Vec<&KeyRotation> {
+        self.rotations.iter().filter(|r| &r.account == account).collect()
+    }
+
+    /// Rotations effectuées à une hauteur de bloc
+    pub fn rotations_at(&self, block_height: u64) -> impl Iterator<Item = &KeyRotation> {
+        self.rotations.iter().filter(move |r| r.block_height == block_height)
+    }
+
+    fn entry(&mut self, account: &PublicKey) -> &mut Account {
+        self.accounts.entry(*account).or_insert_with(|| Account::new(*account))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unrotated_account_resolves_to_itself() {
+        let registry = AccountRegistry::new();
+        let key = [1u8; 32];
+        assert_eq!(registry.resolve(&key), key);
+        assert_eq!(registry.controller(&key), key);
+        assert_eq!(registry.authorize(&key).unwrap(), key);
+        assert_eq!(registry.nonce(&key), 0);
+        assert!(!registry.is_bound(&key));
+    }
+}
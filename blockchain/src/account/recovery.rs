@@ -0,0 +1,282 @@
+//! Récupération sociale d'un compte
+//!
+//! Le titulaire désigne des gardiens (d'autres comptes) et un seuil. Si la
+//! clé de contrôle est perdue, chaque gardien approuve, par une transaction
+//! qu'il signe, la nouvelle clé présentée par le titulaire (avec la même
+//! preuve de possession qu'une rotation). Quand le seuil est atteint pour une
+//! même clé, la récupération est programmée: elle s'exécute après le délai
+//! d'attente de la configuration, sauf si la clé courante l'annule entre-temps
+//! (appareil retrouvé, gardiens malveillants).
+
+use serde::{Deserialize, Serialize};
+use crate::{PublicKey, Timestamp};
+use crate::account::{AccountError, AccountRegistry, PendingRotation, MAX_ROTATION_DELAY_SECS};
+use crate::account::rotation::{rotation_signing_hash, verify_rotation_proof};
+use crate::crypto::keys::KeyPair;
+use crate::crypto::sign;
+
+/// Nombre maximal de gardiens
+pub const MAX_GUARDIANS: usize = 10;
+
+/// Délai d'attente minimal d'une récupération (1 jour)
+pub const MIN_RECOVERY_DELAY_SECS: i64 = 24 * 3600;
+
+/// Configuration de récupération d'un compte
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecoveryConfig {
+    /// Comptes gardiens
+    #[serde(deserialize_with = "crate::wallet::address::deserialize_list")]
+    pub guardians: Vec<PublicKey>,
+    /// Nombre d'approbations requises
+    pub threshold: usize,
+    /// Délai entre l'atteinte du seuil et l'exécution
+    pub delay_secs: i64,
+}
+
+impl RecoveryConfig {
+    /// Vérifier la configuration pour un compte
+    pub fn validate(&self, account: &PublicKey) -> Result<(), AccountError> {
+        let invalid = |reason: &str| Err(AccountError::InvalidRecoveryConfig(reason.to_string()));
+
+        if self.guardians.is_empty() || self.guardians.len() > MAX_GUARDIANS {
+            return invalid("guardian count out of range");
+        }
+        if self.guardians.contains(account) {
+            return invalid("account cannot guard itself");
+        }
+        let mut unique = self.guardians.clone();
+        unique.sort();
+        unique.dedup();
+        if unique.len() != self.guardians.len() {
+            return invalid("duplicate guardian");
+        }
+        if self.threshold == 0 || self.threshold > self.guardians.len() {
+            return invalid("threshold out of range");
+        }
+        if !(MIN_RECOVERY_DELAY_SECS..=MAX_ROTATION_DELAY_SECS).contains(&self.delay_secs) {
+            return invalid("delay out of range");
+        }
+        Ok(())
+    }
+}
+
+/// Approbation d'un gardien
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RecoveryApproval {
+    /// Gardien
+    pub guardian: PublicKey,
+    /// Nouvelle clé approuvée
+    pub new_key: PublicKey,
+    /// Date de l'approbation
+    pub approved_at: Timestamp,
+}
+
+/// Opération portée par une transaction `Recovery`
+///
+/// `Configure`, `Disable` et `Cancel` sont signées par la clé courante
+/// (`from == to`); `Approve` est signée par un gardien avec le compte à
+/// récupérer en `to`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum RecoveryOperation {
+    /// Définir (ou remplacer) les gardiens, le seuil et le délai
+    Configure(RecoveryConfig),
+    /// Supprimer la configuration de récupération
+    Disable,
+    /// Approuver la récupération vers `new_key` (preuve de possession comme
+    /// pour une rotation)
+    Approve {
+        #[serde(deserialize_with = "crate::wallet::address::deserialize")]
+        new_key: PublicKey,
+        proof: String,
+    },
+    /// Annuler la récupération en cours (approbations comprises)
+    Cancel,
+}
+
+impl RecoveryOperation {
+    /// Construire l'approbation d'une nouvelle clé
+    ///
+    /// La preuve est produite par le titulaire sur son nouvel appareil puis
+    /// transmise aux gardiens.
+    pub fn approve(account: &PublicKey, new_keypair: &KeyPair) -> Self {
+        let new_key = *new_keypair.public_key();
+        let proof = sign(&rotation_signing_hash(account, &new_key), new_keypair.private_key());
+        Self::Approve { new_key, proof: hex::encode(proof) }
+    }
+}
+
+impl AccountRegistry {
+    /// Définir la configuration de récupération d'un compte
+    ///
+    /// Les gardiens sont rapportés à l'identité de leur compte. Une
+    /// récupération en cours est abandonnée.
+    pub fn configure_recovery(
+        &mut self,
+        account: &PublicKey,
+        mut config: RecoveryConfig,
+    ) -> Result<(), AccountError> {
+        for guardian in config.guardians.iter_mut() {
+            *guardian = self.resolve(guardian);
+        }
+        config.validate(account)?;
+
+        let entry = self.entry(account);
+        entry.recovery = Some(config);
+        entry.recovery_approvals.clear();
+        entry.scheduled_recovery = None;
+        Ok(())
+    }
+
+    /// Supprimer la configuration de récupération
+    pub fn disable_recovery(&mut self, account: &PublicKey) -> Result<(), AccountError> {
+        let entry = self.accounts.get_mut(account)
+            .filter(|a| a.recovery.is_some())
+            .ok_or(AccountError::NoRecoveryConfig)?;
+        entry.recovery = None;
+        entry.recovery_approvals.clear();
+        entry.scheduled_recovery = None;
+        Ok(())
+    }
+
+    /// Enregistrer l'approbation d'un gardien
+    ///
+    /// Un gardien peut changer d'avis en approuvant une autre clé. Retourne
+    /// la récupération programmée si le seuil est atteint.
+    pub fn approve_recovery(
+        &mut self,
+        account: &PublicKey,
+        guardian: &PublicKey,
+        new_key: PublicKey,
+        proof: &str,
+        now: Timestamp,
+    ) -> Result<Option<PendingRotation>, AccountError> {
+        let config = self.accounts.get(account)
+            .and_then(|a| a.recovery.clone())
+            .ok_or(AccountError::NoRecoveryConfig)?;
+        if !config.guardians.contains(guardian) {
+            return Err(AccountError::NotGuardian);
+        }
+        self.check_new_key(account, &new_key)?;
+        if !verify_rotation_proof(account, &new_key, proof) {
+            return Err(AccountError::InvalidProof);
+        }
+
+        let entry = self.entry(account);
+        if entry.scheduled_recovery.is_some() {
+            return Err(AccountError::RecoveryPending);
+        }
+
+        entry.recovery_approvals.retain(|a| &a.guardian != guardian);
+        entry.recovery_approvals.push(RecoveryApproval {
+            guardian: *guardian,
+            new_key,
+            approved_at: now,
+        });
+
+        let approvals = entry.recovery_approvals.iter().filter(|a| a.new_key == new_key).count();
+        if approvals < config.threshold {
+            return Ok(None);
+        }
+
+        let scheduled = PendingRotation {
+            new_key,
+            requested_at: now,
+            executes_at: now + config.delay_secs,
+        };
+        entry.recovery_approvals.clear();
+        entry.scheduled_recovery = Some(scheduled.clone());
+        Ok(Some(scheduled))
+    }
+
+    /// Annuler la récupération en cours (clé courante)
+    pub fn cancel_recovery(&mut self, account: &PublicKey) -> Result<(), AccountError> {
+        let entry = self.accounts.get_mut(account)
+            .filter(|a| a.scheduled_recovery.is_some() || !a.recovery_approvals.is_empty())
+            .ok_or(AccountError::NoPendingRecovery)?;
+        entry.recovery_approvals.clear();
+        entry.scheduled_recovery = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 3600;
+
+    fn registry_with_guardians(account: &PublicKey, guardians: &[PublicKey], threshold: usize) -> AccountRegistry {
+        let mut registry = AccountRegistry::new();
+        let config = RecoveryConfig { guardians: guardians.to_vec(), threshold, delay_secs: DAY };
+        registry.configure_recovery(account, config).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_guardians_recover_account_after_delay() {
+        let account = [1u8; 32];
+        let guardians = [[2u8; 32], [3u8; 32], [4u8; 32]];
+        let mut registry = registry_with_guardians(&account, &guardians, 2);
+        let new_device = KeyPair::new();
+        let RecoveryOperation::Approve { new_key, proof } = RecoveryOperation::approve(&account, &new_device) else {
+            unreachable!()
+        };
+
+        assert!(matches!(
+            registry.approve_recovery(&account, &[9u8; 32], new_key, &proof, 100),
+            Err(AccountError::NotGuardian)
+        ));
+        assert!(registry.approve_recovery(&account, &guardians[0], new_key, &proof, 100).unwrap().is_none());
+        // Une approbation répétée ne compte qu'une fois
+        assert!(registry.approve_recovery(&account, &guardians[0], new_key, &proof, 150).unwrap().is_none());
+        let scheduled = registry.approve_recovery(&account, &guardians[2], new_key, &proof, 200).unwrap().unwrap();
+        assert_eq!(scheduled.executes_at, 200 + DAY);
+
+        assert!(registry.execute_due(1, 200 + DAY - 1).is_empty());
+        let executed = registry.execute_due(2, 200 + DAY);
+        assert_eq!(executed.len(), 1);
+        assert_eq!(registry.controller(&account), new_key);
+        assert!(registry.get(&account).unwrap().scheduled_recovery.is_none());
+    }
+
+    #[test]
+    fn test_current_key_cancels_recovery() {
+        let account = [1u8; 32];
+        let guardians = [[2u8; 32], [3u8; 32]];
+        let mut registry = registry_with_guardians(&account, &guardians, 1);
+        let attacker = KeyPair::new();
+        let RecoveryOperation::Approve { new_key, proof } = RecoveryOperation::approve(&account, &attacker) else {
+            unreachable!()
+        };
+
+        registry.approve_recovery(&account, &guardians[1], new_key, &proof, 100).unwrap().unwrap();
+        assert!(matches!(
+            registry.approve_recovery(&account, &guardians[0], new_key, &proof, 110),
+            Err(AccountError::RecoveryPending)
+        ));
+        registry.cancel_recovery(&account).unwrap();
+        assert!(registry.execute_due(1, 100 + DAY).is_empty());
+        assert_eq!(registry.controller(&account), account);
+        assert!(matches!(registry.cancel_recovery(&account), Err(AccountError::NoPendingRecovery)));
+    }
+
+    #[test]
+    fn test_invalid_recovery_config() {
+        let account = [1u8; 32];
+        let mut registry = AccountRegistry::new();
+        for (guardians, threshold, delay_secs) in [
+            (vec![], 1, DAY),
+            (vec![[2u8; 32]], 2, DAY),
+            (vec![[2u8; 32], [2u8; 32]], 1, DAY),
+            (vec![account], 1, DAY),
+            (vec![[2u8; 32]], 1, 60),
+        ] {
+            let config = RecoveryConfig { guardians, threshold, delay_secs };
+            assert!(matches!(
+                registry.configure_recovery(&account, config),
+                Err(AccountError::InvalidRecoveryConfig(_))
+            ));
+        }
+        assert!(matches!(registry.disable_recovery(&account), Err(AccountError::NoRecoveryConfig)));
+    }
+}
@@ -0,0 +1,367 @@
+//! Rotation de la clé de contrôle d'un compte
+//!
+//! La clé courante demande la rotation vers une nouvelle clé, en joignant
+//! une preuve de possession signée par la nouvelle clé (une clé mal saisie
+//! ferait perdre le compte). Si le compte impose un délai, la rotation reste
+//! en attente et la clé courante peut s'y opposer (`Cancel`) jusqu'à son
+//! exécution lors de l'application des blocs.
+//!
+//! Réduire le délai ne prend effet qu'après l'écoulement du délai en
+//! vigueur: une clé compromise ne peut pas supprimer la fenêtre de veto.
+
+use serde::{Deserialize, Serialize};
+use crate::{Hash, PublicKey, Signature, Timestamp};
+use crate::account::{AccountError, AccountRegistry};
+use crate::crypto::{hash_data, sign, verify_signature};
+use crate::crypto::keys::KeyPair;
+
+/// Délai de rotation maximal (30 jours)
+pub const MAX_ROTATION_DELAY_SECS: i64 = 30 * 24 * 3600;
+
+/// Domaine de la preuve de possession
+const ROTATION_DOMAIN: &[u8] = b"mahala-key-rotation";
+
+/// Rotation en attente d'exécution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingRotation {
+    /// Nouvelle clé de contrôle
+    pub new_key: PublicKey,
+    /// Date de la demande (timestamp du bloc)
+    pub requested_at: Timestamp,
+    /// Date d'exécution
+    pub executes_at: Timestamp,
+}
+
+/// Réduction du délai de rotation en attente
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PendingDelay {
+    /// Nouveau délai
+    pub delay_secs: i64,
+    /// Date de prise d'effet
+    pub effective_at: Timestamp,
+}
+
+/// Rotation effectuée
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct KeyRotation {
+    /// Compte
+    pub account: PublicKey,
+    /// Clé retirée
+    pub old_key: PublicKey,
+    /// Nouvelle clé de contrôle
+    pub new_key: PublicKey,
+    /// Hauteur du bloc d'exécution
+    pub block_height: u64,
+    /// Timestamp du bloc d'exécution
+    pub timestamp: Timestamp,
+}
+
+/// Opération portée par une transaction `KeyRotation` (signée par la clé courante)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum KeyRotationOperation {
+    /// Demander la rotation vers `new_key` (preuve: signature hex de la
+    /// nouvelle clé, voir `rotation_signing_hash`)
+    Rotate {
+        #[serde(deserialize_with = "crate::wallet::address::deserialize")]
+        new_key: PublicKey,
+        proof: String,
+    },
+    /// Annuler la rotation en attente (veto)
+    Cancel,
+    /// Changer le délai imposé aux rotations
+    SetDelay { delay_secs: i64 },
+}
+
+impl KeyRotationOperation {
+    /// Construire une demande de rotation avec la preuve de possession
+    pub fn rotate(account: &PublicKey, new_keypair: &KeyPair) -> Self {
+        let new_key = *new_keypair.public_key();
+        let proof = sign(&rotation_signing_hash(account, &new_key), new_keypair.private_key());
+        Self::Rotate { new_key, proof: hex::encode(proof) }
+    }
+}
+
+/// Message signé par la nouvelle clé: domaine || compte || nouvelle clé
+pub fn rotation_signing_hash(account: &PublicKey, new_key: &PublicKey) -> Hash {
+    let mut data = Vec::with_capacity(ROTATION_DOMAIN.len() + 64);
+    data.extend_from_slice(ROTATION_DOMAIN);
+    data.extend_from_slice(account);
+    data.extend_from_slice(new_key);
+    hash_data(&data)
+}
+
+/// Vérifier la preuve de possession de la nouvelle clé
+pub fn verify_rotation_proof(account: &PublicKey, new_key: &PublicKey, proof: &str) -> bool {
+    let Ok(bytes) = hex::decode(proof) else {
+        return false;
+    };
+    let Ok(signature) = Signature::try_from(bytes) else {
+        return false;
+    };
+    verify_signature(&rotation_signing_hash(account, new_key), &signature, new_key)
+}
+
+impl AccountRegistry {
+    /// Demander la rotation de la clé d'un compte
+    ///
+    /// Retourne la rotation si elle est immédiate (compte sans délai),
+    /// `None` si elle est mise en attente.
+    pub fn request_rotation(
+        &mut self,
+        account: &PublicKey,
+        new_key: PublicKey,
+        proof: &str,
+        block_height: u64,
+        now: Timestamp,
+    ) -> Result<Option<KeyRotation>, AccountError> {
+        self.check_new_key(account, &new_key)?;
+        if !verify_rotation_proof(account, &new_key, proof) {
+            return Err(AccountError::InvalidProof);
+        }
+
+        let entry = self.entry(account);
+        if entry.pending_rotation.is_some() {
+            return Err(AccountError::RotationPending);
+        }
+
+        if entry.rotation_delay_secs == 0 {
+            return Ok(Some(self.rotate(account, new_key, block_height, now)));
+        }
+
+        entry.pending_rotation = Some(PendingRotation {
+            new_key,
+            requested_at: now,
+            executes_at: now + entry.rotation_delay_secs,
+        });
+        Ok(None)
+    }
+
+    /// Annuler la rotation en attente
+    pub fn cancel_rotation(&mut self, account: &PublicKey) -> Result<PendingRotation, AccountError> {
+        self.accounts.get_mut(account)
+            .and_then(|a| a.pending_rotation.take())
+            .ok_or(AccountError::NoPendingRotation)
+    }
+
+    /// Changer le délai de rotation
+    ///
+    /// Un allongement est immédiat; une réduction prend effet après le délai
+    /// en vigueur.
+    pub fn set_rotation_delay(
+        &mut self,
+        account: &PublicKey,
+        delay_secs: i64,
+        now: Timestamp,
+    ) -> Result<(), AccountError> {
+        if !(0..=MAX_ROTATION_DELAY_SECS).contains(&delay_secs) {
+            return Err(AccountError::InvalidDelay);
+        }
+
+        let entry = self.entry(account);
+        if delay_secs >= entry.rotation_delay_secs {
+            entry.rotation_delay_secs = delay_secs;
+            entry.pending_delay = None;
+        } else {
+            entry.pending_delay = Some(PendingDelay {
+                delay_secs,
+                effective_at: now + entry.rotation_delay_secs,
+            });
+        }
+        Ok(())
+    }
+
+    /// Exécuter les rotations, récupérations et réductions de délai arrivées
+    /// à terme
+    ///
+    /// Appelé à chaque application de bloc; l'ordre est déterministe (par
+    /// échéance puis par compte). Si une rotation et une récupération d'un
+    /// même compte sont dues, seule la plus ancienne s'exécute (elle annule
+    /// l'autre). Une rotation dont la nouvelle clé a été liée entre-temps à
+    /// un autre compte est abandonnée.
+    pub fn execute_due(&mut self, block_height: u64, now: Timestamp) -> Vec<KeyRotation> {
+        for account in self.accounts.values_mut() {
+            if account.pending_delay.as_ref().is_some_and(|d| d.effective_at <= now) {
+                let pending = account.pending_delay.take().expect("checked above");
+                account.rotation_delay_secs = pending.delay_secs;
+            }
+        }
+
+        let mut due: Vec<(Timestamp, PublicKey, PublicKey)> = self.accounts
+            .values()
+            .filter_map(|a| {
+                [&a.pending_rotation, &a.scheduled_recovery]
+                    .into_iter()
+                    .flatten()
+                    .filter(|p| p.executes_at <= now)
+                    .min_by_key(|p| p.executes_at)
+                    .map(|p| (p.executes_at, a.id, p.new_key))
+            })
+            .collect();
+        due.sort();
+
+        let mut executed = Vec::new();
+        for (_, account, new_key) in due {
+            if self.check_new_key(&account, &new_key).is_err() {
+                let entry = self.entry(&account);
+                for pending in [&mut entry.pending_rotation, &mut entry.scheduled_recovery] {
+                    if pending.as_ref().is_some_and(|p| p.new_key == new_key) {
+                        *pending = None;
+                    }
+                }
+                continue;
+            }
+            executed.push(self.rotate(&account, new_key, block_height, now));
+        }
+        executed
+    }
+
+    pub(super) fn check_new_key(&self, account: &PublicKey, new_key: &PublicKey) -> Result<(), AccountError> {
+        if &self.controller(account) == new_key {
+            return Err(AccountError::SameKey);
+        }
+        if new_key == account || self.is_bound(new_key) || self.is_pending_elsewhere(account, new_key) {
+            return Err(AccountError::KeyInUse);
+        }
+        Ok(())
+    }
+
+    /// La clé est-elle visée par la rotation ou la récupération en attente
+    /// d'un autre compte ?
+    fn is_pending_elsewhere(&self, account: &PublicKey, key: &PublicKey) -> bool {
+        self.accounts.values()
+            .filter(|a| &a.id != account)
+            .flat_map(|a| [&a.pending_rotation, &a.scheduled_recovery])
+            .any(|pending| pending.as_ref().is_some_and(|p| &p.new_key == key))
+    }
+
+    /// Lier le compte à sa nouvelle clé (annule rotation et récupération en cours)
+    pub(super) fn rotate(
+        &mut self,
+        account: &PublicKey,
+        new_key: PublicKey,
+        block_height: u64,
+        now: Timestamp,
+    ) -> KeyRotation {
+        let entry = self.entry(account);
+        let old_key = entry.controller;
+        entry.controller = new_key;
+        entry.pending_rotation = None;
+        entry.recovery_approvals.clear();
+        entry.scheduled_recovery = None;
+
+        self.key_index.insert(new_key, *account);
+        if &old_key != account {
+            self.key_index.insert(old_key, *account);
+        }
+
+        let rotation = KeyRotation {
+            account: *account,
+            old_key,
+            new_key,
+            block_height,
+            timestamp: now,
+        };
+        self.rotations.push(rotation.clone());
+        rotation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_immediate_rotation_retires_old_key() {
+        let mut registry = AccountRegistry::new();
+        let original = KeyPair::new();
+        let account = *original.public_key();
+        let first = KeyPair::new();
+        let second = KeyPair::new();
+
+        let KeyRotationOperation::Rotate { new_key, proof } = KeyRotationOperation::rotate(&account, &first) else {
+            unreachable!()
+        };
+        let rotation = registry.request_rotation(&account, new_key, &proof, 1, 100).unwrap().unwrap();
+        assert_eq!(rotation.old_key, account);
+        assert_eq!(registry.authorize(first.public_key()).unwrap(), account);
+        assert!(matches!(registry.authorize(&account), Err(AccountError::RetiredKey)));
+
+        // Deuxième rotation: toutes les clés désignent toujours le compte
+        let KeyRotationOperation::Rotate { new_key, proof } = KeyRotationOperation::rotate(&account, &second) else {
+            unreachable!()
+        };
+        registry.request_rotation(&account, new_key, &proof, 2, 200).unwrap();
+        for key in [&account, first.public_key(), second.public_key()] {
+            assert_eq!(registry.resolve(key), account);
+        }
+        assert!(matches!(registry.authorize(first.public_key()), Err(AccountError::RetiredKey)));
+        assert_eq!(registry.rotations_of(&account).len(), 2);
+    }
+
+    #[test]
+    fn test_rotation_requires_proof_and_fresh_key() {
+        let mut registry = AccountRegistry::new();
+        let account = [1u8; 32];
+        let other_account = [2u8; 32];
+        let new_key = KeyPair::new();
+
+        let forged = hex::encode([0u8; 64]);
+        assert!(matches!(
+            registry.request_rotation(&account, *new_key.public_key(), &forged, 1, 100),
+            Err(AccountError::InvalidProof)
+        ));
+
+        let KeyRotationOperation::Rotate { proof, .. } = KeyRotationOperation::rotate(&account, &new_key) else {
+            unreachable!()
+        };
+        registry.request_rotation(&account, *new_key.public_key(), &proof, 1, 100).unwrap();
+
+        // La preuve est liée au compte et la clé ne peut servir deux fois
+        assert!(matches!(
+            registry.request_rotation(&other_account, *new_key.public_key(), &proof, 2, 200),
+            Err(AccountError::KeyInUse)
+        ));
+    }
+
+    #[test]
+    fn test_delayed_rotation_veto_and_delay_reduction() {
+        let mut registry = AccountRegistry::new();
+        let account = [1u8; 32];
+        registry.set_rotation_delay(&account, 3600, 0).unwrap();
+
+        let stolen = KeyPair::new();
+        let KeyRotationOperation::Rotate { new_key, proof } = KeyRotationOperation::rotate(&account, &stolen) else {
+            unreachable!()
+        };
+        assert!(registry.request_rotation(&account, new_key, &proof, 1, 100).unwrap().is_none());
+        assert!(matches!(
+            registry.request_rotation(&account, new_key, &proof, 1, 100),
+            Err(AccountError::RotationPending)
+        ));
+
+        // Clé réservée par la rotation en attente
+        let other_account = [2u8; 32];
+        let KeyRotationOperation::Rotate { proof: other_proof, .. } = KeyRotationOperation::rotate(&other_account, &stolen) else {
+            unreachable!()
+        };
+        assert!(matches!(
+            registry.request_rotation(&other_account, new_key, &other_proof, 1, 100),
+            Err(AccountError::KeyInUse)
+        ));
+
+        // Veto de la clé courante pendant le délai
+        assert!(registry.execute_due(2, 3000).is_empty());
+        assert_eq!(registry.cancel_rotation(&account).unwrap().new_key, new_key);
+        assert!(registry.execute_due(3, 4000).is_empty());
+        assert_eq!(registry.controller(&account), account);
+
+        // La réduction du délai attend l'écoulement du délai en vigueur
+        registry.set_rotation_delay(&account, 0, 5000).unwrap();
+        assert_eq!(registry.get(&account).unwrap().rotation_delay_secs, 3600);
+        registry.request_rotation(&account, new_key, &proof, 4, 5000).unwrap();
+        let executed = registry.execute_due(5, 8600);
+        assert_eq!(executed.len(), 1);
+        assert_eq!(registry.controller(&account), new_key);
+        assert_eq!(registry.get(&account).unwrap().rotation_delay_secs, 0);
+    }
+}
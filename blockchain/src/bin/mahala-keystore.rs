@@ -0,0 +1,142 @@
+//! Outil en ligne de commande de gestion des keystores Mahala
+//!
+//! Les mots de passe sont lus sur l'entrée standard (une ligne chacun), ou
+//! dans `MAHALA_KEYSTORE_PASSWORD` (et `MAHALA_KEYSTORE_NEW_PASSWORD` pour
+//! un changement de mot de passe) pour un usage non interactif.
+
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use mahala_blockchain::crypto::keys::KeyPair;
+use mahala_blockchain::wallet::Keystore;
+
+const USAGE: &str = "Usage:
+  mahala-keystore new <fichier> [libellé]       Créer une nouvelle clé chiffrée
+  mahala-keystore show <fichier>                Afficher l'adresse et les paramètres
+  mahala-keystore change-password <fichier>     Changer le mot de passe
+  mahala-keystore upgrade <fichier>             Re-chiffrer avec les paramètres courants
+  mahala-keystore migrate <clé-hex> <fichier>   Chiffrer un ancien fichier de clé en clair";
+
+/// Variable d'environnement contenant le mot de passe
+const PASSWORD_ENV: &str = "MAHALA_KEYSTORE_PASSWORD";
+
+/// Variable d'environnement contenant le nouveau mot de passe
+const NEW_PASSWORD_ENV: &str = "MAHALA_KEYSTORE_NEW_PASSWORD";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+
+    let result = match args.as_slice() {
+        ["new", path] => new_keystore(path, None),
+        ["new", path, label] => new_keystore(path, Some(label)),
+        ["show", path] => show(path),
+        ["change-password", path] => change_password(path),
+        ["upgrade", path] => upgrade(path),
+        ["migrate", legacy, path] => migrate(legacy, path),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Erreur: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn new_keystore(path: &str, label: Option<&str>) -> Result<(), String> {
+    let password = new_password(PASSWORD_ENV)?;
+    let mut keystore = Keystore::encrypt(&KeyPair::new(), &password)
+        .map_err(|e| e.to_string())?;
+    keystore.label = label.map(str::to_string);
+    keystore.save(path).map_err(|e| e.to_string())?;
+
+    println!("{}", keystore.address);
+    Ok(())
+}
+
+fn show(path: &str) -> Result<(), String> {
+    let keystore = Keystore::load(path).map_err(|e| e.to_string())?;
+
+    println!("address: {}", keystore.address);
+    println!("label:   {}", keystore.label.as_deref().unwrap_or("-"));
+    println!("version: {}", keystore.version);
+    println!("kdf:     {:?}", keystore.kdf);
+    if keystore.needs_upgrade() {
+        println!("(paramètres obsolètes: lancer `mahala-keystore upgrade {}`)", path);
+    }
+    Ok(())
+}
+
+fn change_password(path: &str) -> Result<(), String> {
+    let keystore = Keystore::load(path).map_err(|e| e.to_string())?;
+    let old_password = current_password("Mot de passe actuel")?;
+    let new_password = new_password(NEW_PASSWORD_ENV)?;
+
+    keystore.change_password(&old_password, &new_password)
+        .and_then(|updated| updated.save(path))
+        .map_err(|e| e.to_string())
+}
+
+fn upgrade(path: &str) -> Result<(), String> {
+    let keystore = Keystore::load(path).map_err(|e| e.to_string())?;
+    if !keystore.needs_upgrade() {
+        println!("Keystore déjà à jour");
+        return Ok(());
+    }
+
+    let password = current_password("Mot de passe")?;
+    keystore.upgrade(&password)
+        .and_then(|updated| updated.save(path))
+        .map_err(|e| e.to_string())
+}
+
+fn migrate(legacy: &str, path: &str) -> Result<(), String> {
+    let password = new_password(PASSWORD_ENV)?;
+    let keystore = Keystore::migrate_plaintext(legacy, &password)
+        .map_err(|e| e.to_string())?;
+    keystore.save(path).map_err(|e| e.to_string())?;
+
+    println!("{}", keystore.address);
+    eprintln!("Pensez à supprimer l'ancien fichier en clair: {}", legacy);
+    Ok(())
+}
+
+/// Lire un nouveau mot de passe (confirmé en mode interactif)
+fn new_password(env_var: &str) -> Result<String, String> {
+    if let Ok(password) = std::env::var(env_var) {
+        return Ok(password);
+    }
+
+    let password = read_password("Nouveau mot de passe")?;
+    if password != read_password("Confirmation")? {
+        return Err("les mots de passe ne correspondent pas".to_string());
+    }
+    Ok(password)
+}
+
+/// Lire le mot de passe actuel (variable d'environnement ou entrée standard)
+fn current_password(prompt: &str) -> Result<String, String> {
+    match std::env::var(PASSWORD_ENV) {
+        Ok(password) => Ok(password),
+        Err(_) => read_password(prompt),
+    }
+}
+
+/// Lire un mot de passe sur une ligne de l'entrée standard
+fn read_password(prompt: &str) -> Result<String, String> {
+    eprint!("{}: ", prompt);
+    io::stderr().flush().map_err(|e| e.to_string())?;
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).map_err(|e| e.to_string())?;
+    let password = line.trim_end_matches(['\r', '\n']).to_string();
+    if password.is_empty() {
+        return Err("mot de passe vide".to_string());
+    }
+    Ok(password)
+}
@@ -4,7 +4,13 @@ use serde::{Deserialize, Serialize};
 use crate::{Hash, Signature, PublicKey, Timestamp, Amount};
 use crate::transaction::Transaction;
 use crate::crypto::{hash_data, hash_to_string};
+#[cfg(not(feature = "parallel"))]
+use crate::crypto::verify_batch;
+#[cfg(feature = "parallel")]
+use crate::crypto::verify_batch_parallel;
 use crate::storage::merkle::MerkleTree;
+use crate::consensus::VRFProof;
+use crate::consensus::bft::{vote_signing_hash, ProposalWitness, VoteKind};
 
 /// En-tête d'un bloc
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,9 +43,18 @@ pub struct Block {
     /// Transactions dans ce bloc
     pub transactions: Vec<Transaction>,
     
-    /// Signatures des validateurs (quorum 67%)
+    /// Signatures des membres du comité (quorum 67%)
     pub validator_signatures: Vec<ValidatorSignature>,
     
+    /// Round BFT où le comité a validé le bloc (hors hash du bloc)
+    #[serde(default)]
+    pub commit_round: u32,
+    
+    /// Propositions reçues aux rounds précédents, une par round (hors hash
+    /// du bloc)
+    #[serde(default)]
+    pub proposal_witnesses: Vec<ProposalWitness>,
+    
     /// Hash du bloc (calculé)
     #[serde(skip)]
     pub hash: Option<Hash>,
@@ -55,6 +70,11 @@ pub struct ValidatorSignature {
     /// Signature du bloc (sérialisée en hex, 64 bytes)
     #[serde(serialize_with = "serialize_signature", deserialize_with = "deserialize_signature")]
     pub signature: Signature,
+    
+    /// Preuve VRF de sélection du validateur dans le comité de la hauteur
+    /// (sérialisée en hex, 80 bytes)
+    #[serde(with = "crate::consensus::rvs::hex_bytes")]
+    pub selection_proof: VRFProof,
 }
 
 fn serialize_signature<S>(bytes: &[u8; 64], serializer: S) -> Result<S::Ok, S::Error>
@@ -177,85 +197,151 @@ impl Block {
             header,
             transactions,
             validator_signatures: Vec::new(),
+            commit_round: 0,
+            proposal_witnesses: Vec::new(),
             hash: None,
         }
     }
 
     /// Calculer le hash du bloc
     pub fn calculate_hash(&mut self) -> Hash {
-        let data = self.header.serialize_for_hash();
-        let hash = hash_data(&data);
+        let hash = self.compute_hash();
         self.hash = Some(hash);
         hash
     }
 
-    /// Ajouter une signature de validateur
-    pub fn add_validator_signature(&mut self, validator: PublicKey, signature: Signature) {
+    /// Calculer le hash du bloc sans le mémoriser
+    pub fn compute_hash(&self) -> Hash {
+        hash_data(&self.header.serialize_for_hash())
+    }
+
+    /// Message signé par les membres du comité: précommit BFT du bloc pour
+    /// sa hauteur et son round de validation
+    pub fn commit_signing_hash(&self, block_hash: &Hash) -> Hash {
+        vote_signing_hash(VoteKind::Precommit, self.header.height, self.commit_round, Some(block_hash))
+    }
+
+    /// Ajouter la signature d'un membre du comité avec sa preuve de sélection
+    pub fn add_validator_signature(
+        &mut self,
+        validator: PublicKey,
+        signature: Signature,
+        selection_proof: VRFProof,
+    ) {
         // Vérifier qu'on n'a pas déjà cette signature
         if !self.validator_signatures.iter().any(|vs| vs.validator == validator) {
             self.validator_signatures.push(ValidatorSignature {
                 validator,
                 signature,
+                selection_proof,
             });
         }
     }
 
-    /// Vérifier les signatures des validateurs
-    fn verify_validator_signatures(&self, block_hash: Hash) -> bool {
-        for vs in &self.validator_signatures {
-            use crate::crypto::verify_signature;
-            if !verify_signature(&block_hash, &vs.signature, &vs.validator) {
+    /// Hash des transactions du bloc (calculés en parallèle avec la
+    /// feature `parallel`)
+    pub fn transaction_hashes(&self) -> Vec<Hash> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            self.transactions.par_iter().map(Transaction::compute_hash).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            self.transactions.iter().map(Transaction::compute_hash).collect()
+        }
+    }
+
+    /// Mémoriser les hash des transactions (après vérification du bloc)
+    pub fn cache_transaction_hashes(&mut self, hashes: &[Hash]) {
+        for (tx, hash) in self.transactions.iter_mut().zip(hashes) {
+            tx.hash = Some(*hash);
+        }
+    }
+
+    /// Vérifier en un seul lot les signatures des transactions et des validateurs
+    fn verify_signatures(&self, tx_hashes: &[Hash], block_hash: &Hash) -> bool {
+        let commit_hash = self.commit_signing_hash(block_hash);
+        let mut items = Vec::with_capacity(tx_hashes.len() + self.validator_signatures.len());
+        for (tx, hash) in self.transactions.iter().zip(tx_hashes) {
+            let Some(signature) = tx.signature_bytes() else {
                 return false;
-            }
+            };
+            items.push((hash.as_slice(), signature, tx.from));
+        }
+        for vs in &self.validator_signatures {
+            items.push((commit_hash.as_slice(), vs.signature, vs.validator));
+        }
+
+        #[cfg(feature = "parallel")]
+        {
+            verify_batch_parallel(&items)
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            verify_batch(&items)
         }
-        true
     }
 
-    /// Vérifier si le bloc a atteint le quorum (67% des validateurs)
-    pub fn has_quorum(&self, total_validators: usize) -> bool {
-        let required = (total_validators * 67 + 99) / 100; // Arrondi supérieur de 67%
+    /// Vérifier si le bloc a atteint le quorum (67% du comité)
+    pub fn has_quorum(&self, committee_size: usize) -> bool {
+        let required = (committee_size * 67 + 99) / 100; // Arrondi supérieur de 67%
         self.validator_signatures.len() >= required
     }
 
     /// Vérifier la validité du bloc
     pub fn is_valid(&self, previous_block_hash: Option<Hash>) -> bool {
+        self.verify(previous_block_hash).is_some()
+    }
+
+    /// Vérifier le bloc et retourner les hash de ses transactions
+    ///
+    /// Chaque hash n'est calculé qu'une fois, pour la racine de Merkle et
+    /// pour les signatures, vérifiées par lot.
+    pub fn verify(&self, previous_block_hash: Option<Hash>) -> Option<Vec<Hash>> {
         // Vérifier le hash précédent
         if let Some(prev_hash) = previous_block_hash {
             if self.header.previous_hash != prev_hash {
-                return false;
+                return None;
             }
         }
 
         // Vérifier la hauteur
         if self.header.height == 0 && !previous_block_hash.is_none() {
-            return false;
+            return None;
         }
 
-        // Vérifier les transactions
-        for tx in &self.transactions {
-            if !tx.is_valid() {
-                return false;
+        // Un validateur ne signe qu'une fois
+        for (i, vs) in self.validator_signatures.iter().enumerate() {
+            if self.validator_signatures[..i].iter().any(|other| other.validator == vs.validator) {
+                return None;
             }
         }
 
+        // Vérifier la forme des transactions (signatures vérifiées plus bas)
+        if !self.transactions.iter().all(Transaction::is_well_formed) {
+            return None;
+        }
+
         // Vérifier la racine de Merkle
-        let calculated_root = if self.transactions.is_empty() {
+        let tx_hashes = self.transaction_hashes();
+        let calculated_root = if tx_hashes.is_empty() {
             hash_data(b"empty_block")
         } else {
-            MerkleTree::from_transactions(&self.transactions).root()
+            MerkleTree::from_hashes(tx_hashes.clone()).root()
         };
 
         if self.header.merkle_root != calculated_root {
-            return false;
+            return None;
         }
 
-        // Vérifier les signatures des validateurs
+        // Vérifier les signatures des transactions et des validateurs
         let block_hash = self.hash.expect("Block hash should be calculated");
-        if !self.verify_validator_signatures(block_hash) {
-            return false;
+        if !self.verify_signatures(&tx_hashes, &block_hash) {
+            return None;
         }
 
-        true
+        Some(tx_hashes)
     }
 
     /// Obtenir le hash du bloc (calculé si nécessaire)
@@ -322,6 +408,51 @@ mod tests {
         assert_ne!(block.header.merkle_root, [0u8; 32]);
     }
 
+    #[test]
+    fn test_block_batch_verification() {
+        let producer = KeyPair::new();
+        let validator = KeyPair::new();
+        let previous_hash = hash_data(b"genesis");
+        let transactions: Vec<Transaction> = (0..10)
+            .map(|i| {
+                let sender = KeyPair::new();
+                let mut tx = Transaction::new(
+                    *sender.public_key(),
+                    *producer.public_key(),
+                    1.0 + i as f64,
+                    0.1,
+                    Default::default(),
+                );
+                tx.sign(sender.private_key()).unwrap();
+                tx
+            })
+            .collect();
+
+        let mut block = Block::new(1, previous_hash, transactions.clone(), *producer.public_key());
+        let block_hash = block.calculate_hash();
+        let commit_hash = block.commit_signing_hash(&block_hash);
+        block.add_validator_signature(*validator.public_key(), crate::crypto::sign(&commit_hash, validator.private_key()), [0u8; 80]);
+        let tx_hashes = block.verify(Some(previous_hash)).unwrap();
+        assert_eq!(tx_hashes[3], transactions[3].compute_hash());
+
+        // Une transaction modifiée après signature invalide le lot
+        let mut tampered = block.clone();
+        tampered.transactions[3].amount = 1000.0;
+        tampered.header.merkle_root = MerkleTree::from_transactions(&tampered.transactions).root();
+        tampered.calculate_hash();
+        assert!(!tampered.is_valid(Some(previous_hash)));
+
+        // Une signature de validateur invalide aussi
+        let mut forged = block.clone();
+        forged.validator_signatures[0].signature = [1u8; 64];
+        assert!(!forged.is_valid(Some(previous_hash)));
+
+        // Deux signatures du même validateur aussi
+        let mut duplicated = block.clone();
+        duplicated.validator_signatures.push(block.validator_signatures[0].clone());
+        assert!(!duplicated.is_valid(Some(previous_hash)));
+    }
+
     #[test]
     fn test_block_quorum() {
         let keypair = KeyPair::new();
@@ -336,15 +467,15 @@ mod tests {
         // Ajouter 3 signatures pour atteindre le quorum
         let keypair2 = KeyPair::new();
         let keypair3 = KeyPair::new();
-        block2.add_validator_signature(*keypair.public_key(), [0u8; 64]);
-        block2.add_validator_signature(*keypair2.public_key(), [0u8; 64]);
-        block2.add_validator_signature(*keypair3.public_key(), [0u8; 64]);
+        block2.add_validator_signature(*keypair.public_key(), [0u8; 64], [0u8; 80]);
+        block2.add_validator_signature(*keypair2.public_key(), [0u8; 64], [0u8; 80]);
+        block2.add_validator_signature(*keypair3.public_key(), [0u8; 64], [0u8; 80]);
         assert!(block2.has_quorum(3));
         
         // Avec 2 signatures sur 3, ça ne devrait pas suffire
         let mut block3 = Block::new(0, hash_data(b"genesis"), Vec::new(), *keypair.public_key());
-        block3.add_validator_signature(*keypair.public_key(), [0u8; 64]);
-        block3.add_validator_signature(*keypair2.public_key(), [0u8; 64]);
+        block3.add_validator_signature(*keypair.public_key(), [0u8; 64], [0u8; 80]);
+        block3.add_validator_signature(*keypair2.public_key(), [0u8; 64], [0u8; 80]);
         assert!(!block3.has_quorum(3));
     }
 }
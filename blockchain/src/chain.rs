@@ -1,12 +1,25 @@
 //! Gestion de la chaîne de blocs Mahala
 
-use std::collections::HashMap;
-use crate::{Hash, PublicKey, Amount};
+use std::borrow::Cow;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::{Hash, PublicKey, Amount, Signature, Timestamp};
+use crate::account::{Account, AccountRegistry, KeyRotation, KeyRotationOperation, RecoveryOperation};
 use crate::block::Block;
-use crate::transaction::Transaction;
-use crate::consensus::rvs::{RVS, RVSConfig};
+use crate::transaction::{Transaction, TransactionType};
+use crate::nft::{NFTContract, NFTOperation, NFTEventContext, MarketplaceConfig};
+use crate::nft::contract::NFTEntry;
+use crate::payments::{
+    ChannelOperation, ChannelRegistry, EscrowOperation, EscrowRegistry, StandingOrderOperation,
+    StandingOrderRegistry,
+};
+use crate::payments::channel::ChannelSettlement;
+use crate::consensus::evidence::{DoubleSignEvidence, SlashingRegistry};
+use crate::consensus::rvs::{EpochSchedule, RVS, RVSConfig, ValidatorOperation};
 use crate::du::{DUCalculator, DUConfig};
 use crate::storage::checkpoint::{Checkpoint, CheckpointConfig};
+use crate::storage::snapshot::{SnapshotChunk, SnapshotError, SnapshotManifest, StateSnapshot};
 
 /// État de la blockchain (balances, etc.)
 #[derive(Debug, Clone)]
@@ -20,8 +33,33 @@ pub struct BlockchainState {
     /// Masse monétaire totale
     pub total_mass: Amount,
     
-    /// Timestamp de dernière distribution DU
+    /// Timestamp (de bloc) de dernière distribution DU, 0 avant le premier
+    /// bloc après la genèse
     pub last_du_distribution: i64,
+    
+    /// Contrat NFT (NFTs, listings et journal de provenance)
+    pub nfts: NFTContract,
+    
+    /// Séquestres des achats en boutique
+    pub escrows: EscrowRegistry,
+    
+    /// Ordres permanents (paiements récurrents)
+    pub standing_orders: StandingOrderRegistry,
+    
+    /// Canaux de paiement (micropaiements hors chaîne)
+    pub channels: ChannelRegistry,
+    
+    /// Comptes (clé de contrôle, nonce, rotations)
+    pub accounts: AccountRegistry,
+    
+    /// Fautes des validateurs sanctionnées (équivocations)
+    pub slashing: SlashingRegistry,
+    
+    /// Membres (identités de compte), seuls admis comme validateurs
+    pub members: HashSet<PublicKey>,
+    
+    /// Validateurs du consensus RVS (enregistrement, activité, sanctions)
+    pub consensus: RVS,
 }
 
 impl Default for BlockchainState {
@@ -30,22 +68,176 @@ impl Default for BlockchainState {
             balances: HashMap::new(),
             member_count: 0,
             total_mass: 0.0,
-            last_du_distribution: chrono::Utc::now().timestamp(),
+            last_du_distribution: 0,
+            nfts: NFTContract::new(),
+            escrows: EscrowRegistry::new(),
+            standing_orders: StandingOrderRegistry::new(),
+            channels: ChannelRegistry::new(),
+            accounts: AccountRegistry::new(),
+            slashing: SlashingRegistry::new(),
+            members: HashSet::new(),
+            consensus: RVS::new(RVSConfig::default()),
+        }
+    }
+}
+
+/// Journal d'annulation des transactions d'un bloc
+///
+/// Garde les valeurs d'avant le bloc de ce que les transactions modifient:
+/// soldes, comptes et NFTs touchés, masse monétaire, longueur du journal de
+/// provenance, et une copie des autres registres prise avant leur première
+/// modification. Un bloc refusé est annulé sans copier tout l'état.
+struct StateJournal {
+    /// Soldes touchés (absent: pas de solde avant le bloc)
+    balances: HashMap<PublicKey, Option<Amount>>,
+    /// Comptes dont le nonce a changé, tant que le registre n'est pas copié
+    accounts: HashMap<PublicKey, Option<Account>>,
+    total_mass: Amount,
+    account_registry: Option<AccountRegistry>,
+    /// NFTs et listings visés par les opérations du bloc
+    nfts: HashMap<String, NFTEntry>,
+    /// Nombre d'événements NFT avant la première opération NFT du bloc
+    nft_events: Option<usize>,
+    escrows: Option<EscrowRegistry>,
+    standing_orders: Option<StandingOrderRegistry>,
+    channels: Option<ChannelRegistry>,
+    slashing: Option<SlashingRegistry>,
+    consensus: Option<RVS>,
+}
+
+impl StateJournal {
+    fn new(state: &BlockchainState) -> Self {
+        Self {
+            balances: HashMap::new(),
+            accounts: HashMap::new(),
+            total_mass: state.total_mass,
+            account_registry: None,
+            nfts: HashMap::new(),
+            nft_events: None,
+            escrows: None,
+            standing_orders: None,
+            channels: None,
+            slashing: None,
+            consensus: None,
+        }
+    }
+
+    /// Copier les entrées et registres qu'une transaction peut modifier,
+    /// avant qu'elle ne s'applique
+    fn save_registries(&mut self, state: &BlockchainState, tx: &Transaction) {
+        fn save<T: Clone>(saved: &mut Option<T>, current: &T) {
+            if saved.is_none() {
+                *saved = Some(current.clone());
+            }
+        }
+        match tx.metadata.transaction_type {
+            TransactionType::NFT => {
+                self.nft_events.get_or_insert(state.nfts.events().len());
+                let operation = tx.metadata.payload::<NFTOperation>().ok();
+                if let Some(nft_id) = operation.as_ref().and_then(NFTOperation::nft_id) {
+                    if !self.nfts.contains_key(nft_id) {
+                        self.nfts.insert(nft_id.to_string(), state.nfts.entry(nft_id));
+                    }
+                }
+            }
+            TransactionType::ShopPurchase if tx.metadata.extra_data.is_some() => {
+                save(&mut self.escrows, &state.escrows)
+            }
+            TransactionType::Escrow => save(&mut self.escrows, &state.escrows),
+            TransactionType::StandingOrder => save(&mut self.standing_orders, &state.standing_orders),
+            TransactionType::PaymentChannel => save(&mut self.channels, &state.channels),
+            TransactionType::KeyRotation | TransactionType::Recovery => {
+                save(&mut self.account_registry, &state.accounts)
+            }
+            TransactionType::Evidence => save(&mut self.slashing, &state.slashing),
+            TransactionType::Validator => save(&mut self.consensus, &state.consensus),
+            _ => {}
+        }
+    }
+
+    /// Remettre l'état tel qu'avant le bloc
+    ///
+    /// Les comptes journalisés avant la copie du registre sont restaurés
+    /// après elle.
+    fn undo(self, state: &mut BlockchainState) {
+        for (key, balance) in self.balances {
+            match balance {
+                Some(balance) => state.balances.insert(key, balance),
+                None => state.balances.remove(&key),
+            };
+        }
+        state.total_mass = self.total_mass;
+        if let Some(accounts) = self.account_registry {
+            state.accounts = accounts;
+        }
+        for (id, account) in self.accounts {
+            state.accounts.restore(id, account);
+        }
+        if let Some(events_len) = self.nft_events {
+            state.nfts.undo(events_len, self.nfts);
+        }
+        if let Some(escrows) = self.escrows {
+            state.escrows = escrows;
+        }
+        if let Some(standing_orders) = self.standing_orders {
+            state.standing_orders = standing_orders;
+        }
+        if let Some(channels) = self.channels {
+            state.channels = channels;
+        }
+        if let Some(slashing) = self.slashing {
+            state.slashing = slashing;
+        }
+        if let Some(consensus) = self.consensus {
+            state.consensus = consensus;
         }
     }
 }
 
+/// Nombre maximal d'états conservés pour des checkpoints pas encore finals
+///
+/// Borne la mémoire quand la finalisation prend du retard.
+pub const MAX_PENDING_CHECKPOINT_STATES: usize = 4;
+
+/// Réorganisation de la chaîne principale vers une branche plus lourde
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reorg {
+    /// Hauteur du dernier bloc commun aux deux branches
+    pub fork_height: u64,
+    
+    /// Blocs retirés de la chaîne principale (du plus ancien au plus récent)
+    pub reverted: Vec<Hash>,
+    
+    /// Blocs de la nouvelle branche (du plus ancien au plus récent)
+    pub applied: Vec<Hash>,
+    
+    /// Transactions des blocs retirés absentes de la nouvelle branche, à
+    /// remettre dans le mempool
+    pub orphaned_transactions: Vec<Transaction>,
+}
+
 /// Blockchain Mahala
+///
+/// Les blocs forment un arbre: la chaîne principale et des branches
+/// concurrentes. La branche retenue est celle qui porte le plus de
+/// signatures de comité depuis leur dernier bloc commun (puis la plus
+/// longue, puis celle dont le premier bloc a le plus petit hash, pour que
+/// tous les nœuds fassent le même choix). Les blocs finals ne sont jamais
+/// annulés.
+///
+/// Un nœud démarré depuis un instantané ne conserve les blocs qu'à partir
+/// du bloc d'ancrage de l'époque du checkpoint.
 pub struct Blockchain {
     /// Blocs de la chaîne
     blocks: Vec<Block>,
     
+    /// Hauteur du premier bloc conservé (genesis, ou bloc d'ancrage d'un
+    /// instantané)
+    first_height: u64,
+    
     /// État actuel
     state: BlockchainState,
     
-    /// Consensus RVS
-    consensus: RVS,
-    
     /// Calculateur DU
     du_calculator: DUCalculator,
     
@@ -54,6 +246,30 @@ pub struct Blockchain {
     
     /// Checkpoints sauvegardés
     checkpoints: Vec<Checkpoint>,
+    
+    /// Hauteur du dernier checkpoint signé par un quorum: les blocs jusqu'à
+    /// cette hauteur sont finaux et ne sont jamais annulés
+    finalized_height: u64,
+    
+    /// Blocs connus hors de la chaîne principale (branches concurrentes,
+    /// blocs retirés par une réorganisation), par hash
+    side_blocks: HashMap<Hash, Block>,
+    
+    /// État après le dernier bloc final, point de départ des réorganisations
+    finalized_state: Option<BlockchainState>,
+    
+    /// États aux checkpoints pas encore finals
+    checkpoint_states: BTreeMap<u64, BlockchainState>,
+    
+    /// Blocs de données de l'état au dernier checkpoint final (instantané
+    /// servi aux nœuds qui démarrent)
+    finalized_chunks: Vec<SnapshotChunk>,
+    
+    /// Réorganisations pas encore relevées par le nœud
+    reorgs: Vec<Reorg>,
+    
+    /// Journal du bloc en cours d'application
+    journal: Option<StateJournal>,
 }
 
 impl Blockchain {
@@ -63,12 +279,51 @@ impl Blockchain {
         
         Self {
             blocks: Vec::new(),
-            state: BlockchainState::default(),
-            consensus: RVS::new(rvs_config),
+            first_height: 0,
+            state: BlockchainState {
+                consensus: RVS::new(rvs_config),
+                ..Default::default()
+            },
             du_calculator,
             checkpoint_config: CheckpointConfig::default(),
             checkpoints: Vec::new(),
+            finalized_height: 0,
+            side_blocks: HashMap::new(),
+            finalized_state: None,
+            checkpoint_states: BTreeMap::new(),
+            finalized_chunks: Vec::new(),
+            reorgs: Vec::new(),
+            journal: None,
+        }
+    }
+
+    /// Démarrer depuis l'instantané d'un checkpoint final
+    ///
+    /// L'instantané est vérifié contre le calendrier de l'époque de son
+    /// checkpoint, connu par ailleurs. Le checkpoint devient le dernier bloc
+    /// final, et les blocs suivants sont ajoutés normalement.
+    pub fn from_snapshot(
+        du_config: DUConfig,
+        rvs_config: RVSConfig,
+        snapshot: StateSnapshot,
+        schedule: &EpochSchedule,
+    ) -> Result<Self, SnapshotError> {
+        snapshot.verify(schedule)?;
+        let state = BlockchainState::from_snapshot_chunks(&snapshot.chunks, rvs_config.clone())?;
+        let SnapshotManifest { checkpoint, mut blocks, .. } = snapshot.manifest;
+        for block in &mut blocks {
+            block.calculate_hash();
         }
+        
+        let mut blockchain = Self::new(du_config, rvs_config);
+        blockchain.first_height = blocks[0].header.height;
+        blockchain.blocks = blocks;
+        blockchain.finalized_height = checkpoint.height;
+        blockchain.finalized_state = Some(state.clone());
+        blockchain.finalized_chunks = snapshot.chunks;
+        blockchain.state = state;
+        blockchain.checkpoints.push(checkpoint);
+        Ok(blockchain)
     }
 
     /// Créer le bloc genesis
@@ -86,38 +341,68 @@ impl Blockchain {
         Ok(hash)
     }
 
-    /// Ajouter un bloc à la chaîne
-    pub fn add_block(&mut self, mut block: Block) -> Result<Hash, String> {
+    /// Ajouter un bloc à l'arbre des blocs
+    ///
+    /// Un bloc qui prolonge la chaîne principale est validé et appliqué. Un
+    /// bloc d'une branche concurrente est conservé; si sa branche devient
+    /// plus lourde que la chaîne principale, la chaîne est réorganisée.
+    pub fn add_block(&mut self, block: Block) -> Result<Hash, String> {
+        match self.last_block_hash() {
+            Some(tip) if block.header.previous_hash != tip => self.add_fork_block(block),
+            _ => self.extend_chain(block),
+        }
+    }
+
+    /// Vérifier un bloc proposé au sommet de la chaîne principale, sans
+    /// l'appliquer
+    ///
+    /// Structure, hauteur et parent, puis transactions rejouées sur l'état et
+    /// annulées par le journal (les échéances et le DU ne peuvent pas faire
+    /// échouer un bloc). Le comité n'est pas vérifié: une proposition ne
+    /// porte pas encore les précommits.
+    pub fn check_proposal(&mut self, block: &Block) -> Result<(), String> {
+        let expected_height = self.height();
+        if block.header.height != expected_height {
+            return Err(format!("Invalid block height: expected {}, got {}",
+                expected_height, block.header.height));
+        }
+        let previous_hash = self.last_block_hash().unwrap_or([0u8; 32]);
+        if block.verify(Some(previous_hash)).is_none() {
+            return Err("Invalid block".to_string());
+        }
+        
+        self.journal = Some(StateJournal::new(&self.state));
+        let result = self.apply_transactions(block.header.height, block.header.timestamp, &block.transactions);
+        self.end_journal(true);
+        result
+    }
+
+    /// Valider et appliquer un bloc au sommet de la chaîne principale
+    fn extend_chain(&mut self, mut block: Block) -> Result<Hash, String> {
         // Vérifier la validité du bloc
         let previous_hash = self.blocks.last()
             .and_then(|b| b.hash)
             .unwrap_or([0u8; 32]);
         
-        if !block.is_valid(Some(previous_hash)) {
+        let Some(tx_hashes) = block.verify(Some(previous_hash)) else {
             return Err("Invalid block".to_string());
-        }
+        };
+        block.cache_transaction_hashes(&tx_hashes);
 
         // Vérifier la hauteur
-        let expected_height = self.blocks.len() as u64;
+        let expected_height = self.height();
         if block.header.height != expected_height {
             return Err(format!("Invalid block height: expected {}, got {}", 
                 expected_height, block.header.height));
         }
 
-        // Vérifier le quorum
-        let total_validators = self.consensus.active_validators_count();
-        if total_validators > 0 && !block.has_quorum(total_validators) {
-            return Err("Block does not have required quorum".to_string());
-        }
-
         // Calculer le hash
         let hash = block.calculate_hash();
         
-        // Appliquer les transactions
-        self.apply_transactions(&block.transactions)?;
-        
-        // Distribuer le DU si nécessaire
-        self.distribute_du_if_needed()?;
+        if self.finalized_state.is_none() && expected_height == self.finalized_height + 1 {
+            self.finalized_state = Some(self.state.clone());
+        }
+        self.apply_block(&block)?;
         
         // Ajouter le bloc
         self.blocks.push(block);
@@ -130,161 +415,2211 @@ impl Blockchain {
         Ok(hash)
     }
 
-    /// Appliquer les transactions d'un bloc
-    fn apply_transactions(&mut self, transactions: &[Transaction]) -> Result<(), String> {
-        for tx in transactions {
-            // Vérifier que l'expéditeur a suffisamment de fonds
-            let sender_balance = self.state.balances.get(&tx.from)
-                .copied()
-                .unwrap_or(0.0);
-            
-            let total_needed = tx.amount + tx.fee;
-            if sender_balance < total_needed {
-                return Err(format!("Insufficient balance: need {}, have {}", 
-                    total_needed, sender_balance));
-            }
-
-            // Débiter l'expéditeur
-            *self.state.balances.entry(tx.from).or_insert(0.0) -= total_needed;
-            
-            // Créditer le destinataire
-            *self.state.balances.entry(tx.to).or_insert(0.0) += tx.amount;
-            
-            // Les frais sont brûlés (réduisent la masse monétaire)
-            self.state.total_mass -= tx.fee;
+    /// Appliquer à l'état un bloc de structure vérifiée, à la hauteur
+    /// suivant l'état
+    ///
+    /// Comité et quorum, puis transactions (annulées par le journal si l'une
+    /// échoue), échéances, validateurs et DU.
+    fn apply_block(&mut self, block: &Block) -> Result<(), String> {
+        // Vérifier le comité de la hauteur et son quorum; relever les
+        // proposeurs absents des rounds sans décision
+        let height = block.header.height;
+        let mut missed_proposers = Vec::new();
+        let consensus = &self.state.consensus;
+        if consensus.expected_committee_size(height) > 0 {
+            let seed = self.selection_seed(height)
+                .ok_or("Missing epoch seed block")?;
+            consensus.verify_block_committee(block, &seed)?;
+            missed_proposers = consensus.missed_proposers(block, &seed)?;
         }
         
+        self.journal = Some(StateJournal::new(&self.state));
+        let result = self.apply_transactions(height, block.header.timestamp, &block.transactions);
+        self.end_journal(result.is_err());
+        result?;
+        self.process_scheduled(height, block.header.timestamp);
+        
+        self.update_validators(block, &missed_proposers);
+        
+        // Distribuer le DU si nécessaire
+        self.distribute_du_if_needed(block.header.timestamp);
         Ok(())
     }
 
-    /// Distribuer le Dividende Universel si nécessaire
-    fn distribute_du_if_needed(&mut self) -> Result<(), String> {
-        let now = chrono::Utc::now().timestamp();
-        
-        // Distribuer le DU quotidiennement
-        // (Dans une vraie implémentation, on vérifierait si un jour s'est écoulé)
-        let days_since = (now - self.state.last_du_distribution) / 86400;
-        
-        if days_since >= 1 {
-            let du = self.du_calculator.calculate_current_du(
-                self.state.total_mass,
-                self.state.member_count,
-            );
-            
-            // Distribuer le DU à tous les membres
-            if self.state.member_count > 0 && du > 0.0 {
-                let du_per_member = du;
-                let total_du = du_per_member * (self.state.member_count as f64);
-                
-                // Créditer chaque membre
-                for (_, balance) in self.state.balances.iter_mut() {
-                    *balance += du_per_member;
-                }
-                
-                // Mettre à jour la masse monétaire
-                self.state.total_mass += total_du;
+    /// Clore le journal du bloc, en annulant ses transactions si demandé
+    fn end_journal(&mut self, undo: bool) {
+        if let Some(journal) = self.journal.take() {
+            if undo {
+                journal.undo(&mut self.state);
             }
-            
-            self.state.last_du_distribution = now;
+        }
+    }
+
+    /// Conserver un bloc d'une branche concurrente et réorganiser la chaîne
+    /// si cette branche devient la plus lourde
+    ///
+    /// La structure, les signatures et le comité du bloc sont vérifiés ici,
+    /// pour que seules des signatures de comité pèsent dans le choix de la
+    /// branche; les transactions le sont quand la branche est appliquée.
+    fn add_fork_block(&mut self, mut block: Block) -> Result<Hash, String> {
+        let hash = block.calculate_hash();
+        if self.side_blocks.contains_key(&hash) || self.is_canonical(&hash, block.header.height) {
+            return Err("Block already known".to_string());
+        }
+        let previous_hash = block.header.previous_hash;
+        let parent_height = self.block_height(&previous_hash)
+            .ok_or("Unknown parent block")?;
+        if block.header.height != parent_height + 1 {
+            return Err(format!("Invalid block height: expected {}, got {}",
+                parent_height + 1, block.header.height));
+        }
+        if block.verify(Some(previous_hash)).is_none() {
+            return Err("Invalid block".to_string());
         }
         
-        Ok(())
+        self.side_blocks.insert(hash, block);
+        let Some((fork_height, branch)) = self.branch_of(hash) else {
+            self.side_blocks.remove(&hash);
+            return Err("Unknown parent block".to_string());
+        };
+        if fork_height < self.finalized_height {
+            self.side_blocks.remove(&hash);
+            return Err("Block conflicts with a finalized block".to_string());
+        }
+        if let Err(e) = self.verify_fork_committee(&self.side_blocks[&hash], fork_height) {
+            self.side_blocks.remove(&hash);
+            return Err(e);
+        }
+        
+        if self.is_heavier(fork_height, &branch) {
+            self.reorganize(fork_height, &branch)?;
+        }
+        Ok(hash)
     }
 
-    /// Créer un checkpoint
-    fn create_checkpoint(&mut self) {
-        if let Some(block) = self.blocks.last() {
-            let state_hash = self.calculate_state_hash();
-            let checkpoint = Checkpoint::from_block(
-                block,
-                state_hash,
-                self.checkpoint_config.interval,
-            );
-            self.checkpoints.push(checkpoint);
+    /// Vérifier le comité et le quorum d'un bloc d'une branche
+    ///
+    /// Le calendrier de l'époque du bloc est celui de la chaîne principale:
+    /// une branche qui remplace le bloc d'ancrage de l'époque (final dès son
+    /// ajout) est refusée.
+    fn verify_fork_committee(&self, block: &Block, fork_height: u64) -> Result<(), String> {
+        let consensus = &self.state.consensus;
+        let height = block.header.height;
+        if consensus.expected_committee_size(height) == 0 {
+            return Ok(());
+        }
+        if consensus.epoch_anchor_height(consensus.epoch(height)) > fork_height {
+            return Err("Fork block replaces an epoch anchor block".to_string());
         }
+        let seed = self.selection_seed(height).ok_or("Missing epoch seed block")?;
+        consensus.verify_block_committee(block, &seed)
     }
 
-    /// Calculer le hash de l'état
-    fn calculate_state_hash(&self) -> Hash {
-        use crate::crypto::hash_data;
-        use serde_json;
-        
-        let state_data = serde_json::to_vec(&self.state.balances)
-            .expect("Serialization should never fail");
-        hash_data(&state_data)
+    /// Le bloc est-il sur la chaîne principale à cette hauteur ?
+    fn is_canonical(&self, hash: &Hash, height: u64) -> bool {
+        self.get_block(height).and_then(|b| b.hash) == Some(*hash)
     }
 
-    /// Obtenir la hauteur actuelle
-    pub fn height(&self) -> u64 {
-        self.blocks.len() as u64
+    /// Hauteur d'un bloc connu (chaîne principale ou branche)
+    fn block_height(&self, hash: &Hash) -> Option<u64> {
+        self.side_blocks.get(hash)
+            .map(|b| b.header.height)
+            .or_else(|| {
+                self.blocks.iter()
+                    .rposition(|b| b.hash == Some(*hash))
+                    .map(|i| self.first_height + i as u64)
+            })
     }
 
-    /// Obtenir le hash du dernier bloc
-    pub fn last_block_hash(&self) -> Option<Hash> {
-        self.blocks.last().and_then(|b| b.hash)
+    /// Branche d'un bloc hors de la chaîne principale: hauteur du dernier
+    /// bloc commun et hash des blocs de la branche, du plus ancien au bloc
+    fn branch_of(&self, hash: Hash) -> Option<(u64, Vec<Hash>)> {
+        let mut branch = vec![hash];
+        let mut block = self.side_blocks.get(&hash)?;
+        loop {
+            let parent = block.header.previous_hash;
+            let parent_height = block.header.height.checked_sub(1)?;
+            if self.is_canonical(&parent, parent_height) {
+                branch.reverse();
+                return Some((parent_height, branch));
+            }
+            block = self.side_blocks.get(&parent)?;
+            branch.push(parent);
+        }
     }
 
-    /// Obtenir la balance d'un wallet
-    pub fn get_balance(&self, address: &PublicKey) -> Amount {
-        self.state.balances.get(address).copied().unwrap_or(0.0)
+    /// La branche l'emporte-t-elle sur la chaîne principale depuis leur
+    /// dernier bloc commun ?
+    ///
+    /// Tous les blocs de l'arbre ont passé la vérification du comité de leur
+    /// époque (à l'application, ou à l'entrée d'une branche): chaque
+    /// signature comptée est celle d'un membre du comité du bloc.
+    fn is_heavier(&self, fork_height: u64, branch: &[Hash]) -> bool {
+        let canonical = &self.blocks[(fork_height - self.first_height) as usize + 1..];
+        let Some(first) = canonical.first() else {
+            return true;
+        };
+        let weight = |blocks: &mut dyn Iterator<Item = &Block>| {
+            blocks.map(|b| b.validator_signatures.len()).sum::<usize>()
+        };
+        let branch_weight = weight(&mut branch.iter().map(|h| &self.side_blocks[h]));
+        let canonical_weight = weight(&mut canonical.iter());
+        (branch_weight, branch.len(), Reverse(branch[0]))
+            > (canonical_weight, canonical.len(), Reverse(first.hash.unwrap_or_default()))
     }
 
-    /// Obtenir le consensus
-    pub fn consensus_mut(&mut self) -> &mut RVS {
-        &mut self.consensus
+    /// Remplacer les blocs de la chaîne principale au-dessus d'une hauteur
+    /// par une branche
+    ///
+    /// Si un bloc de la branche est invalide, la chaîne précédente est
+    /// restaurée; le bloc invalide et ses descendants sont oubliés.
+    fn reorganize(&mut self, fork_height: u64, branch: &[Hash]) -> Result<(), String> {
+        let reverted = self.revert_to(fork_height)?;
+        let mut applied = Vec::new();
+        for (i, hash) in branch.iter().enumerate() {
+            let block = self.side_blocks.remove(hash).ok_or("Missing branch block")?;
+            if let Err(e) = self.extend_chain(block.clone()) {
+                for hash in &branch[i + 1..] {
+                    self.side_blocks.remove(hash);
+                }
+                for block in self.revert_to(fork_height)? {
+                    self.side_blocks.insert(block.hash.unwrap_or_default(), block);
+                }
+                for block in reverted {
+                    self.extend_chain(block)?;
+                }
+                return Err(format!("Invalid fork block: {}", e));
+            }
+            applied.push(block);
+        }
+        
+        let included: HashSet<Hash> = applied.iter()
+            .flat_map(|b| b.transactions.iter().map(Transaction::id))
+            .collect();
+        let orphaned_transactions = reverted.iter()
+            .flat_map(|b| b.transactions.iter())
+            .filter(|tx| !included.contains(&tx.id()))
+            .cloned()
+            .collect();
+        let reverted_hashes = reverted.iter().map(|b| b.hash.unwrap_or_default()).collect();
+        for block in reverted {
+            self.side_blocks.insert(block.hash.unwrap_or_default(), block);
+        }
+        self.reorgs.push(Reorg {
+            fork_height,
+            reverted: reverted_hashes,
+            applied: branch.to_vec(),
+            orphaned_transactions,
+        });
+        Ok(())
     }
 
-    /// Obtenir le consensus (immutable)
-    pub fn consensus(&self) -> &RVS {
-        &self.consensus
+    /// Ramener la chaîne principale et l'état à une hauteur
+    ///
+    /// L'état est reconstruit depuis celui du dernier bloc final, en
+    /// rejouant les blocs jusqu'à la hauteur. Retourne les blocs retirés.
+    fn revert_to(&mut self, height: u64) -> Result<Vec<Block>, String> {
+        if height < self.finalized_height {
+            return Err("Cannot revert a finalized block".to_string());
+        }
+        let start = (self.finalized_height - self.first_height) as usize + 1;
+        if self.blocks.len() <= start {
+            return Ok(Vec::new());
+        }
+        let state = self.finalized_state.clone().ok_or("Missing finalized state")?;
+        
+        let mut replay = self.blocks.split_off(start);
+        let removed = replay.split_off((height - self.finalized_height) as usize);
+        self.state = state;
+        self.checkpoints.retain(|c| c.height <= height);
+        self.checkpoint_states.retain(|h, _| *h <= height);
+        for block in replay {
+            self.extend_chain(block)?;
+        }
+        Ok(removed)
     }
 
-    /// Obtenir un bloc par hauteur
-    pub fn get_block(&self, height: u64) -> Option<&Block> {
-        self.blocks.get(height as usize)
+    /// Relever les réorganisations survenues depuis le dernier appel
+    pub fn take_reorgs(&mut self) -> Vec<Reorg> {
+        std::mem::take(&mut self.reorgs)
     }
 
-    /// Obtenir le dernier checkpoint
-    pub fn last_checkpoint(&self) -> Option<&Checkpoint> {
-        self.checkpoints.last()
+    /// Obtenir un bloc connu par hash (chaîne principale ou branche)
+    pub fn get_block_by_hash(&self, hash: &Hash) -> Option<&Block> {
+        self.side_blocks.get(hash)
+            .or_else(|| self.blocks.iter().rev().find(|b| b.hash == Some(*hash)))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::crypto::keys::KeyPair;
+    /// Mettre à jour les validateurs après l'application d'un bloc
+    ///
+    /// Rotations de clé, sanctions des équivocations prouvées dans le bloc,
+    /// réintégration des validateurs dont l'exclusion finit à la hauteur
+    /// suivante, puis activité, statistiques et réputation du producteur,
+    /// des signataires et des proposeurs absents. Ces
+    /// changements du registre courant ne s'appliquent aux comités qu'à
+    /// l'époque suivante.
+    fn update_validators(&mut self, block: &Block, missed_proposers: &[PublicKey]) {
+        let height = block.header.height;
+        let consensus = &mut self.state.consensus;
+        for rotation in self.state.accounts.rotations_at(height) {
+            consensus.rotate_validator_key(&rotation.old_key, rotation.new_key);
+        }
+        for offence in self.state.slashing.offences_at(height) {
+            consensus.jail_validator(&offence.validator, offence.jailed_until);
+        }
+        consensus.release_validators(height + 1);
+        
+        // Le producteur qui signe aussi le bloc n'est compté qu'une fois
+        let mut active: Vec<PublicKey> = block.validator_signatures.iter().map(|vs| vs.validator).collect();
+        if !active.contains(&block.header.validator) {
+            active.push(block.header.validator);
+        }
+        consensus.record_liveness(height, &active, missed_proposers);
+        consensus.record_participation(&active, missed_proposers);
+        
+        // Dernier bloc de l'époque: figer l'ensemble de la suivante
+        let next_epoch = consensus.epoch(height) + 1;
+        if height == consensus.epoch_anchor_height(next_epoch) {
+            consensus.begin_epoch(next_epoch);
+        }
+    }
 
-    #[test]
-    fn test_blockchain_creation() {
-        let blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
-        assert_eq!(blockchain.height(), 0);
+    /// Appliquer les transactions d'un bloc
+    fn apply_transactions(
+        &mut self,
+        block_height: u64,
+        block_timestamp: Timestamp,
+        transactions: &[Transaction],
+    ) -> Result<(), String> {
+        for tx in transactions {
+            let tx = self.resolve_accounts(tx)?;
+            let tx = tx.as_ref();
+            if let Some(journal) = &mut self.journal {
+                journal.save_registries(&self.state, tx);
+            }
+            
+            match tx.metadata.transaction_type {
+                TransactionType::NFT => {
+                    self.apply_nft_operation(block_height, block_timestamp, tx)?
+                }
+                TransactionType::ShopPurchase if tx.metadata.extra_data.is_some() => {
+                    self.apply_escrow_operation(block_timestamp, tx)?
+                }
+                TransactionType::Escrow => self.apply_escrow_operation(block_timestamp, tx)?,
+                TransactionType::StandingOrder => {
+                    self.apply_standing_order_operation(block_height, block_timestamp, tx)?
+                }
+                TransactionType::PaymentChannel => {
+                    self.apply_channel_operation(block_timestamp, tx)?
+                }
+                TransactionType::KeyRotation => {
+                    self.apply_key_rotation_operation(block_height, block_timestamp, tx)?
+                }
+                TransactionType::Recovery => self.apply_recovery_operation(block_timestamp, tx)?,
+                TransactionType::Evidence => self.apply_evidence(block_height, tx)?,
+                TransactionType::Validator => self.apply_validator_operation(block_height, tx)?,
+                _ => self.apply_transfer(tx)?,
+            }
+        }
+        
+        Ok(())
     }
 
-    #[test]
-    fn test_genesis_block() {
-        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
-        let keypair = KeyPair::new();
+    /// Rapporter une transaction aux identités des comptes
+    ///
+    /// La clé signataire doit être la clé de contrôle courante de son
+    /// compte. `from` et `to` sont remplacés par les identités des comptes;
+    /// l'ID de la transaction reste celui de la transaction signée.
+    fn resolve_accounts<'a>(&mut self, tx: &'a Transaction) -> Result<Cow<'a, Transaction>, String> {
+        let accounts = &mut self.state.accounts;
+        let from = accounts.authorize(&tx.from)
+            .map_err(|e| format!("Transaction rejected: {}", e))?;
+        let to = accounts.resolve(&tx.to);
+        if let Some(journal) = self.journal.as_mut().filter(|j| j.account_registry.is_none()) {
+            journal.accounts.entry(from).or_insert_with(|| accounts.get(&from).cloned());
+        }
+        accounts.record_transaction(&from);
         
-        let result = blockchain.create_genesis(*keypair.public_key());
-        assert!(result.is_ok());
-        assert_eq!(blockchain.height(), 1);
+        if from == tx.from && to == tx.to {
+            return Ok(Cow::Borrowed(tx));
+        }
+        
+        let mut resolved = tx.clone();
+        resolved.hash = Some(tx.id());
+        resolved.from = from;
+        resolved.to = to;
+        Ok(Cow::Owned(resolved))
     }
 
-    #[test]
-    fn test_add_block() {
-        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
-        let keypair = KeyPair::new();
+    /// Appliquer un transfert simple
+    fn apply_transfer(&mut self, tx: &Transaction) -> Result<(), String> {
+        // Débiter l'expéditeur
+        self.debit(&tx.from, tx.amount + tx.fee)?;
         
-        blockchain.create_genesis(*keypair.public_key()).unwrap();
+        // Créditer le destinataire
+        self.credit(&tx.to, tx.amount);
         
-        let previous_hash = blockchain.last_block_hash().unwrap();
-        let mut block = Block::new(1, previous_hash, Vec::new(), *keypair.public_key());
-        block.calculate_hash(); // Calculer le hash avant d'ajouter
+        // Les frais sont brûlés (réduisent la masse monétaire)
+        self.state.total_mass -= tx.fee;
         
-        let result = blockchain.add_block(block);
-        assert!(result.is_ok());
-        assert_eq!(blockchain.height(), 2);
+        Ok(())
+    }
+
+    /// Appliquer une opération NFT
+    fn apply_nft_operation(
+        &mut self,
+        block_height: u64,
+        block_timestamp: Timestamp,
+        tx: &Transaction,
+    ) -> Result<(), String> {
+        let operation: NFTOperation = tx.metadata.payload()?;
+        
+        // Seul un achat transporte un paiement
+        let is_buy = matches!(operation, NFTOperation::Buy { .. });
+        if !is_buy && tx.amount > 0.0 {
+            return Err("NFT operation must not carry an amount".to_string());
+        }
+        
+        // Débiter le paiement et les frais (brûlés)
+        self.debit(&tx.from, tx.amount + tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let tx_hash = tx.id();
+        
+        let nfts = &mut self.state.nfts;
+        nfts.set_context(NFTEventContext {
+            block_height,
+            tx_hash,
+            timestamp: block_timestamp,
+        });
+        
+        let result = match operation {
+            NFTOperation::Mint { metadata, royalty_percentage } => {
+                nfts.mint(tx.from, metadata, royalty_percentage).map(|_| None)
+            }
+            NFTOperation::List { nft_id, price, duration_days, marketplace_fee } => {
+                nfts.list_for_sale_via(&nft_id, &tx.from, price, duration_days, marketplace_fee)
+                    .map(|_| None)
+            }
+            NFTOperation::Delist { nft_id } => nfts.delist(&nft_id, &tx.from).map(|_| None),
+            NFTOperation::Buy { nft_id } => nfts.buy_nft(&nft_id, &tx.from, tx.amount).map(Some),
+            NFTOperation::Transfer { nft_id } => {
+                nfts.transfer(&nft_id, &tx.from, &tx.to).map(|_| None)
+            }
+            NFTOperation::Burn { nft_id } => nfts.burn(&nft_id, &tx.from).map(|_| None),
+        };
+        nfts.clear_context();
+        
+        let sale = result.map_err(|e| format!("NFT operation failed: {}", e))?;
+        
+        // Répartir le paiement entre vendeur, créateur et place de marché
+        if let Some(sale) = sale {
+            self.credit(&sale.seller, sale.seller_receives);
+            self.credit(&sale.creator, sale.creator_receives);
+            if let Some(marketplace) = sale.marketplace {
+                self.credit(&marketplace, sale.marketplace_receives);
+            }
+        }
+        
+        Ok(())
+    }
+
+    /// Appliquer une opération de séquestre
+    ///
+    /// L'ouverture est portée par un `ShopPurchase` (montant bloqué, `to` =
+    /// marchand); les transitions suivantes par une transaction `Escrow`.
+    fn apply_escrow_operation(
+        &mut self,
+        block_timestamp: Timestamp,
+        tx: &Transaction,
+    ) -> Result<(), String> {
+        let operation: EscrowOperation = tx.metadata.payload()?;
+        let is_open = matches!(operation, EscrowOperation::Open(_));
+        
+        if is_open != (tx.metadata.transaction_type == TransactionType::ShopPurchase) {
+            return Err("Escrow must be opened by a ShopPurchase transaction".to_string());
+        }
+        if !is_open && tx.amount > 0.0 {
+            return Err("Escrow operation must not carry an amount".to_string());
+        }
+        
+        // Débiter le montant bloqué et les frais (brûlés)
+        self.debit(&tx.from, tx.amount + tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let escrows = &mut self.state.escrows;
+        let payouts = match operation {
+            EscrowOperation::Open(mut terms) => {
+                let escrow_id = tx.id();
+                terms.arbiter = self.state.accounts.resolve(&terms.arbiter);
+                escrows.open(escrow_id, tx.from, tx.to, tx.amount, terms, block_timestamp)
+                    .map(|_| Vec::new())
+            }
+            EscrowOperation::Confirm { escrow_id } => {
+                escrows.confirm(&escrow_id, &tx.from).map(|payout| vec![payout])
+            }
+            EscrowOperation::Refund { escrow_id } => {
+                escrows.refund(&escrow_id, &tx.from).map(|payout| vec![payout])
+            }
+            EscrowOperation::Dispute { escrow_id } => {
+                escrows.dispute(&escrow_id, &tx.from, block_timestamp).map(|_| Vec::new())
+            }
+            EscrowOperation::Resolve { escrow_id, merchant_amount } => {
+                escrows.resolve(&escrow_id, &tx.from, merchant_amount)
+            }
+        }
+        .map_err(|e| format!("Escrow operation failed: {}", e))?;
+        
+        for payout in payouts {
+            self.credit(&payout.to, payout.amount);
+        }
+        
+        Ok(())
+    }
+
+    /// Appliquer une opération sur un ordre permanent
+    ///
+    /// La création ne débite rien: les échéances sont prélevées par
+    /// `process_scheduled`, y compris la première si elle est immédiate.
+    fn apply_standing_order_operation(
+        &mut self,
+        block_height: u64,
+        block_timestamp: Timestamp,
+        tx: &Transaction,
+    ) -> Result<(), String> {
+        let operation: StandingOrderOperation = tx.metadata.payload()?;
+        if tx.amount > 0.0 {
+            return Err("Standing order operation must not carry an amount".to_string());
+        }
+        
+        // Débiter les frais (brûlés)
+        self.debit(&tx.from, tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let standing_orders = &mut self.state.standing_orders;
+        match operation {
+            StandingOrderOperation::Create(terms) => {
+                let order_id = tx.id();
+                standing_orders
+                    .create(order_id, tx.from, tx.to, terms, block_height, block_timestamp)
+                    .map(|_| ())
+            }
+            StandingOrderOperation::Cancel { order_id } => {
+                standing_orders.cancel(&order_id, &tx.from, block_height, block_timestamp)
+            }
+        }
+        .map_err(|e| format!("Standing order operation failed: {}", e))
     }
-}
 
+    /// Appliquer une opération de canal de paiement
+    ///
+    /// Seules l'ouverture et la recharge transportent un montant (le dépôt).
+    fn apply_channel_operation(
+        &mut self,
+        block_timestamp: Timestamp,
+        tx: &Transaction,
+    ) -> Result<(), String> {
+        let operation: ChannelOperation = tx.metadata.payload()?;
+        let carries_deposit = matches!(operation, ChannelOperation::Open(_) | ChannelOperation::TopUp { .. });
+        if !carries_deposit && tx.amount > 0.0 {
+            return Err("Channel operation must not carry an amount".to_string());
+        }
+        
+        // Débiter le dépôt et les frais (brûlés)
+        self.debit(&tx.from, tx.amount + tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let channels = &mut self.state.channels;
+        let accounts = &self.state.accounts;
+        let settlement = match operation {
+            ChannelOperation::Open(terms) => {
+                let channel_id = tx.id();
+                channels.open(channel_id, tx.from, tx.to, tx.amount, terms, block_timestamp)
+                    .map(|_| None)
+            }
+            ChannelOperation::TopUp { channel_id } => {
+                channels.top_up(&channel_id, &tx.from, tx.amount).map(|_| None)
+            }
+            ChannelOperation::Close { update } => {
+                let payer_key = channel_payer_key(channels, accounts, &update.update.channel_id);
+                channels.close(&tx.from, &update, &payer_key).map(Some)
+            }
+            ChannelOperation::StartClose { channel_id, update } => {
+                let payer_key = channel_payer_key(channels, accounts, &channel_id);
+                channels.start_close(&channel_id, &tx.from, update.as_ref(), &payer_key, block_timestamp)
+                    .map(|_| None)
+            }
+        }
+        .map_err(|e| format!("Channel operation failed: {}", e))?;
+        
+        if let Some(settlement) = settlement {
+            self.credit_settlement(settlement);
+        }
+        
+        Ok(())
+    }
+
+    /// Appliquer une opération de rotation de clé (signée par la clé courante)
+    fn apply_key_rotation_operation(
+        &mut self,
+        block_height: u64,
+        block_timestamp: Timestamp,
+        tx: &Transaction,
+    ) -> Result<(), String> {
+        let operation: KeyRotationOperation = tx.metadata.payload()?;
+        if tx.amount > 0.0 {
+            return Err("Key rotation must not carry an amount".to_string());
+        }
+        
+        // Débiter les frais (brûlés)
+        self.debit(&tx.from, tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let accounts = &mut self.state.accounts;
+        let rotation = match operation {
+            KeyRotationOperation::Rotate { new_key, proof } => {
+                if key_in_use(&self.state.members, &self.state.consensus, &new_key) {
+                    return Err("Key rotation failed: Key is already bound to an account".to_string());
+                }
+                accounts.request_rotation(&tx.from, new_key, &proof, block_height, block_timestamp)
+            }
+            KeyRotationOperation::Cancel => accounts.cancel_rotation(&tx.from).map(|_| None),
+            KeyRotationOperation::SetDelay { delay_secs } => {
+                accounts.set_rotation_delay(&tx.from, delay_secs, block_timestamp).map(|_| None)
+            }
+        }
+        .map_err(|e| format!("Key rotation failed: {}", e))?;
+        
+        if let Some(rotation) = rotation {
+            self.absorb_new_key_balance(&rotation);
+        }
+        
+        Ok(())
+    }
+
+    /// Appliquer une preuve d'équivocation
+    ///
+    /// N'importe quel compte peut la soumettre. Le fautif est sanctionné
+    /// sous la clé courante de son compte, à l'application du bloc.
+    fn apply_evidence(&mut self, block_height: u64, tx: &Transaction) -> Result<(), String> {
+        let evidence: DoubleSignEvidence = tx.metadata.payload()?;
+        if tx.amount > 0.0 {
+            return Err("Evidence must not carry an amount".to_string());
+        }
+        
+        // Débiter les frais (brûlés)
+        self.debit(&tx.from, tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let accounts = &self.state.accounts;
+        let validator = accounts.controller(&accounts.resolve(&evidence.offender()));
+        self.state.consensus.check_evidence(&evidence, &validator, block_height)
+            .and_then(|jailed_until| {
+                self.state.slashing.record(&evidence, validator, block_height, jailed_until).map(|_| ())
+            })
+            .map_err(|e| format!("Evidence rejected: {}", e))
+    }
+
+    /// Appliquer une opération de validateur
+    ///
+    /// Le validateur est la clé de contrôle du compte émetteur; seuls les
+    /// membres peuvent s'enregistrer.
+    fn apply_validator_operation(&mut self, block_height: u64, tx: &Transaction) -> Result<(), String> {
+        let operation: ValidatorOperation = tx.metadata.payload()?;
+        if tx.amount > 0.0 {
+            return Err("Validator operation must not carry an amount".to_string());
+        }
+        
+        // Débiter les frais (brûlés)
+        self.debit(&tx.from, tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let key = self.state.accounts.controller(&tx.from);
+        let consensus = &mut self.state.consensus;
+        match operation {
+            ValidatorOperation::Register if !self.state.members.contains(&tx.from) => {
+                Err("Validator registration is restricted to members".to_string())
+            }
+            ValidatorOperation::Register => consensus.register_validator_at(key, tx.from, block_height),
+            ValidatorOperation::Unregister => consensus.unregister_validator(&key),
+            ValidatorOperation::Heartbeat => consensus.heartbeat(&key, block_height),
+        }
+        .map_err(|e| format!("Validator operation failed: {}", e))
+    }
+
+    /// Appliquer une opération de récupération sociale
+    ///
+    /// La configuration et l'annulation sont signées par la clé courante du
+    /// compte; une approbation est signée par un gardien (`to` = compte). La
+    /// récupération elle-même s'exécute à l'échéance (`process_scheduled`).
+    fn apply_recovery_operation(&mut self, block_timestamp: Timestamp, tx: &Transaction) -> Result<(), String> {
+        let operation: RecoveryOperation = tx.metadata.payload()?;
+        if tx.amount > 0.0 {
+            return Err("Recovery operation must not carry an amount".to_string());
+        }
+        let is_guardian_approval = matches!(operation, RecoveryOperation::Approve { .. });
+        if !is_guardian_approval && tx.from != tx.to {
+            return Err("Recovery settings must be changed by the account itself".to_string());
+        }
+        
+        // Débiter les frais (brûlés)
+        self.debit(&tx.from, tx.fee)?;
+        self.state.total_mass -= tx.fee;
+        
+        let accounts = &mut self.state.accounts;
+        match operation {
+            RecoveryOperation::Configure(config) => accounts.configure_recovery(&tx.from, config),
+            RecoveryOperation::Disable => accounts.disable_recovery(&tx.from),
+            RecoveryOperation::Approve { new_key, proof } => {
+                // Même règle que pour une rotation
+                if key_in_use(&self.state.members, &self.state.consensus, &new_key) {
+                    return Err("Recovery failed: Key is already bound to an account".to_string());
+                }
+                accounts.approve_recovery(&tx.to, &tx.from, new_key, &proof, block_timestamp).map(|_| ())
+            }
+            RecoveryOperation::Cancel => accounts.cancel_recovery(&tx.from),
+        }
+        .map_err(|e| format!("Recovery failed: {}", e))
+    }
+
+    /// Rattacher au compte les fonds reçus par la nouvelle clé avant la rotation
+    fn absorb_new_key_balance(&mut self, rotation: &KeyRotation) {
+        self.journal_balance(&rotation.new_key);
+        if let Some(amount) = self.state.balances.remove(&rotation.new_key) {
+            self.credit(&rotation.account, amount);
+        }
+    }
+
+    /// Créditer un compte (adresse rapportée à l'identité du compte)
+    fn credit(&mut self, address: &PublicKey, amount: Amount) {
+        let account = self.state.accounts.resolve(address);
+        self.journal_balance(&account);
+        *self.state.balances.entry(account).or_insert(0.0) += amount;
+    }
+
+    /// Relever dans le journal le solde d'avant le bloc
+    fn journal_balance(&mut self, address: &PublicKey) {
+        if let Some(journal) = &mut self.journal {
+            journal.balances.entry(*address).or_insert_with(|| self.state.balances.get(address).copied());
+        }
+    }
+
+    /// Créditer les deux parties d'un canal réglé
+    fn credit_settlement(&mut self, settlement: ChannelSettlement) {
+        for (address, amount) in [settlement.payee, settlement.payer] {
+            if amount > 0.0 {
+                self.credit(&address, amount);
+            }
+        }
+    }
+
+    /// Exécuter les échéances du bloc (libération des séquestres expirés,
+    /// paiements des ordres permanents, règlement des canaux en clôture)
+    fn process_scheduled(&mut self, block_height: u64, block_timestamp: Timestamp) {
+        for (_, payout) in self.state.escrows.release_expired(block_timestamp) {
+            self.credit(&payout.to, payout.amount);
+        }
+        
+        // Une échéance sans provision est consignée par le registre, sans
+        // faire échouer le bloc
+        let balances = &mut self.state.balances;
+        let accounts = &self.state.accounts;
+        self.state.standing_orders.execute_due(block_height, block_timestamp, |payer, payee, amount| {
+            match balances.get_mut(payer) {
+                Some(balance) if *balance >= amount => *balance -= amount,
+                _ => return false,
+            }
+            *balances.entry(accounts.resolve(payee)).or_insert(0.0) += amount;
+            true
+        });
+        
+        for (_, settlement) in self.state.channels.settle_expired(block_timestamp) {
+            self.credit_settlement(settlement);
+        }
+        
+        for rotation in self.state.accounts.execute_due(block_height, block_timestamp) {
+            self.absorb_new_key_balance(&rotation);
+        }
+    }
+
+    /// Débiter un compte en vérifiant qu'il a suffisamment de fonds
+    fn debit(&mut self, address: &PublicKey, amount: Amount) -> Result<(), String> {
+        let balance = self.state.balances.get(address)
+            .copied()
+            .unwrap_or(0.0);
+        
+        if balance < amount {
+            return Err(format!("Insufficient balance: need {}, have {}", 
+                amount, balance));
+        }
+        
+        self.journal_balance(address);
+        *self.state.balances.entry(*address).or_insert(0.0) -= amount;
+        Ok(())
+    }
+
+    /// Distribuer le Dividende Universel si un jour s'est écoulé depuis la
+    /// dernière distribution
+    ///
+    /// Le temps est celui des blocs, identique sur tous les nœuds: l'horloge
+    /// locale n'entre pas dans l'état. Le premier bloc après la genèse
+    /// démarre le décompte.
+    fn distribute_du_if_needed(&mut self, block_timestamp: Timestamp) {
+        if self.state.last_du_distribution == 0 {
+            self.state.last_du_distribution = block_timestamp;
+            return;
+        }
+        
+        // Distribuer le DU quotidiennement
+        let days_since = (block_timestamp - self.state.last_du_distribution) / 86400;
+        
+        if days_since >= 1 {
+            let du = self.du_calculator.calculate_du(
+                self.state.total_mass,
+                self.state.member_count,
+                block_timestamp,
+            );
+            
+            // Distribuer le DU à tous les membres
+            if self.state.member_count > 0 && du > 0.0 {
+                let du_per_member = du;
+                let total_du = du_per_member * (self.state.member_count as f64);
+                
+                // Créditer chaque membre
+                for (_, balance) in self.state.balances.iter_mut() {
+                    *balance += du_per_member;
+                }
+                
+                // Mettre à jour la masse monétaire
+                self.state.total_mass += total_du;
+            }
+            
+            self.state.last_du_distribution = block_timestamp;
+        }
+    }
+
+    /// Créer un checkpoint
+    ///
+    /// Un checkpoint conservé lors d'une réorganisation n'est pas recréé:
+    /// ses signatures restent valides.
+    fn create_checkpoint(&mut self) {
+        if let Some(block) = self.blocks.last() {
+            if self.checkpoint(block.header.height).is_some() {
+                return;
+            }
+            self.checkpoint_states.insert(block.header.height, self.state.clone());
+            // Au-delà du plafond, les états les plus anciens sont retirés; ils
+            // sont reconstruits si leur checkpoint devient final
+            while self.checkpoint_states.len() > MAX_PENDING_CHECKPOINT_STATES {
+                self.checkpoint_states.pop_first();
+            }
+            let state_hash = self.state.state_root();
+            let checkpoint = Checkpoint::from_block(
+                block,
+                state_hash,
+                self.checkpoint_config.interval,
+            );
+            self.checkpoints.push(checkpoint);
+        }
+    }
+
+    /// Ajouter la signature d'un membre du comité à un checkpoint
+    ///
+    /// Retourne `true` si le checkpoint devient final: son certificat et ses
+    /// signatures atteignent le quorum de l'époque.
+    pub fn add_checkpoint_signature(
+        &mut self,
+        height: u64,
+        validator: PublicKey,
+        signature: Signature,
+    ) -> Result<bool, String> {
+        let schedule = self.epoch_schedule(self.state.consensus.epoch(height))
+            .ok_or("Epoch schedule not available")?;
+        let checkpoint = self.checkpoints.iter_mut()
+            .find(|c| c.height == height)
+            .ok_or("Checkpoint not found")?;
+        checkpoint.add_signature(validator, signature)
+            .map_err(|e| format!("Checkpoint signature rejected: {}", e))?;
+        
+        if height > self.finalized_height && checkpoint.verify(&schedule).is_ok() {
+            self.finalize(height);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// Rendre final le bloc d'un checkpoint: les branches qui ne le
+    /// contiennent pas sont abandonnées
+    fn finalize(&mut self, height: u64) {
+        let state = match self.checkpoint_states.remove(&height) {
+            Some(state) => Some(state),
+            None => self.rebuild_state(height),
+        };
+        self.finalized_height = height;
+        self.finalized_state = state;
+        self.finalized_chunks = self.finalized_state.as_ref()
+            .map(BlockchainState::snapshot_chunks)
+            .unwrap_or_default();
+        self.checkpoint_states.retain(|h, _| *h > height);
+        self.side_blocks.retain(|_, b| b.header.height > height);
+    }
+
+    /// Reconstruire l'état à une hauteur de la chaîne principale en
+    /// rejouant les blocs depuis l'état du dernier bloc final
+    ///
+    /// Utilisé pour un checkpoint dont l'état a été retiré
+    /// (`MAX_PENDING_CHECKPOINT_STATES`); l'état courant est conservé.
+    fn rebuild_state(&mut self, height: u64) -> Option<BlockchainState> {
+        let start = (self.finalized_height - self.first_height) as usize + 1;
+        let end = (height - self.first_height) as usize + 1;
+        let replay = self.blocks.get(start..end)?.to_vec();
+        let base = self.finalized_state.clone()?;
+        
+        let current = std::mem::replace(&mut self.state, base);
+        let result = replay.iter().try_for_each(|block| self.apply_block(block));
+        let rebuilt = std::mem::replace(&mut self.state, current);
+        result.ok().map(|_| rebuilt)
+    }
+
+    /// Obtenir la hauteur actuelle
+    pub fn height(&self) -> u64 {
+        self.first_height + self.blocks.len() as u64
+    }
+
+    /// Obtenir le hash du dernier bloc
+    pub fn last_block_hash(&self) -> Option<Hash> {
+        self.blocks.last().and_then(|b| b.hash)
+    }
+
+    /// Graine de sélection du comité pour une hauteur
+    ///
+    /// Dérivée du hash du dernier bloc de l'époque précédente; `None` si ce
+    /// bloc n'est pas encore connu.
+    pub fn selection_seed(&self, height: u64) -> Option<Hash> {
+        let epoch = self.state.consensus.epoch(height);
+        let anchor = self.get_block(self.state.consensus.epoch_anchor_height(epoch))?;
+        Some(RVS::epoch_seed(epoch, &anchor.hash?))
+    }
+
+    /// Calendrier d'une époque (`None` si son ensemble n'est pas encore
+    /// figé ou n'est plus conservé)
+    pub fn epoch_schedule(&self, epoch: u64) -> Option<EpochSchedule> {
+        let consensus = &self.state.consensus;
+        let seed = self.selection_seed(epoch * consensus.config().epoch_length.max(1))?;
+        consensus.epoch_schedule(epoch, seed)
+    }
+
+    /// Proposeur attendu d'un round à une hauteur
+    pub fn proposer(&self, height: u64, round: u32) -> Option<PublicKey> {
+        let seed = self.selection_seed(height)?;
+        self.state.consensus.proposer(&seed, height, round)
+    }
+
+    /// Obtenir la balance d'un wallet (clé courante, ancienne clé ou identité du compte)
+    pub fn get_balance(&self, address: &PublicKey) -> Amount {
+        let account = self.state.accounts.resolve(address);
+        self.state.balances.get(&account).copied().unwrap_or(0.0)
+    }
+
+    /// Identité du compte désigné par une adresse
+    pub fn resolve_account(&self, address: &PublicKey) -> PublicKey {
+        self.state.accounts.resolve(address)
+    }
+
+    /// Obtenir le registre des comptes
+    pub fn accounts(&self) -> &AccountRegistry {
+        &self.state.accounts
+    }
+
+    /// Admettre un membre (paramètre de genèse, en attendant une toile de
+    /// confiance on-chain)
+    pub fn add_member(&mut self, address: &PublicKey) {
+        let account = self.state.accounts.resolve(address);
+        self.state.members.insert(account);
+        self.state.member_count = self.state.members.len() as u64;
+    }
+
+    /// L'adresse désigne-t-elle un membre ?
+    pub fn is_member(&self, address: &PublicKey) -> bool {
+        self.state.members.contains(&self.state.accounts.resolve(address))
+    }
+
+    /// Obtenir le registre des fautes sanctionnées
+    pub fn slashing(&self) -> &SlashingRegistry {
+        &self.state.slashing
+    }
+
+    /// Obtenir le consensus
+    pub fn consensus_mut(&mut self) -> &mut RVS {
+        &mut self.state.consensus
+    }
+
+    /// Obtenir le consensus (immutable)
+    pub fn consensus(&self) -> &RVS {
+        &self.state.consensus
+    }
+
+    /// Obtenir le contrat NFT
+    pub fn nfts(&self) -> &NFTContract {
+        &self.state.nfts
+    }
+
+    /// Obtenir le registre des séquestres
+    pub fn escrows(&self) -> &EscrowRegistry {
+        &self.state.escrows
+    }
+
+    /// Obtenir le registre des canaux de paiement
+    pub fn channels(&self) -> &ChannelRegistry {
+        &self.state.channels
+    }
+
+    /// Obtenir le registre des ordres permanents
+    pub fn standing_orders(&self) -> &StandingOrderRegistry {
+        &self.state.standing_orders
+    }
+
+    /// Configurer les commissions de marché (paramètre de consensus)
+    pub fn set_marketplace_config(&mut self, config: MarketplaceConfig) -> Result<(), String> {
+        self.state.nfts.set_marketplace_config(config)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Obtenir le checkpoint d'une hauteur
+    pub fn checkpoint(&self, height: u64) -> Option<&Checkpoint> {
+        self.checkpoints.iter().find(|c| c.height == height)
+    }
+
+    /// Dernier checkpoint final (signé par un quorum)
+    pub fn finalized_checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoint(self.finalized_height).filter(|_| self.finalized_height > 0)
+    }
+
+    /// Hauteur du dernier bloc final
+    pub fn finalized_height(&self) -> u64 {
+        self.finalized_height
+    }
+
+    /// Manifeste de l'instantané du dernier checkpoint final
+    pub fn snapshot_manifest(&self) -> Option<SnapshotManifest> {
+        let checkpoint = self.finalized_checkpoint()?;
+        if self.finalized_chunks.is_empty() {
+            return None;
+        }
+        let consensus = &self.state.consensus;
+        let anchor = consensus.epoch_anchor_height(consensus.epoch(checkpoint.height));
+        let blocks = (anchor..=checkpoint.height)
+            .map(|height| self.get_block(height).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        Some(SnapshotManifest {
+            checkpoint: checkpoint.clone(),
+            blocks,
+            chunks: self.finalized_chunks.iter().map(SnapshotChunk::hash).collect(),
+        })
+    }
+
+    /// Bloc de données de l'instantané du dernier checkpoint final
+    pub fn snapshot_chunk(&self, index: usize) -> Option<&SnapshotChunk> {
+        self.finalized_chunks.get(index)
+    }
+
+    /// Instantané complet du dernier checkpoint final (export)
+    pub fn snapshot(&self) -> Option<StateSnapshot> {
+        Some(StateSnapshot {
+            manifest: self.snapshot_manifest()?,
+            chunks: self.finalized_chunks.clone(),
+        })
+    }
+
+    /// Obtenir un bloc par hauteur
+    pub fn get_block(&self, height: u64) -> Option<&Block> {
+        let index = height.checked_sub(self.first_height)?;
+        self.blocks.get(index as usize)
+    }
+
+    /// Obtenir le dernier checkpoint
+    pub fn last_checkpoint(&self) -> Option<&Checkpoint> {
+        self.checkpoints.last()
+    }
+}
+
+/// La clé est-elle celle d'un membre ou d'un validateur ?
+///
+/// Le registre des comptes refuse de son côté les clés déjà liées ou visées
+/// par la rotation d'un autre compte. Un simple solde ne réserve pas une
+/// clé: il est rattaché au compte quand la rotation s'exécute.
+fn key_in_use(members: &HashSet<PublicKey>, consensus: &RVS, key: &PublicKey) -> bool {
+    members.contains(key) || consensus.is_validator(key)
+}
+
+/// Clé courante du payeur d'un canal (les mises à jour hors chaîne sont
+/// signées par la clé de contrôle du compte)
+fn channel_payer_key(channels: &ChannelRegistry, accounts: &AccountRegistry, id: &Hash) -> PublicKey {
+    channels.get(id)
+        .map(|channel| accounts.controller(&channel.payer))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::keys::KeyPair;
+
+    #[test]
+    fn test_blockchain_creation() {
+        let blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        assert_eq!(blockchain.height(), 0);
+    }
+
+    #[test]
+    fn test_genesis_block() {
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let keypair = KeyPair::new();
+        
+        let result = blockchain.create_genesis(*keypair.public_key());
+        assert!(result.is_ok());
+        assert_eq!(blockchain.height(), 1);
+    }
+
+    #[test]
+    fn test_add_block() {
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let keypair = KeyPair::new();
+        
+        blockchain.create_genesis(*keypair.public_key()).unwrap();
+        
+        let previous_hash = blockchain.last_block_hash().unwrap();
+        let mut block = Block::new(1, previous_hash, Vec::new(), *keypair.public_key());
+        block.calculate_hash(); // Calculer le hash avant d'ajouter
+        
+        let result = blockchain.add_block(block);
+        assert!(result.is_ok());
+        assert_eq!(blockchain.height(), 2);
+    }
+
+    #[test]
+    fn test_check_proposal_replays_transactions() {
+        use crate::transaction::TransactionMetadata;
+        
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let (payer, payee) = (KeyPair::new(), KeyPair::new());
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(*payer.public_key(), 10.0);
+        let parent = blockchain.last_block_hash().unwrap();
+        
+        let proposal = |amount| {
+            let mut tx = Transaction::new(*payer.public_key(), *payee.public_key(), amount, 0.0, TransactionMetadata::default());
+            tx.sign(payer.private_key()).unwrap();
+            let mut block = Block::new(1, parent, vec![tx], *producer.public_key());
+            block.calculate_hash();
+            block
+        };
+        
+        // Bien formé mais insolvable: refusé sans toucher à l'état
+        let overdraft = proposal(50.0);
+        assert!(overdraft.is_valid(Some(parent)));
+        assert!(blockchain.check_proposal(&overdraft).is_err());
+        assert!(blockchain.check_proposal(&proposal(5.0)).is_ok());
+        assert_eq!(blockchain.get_balance(payer.public_key()), 10.0);
+        assert_eq!(blockchain.get_balance(payee.public_key()), 0.0);
+        assert_eq!(blockchain.height(), 1);
+    }
+
+    #[test]
+    fn test_rejected_block_is_undone() {
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let (payer, payee) = (KeyPair::new(), KeyPair::new());
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(*payer.public_key(), 10.0);
+        blockchain.add_member(payer.public_key());
+
+        let signed = |to: &KeyPair, amount, fee, metadata| {
+            let mut tx = Transaction::new(*payer.public_key(), *to.public_key(), amount, fee, metadata);
+            tx.sign(payer.private_key()).unwrap();
+            tx
+        };
+        let register = TransactionMetadata::with_payload(TransactionType::Validator, &ValidatorOperation::Register);
+        let transactions = |overdraft| vec![
+            signed(&payee, 4.0, 0.5, TransactionMetadata::default()),
+            signed(&payer, 0.0, 0.5, register.clone()),
+            signed(&payee, overdraft, 0.0, TransactionMetadata::default()),
+        ];
+
+        // Transfert, enregistrement puis découvert: rien ne reste des deux
+        // premières transactions
+        let root = blockchain.state.state_root();
+        assert!(add_block_at(&mut blockchain, &producer, transactions(50.0), None).is_err());
+        assert_eq!(blockchain.state.state_root(), root);
+        assert_eq!(blockchain.accounts().nonce(payer.public_key()), 0);
+        assert!(!blockchain.state.balances.contains_key(payee.public_key()));
+        assert!(blockchain.consensus().validators().is_empty());
+
+        add_block_at(&mut blockchain, &producer, transactions(1.0), None).unwrap();
+        assert_eq!(blockchain.get_balance(payer.public_key()), 4.0);
+        assert_eq!(blockchain.get_balance(payee.public_key()), 5.0);
+        assert_eq!(blockchain.accounts().nonce(payer.public_key()), 3);
+        assert_eq!(blockchain.consensus().validators().len(), 1);
+    }
+
+    #[test]
+    fn test_block_requires_committee_quorum() {
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig {
+            validator_count: 10,
+            ..Default::default()
+        });
+        // Genesis déterministe: le comité ne dépend pas de l'heure du test
+        let producer = KeyPair::from_seed(&[255u8; 32]);
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.blocks[0].header.timestamp = 0;
+        blockchain.blocks[0].calculate_hash();
+        
+        let validators: Vec<KeyPair> = (0..40u8).map(|i| KeyPair::from_seed(&[i; 32])).collect();
+        for validator in &validators {
+            blockchain.consensus_mut().register_validator(*validator.public_key(), *validator.public_key());
+        }
+        // Quorum sur la borne haute du comité (et non sur les 40 validateurs),
+        // sous la taille attendue du comité
+        let required = blockchain.consensus().required_quorum(1);
+        assert_eq!(blockchain.consensus().expected_committee_size(1), 28);
+        assert_eq!(blockchain.consensus().committee_bound(1), 37);
+        assert_eq!(required, 25);
+        
+        // Le comité du premier round atteint le quorum
+        let (round, seed) = (0, blockchain.selection_seed(1).unwrap());
+        let (committee, outsiders): (Vec<_>, Vec<_>) = validators.iter()
+            .map(|v| (v, blockchain.consensus().prove_selection(v.public_key(), v.private_key(), &seed, 1, round)))
+            .partition(|(_, proof)| proof.is_some());
+        assert!(committee.len() >= required);
+        
+        let previous_hash = blockchain.last_block_hash().unwrap();
+        let new_block = |signers: &[(&KeyPair, [u8; 80])]| {
+            let mut block = Block::new(1, previous_hash, Vec::new(), *producer.public_key());
+            block.commit_round = round;
+            let hash = block.calculate_hash();
+            let commit_hash = block.commit_signing_hash(&hash);
+            for (signer, proof) in signers {
+                block.add_validator_signature(*signer.public_key(), crate::crypto::sign(&commit_hash, signer.private_key()), *proof);
+            }
+            block
+        };
+        let members: Vec<(&KeyPair, [u8; 80])> = committee.iter()
+            .map(|(v, proof)| (*v, proof.as_ref().unwrap().proof))
+            .collect();
+        
+        // Sous le quorum du comité
+        assert!(blockchain.add_block(new_block(&members[..required - 1])).is_err());
+        
+        // Un validateur hors comité, même avec la preuve d'un autre membre
+        let mut with_outsider = members[..required].to_vec();
+        with_outsider.push((outsiders[0].0, members[0].1));
+        assert!(blockchain.add_block(new_block(&with_outsider)).unwrap_err().contains("not in the committee"));
+        
+        assert!(blockchain.add_block(new_block(&members[..required])).is_ok());
+        assert_eq!(blockchain.height(), 2);
+    }
+
+    #[test]
+    fn test_double_sign_evidence_jails_validator() {
+        use crate::consensus::bft::{Vote, VoteKind};
+        use crate::transaction::TransactionMetadata;
+        
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        let validator = KeyPair::new();
+        let offender = *validator.public_key();
+        blockchain.consensus_mut().register_validator(offender, offender);
+        
+        // Seul validateur: membre de tous les comités
+        let commit = |blockchain: &Blockchain, transactions| {
+            committee_block(blockchain, &producer, &[&validator], transactions)
+        };
+        
+        // Le validateur signe le bloc 1 et précommite un autre bloc au même round
+        let block = commit(&blockchain, Vec::new());
+        let hash = blockchain.add_block(block.clone()).unwrap();
+        let decided = Vote::from_commit_signature(&block, hash, &block.validator_signatures[0]);
+        let selection = decided.selection();
+        let conflicting = Vote::new(VoteKind::Precommit, 1, 0, Some([9u8; 32]), &selection, &validator);
+        let evidence = DoubleSignEvidence::new(decided, conflicting).unwrap();
+        
+        let reporter = KeyPair::new();
+        let evidence_tx = || {
+            let mut tx = Transaction::new(
+                *reporter.public_key(),
+                *reporter.public_key(),
+                0.0,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::Evidence, &evidence),
+            );
+            tx.sign(reporter.private_key()).unwrap();
+            tx
+        };
+        blockchain.add_block(commit(&blockchain, vec![evidence_tx()])).unwrap();
+        
+        // Sanction visible dans l'état: exclusion et réputation minimale
+        let offence = blockchain.slashing().offences_of(&offender).next().unwrap().clone();
+        assert_eq!((offence.height, offence.round, offence.reported_at), (1, 0, 2));
+        assert_eq!(offence.jailed_until, 24 * 720);
+        assert_eq!(blockchain.consensus().jailed_until(&offender), Some(24 * 720));
+        assert_eq!(blockchain.consensus().active_validators_count(), 0);
+        // L'ensemble de l'époque en cours reste inchangé jusqu'à sa fin
+        assert!(blockchain.consensus().validator_set(3).unwrap().get(&offender).is_some());
+        
+        // Une même faute n'est sanctionnée qu'une fois
+        let err = blockchain.add_block(commit(&blockchain, vec![evidence_tx()])).unwrap_err();
+        assert!(err.contains("already punished"));
+    }
+
+    #[test]
+    fn test_checkpoint_finalized_by_quorum() {
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        blockchain.checkpoint_config = CheckpointConfig { interval: 2, first_checkpoint: 2 };
+        let producer = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        let validators: Vec<KeyPair> = (0..3).map(|_| KeyPair::new()).collect();
+        for validator in &validators {
+            blockchain.consensus_mut().register_validator(*validator.public_key(), *validator.public_key());
+        }
+        let signers: Vec<&KeyPair> = validators.iter().collect();
+        for _ in 0..2 {
+            blockchain.add_block(committee_block(&blockchain, &producer, &signers, Vec::new())).unwrap();
+        }
+        
+        // Checkpoint créé au bloc 2, final une fois signé par un quorum du comité
+        let checkpoint = blockchain.checkpoint(2).unwrap().clone();
+        assert_eq!(checkpoint.certificate.signatures.len(), 3);
+        assert_eq!(blockchain.finalized_height(), 0);
+        let signing_hash = checkpoint.signing_hash();
+        let outsider = KeyPair::new();
+        let forged = crate::crypto::sign(&signing_hash, outsider.private_key());
+        assert!(blockchain.add_checkpoint_signature(2, *outsider.public_key(), forged).is_err());
+        
+        let quorum = blockchain.consensus().required_quorum(2);
+        for (i, validator) in validators.iter().take(quorum).enumerate() {
+            let signature = crate::crypto::sign(&signing_hash, validator.private_key());
+            let finalized = blockchain.add_checkpoint_signature(2, *validator.public_key(), signature).unwrap();
+            assert_eq!(finalized, i + 1 == quorum);
+        }
+        assert_eq!(blockchain.finalized_height(), 2);
+        
+        // Un client qui connaît le calendrier de l'époque vérifie le checkpoint
+        let schedule = blockchain.epoch_schedule(0).unwrap();
+        let checkpoint = blockchain.finalized_checkpoint().unwrap().clone();
+        assert_eq!(checkpoint.verify(&schedule), Ok(()));
+        let mut tampered = checkpoint.clone();
+        tampered.state_hash = [7u8; 32];
+        assert!(tampered.verify(&schedule).is_err());
+        let mut tampered = checkpoint;
+        tampered.certificate.signatures.truncate(quorum - 1);
+        assert!(tampered.verify(&schedule).is_err());
+    }
+
+    #[test]
+    fn test_pending_checkpoint_states_are_capped() {
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        blockchain.checkpoint_config = CheckpointConfig { interval: 2, first_checkpoint: 2 };
+        let producer = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        let validators: Vec<KeyPair> = (0..3).map(|_| KeyPair::new()).collect();
+        for validator in &validators {
+            blockchain.consensus_mut().register_validator(*validator.public_key(), *validator.public_key());
+        }
+        let signers: Vec<&KeyPair> = validators.iter().collect();
+
+        // La finalisation prend du retard: seuls les derniers états sont gardés
+        let pending = MAX_PENDING_CHECKPOINT_STATES as u64 + 2;
+        for _ in 0..2 * pending {
+            blockchain.add_block(committee_block(&blockchain, &producer, &signers, Vec::new())).unwrap();
+        }
+        assert_eq!(blockchain.checkpoint_states.len(), MAX_PENDING_CHECKPOINT_STATES);
+        assert!(!blockchain.checkpoint_states.contains_key(&2));
+        let root = blockchain.state.state_root();
+
+        // Un checkpoint dont l'état a été retiré devient final quand même:
+        // l'état est reconstruit et correspond au checkpoint
+        for height in [2, 4] {
+            let signing_hash = blockchain.checkpoint(height).unwrap().signing_hash();
+            for validator in &validators {
+                let signature = crate::crypto::sign(&signing_hash, validator.private_key());
+                blockchain.add_checkpoint_signature(height, *validator.public_key(), signature).unwrap();
+            }
+            assert_eq!(blockchain.finalized_height(), height);
+            let state = blockchain.finalized_state.as_ref().unwrap();
+            assert_eq!(state.state_root(), blockchain.checkpoint(height).unwrap().state_hash);
+            assert!(blockchain.snapshot().is_some());
+        }
+        assert_eq!(blockchain.state.state_root(), root);
+        assert_eq!(blockchain.checkpoint_states.len(), MAX_PENDING_CHECKPOINT_STATES);
+    }
+
+    #[test]
+    fn test_state_root_is_node_independent() {
+        let producer = KeyPair::new();
+        let members: Vec<KeyPair> = (0..3).map(|_| KeyPair::new()).collect();
+        let new_chain = |genesis: Option<&Block>| {
+            let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+            blockchain.checkpoint_config = CheckpointConfig { interval: 2, first_checkpoint: 2 };
+            match genesis {
+                Some(genesis) => blockchain.blocks.push(genesis.clone()),
+                None => {
+                    blockchain.create_genesis(*producer.public_key()).unwrap();
+                }
+            }
+            for member in &members {
+                blockchain.add_member(member.public_key());
+                blockchain.state.balances.insert(*member.public_key(), 100.0);
+            }
+            blockchain.state.total_mass = 300.0;
+            blockchain
+        };
+        
+        // Deux nœuds démarrés à des instants différents, avec la même genèse
+        let mut a = new_chain(None);
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let mut b = new_chain(a.get_block(0));
+        
+        // Blocs à un jour d'intervalle: le DU suit le temps des blocs
+        let start = chrono::Utc::now().timestamp();
+        for timestamp in [start, start + 86400] {
+            let mut block = Block::new(a.height(), a.last_block_hash().unwrap(), Vec::new(), *producer.public_key());
+            block.header.timestamp = timestamp;
+            block.calculate_hash();
+            a.add_block(block.clone()).unwrap();
+            b.add_block(block).unwrap();
+        }
+        
+        assert!(a.get_balance(members[0].public_key()) > 100.0);
+        assert_eq!(a.state.state_root(), b.state.state_root());
+        assert_eq!(a.checkpoint(2).unwrap().state_hash, b.checkpoint(2).unwrap().state_hash);
+    }
+
+    #[test]
+    fn test_snapshot_bootstrap() {
+        use crate::storage::snapshot::SnapshotError;
+
+        let config = RVSConfig { epoch_length: 2, ..Default::default() };
+        let mut blockchain = Blockchain::new(DUConfig::default(), config.clone());
+        blockchain.checkpoint_config = CheckpointConfig { interval: 2, first_checkpoint: 2 };
+        let producer = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        let validators: Vec<KeyPair> = (0..3).map(|_| KeyPair::new()).collect();
+        for validator in &validators {
+            blockchain.consensus_mut().register_validator(*validator.public_key(), *validator.public_key());
+            blockchain.add_member(validator.public_key());
+            blockchain.state.balances.insert(*validator.public_key(), 12.5);
+        }
+        let signers: Vec<&KeyPair> = validators.iter().collect();
+        for _ in 0..2 {
+            blockchain.add_block(committee_block(&blockchain, &producer, &signers, Vec::new())).unwrap();
+        }
+        assert!(blockchain.snapshot().is_none());
+        let signing_hash = blockchain.checkpoint(2).unwrap().signing_hash();
+        for validator in &validators {
+            let signature = crate::crypto::sign(&signing_hash, validator.private_key());
+            blockchain.add_checkpoint_signature(2, *validator.public_key(), signature).unwrap();
+        }
+
+        // L'instantané exporté survit à la sérialisation et se vérifie
+        let schedule = blockchain.epoch_schedule(1).unwrap();
+        let json = serde_json::to_string(&blockchain.snapshot().unwrap()).unwrap();
+        let snapshot: StateSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot.verify(&schedule), Ok(()));
+        assert_eq!(snapshot.epoch_schedule(config.clone()).unwrap().seed, schedule.seed);
+        let mut tampered = snapshot.clone();
+        tampered.chunks[1].data[0] ^= 1;
+        assert_eq!(tampered.verify(&schedule), Err(SnapshotError::Chunk(1)));
+        let mut tampered = snapshot.clone();
+        tampered.manifest.chunks.pop();
+        assert_eq!(tampered.verify(&schedule), Err(SnapshotError::StateRoot));
+
+        // Le nœud démarré depuis l'instantané reprend l'état au checkpoint
+        let mut restored = Blockchain::from_snapshot(DUConfig::default(), config, snapshot, &schedule).unwrap();
+        let checkpoint = blockchain.finalized_checkpoint().unwrap();
+        assert_eq!(restored.height(), 3);
+        assert_eq!(restored.finalized_height(), 2);
+        assert_eq!(restored.state.state_root(), checkpoint.state_hash);
+        assert_eq!(restored.get_balance(validators[0].public_key()), 12.5);
+        assert!(restored.is_member(validators[1].public_key()));
+        assert_eq!(restored.consensus().active_validators_count(), 3);
+        // Seuls les blocs depuis l'ancrage de l'époque sont conservés
+        assert!(restored.get_block(0).is_none());
+        assert!(restored.get_block(1).is_some());
+
+        // puis se synchronise sur les blocs suivants
+        restored.checkpoint_config = blockchain.checkpoint_config.clone();
+        for _ in 0..2 {
+            let block = committee_block(&blockchain, &producer, &signers, Vec::new());
+            blockchain.add_block(block.clone()).unwrap();
+            restored.add_block(block).unwrap();
+        }
+        assert_eq!(restored.last_block_hash(), blockchain.last_block_hash());
+        assert_eq!(restored.checkpoint(4).unwrap().state_hash, blockchain.checkpoint(4).unwrap().state_hash);
+    }
+
+    #[test]
+    fn test_fork_choice_and_reorg() {
+        use crate::transaction::TransactionMetadata;
+        
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        blockchain.checkpoint_config = CheckpointConfig { interval: 2, first_checkpoint: 2 };
+        let producer = KeyPair::new();
+        let genesis = blockchain.create_genesis(*producer.public_key()).unwrap();
+        let validators: Vec<KeyPair> = (0..4).map(|_| KeyPair::new()).collect();
+        for validator in &validators {
+            blockchain.consensus_mut().register_validator(*validator.public_key(), *validator.public_key());
+        }
+        let (alice, bob) = (KeyPair::new(), KeyPair::new());
+        blockchain.state.balances.insert(*alice.public_key(), 100.0);
+        let mut payment = Transaction::new(*alice.public_key(), *bob.public_key(), 10.0, 0.0, TransactionMetadata::default());
+        payment.sign(alice.private_key()).unwrap();
+        
+        // Quorum de 3 sur 4: les branches peuvent porter 3 ou 4 signatures
+        let three: Vec<&KeyPair> = validators.iter().take(3).collect();
+        let four: Vec<&KeyPair> = validators.iter().collect();
+        let a1 = committee_block_on(&blockchain, genesis, 1, &producer, &three, vec![payment.clone()]);
+        let a1_hash = blockchain.add_block(a1).unwrap();
+        assert_eq!(blockchain.get_balance(bob.public_key()), 10.0);
+        
+        // Branche concurrente plus signée: réorganisation, paiement orphelin
+        let b1 = committee_block_on(&blockchain, genesis, 1, &producer, &four, Vec::new());
+        let b1_hash = blockchain.add_block(b1.clone()).unwrap();
+        assert_eq!(blockchain.last_block_hash(), Some(b1_hash));
+        assert_eq!(blockchain.get_balance(bob.public_key()), 0.0);
+        let reorgs = blockchain.take_reorgs();
+        assert_eq!(reorgs.len(), 1);
+        assert_eq!((reorgs[0].fork_height, &reorgs[0].reverted, &reorgs[0].applied), (0, &vec![a1_hash], &vec![b1_hash]));
+        assert_eq!(reorgs[0].orphaned_transactions[0].id(), payment.id());
+        assert!(blockchain.add_block(b1).unwrap_err().contains("already known"));
+        
+        // Un bloc concurrent sans quorum, ou alourdi par une signature hors
+        // comité, n'entre pas dans l'arbre
+        let other = KeyPair::new();
+        let weak = committee_block_on(&blockchain, genesis, 1, &other, &three[..2], Vec::new());
+        assert!(blockchain.add_block(weak.clone()).unwrap_err().contains("quorum"));
+        assert!(blockchain.add_block(weak).unwrap_err().contains("quorum"));
+        let mut padded = committee_block_on(&blockchain, genesis, 1, &other, &three, Vec::new());
+        let padded_hash = padded.calculate_hash();
+        let proof = padded.validator_signatures[0].selection_proof;
+        let forged = crate::crypto::sign(&padded.commit_signing_hash(&padded_hash), other.private_key());
+        padded.add_validator_signature(*other.public_key(), forged, proof);
+        assert!(blockchain.add_block(padded).unwrap_err().contains("not in the committee"));
+        assert_eq!(blockchain.last_block_hash(), Some(b1_hash));
+        
+        // La branche retirée reste connue: prolongée, elle redevient la plus lourde
+        let a2 = committee_block_on(&blockchain, a1_hash, 2, &producer, &three, Vec::new());
+        let a2_hash = blockchain.add_block(a2).unwrap();
+        assert_eq!(blockchain.last_block_hash(), Some(a2_hash));
+        assert_eq!(blockchain.get_block(1).unwrap().hash, Some(a1_hash));
+        assert_eq!(blockchain.get_balance(bob.public_key()), 10.0);
+        assert!(blockchain.take_reorgs()[0].orphaned_transactions.is_empty());
+        
+        // Un bloc invalide dans une branche plus lourde laisse la chaîne intacte
+        let mut overdraft = Transaction::new(*bob.public_key(), *alice.public_key(), 1000.0, 0.0, TransactionMetadata::default());
+        overdraft.sign(bob.private_key()).unwrap();
+        let b2 = committee_block_on(&blockchain, b1_hash, 2, &producer, &four, vec![overdraft]);
+        assert!(blockchain.add_block(b2).unwrap_err().contains("Invalid fork block"));
+        assert_eq!(blockchain.last_block_hash(), Some(a2_hash));
+        assert_eq!(blockchain.get_balance(bob.public_key()), 10.0);
+        
+        // Checkpoint du bloc 2 final: une branche qui l'exclut est refusée
+        let signing_hash = blockchain.checkpoint(2).unwrap().signing_hash();
+        for validator in &three {
+            let signature = crate::crypto::sign(&signing_hash, validator.private_key());
+            blockchain.add_checkpoint_signature(2, *validator.public_key(), signature).unwrap();
+        }
+        assert_eq!(blockchain.finalized_height(), 2);
+        let rival = committee_block_on(&blockchain, a1_hash, 2, &producer, &four, Vec::new());
+        assert!(blockchain.add_block(rival).unwrap_err().contains("finalized"));
+        
+        // La chaîne continue depuis l'état final
+        blockchain.add_block(committee_block(&blockchain, &producer, &three, Vec::new())).unwrap();
+        assert_eq!(blockchain.height(), 4);
+    }
+
+    #[test]
+    fn test_participation_counts_each_validator_once() {
+        use crate::consensus::rvs::{ValidatorStats, REPUTATION_SCALE};
+        
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let validators: Vec<KeyPair> = (0..4).map(|_| KeyPair::new()).collect();
+        blockchain.create_genesis(*validators[0].public_key()).unwrap();
+        for validator in &validators {
+            blockchain.consensus_mut().register_validator(*validator.public_key(), *validator.public_key());
+        }
+        
+        // Le producteur signe aussi le bloc; le quatrième membre du comité
+        // ne signe pas
+        let signers: Vec<&KeyPair> = validators.iter().take(3).collect();
+        blockchain.add_block(committee_block(&blockchain, &validators[0], &signers, Vec::new())).unwrap();
+        
+        let reward = blockchain.consensus().config().reputation_reward;
+        let producer = blockchain.consensus().validator(validators[0].public_key()).unwrap();
+        assert_eq!(producer.stats, ValidatorStats { times_selected: 1, blocks_signed: 1, absences: 0 });
+        assert_eq!(producer.reputation, REPUTATION_SCALE + reward);
+        // Sa sélection reste privée: ni sélection ni absence comptées
+        let silent = blockchain.consensus().validator(validators[3].public_key()).unwrap();
+        assert_eq!(silent.stats, ValidatorStats::default());
+        assert_eq!(silent.reputation, REPUTATION_SCALE);
+    }
+
+    #[test]
+    fn test_failed_round_with_proposal_is_not_an_absence() {
+        use crate::consensus::bft::Proposal;
+        use crate::consensus::rvs::REPUTATION_SCALE;
+        
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let validators: Vec<KeyPair> = (0..4u8).map(|i| KeyPair::from_seed(&[i + 1; 32])).collect();
+        blockchain.create_genesis(*validators[0].public_key()).unwrap();
+        for validator in &validators {
+            blockchain.consensus_mut().register_validator(*validator.public_key(), *validator.public_key());
+        }
+        
+        // Le proposeur du round 0 propose, mais le round n'atteint pas le
+        // quorum; le bloc est décidé au round 1 par les trois autres
+        let seed = blockchain.selection_seed(1).unwrap();
+        let proposer = blockchain.proposer(1, 0).unwrap();
+        let proposer_keys = validators.iter().find(|v| *v.public_key() == proposer).unwrap();
+        let others: Vec<&KeyPair> = validators.iter().filter(|v| *v.public_key() != proposer).collect();
+        let decided = |witness: bool| {
+            let parent = blockchain.last_block_hash().unwrap();
+            let mut failed = Block::new(1, parent, Vec::new(), proposer);
+            failed.header.timestamp += 1;
+            let failed_hash = failed.calculate_hash();
+            let proposal = Proposal::new(1, 0, None, failed, proposer_keys);
+            
+            let mut block = Block::new(1, parent, Vec::new(), *others[0].public_key());
+            block.commit_round = 1;
+            let hash = block.calculate_hash();
+            let commit_hash = block.commit_signing_hash(&hash);
+            for signer in &others {
+                let selection = blockchain.consensus()
+                    .prove_selection(signer.public_key(), signer.private_key(), &seed, 1, 1)
+                    .unwrap();
+                block.add_validator_signature(*signer.public_key(), crate::crypto::sign(&commit_hash, signer.private_key()), selection.proof);
+            }
+            if witness {
+                block.proposal_witnesses.push(proposal.witness(failed_hash));
+            }
+            block
+        };
+        
+        let (with_witness, without_witness) = (decided(true), decided(false));
+        
+        // Sans trace, le proposeur serait compté absent
+        assert_eq!(blockchain.consensus().missed_proposers(&without_witness, &seed), Ok(vec![proposer]));
+        
+        // Une trace qui ne vient pas du proposeur du round est refusée
+        let mut forged = with_witness.clone();
+        forged.proposal_witnesses[0].round = 1;
+        assert!(blockchain.add_block(forged).unwrap_err().contains("proposal witness"));
+        
+        blockchain.add_block(with_witness).unwrap();
+        let info = blockchain.consensus().validator(&proposer).unwrap();
+        assert_eq!(info.stats.absences, 0);
+        assert_eq!(info.missed_rounds, 0);
+        assert_eq!(info.reputation, REPUTATION_SCALE);
+    }
+
+    #[test]
+    fn test_validator_registration_and_liveness() {
+        use crate::transaction::TransactionMetadata;
+        
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig {
+            epoch_length: 2,
+            liveness_epochs: 2,
+            ..Default::default()
+        });
+        let producer = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        let (member, outsider) = (KeyPair::new(), KeyPair::new());
+        blockchain.add_member(member.public_key());
+        // Validateurs initiaux: sans le membre, ils atteignent le quorum
+        let others: Vec<KeyPair> = (0..3).map(|_| KeyPair::new()).collect();
+        for other in &others {
+            blockchain.consensus_mut().register_validator(*other.public_key(), *other.public_key());
+        }
+        let mut signers: Vec<&KeyPair> = others.iter().collect();
+        assert_eq!(blockchain.state.member_count, 1);
+        
+        let operation = |signer: &KeyPair, operation: ValidatorOperation| {
+            let mut tx = Transaction::new(
+                *signer.public_key(),
+                *signer.public_key(),
+                0.0,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::Validator, &operation),
+            );
+            tx.sign(signer.private_key()).unwrap();
+            tx
+        };
+        
+        // Réservé aux membres
+        let block = committee_block(&blockchain, &producer, &signers, vec![operation(&outsider, ValidatorOperation::Register)]);
+        assert!(blockchain.add_block(block).unwrap_err().contains("restricted to members"));
+        let block = committee_block(&blockchain, &producer, &signers, vec![operation(&member, ValidatorOperation::Register)]);
+        blockchain.add_block(block).unwrap();
+        let info = blockchain.consensus().validator(member.public_key()).unwrap();
+        assert!(info.is_active());
+        assert_eq!(info.registered_at, 1);
+        // Le bloc 1 clôt l'époque 0: le membre entre dans l'ensemble de l'époque 1
+        let schedule = blockchain.epoch_schedule(1).unwrap();
+        assert_eq!((schedule.start_height, schedule.end_height), (2, 3));
+        assert!(schedule.validators.get(member.public_key()).is_some());
+        assert_eq!(blockchain.epoch_schedule(0).unwrap().validators.len(), 3);
+        assert!(blockchain.epoch_schedule(2).is_none());
+        assert!(blockchain.proposer(2, 0).is_some());
+        
+        // Le membre signe deux blocs, puis ne se manifeste que par un
+        // heartbeat (hauteur 4)
+        let mut with_member = signers.clone();
+        with_member.push(&member);
+        for _ in 0..2 {
+            blockchain.add_block(committee_block(&blockchain, &producer, &with_member, Vec::new())).unwrap();
+        }
+        let heartbeat = vec![operation(&member, ValidatorOperation::Heartbeat)];
+        for transactions in [heartbeat, Vec::new(), Vec::new(), Vec::new(), Vec::new()] {
+            blockchain.add_block(committee_block(&blockchain, &producer, &signers, transactions)).unwrap();
+        }
+        assert!(blockchain.consensus().validator(member.public_key()).unwrap().is_active());
+        
+        // Inactif pendant plus de 2 époques (4 blocs): désactivé
+        blockchain.add_block(committee_block(&blockchain, &producer, &signers, Vec::new())).unwrap();
+        let info = blockchain.consensus().validator(member.public_key()).unwrap();
+        assert_eq!(info.status, crate::consensus::rvs::ValidatorStatus::Inactive);
+        assert_eq!(blockchain.consensus().active_validators_count(), 3);
+        
+        // Réactivation, puis départ
+        let register = vec![operation(&member, ValidatorOperation::Register)];
+        blockchain.add_block(committee_block(&blockchain, &producer, &signers, register)).unwrap();
+        assert_eq!(blockchain.consensus().active_validators_count(), 4);
+        signers.push(&member);
+        let unregister = vec![operation(&member, ValidatorOperation::Unregister)];
+        blockchain.add_block(committee_block(&blockchain, &producer, &signers, unregister)).unwrap();
+        assert!(!blockchain.consensus().is_validator(member.public_key()));
+    }
+
+    /// Construire un bloc signé par les membres sélectionnés du comité
+    /// (round 0) parmi des validateurs
+    fn committee_block(
+        blockchain: &Blockchain,
+        producer: &KeyPair,
+        validators: &[&KeyPair],
+        transactions: Vec<Transaction>,
+    ) -> Block {
+        let parent = blockchain.last_block_hash().unwrap();
+        committee_block_on(blockchain, parent, blockchain.height(), producer, validators, transactions)
+    }
+
+    /// Construire un bloc signé par le comité sur un parent quelconque
+    /// (branche concurrente)
+    fn committee_block_on(
+        blockchain: &Blockchain,
+        parent: Hash,
+        height: u64,
+        producer: &KeyPair,
+        validators: &[&KeyPair],
+        transactions: Vec<Transaction>,
+    ) -> Block {
+        let seed = blockchain.selection_seed(height).unwrap();
+        let mut block = Block::new(height, parent, transactions, *producer.public_key());
+        let hash = block.calculate_hash();
+        let commit_hash = block.commit_signing_hash(&hash);
+        for validator in validators {
+            let key = validator.public_key();
+            if let Some(selection) = blockchain.consensus().prove_selection(key, validator.private_key(), &seed, height, 0) {
+                block.add_validator_signature(*key, crate::crypto::sign(&commit_hash, validator.private_key()), selection.proof);
+            }
+        }
+        block
+    }
+
+    /// Construire et ajouter un bloc avec un timestamp donné
+    fn add_block_at(
+        blockchain: &mut Blockchain,
+        producer: &KeyPair,
+        transactions: Vec<Transaction>,
+        timestamp: Option<Timestamp>,
+    ) -> Result<Hash, String> {
+        let previous_hash = blockchain.last_block_hash().unwrap();
+        let mut block = Block::new(blockchain.height(), previous_hash, transactions, *producer.public_key());
+        if let Some(timestamp) = timestamp {
+            block.header.timestamp = timestamp;
+        }
+        block.calculate_hash();
+        blockchain.add_block(block)
+    }
+
+    /// Construire, signer et ajouter un bloc contenant une transaction
+    fn add_signed_tx(
+        blockchain: &mut Blockchain,
+        producer: &KeyPair,
+        signer: &KeyPair,
+        mut tx: Transaction,
+    ) -> Result<Hash, String> {
+        tx.sign(signer.private_key()).unwrap();
+        add_block_at(blockchain, producer, vec![tx], None)
+    }
+
+    #[test]
+    fn test_nft_operations_in_blocks() {
+        use crate::nft::{NFTMetadata, NFTType, NFTEventKind};
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let creator = KeyPair::new();
+        let buyer = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(*buyer.public_key(), 200.0);
+
+        let nft_tx = |from: &KeyPair, amount: Amount, op: &NFTOperation| {
+            Transaction::new(
+                *from.public_key(),
+                *from.public_key(),
+                amount,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::NFT, op),
+            )
+        };
+
+        let mint = NFTOperation::Mint {
+            metadata: NFTMetadata {
+                name: "Statue".to_string(),
+                description: "Statue 3D".to_string(),
+                nft_type: NFTType::Model3D,
+                media_url: "ipfs://statue".to_string(),
+                media_hash: crate::crypto::hash_data(b"statue.gltf"),
+                thumbnail_url: "ipfs://statue-thumb".to_string(),
+                thumbnail_hash: None,
+                attributes: HashMap::from([
+                    ("polygon_count".to_string(), "800".to_string()),
+                    ("textures".to_string(), "marble.png".to_string()),
+                ]),
+            },
+            royalty_percentage: 10,
+        };
+        add_signed_tx(&mut blockchain, &producer, &creator, nft_tx(&creator, 0.0, &mint)).unwrap();
+        let nft_id = blockchain.nfts().get_nfts_by_owner(creator.public_key())[0].id.clone();
+
+        let list = NFTOperation::List {
+            nft_id: nft_id.clone(),
+            price: 100.0,
+            duration_days: None,
+            marketplace_fee: None,
+        };
+        add_signed_tx(&mut blockchain, &producer, &creator, nft_tx(&creator, 0.0, &list)).unwrap();
+
+        // Paiement insuffisant: le bloc est rejeté et l'état inchangé
+        let buy = NFTOperation::Buy { nft_id: nft_id.clone() };
+        assert!(add_signed_tx(&mut blockchain, &producer, &buyer, nft_tx(&buyer, 50.0, &buy)).is_err());
+        assert_eq!(blockchain.get_balance(buyer.public_key()), 200.0);
+
+        add_signed_tx(&mut blockchain, &producer, &buyer, nft_tx(&buyer, 100.0, &buy)).unwrap();
+        assert_eq!(blockchain.get_balance(buyer.public_key()), 100.0);
+        assert_eq!(blockchain.get_balance(creator.public_key()), 100.0);
+        assert_eq!(blockchain.nfts().get_nft(&nft_id).unwrap().owner, *buyer.public_key());
+
+        let history = blockchain.nfts().history(&nft_id);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].kind, NFTEventKind::Mint);
+        assert_eq!(history[0].block_height, 1);
+        assert_eq!(history[2].kind, NFTEventKind::Sale);
+        assert_eq!(history[2].block_height, 3);
+
+        // Mise en vente et mint annulés avec le bloc: NFTs, listings et
+        // journal de provenance redeviennent ceux d'avant le bloc
+        let root = blockchain.state.state_root();
+        let relist = NFTOperation::List { nft_id: nft_id.clone(), price: 150.0, duration_days: None, marketplace_fee: None };
+        let rejected: Vec<Transaction> = [nft_tx(&buyer, 0.0, &relist), nft_tx(&buyer, 0.0, &mint), nft_tx(&buyer, 10.0, &buy)]
+            .into_iter()
+            .map(|mut tx| {
+                tx.sign(buyer.private_key()).unwrap();
+                tx
+            })
+            .collect();
+        let error = add_block_at(&mut blockchain, &producer, rejected, None).unwrap_err();
+        assert!(error.contains("Insufficient payment"), "{}", error);
+        assert_eq!(blockchain.state.state_root(), root);
+        assert!(blockchain.nfts().get_listing(&nft_id).is_none());
+        assert_eq!(blockchain.nfts().get_nfts_by_owner(buyer.public_key()).len(), 1);
+        assert_eq!(blockchain.nfts().events().len(), 3);
+        assert_eq!(blockchain.nfts().events_for_address(buyer.public_key()).len(), 1);
+    }
+
+    #[test]
+    fn test_escrow_dispute_and_timeout() {
+        use crate::payments::{EscrowStatus, EscrowTerms};
+        use crate::payments::escrow::DEFAULT_ESCROW_TIMEOUT_SECS;
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let buyer = KeyPair::new();
+        let merchant = KeyPair::new();
+        let arbiter = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(*buyer.public_key(), 100.0);
+
+        let purchase = |order: &str| {
+            let terms = EscrowTerms {
+                arbiter: *arbiter.public_key(),
+                timeout_secs: None,
+                order_ref: Some(order.to_string()),
+            };
+            let mut tx = Transaction::new(
+                *buyer.public_key(),
+                *merchant.public_key(),
+                40.0,
+                0.0,
+                TransactionMetadata::with_payload(
+                    TransactionType::ShopPurchase,
+                    &EscrowOperation::Open(terms),
+                ),
+            );
+            tx.sign(buyer.private_key()).unwrap();
+            tx
+        };
+        let escrow_tx = |signer: &KeyPair, op: &EscrowOperation| {
+            let mut tx = Transaction::new(
+                *signer.public_key(),
+                *signer.public_key(),
+                0.0,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::Escrow, op),
+            );
+            tx.sign(signer.private_key()).unwrap();
+            tx
+        };
+
+        // Deux achats séquestrés: les fonds quittent l'acheteur sans atteindre le marchand
+        let start = 1_700_000_000;
+        let first = purchase("CMD-1");
+        let second = purchase("CMD-2");
+        let first_id = first.clone().calculate_hash();
+        let second_id = second.clone().calculate_hash();
+        add_block_at(&mut blockchain, &producer, vec![first, second], Some(start)).unwrap();
+        assert_eq!(blockchain.get_balance(buyer.public_key()), 20.0);
+        assert_eq!(blockchain.get_balance(merchant.public_key()), 0.0);
+
+        // Litige sur le premier, tranché par l'arbitre (moitié-moitié)
+        let dispute = escrow_tx(&buyer, &EscrowOperation::Dispute { escrow_id: first_id });
+        add_block_at(&mut blockchain, &producer, vec![dispute], Some(start + 60)).unwrap();
+        let unauthorized = escrow_tx(&merchant, &EscrowOperation::Resolve { escrow_id: first_id, merchant_amount: 40.0 });
+        assert!(add_block_at(&mut blockchain, &producer, vec![unauthorized], Some(start + 120)).is_err());
+        let resolve = escrow_tx(&arbiter, &EscrowOperation::Resolve { escrow_id: first_id, merchant_amount: 20.0 });
+        add_block_at(&mut blockchain, &producer, vec![resolve], Some(start + 120)).unwrap();
+        assert_eq!(blockchain.get_balance(buyer.public_key()), 40.0);
+        assert_eq!(blockchain.get_balance(merchant.public_key()), 20.0);
+
+        // Le second est libéré automatiquement au marchand à l'échéance
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + DEFAULT_ESCROW_TIMEOUT_SECS)).unwrap();
+        assert_eq!(blockchain.get_balance(merchant.public_key()), 60.0);
+        assert_eq!(blockchain.escrows().get(&second_id).unwrap().status, EscrowStatus::Released);
+        assert_eq!(blockchain.escrows().get(&first_id).unwrap().status, EscrowStatus::Resolved);
+    }
+
+    #[test]
+    fn test_standing_order_payments() {
+        use crate::payments::{StandingOrderEventKind, StandingOrderStatus, StandingOrderTerms};
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let tenant = KeyPair::new();
+        let landlord = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(*tenant.public_key(), 25.0);
+
+        let day = 86400;
+        let start = 1_700_000_000;
+        let terms = StandingOrderTerms {
+            amount: 10.0,
+            period_secs: day,
+            start_at: None,
+            max_payments: Some(4),
+            end_at: None,
+            reference: Some("rental-7".to_string()),
+        };
+        let mut create = Transaction::new(
+            *tenant.public_key(),
+            *landlord.public_key(),
+            0.0,
+            0.0,
+            TransactionMetadata::with_payload(
+                TransactionType::StandingOrder,
+                &StandingOrderOperation::Create(terms),
+            ),
+        );
+        create.sign(tenant.private_key()).unwrap();
+        let order_id = create.clone().calculate_hash();
+
+        // La première échéance est prélevée dans le bloc de création
+        add_block_at(&mut blockchain, &producer, vec![create], Some(start)).unwrap();
+        assert_eq!(blockchain.get_balance(tenant.public_key()), 15.0);
+        assert_eq!(blockchain.get_balance(landlord.public_key()), 10.0);
+
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + day)).unwrap();
+        // Provision insuffisante: le bloc est accepté et l'impayé consigné
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + 2 * day)).unwrap();
+        assert_eq!(blockchain.height(), 4);
+        assert_eq!(blockchain.get_balance(tenant.public_key()), 5.0);
+        assert_eq!(blockchain.get_balance(landlord.public_key()), 20.0);
+        let last_event = *blockchain.standing_orders().events_for(&order_id).last().unwrap();
+        assert_eq!(last_event.kind, StandingOrderEventKind::InsufficientFunds);
+        assert_eq!(last_event.block_height, 3);
+
+        // Le bailleur annule avant la dernière échéance
+        let mut cancel = Transaction::new(
+            *landlord.public_key(),
+            *landlord.public_key(),
+            0.0,
+            0.0,
+            TransactionMetadata::with_payload(
+                TransactionType::StandingOrder,
+                &StandingOrderOperation::Cancel { order_id },
+            ),
+        );
+        cancel.sign(landlord.private_key()).unwrap();
+        add_block_at(&mut blockchain, &producer, vec![cancel], Some(start + 2 * day + 60)).unwrap();
+        blockchain.state.balances.insert(*tenant.public_key(), 100.0);
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + 3 * day)).unwrap();
+        assert_eq!(blockchain.get_balance(tenant.public_key()), 100.0);
+
+        let order = blockchain.standing_orders().get(&order_id).unwrap();
+        assert_eq!(order.status, StandingOrderStatus::Cancelled);
+        assert_eq!((order.payments_made, order.payments_failed), (2, 1));
+    }
+
+    #[test]
+    fn test_payment_channel_lifecycle() {
+        use crate::payments::{ChannelSender, ChannelStatus, ChannelTerms};
+        use crate::payments::channel::DEFAULT_DISPUTE_PERIOD_SECS;
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let player = KeyPair::new();
+        let server = KeyPair::new();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(*player.public_key(), 50.0);
+
+        let channel_tx = |signer: &KeyPair, to: &KeyPair, amount: Amount, op: &ChannelOperation| {
+            let mut tx = Transaction::new(
+                *signer.public_key(),
+                *to.public_key(),
+                amount,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::PaymentChannel, op),
+            );
+            tx.sign(signer.private_key()).unwrap();
+            tx
+        };
+
+        // Deux canaux: l'un clôturé par le serveur, l'autre par le joueur seul
+        let start = 1_700_000_000;
+        let open = ChannelOperation::Open(ChannelTerms::default());
+        let first = channel_tx(&player, &server, 20.0, &open);
+        let second = channel_tx(&player, &server, 10.0, &open);
+        let first_id = first.clone().calculate_hash();
+        let second_id = second.clone().calculate_hash();
+        add_block_at(&mut blockchain, &producer, vec![first, second], Some(start)).unwrap();
+        assert_eq!(blockchain.get_balance(player.public_key()), 20.0);
+
+        // Téléportations payées hors chaîne, règlement immédiat par le serveur
+        let mut sender = ChannelSender::new(first_id, 20.0);
+        for _ in 0..5 {
+            sender.pay(0.5, player.private_key()).unwrap();
+        }
+        let latest = sender.pay(0.5, player.private_key()).unwrap();
+        let close = channel_tx(&server, &server, 0.0, &ChannelOperation::Close { update: latest });
+        add_block_at(&mut blockchain, &producer, vec![close], Some(start + 600)).unwrap();
+        assert_eq!(blockchain.get_balance(server.public_key()), 3.0);
+        assert_eq!(blockchain.get_balance(player.public_key()), 37.0);
+
+        // Clôture unilatérale: réglée à la fin de la période de contestation
+        let start_close = ChannelOperation::StartClose { channel_id: second_id, update: None };
+        add_block_at(&mut blockchain, &producer, vec![channel_tx(&player, &player, 0.0, &start_close)], Some(start + 700)).unwrap();
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + 700 + DEFAULT_DISPUTE_PERIOD_SECS - 1)).unwrap();
+        assert_eq!(blockchain.get_balance(player.public_key()), 37.0);
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + 700 + DEFAULT_DISPUTE_PERIOD_SECS)).unwrap();
+        assert_eq!(blockchain.get_balance(player.public_key()), 47.0);
+        assert_eq!(blockchain.channels().get(&second_id).unwrap().status, ChannelStatus::Closed);
+    }
+
+    #[test]
+    fn test_key_rotation_keeps_account() {
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let owner = KeyPair::new();
+        let new_key = KeyPair::new();
+        let friend = KeyPair::new();
+        let account = *owner.public_key();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(account, 100.0);
+
+        let transfer = |from: &KeyPair, to: &PublicKey, amount: Amount| {
+            Transaction::new(*from.public_key(), *to, amount, 0.0, TransactionMetadata::default())
+        };
+        let rotation_tx = |signer: &KeyPair, op: &KeyRotationOperation| {
+            Transaction::new(
+                *signer.public_key(),
+                *signer.public_key(),
+                0.0,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::KeyRotation, op),
+            )
+        };
+
+        add_signed_tx(&mut blockchain, &producer, &owner, transfer(&owner, friend.public_key(), 10.0)).unwrap();
+
+        // La clé d'un membre est prise; une poussière envoyée à la nouvelle
+        // clé ne bloque pas la rotation et rejoint le compte
+        let member = KeyPair::new();
+        blockchain.add_member(member.public_key());
+        let to_member = KeyRotationOperation::rotate(&account, &member);
+        assert!(add_signed_tx(&mut blockchain, &producer, &owner, rotation_tx(&owner, &to_member)).is_err());
+        add_signed_tx(&mut blockchain, &producer, &friend, transfer(&friend, new_key.public_key(), 0.5)).unwrap();
+        let rotate = KeyRotationOperation::rotate(&account, &new_key);
+        add_signed_tx(&mut blockchain, &producer, &owner, rotation_tx(&owner, &rotate)).unwrap();
+
+        // L'ancienne clé ne peut plus signer; le compte reste le même
+        assert!(add_signed_tx(&mut blockchain, &producer, &owner, transfer(&owner, friend.public_key(), 1.0)).is_err());
+        assert_eq!(blockchain.get_balance(new_key.public_key()), 90.5);
+        assert_eq!(blockchain.get_balance(&account), 90.5);
+
+        // Les fonds envoyés à l'ancienne ou à la nouvelle clé arrivent sur le compte
+        add_signed_tx(&mut blockchain, &producer, &friend, transfer(&friend, &account, 5.0)).unwrap();
+        add_signed_tx(&mut blockchain, &producer, &friend, transfer(&friend, new_key.public_key(), 4.5)).unwrap();
+        add_signed_tx(&mut blockchain, &producer, &new_key, transfer(&new_key, friend.public_key(), 20.0)).unwrap();
+        assert_eq!(blockchain.get_balance(&account), 80.0);
+        assert!(!blockchain.state.balances.contains_key(new_key.public_key()));
+        assert_eq!(blockchain.accounts().nonce(&account), 3);
+        assert_eq!(blockchain.accounts().rotations_of(&account).len(), 1);
+    }
+
+    #[test]
+    fn test_delayed_key_rotation() {
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let owner = KeyPair::new();
+        let new_key = KeyPair::new();
+        let account = *owner.public_key();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(account, 100.0);
+        blockchain.state.balances.insert(*producer.public_key(), 10.0);
+
+        let rotation_tx = |op: &KeyRotationOperation| {
+            let mut tx = Transaction::new(
+                account,
+                account,
+                0.0,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::KeyRotation, op),
+            );
+            tx.sign(owner.private_key()).unwrap();
+            tx
+        };
+
+        let start = chrono::Utc::now().timestamp();
+        let set_delay = rotation_tx(&KeyRotationOperation::SetDelay { delay_secs: 3600 });
+        add_block_at(&mut blockchain, &producer, vec![set_delay], Some(start)).unwrap();
+        let rotate = rotation_tx(&KeyRotationOperation::rotate(&account, &new_key));
+        add_block_at(&mut blockchain, &producer, vec![rotate], Some(start + 10)).unwrap();
+        assert!(blockchain.accounts().get(&account).unwrap().pending_rotation.is_some());
+
+        // Des fonds reçus par la nouvelle clé pendant le délai sont rattachés au compte
+        let mut gift = Transaction::new(
+            *producer.public_key(),
+            *new_key.public_key(),
+            5.0,
+            0.0,
+            TransactionMetadata::default(),
+        );
+        gift.sign(producer.private_key()).unwrap();
+        add_block_at(&mut blockchain, &producer, vec![gift], Some(start + 20)).unwrap();
+        assert_eq!(blockchain.accounts().controller(&account), account);
+
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + 3610)).unwrap();
+        assert_eq!(blockchain.accounts().controller(&account), *new_key.public_key());
+        assert_eq!(blockchain.get_balance(&account), 105.0);
+        assert!(!blockchain.state.balances.contains_key(new_key.public_key()));
+    }
+
+    #[test]
+    fn test_social_recovery() {
+        use crate::account::RecoveryConfig;
+        use crate::transaction::TransactionMetadata;
+
+        let mut blockchain = Blockchain::new(DUConfig::default(), RVSConfig::default());
+        let producer = KeyPair::new();
+        let owner = KeyPair::new();
+        let guardians = [KeyPair::new(), KeyPair::new(), KeyPair::new()];
+        let new_device = KeyPair::new();
+        let account = *owner.public_key();
+        blockchain.create_genesis(*producer.public_key()).unwrap();
+        blockchain.state.balances.insert(account, 100.0);
+
+        let recovery_tx = |signer: &KeyPair, to: &PublicKey, op: &RecoveryOperation| {
+            let mut tx = Transaction::new(
+                *signer.public_key(),
+                *to,
+                0.0,
+                0.0,
+                TransactionMetadata::with_payload(TransactionType::Recovery, op),
+            );
+            tx.sign(signer.private_key()).unwrap();
+            tx
+        };
+
+        let start = chrono::Utc::now().timestamp();
+        let configure = RecoveryOperation::Configure(RecoveryConfig {
+            guardians: guardians.iter().map(|g| *g.public_key()).collect(),
+            threshold: 2,
+            delay_secs: 24 * 3600,
+        });
+        add_block_at(&mut blockchain, &producer, vec![recovery_tx(&owner, &account, &configure)], Some(start)).unwrap();
+
+        // Les réglages ne peuvent être changés que par le compte lui-même
+        assert!(add_block_at(
+            &mut blockchain,
+            &producer,
+            vec![recovery_tx(&guardians[0], &account, &RecoveryOperation::Disable)],
+            Some(start + 1),
+        ).is_err());
+
+        // Une poussière reçue par la nouvelle clé ne bloque pas la récupération
+        blockchain.state.balances.insert(*new_device.public_key(), 0.5);
+        let approve = RecoveryOperation::approve(&account, &new_device);
+        let approvals = vec![
+            recovery_tx(&guardians[0], &account, &approve),
+            recovery_tx(&guardians[2], &account, &approve),
+        ];
+        add_block_at(&mut blockchain, &producer, approvals, Some(start + 10)).unwrap();
+        let scheduled = blockchain.accounts().get(&account).unwrap().scheduled_recovery.clone().unwrap();
+        assert_eq!(scheduled.new_key, *new_device.public_key());
+
+        add_block_at(&mut blockchain, &producer, Vec::new(), Some(start + 10 + 24 * 3600)).unwrap();
+        assert_eq!(blockchain.accounts().controller(&account), *new_device.public_key());
+
+        // Le nouvel appareil dépense les fonds du compte
+        let mut transfer = Transaction::new(
+            *new_device.public_key(),
+            *producer.public_key(),
+            40.0,
+            0.0,
+            TransactionMetadata::default(),
+        );
+        transfer.sign(new_device.private_key()).unwrap();
+        add_block_at(&mut blockchain, &producer, vec![transfer], Some(start + 20 + 24 * 3600)).unwrap();
+        assert_eq!(blockchain.get_balance(&account), 60.5);
+    }
+}
@@ -0,0 +1,1112 @@
+//! Moteur de consensus BFT par rounds (propose / prevote / precommit)
+//!
+//! Algorithme de type Tendermint, hauteur par hauteur:
+//! - le proposeur du round (tirage RVS) diffuse un bloc;
+//! - les membres du comité du round (sélection VRF) prévotent le bloc, ou
+//!   nil s'il est invalide, absent ou contraire à leur verrou;
+//! - un quorum de prevotes pour le bloc (polka) verrouille les membres sur
+//!   ce bloc, qu'ils précommitent;
+//! - un quorum de précommits décide le bloc, qui porte ces signatures.
+//!
+//! Un validateur verrouillé ne prévote un autre bloc que si celui-ci a
+//! obtenu un polka dans un round postérieur à son verrou. Les délais
+//! d'expiration, croissants avec le round, font passer au round suivant si
+//! le proposeur est absent ou si le vote n'aboutit pas.
+//!
+//! Le moteur est sans E/S: les messages et les délais passent par
+//! `Transport`, la construction, la validation et l'application des blocs
+//! par `ConsensusApp`. Une simulation en mémoire peut ainsi perdre, retarder
+//! et réordonner les messages de façon déterministe.
+
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use crate::{Hash, PublicKey, Signature};
+use crate::block::{Block, ValidatorSignature};
+use crate::consensus::evidence::DoubleSignEvidence;
+use crate::consensus::rvs::{hex_bytes, SelectionProof, RVS};
+use crate::consensus::VRFProof;
+use crate::crypto::keys::KeyPair;
+use crate::crypto::{hash_data, sign, verify_signature};
+
+/// Étape d'un round
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Step {
+    /// Attente de la proposition
+    Propose,
+    /// Prevote émis, attente d'un quorum
+    Prevote,
+    /// Précommit émis, attente d'un quorum
+    Precommit,
+    /// Bloc décidé pour la hauteur
+    Commit,
+}
+
+/// Type de vote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VoteKind {
+    Prevote,
+    Precommit,
+}
+
+/// Message signé par un vote
+///
+/// Le précommit d'un bloc est aussi la signature du comité portée par le
+/// bloc décidé (`Block::commit_signing_hash`).
+pub fn vote_signing_hash(kind: VoteKind, height: u64, round: u32, block_hash: Option<&Hash>) -> Hash {
+    let mut data = Vec::with_capacity(11 + 1 + 8 + 4 + 33);
+    data.extend_from_slice(b"mahala-vote");
+    data.push(kind as u8);
+    data.extend_from_slice(&height.to_le_bytes());
+    data.extend_from_slice(&round.to_le_bytes());
+    match block_hash {
+        Some(hash) => {
+            data.push(1);
+            data.extend_from_slice(hash);
+        }
+        None => data.push(0),
+    }
+    hash_data(&data)
+}
+
+/// Vote d'un membre du comité
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vote {
+    pub kind: VoteKind,
+    pub height: u64,
+    pub round: u32,
+    /// Bloc voté (`None` = nil)
+    pub block_hash: Option<Hash>,
+    #[serde(with = "hex_bytes")]
+    pub validator: PublicKey,
+    /// Preuve de sélection dans le comité du round
+    #[serde(with = "hex_bytes")]
+    pub selection_proof: VRFProof,
+    #[serde(with = "hex_bytes")]
+    pub signature: Signature,
+}
+
+impl Vote {
+    /// Créer et signer un vote
+    pub fn new(
+        kind: VoteKind,
+        height: u64,
+        round: u32,
+        block_hash: Option<Hash>,
+        selection: &SelectionProof,
+        keypair: &KeyPair,
+    ) -> Self {
+        let signature = sign(&vote_signing_hash(kind, height, round, block_hash.as_ref()), keypair.private_key());
+        Self {
+            kind,
+            height,
+            round,
+            block_hash,
+            validator: selection.validator,
+            selection_proof: selection.proof,
+            signature,
+        }
+    }
+
+    /// Précommit porté par un bloc décidé (signature de comité)
+    pub fn from_commit_signature(block: &Block, block_hash: Hash, signature: &ValidatorSignature) -> Self {
+        Self {
+            kind: VoteKind::Precommit,
+            height: block.header.height,
+            round: block.commit_round,
+            block_hash: Some(block_hash),
+            validator: signature.validator,
+            selection_proof: signature.selection_proof,
+            signature: signature.signature,
+        }
+    }
+
+    /// Vérifier la signature du vote
+    pub fn verify_signature(&self) -> bool {
+        let hash = vote_signing_hash(self.kind, self.height, self.round, self.block_hash.as_ref());
+        verify_signature(&hash, &self.signature, &self.validator)
+    }
+
+    /// Preuve de sélection du votant
+    pub fn selection(&self) -> SelectionProof {
+        SelectionProof { validator: self.validator, proof: self.selection_proof }
+    }
+}
+
+/// Proposition de bloc pour un round
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Proposal {
+    pub height: u64,
+    pub round: u32,
+    /// Round du polka déjà obtenu par ce bloc (re-proposition)
+    pub valid_round: Option<u32>,
+    pub block: Block,
+    #[serde(with = "hex_bytes")]
+    pub proposer: PublicKey,
+    #[serde(with = "hex_bytes")]
+    pub signature: Signature,
+}
+
+impl Proposal {
+    /// Créer et signer une proposition
+    pub fn new(height: u64, round: u32, valid_round: Option<u32>, block: Block, keypair: &KeyPair) -> Self {
+        let hash = Self::signing_hash(height, round, valid_round, &block.compute_hash());
+        Self {
+            height,
+            round,
+            valid_round,
+            block,
+            proposer: *keypair.public_key(),
+            signature: sign(&hash, keypair.private_key()),
+        }
+    }
+
+    fn signing_hash(height: u64, round: u32, valid_round: Option<u32>, block_hash: &Hash) -> Hash {
+        let mut data = Vec::with_capacity(15 + 8 + 4 + 5 + 32);
+        data.extend_from_slice(b"mahala-proposal");
+        data.extend_from_slice(&height.to_le_bytes());
+        data.extend_from_slice(&round.to_le_bytes());
+        match valid_round {
+            Some(valid_round) => {
+                data.push(1);
+                data.extend_from_slice(&valid_round.to_le_bytes());
+            }
+            None => data.push(0),
+        }
+        data.extend_from_slice(block_hash);
+        hash_data(&data)
+    }
+
+    /// Vérifier la signature du proposeur pour le hash du bloc
+    pub fn verify_signature(&self, block_hash: &Hash) -> bool {
+        let hash = Self::signing_hash(self.height, self.round, self.valid_round, block_hash);
+        verify_signature(&hash, &self.signature, &self.proposer)
+    }
+
+    /// Trace de la proposition, portée par le bloc décidé à un round ultérieur
+    pub fn witness(&self, block_hash: Hash) -> ProposalWitness {
+        ProposalWitness {
+            round: self.round,
+            valid_round: self.valid_round,
+            block_hash,
+            signature: self.signature,
+        }
+    }
+}
+
+/// Proposition signée d'un round sans décision, portée par le bloc décidé
+///
+/// Le proposeur de ce round n'est pas compté absent: le round a échoué
+/// malgré sa proposition (comité trop petit, votes perdus).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposalWitness {
+    pub round: u32,
+    pub valid_round: Option<u32>,
+    /// Hash du bloc proposé
+    #[serde(with = "hex_bytes")]
+    pub block_hash: Hash,
+    /// Signature du proposeur
+    #[serde(with = "hex_bytes")]
+    pub signature: Signature,
+}
+
+impl ProposalWitness {
+    /// Vérifier la signature du proposeur du round pour une hauteur
+    pub fn verify(&self, height: u64, proposer: &PublicKey) -> bool {
+        let hash = Proposal::signing_hash(height, self.round, self.valid_round, &self.block_hash);
+        verify_signature(&hash, &self.signature, proposer)
+    }
+}
+
+/// Message échangé entre validateurs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ConsensusMessage {
+    Proposal(Proposal),
+    Vote(Vote),
+}
+
+impl ConsensusMessage {
+    /// Hauteur du message
+    pub fn height(&self) -> u64 {
+        match self {
+            Self::Proposal(proposal) => proposal.height,
+            Self::Vote(vote) => vote.height,
+        }
+    }
+
+    /// Validateur signataire du message
+    pub fn sender(&self) -> PublicKey {
+        match self {
+            Self::Proposal(proposal) => proposal.proposer,
+            Self::Vote(vote) => vote.validator,
+        }
+    }
+
+    /// Vérifier la signature du message
+    pub fn verify_signature(&self) -> bool {
+        match self {
+            Self::Proposal(proposal) => proposal.verify_signature(&proposal.block.compute_hash()),
+            Self::Vote(vote) => vote.verify_signature(),
+        }
+    }
+}
+
+/// Messages de la hauteur suivante conservés par validateur, en attendant
+/// de pouvoir vérifier leur preuve de sélection
+pub const FUTURE_MESSAGES_PER_SENDER: usize = 16;
+
+/// Délai d'expiration d'une étape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Timeout {
+    pub height: u64,
+    pub round: u32,
+    pub step: Step,
+}
+
+/// Durées des délais d'expiration (millisecondes)
+#[derive(Debug, Clone)]
+pub struct TimeoutConfig {
+    /// Attente de la proposition
+    pub propose_ms: u64,
+    /// Attente d'un quorum de prevotes avant de précommiter nil
+    pub prevote_ms: u64,
+    /// Attente d'une décision avant de passer au round suivant
+    pub precommit_ms: u64,
+    /// Allongement par round
+    pub delta_ms: u64,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            propose_ms: 3000,
+            prevote_ms: 1000,
+            precommit_ms: 1000,
+            delta_ms: 500,
+        }
+    }
+}
+
+impl TimeoutConfig {
+    /// Durée d'une étape pour un round
+    pub fn duration_ms(&self, step: Step, round: u32) -> u64 {
+        let base = match step {
+            Step::Propose => self.propose_ms,
+            Step::Prevote => self.prevote_ms,
+            Step::Precommit | Step::Commit => self.precommit_ms,
+        };
+        base + self.delta_ms * round as u64
+    }
+}
+
+/// Acheminement des messages et des délais d'un validateur
+pub trait Transport {
+    /// Diffuser un message aux autres validateurs
+    fn broadcast(&mut self, message: ConsensusMessage);
+    /// Rappeler `BftEngine::handle_timeout` après `delay_ms`
+    fn schedule_timeout(&mut self, timeout: Timeout, delay_ms: u64);
+}
+
+/// Application pilotée par le moteur
+pub trait ConsensusApp {
+    /// Construire le bloc à proposer pour la hauteur
+    fn build_block(&mut self, height: u64, proposer: &PublicKey) -> Block;
+    /// Vérifier un bloc proposé (hauteur, parent, transactions)
+    fn validate_block(&mut self, block: &Block) -> bool;
+    /// Appliquer le bloc décidé, signé par le quorum du comité
+    fn commit_block(&mut self, block: Block);
+}
+
+/// Moteur BFT d'un validateur
+pub struct BftEngine {
+    keypair: KeyPair,
+    timeouts: TimeoutConfig,
+    /// Validateurs de la hauteur en cours
+    validators: RVS,
+    /// Graine de sélection de la hauteur en cours
+    seed: Hash,
+    height: u64,
+    round: u32,
+    step: Step,
+    /// Preuve de sélection dans le comité du round (`None` hors comité)
+    selection: Option<SelectionProof>,
+    locked: Option<(u32, Block)>,
+    valid: Option<(u32, Block)>,
+    /// Propositions reçues par round, avec le hash du bloc
+    proposals: HashMap<u32, (Proposal, Hash)>,
+    votes: HashMap<(u32, VoteKind), Vec<Vote>>,
+    /// Membres du comité déjà vérifiés, par round
+    committee: HashSet<(u32, PublicKey)>,
+    /// Validité des blocs proposés (par hash)
+    validity: HashMap<Hash, bool>,
+    /// Rounds dont le polka a déjà été pris en compte
+    polka_rounds: HashSet<u32>,
+    /// Messages signés de la hauteur suivante reçus en avance (au plus
+    /// `FUTURE_MESSAGES_PER_SENDER` par validateur)
+    future: Vec<ConsensusMessage>,
+    /// Équivocations constatées, à soumettre à la chaîne
+    evidence: Vec<DoubleSignEvidence>,
+}
+
+impl BftEngine {
+    /// Créer le moteur d'un validateur
+    pub fn new(keypair: KeyPair, timeouts: TimeoutConfig) -> Self {
+        Self {
+            keypair,
+            timeouts,
+            validators: RVS::new(Default::default()),
+            seed: [0u8; 32],
+            height: 0,
+            round: 0,
+            step: Step::Commit,
+            selection: None,
+            locked: None,
+            valid: None,
+            proposals: HashMap::new(),
+            votes: HashMap::new(),
+            committee: HashSet::new(),
+            validity: HashMap::new(),
+            polka_rounds: HashSet::new(),
+            future: Vec::new(),
+            evidence: Vec::new(),
+        }
+    }
+
+    /// Hauteur en cours
+    pub fn height(&self) -> u64 {
+        self.height
+    }
+
+    /// Round en cours
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    /// Étape en cours
+    pub fn step(&self) -> Step {
+        self.step
+    }
+
+    /// Round du verrou (bloc précommité)
+    pub fn locked_round(&self) -> Option<u32> {
+        self.locked.as_ref().map(|(round, _)| *round)
+    }
+
+    /// Retirer les preuves d'équivocation constatées depuis le dernier appel
+    pub fn take_evidence(&mut self) -> Vec<DoubleSignEvidence> {
+        std::mem::take(&mut self.evidence)
+    }
+
+    /// Démarrer une hauteur avec les validateurs et la graine de la chaîne
+    pub fn start_height(
+        &mut self,
+        height: u64,
+        seed: Hash,
+        validators: RVS,
+        app: &mut impl ConsensusApp,
+        transport: &mut impl Transport,
+    ) {
+        self.height = height;
+        self.seed = seed;
+        self.validators = validators;
+        self.locked = None;
+        self.valid = None;
+        self.proposals.clear();
+        self.votes.clear();
+        self.committee.clear();
+        self.validity.clear();
+        self.polka_rounds.clear();
+        self.start_round(0, app, transport);
+
+        let future = std::mem::take(&mut self.future);
+        for message in future.into_iter().filter(|m| m.height() == height) {
+            self.record(message);
+        }
+        self.run_rules(app, transport);
+    }
+
+    /// Traiter un message d'un autre validateur
+    pub fn handle_message(
+        &mut self,
+        message: ConsensusMessage,
+        app: &mut impl ConsensusApp,
+        transport: &mut impl Transport,
+    ) {
+        let height = message.height();
+        if height == self.height + 1 {
+            self.queue_future(message);
+        } else if height == self.height && self.step != Step::Commit {
+            self.record(message);
+            self.run_rules(app, transport);
+        }
+    }
+
+    /// Mettre de côté un message de la hauteur suivante
+    ///
+    /// Seuls les messages signés par un validateur de l'ensemble de la
+    /// hauteur suivante sont conservés, dans la limite de
+    /// `FUTURE_MESSAGES_PER_SENDER` par validateur. Si cet ensemble n'est pas
+    /// encore figé (changement d'époque), les validateurs actifs du registre
+    /// sont admis.
+    fn queue_future(&mut self, message: ConsensusMessage) {
+        let sender = message.sender();
+        let member = match self.validators.validator_set(self.height + 1) {
+            Some(set) => set.get(&sender).is_some(),
+            None => self.validators.validator(&sender).is_some_and(|info| info.is_active()),
+        };
+        let queued = self.future.iter().filter(|m| m.sender() == sender).count();
+        if member && queued < FUTURE_MESSAGES_PER_SENDER && message.verify_signature() {
+            self.future.push(message);
+        }
+    }
+
+    /// Traiter un délai expiré
+    pub fn handle_timeout(
+        &mut self,
+        timeout: Timeout,
+        app: &mut impl ConsensusApp,
+        transport: &mut impl Transport,
+    ) {
+        if timeout.height != self.height || timeout.round != self.round || self.step == Step::Commit {
+            return;
+        }
+        match timeout.step {
+            Step::Propose if self.step == Step::Propose => {
+                self.cast_vote(VoteKind::Prevote, None, transport);
+                self.enter_step(Step::Prevote, transport);
+            }
+            Step::Prevote if self.step == Step::Prevote => {
+                self.cast_vote(VoteKind::Precommit, None, transport);
+                self.enter_step(Step::Precommit, transport);
+            }
+            Step::Precommit => self.start_round(self.round + 1, app, transport),
+            _ => return,
+        }
+        self.run_rules(app, transport);
+    }
+
+    /// Démarrer un round: proposer si désigné, sinon attendre la proposition
+    fn start_round(&mut self, round: u32, app: &mut impl ConsensusApp, transport: &mut impl Transport) {
+        self.round = round;
+        self.step = Step::Propose;
+        self.selection = self.validators.prove_selection(
+            self.keypair.public_key(),
+            self.keypair.private_key(),
+            &self.seed,
+            self.height,
+            round,
+        );
+
+        let proposer = self.validators.proposer(&self.seed, self.height, round);
+        if proposer.as_ref() == Some(self.keypair.public_key()) {
+            let (valid_round, block) = match &self.valid {
+                Some((valid_round, block)) => (Some(*valid_round), block.clone()),
+                None => (None, app.build_block(self.height, self.keypair.public_key())),
+            };
+            // Une re-proposition est accompagnée des prevotes de son polka,
+            // que des membres ont pu manquer
+            if let Some(valid_round) = valid_round {
+                let hash = block.compute_hash();
+                for vote in self.votes.get(&(valid_round, VoteKind::Prevote)).into_iter().flatten() {
+                    if vote.block_hash == Some(hash) {
+                        transport.broadcast(ConsensusMessage::Vote(vote.clone()));
+                    }
+                }
+            }
+            let proposal = Proposal::new(self.height, round, valid_round, block, &self.keypair);
+            transport.broadcast(ConsensusMessage::Proposal(proposal.clone()));
+            self.record(ConsensusMessage::Proposal(proposal));
+        } else {
+            let timeout = Timeout { height: self.height, round, step: Step::Propose };
+            transport.schedule_timeout(timeout, self.timeouts.duration_ms(Step::Propose, round));
+        }
+    }
+
+    /// Passer à l'étape de vote suivante et armer son délai
+    ///
+    /// Le délai part à l'entrée dans l'étape: les messages perdus ne
+    /// bloquent pas le round, qui finit toujours par expirer.
+    fn enter_step(&mut self, step: Step, transport: &mut impl Transport) {
+        self.step = step;
+        let timeout = Timeout { height: self.height, round: self.round, step };
+        transport.schedule_timeout(timeout, self.timeouts.duration_ms(step, self.round));
+    }
+
+    /// Enregistrer un message vérifié de la hauteur en cours
+    fn record(&mut self, message: ConsensusMessage) {
+        match message {
+            ConsensusMessage::Proposal(mut proposal) => {
+                if self.proposals.contains_key(&proposal.round)
+                    || proposal.block.header.height != self.height
+                    || proposal.valid_round.is_some_and(|vr| vr >= proposal.round)
+                    || self.validators.proposer(&self.seed, self.height, proposal.round) != Some(proposal.proposer)
+                {
+                    return;
+                }
+                let block_hash = proposal.block.calculate_hash();
+                if proposal.verify_signature(&block_hash) {
+                    self.proposals.insert(proposal.round, (proposal, block_hash));
+                }
+            }
+            ConsensusMessage::Vote(vote) => {
+                // Un seul vote par membre et par étape: le premier est retenu,
+                // un second vote contraire et signé est une équivocation
+                let first = self.votes.get(&(vote.round, vote.kind))
+                    .and_then(|votes| votes.iter().find(|v| v.validator == vote.validator));
+                if let Some(first) = first {
+                    let first = first.clone();
+                    if let Some(evidence) = DoubleSignEvidence::new(first, vote) {
+                        if evidence.verify().is_ok() && !self.evidence.contains(&evidence) {
+                            self.evidence.push(evidence);
+                        }
+                    }
+                    return;
+                }
+                if !vote.verify_signature() {
+                    return;
+                }
+                let member = (vote.round, vote.validator);
+                if !self.committee.contains(&member) {
+                    if !self.validators.verify_selection_proof(&vote.selection(), &self.seed, self.height, vote.round) {
+                        return;
+                    }
+                    self.committee.insert(member);
+                }
+                // Entrée créée seulement pour un vote vérifié
+                self.votes.entry((vote.round, vote.kind)).or_default().push(vote);
+            }
+        }
+    }
+
+    /// Voter (si membre du comité du round) et diffuser le vote
+    fn cast_vote(&mut self, kind: VoteKind, block_hash: Option<Hash>, transport: &mut impl Transport) {
+        let Some(selection) = &self.selection else {
+            return;
+        };
+        let vote = Vote::new(kind, self.height, self.round, block_hash, selection, &self.keypair);
+        transport.broadcast(ConsensusMessage::Vote(vote.clone()));
+        self.record(ConsensusMessage::Vote(vote));
+    }
+
+    /// Appliquer les règles jusqu'à stabilité
+    fn run_rules(&mut self, app: &mut impl ConsensusApp, transport: &mut impl Transport) {
+        while self.step != Step::Commit && self.apply_rule(app, transport) {}
+    }
+
+    /// Appliquer la première règle déclenchable; `false` si aucune
+    fn apply_rule(&mut self, app: &mut impl ConsensusApp, transport: &mut impl Transport) -> bool {
+        let round = self.round;
+        let quorum = self.validators.required_quorum(self.height);
+
+        // Décision: quorum de précommits pour un bloc connu, quel que soit le round
+        let decisions: Vec<(u32, Hash)> = self.votes
+            .keys()
+            .filter(|(_, kind)| *kind == VoteKind::Precommit)
+            .filter_map(|(r, _)| match self.quorum_value(*r, VoteKind::Precommit) {
+                Some(Some(hash)) => Some((*r, hash)),
+                _ => None,
+            })
+            .collect();
+        for (commit_round, hash) in decisions {
+            if let Some(block) = self.proposed_block(&hash) {
+                if self.is_valid_block(app, &hash, &block) {
+                    self.commit(commit_round, hash, block, app);
+                    return true;
+                }
+            }
+        }
+
+        // Rattrapage: assez de membres sont déjà à un round ultérieur (le plus
+        // avancé des rounds qui atteignent ce seuil)
+        let skip = (self.validators.committee_bound(self.height) + 1).saturating_sub(quorum).max(1);
+        let later = self.votes.keys()
+            .map(|(r, _)| *r)
+            .filter(|r| *r > round && self.senders(*r) >= skip)
+            .max();
+        if let Some(later) = later {
+            self.start_round(later, app, transport);
+            return true;
+        }
+
+        // Prevote sur la proposition du round
+        if self.step == Step::Propose {
+            if let Some((proposal, hash)) = self.proposals.get(&round).cloned() {
+                let vote = match proposal.valid_round {
+                    None => Some(self.locked.as_ref().is_none_or(|(_, locked)| locked.compute_hash() == hash)),
+                    Some(vr) if self.count(vr, VoteKind::Prevote, Some(hash)) >= quorum => Some(
+                        self.locked.as_ref().is_none_or(|(locked_round, locked)| {
+                            *locked_round <= vr || locked.compute_hash() == hash
+                        }),
+                    ),
+                    Some(_) => None,
+                };
+                if let Some(unlocked) = vote {
+                    let accept = unlocked && self.is_valid_block(app, &hash, &proposal.block);
+                    self.cast_vote(VoteKind::Prevote, accept.then_some(hash), transport);
+                    self.enter_step(Step::Prevote, transport);
+                    return true;
+                }
+            }
+        }
+
+        // Polka pour la proposition du round: verrou et précommit
+        if self.step >= Step::Prevote && !self.polka_rounds.contains(&round) {
+            if let Some((proposal, hash)) = self.proposals.get(&round).cloned() {
+                if self.count(round, VoteKind::Prevote, Some(hash)) >= quorum
+                    && self.is_valid_block(app, &hash, &proposal.block)
+                {
+                    self.polka_rounds.insert(round);
+                    if self.step == Step::Prevote {
+                        self.locked = Some((round, proposal.block.clone()));
+                        self.cast_vote(VoteKind::Precommit, Some(hash), transport);
+                        self.enter_step(Step::Precommit, transport);
+                    }
+                    self.valid = Some((round, proposal.block));
+                    return true;
+                }
+            }
+        }
+
+        // Polka nil: précommit nil
+        if self.step == Step::Prevote && self.count(round, VoteKind::Prevote, None) >= quorum {
+            self.cast_vote(VoteKind::Precommit, None, transport);
+            self.enter_step(Step::Precommit, transport);
+            return true;
+        }
+
+        false
+    }
+
+    /// Décider le bloc avec les précommits du round et les propositions
+    /// reçues aux rounds précédents
+    fn commit(&mut self, commit_round: u32, hash: Hash, mut block: Block, app: &mut impl ConsensusApp) {
+        block.commit_round = commit_round;
+        block.hash = Some(hash);
+        block.validator_signatures.clear();
+        for vote in &self.votes[&(commit_round, VoteKind::Precommit)] {
+            if vote.block_hash == Some(hash) {
+                block.add_validator_signature(vote.validator, vote.signature, vote.selection_proof);
+            }
+        }
+        block.proposal_witnesses = self.proposals
+            .values()
+            .filter(|(proposal, _)| proposal.round < commit_round)
+            .map(|(proposal, block_hash)| proposal.witness(*block_hash))
+            .collect();
+        block.proposal_witnesses.sort_by_key(|witness| witness.round);
+        self.step = Step::Commit;
+        app.commit_block(block);
+    }
+
+    /// Bloc proposé (dans n'importe quel round) ayant ce hash
+    fn proposed_block(&self, hash: &Hash) -> Option<Block> {
+        self.proposals
+            .values()
+            .find(|(_, block_hash)| block_hash == hash)
+            .map(|(proposal, _)| proposal.block.clone())
+    }
+
+    /// Validité d'un bloc proposé (demandée une fois à l'application)
+    fn is_valid_block(&mut self, app: &mut impl ConsensusApp, hash: &Hash, block: &Block) -> bool {
+        *self.validity.entry(*hash).or_insert_with(|| app.validate_block(block))
+    }
+
+    /// Nombre de votes d'une étape pour une valeur
+    fn count(&self, round: u32, kind: VoteKind, value: Option<Hash>) -> usize {
+        self.votes.get(&(round, kind)).map_or(0, |votes| votes.iter().filter(|v| v.block_hash == value).count())
+    }
+
+    /// Valeur ayant atteint le quorum pour une étape
+    fn quorum_value(&self, round: u32, kind: VoteKind) -> Option<Option<Hash>> {
+        let quorum = self.validators.required_quorum(self.height);
+        let votes = self.votes.get(&(round, kind))?;
+        votes.iter()
+            .map(|v| v.block_hash)
+            .find(|value| self.count(round, kind, *value) >= quorum)
+    }
+
+    /// Nombre de membres distincts ayant voté dans un round
+    fn senders(&self, round: u32) -> usize {
+        let mut senders: HashSet<&PublicKey> = HashSet::new();
+        for kind in [VoteKind::Prevote, VoteKind::Precommit] {
+            if let Some(votes) = self.votes.get(&(round, kind)) {
+                senders.extend(votes.iter().map(|v| &v.validator));
+            }
+        }
+        senders.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::rvs::RVSConfig;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    /// Application de test: chaîne de blocs vides
+    #[derive(Default)]
+    struct TestApp {
+        blocks: Vec<Block>,
+    }
+
+    impl TestApp {
+        fn last_hash(&self) -> Hash {
+            self.blocks.last().and_then(|b| b.hash).unwrap_or([0u8; 32])
+        }
+    }
+
+    impl ConsensusApp for TestApp {
+        fn build_block(&mut self, height: u64, proposer: &PublicKey) -> Block {
+            let mut block = Block::new(height, self.last_hash(), Vec::new(), *proposer);
+            block.calculate_hash();
+            block
+        }
+
+        fn validate_block(&mut self, block: &Block) -> bool {
+            block.header.height == self.blocks.len() as u64 + 1 && block.header.previous_hash == self.last_hash()
+        }
+
+        fn commit_block(&mut self, block: Block) {
+            self.blocks.push(block);
+        }
+    }
+
+    /// Événement de la simulation
+    enum Event {
+        Deliver(usize, Box<ConsensusMessage>),
+        Timeout(usize, Timeout),
+    }
+
+    /// Réseau simulé: pertes et délais tirés d'un générateur déterministe
+    struct SimNetwork {
+        now: u64,
+        sequence: u64,
+        queue: BinaryHeap<Reverse<(u64, u64)>>,
+        events: HashMap<u64, Event>,
+        rng: StdRng,
+        drop_rate: f64,
+        max_delay_ms: u64,
+        offline: HashSet<usize>,
+        nodes: usize,
+    }
+
+    impl SimNetwork {
+        fn new(nodes: usize, seed: u64, drop_rate: f64, max_delay_ms: u64) -> Self {
+            Self {
+                now: 0,
+                sequence: 0,
+                queue: BinaryHeap::new(),
+                events: HashMap::new(),
+                rng: StdRng::seed_from_u64(seed),
+                drop_rate,
+                max_delay_ms,
+                offline: HashSet::new(),
+                nodes,
+            }
+        }
+
+        fn push(&mut self, delay_ms: u64, event: Event) {
+            self.sequence += 1;
+            self.queue.push(Reverse((self.now + delay_ms, self.sequence)));
+            self.events.insert(self.sequence, event);
+        }
+
+        fn pop(&mut self) -> Option<Event> {
+            let Reverse((time, sequence)) = self.queue.pop()?;
+            self.now = time;
+            self.events.remove(&sequence)
+        }
+    }
+
+    /// Transport d'un nœud de la simulation
+    struct SimTransport<'a> {
+        network: &'a mut SimNetwork,
+        node: usize,
+    }
+
+    impl Transport for SimTransport<'_> {
+        fn broadcast(&mut self, message: ConsensusMessage) {
+            if self.network.offline.contains(&self.node) {
+                return;
+            }
+            for target in (0..self.network.nodes).filter(|t| *t != self.node) {
+                if self.network.rng.gen_bool(self.network.drop_rate) {
+                    continue;
+                }
+                let delay = self.network.rng.gen_range(1..=self.network.max_delay_ms);
+                self.network.push(delay, Event::Deliver(target, Box::new(message.clone())));
+            }
+        }
+
+        fn schedule_timeout(&mut self, timeout: Timeout, delay_ms: u64) {
+            self.network.push(delay_ms, Event::Timeout(self.node, timeout));
+        }
+    }
+
+    /// Validateurs de test: tous membres du comité de chaque round
+    fn validator_set(count: usize) -> (Vec<KeyPair>, RVS) {
+        let keypairs: Vec<KeyPair> = (0..count).map(|i| KeyPair::from_seed(&[i as u8 + 1; 32])).collect();
+        let mut rvs = RVS::new(RVSConfig { validator_count: count, ..Default::default() });
+        for keypair in &keypairs {
+            rvs.register_validator(*keypair.public_key(), *keypair.public_key());
+        }
+        (keypairs, rvs)
+    }
+
+    const SEED: Hash = [7u8; 32];
+
+    /// Faire tourner la simulation jusqu'à `target` blocs chez les nœuds en
+    /// ligne; les nœuds en retard récupèrent les blocs décidés (synchro)
+    fn simulate(network: &mut SimNetwork, keypairs: &[KeyPair], rvs: &RVS, target: usize) -> Vec<TestApp> {
+        let mut engines: Vec<BftEngine> = keypairs
+            .iter()
+            .map(|k| BftEngine::new(k.clone(), TimeoutConfig::default()))
+            .collect();
+        let mut apps: Vec<TestApp> = keypairs.iter().map(|_| TestApp::default()).collect();
+        for (node, (engine, app)) in engines.iter_mut().zip(apps.iter_mut()).enumerate() {
+            let mut transport = SimTransport { network, node };
+            engine.start_height(1, SEED, rvs.clone(), app, &mut transport);
+        }
+
+        let online: Vec<usize> = (0..keypairs.len()).filter(|n| !network.offline.contains(n)).collect();
+        while online.iter().any(|n| apps[*n].blocks.len() < target) {
+            assert!(network.now < 600_000, "no progress after 10 minutes");
+            let event = network.pop().expect("timeouts keep the simulation running");
+            let node = match &event {
+                Event::Deliver(node, _) | Event::Timeout(node, _) => *node,
+            };
+            if network.offline.contains(&node) {
+                continue;
+            }
+
+            let mut transport = SimTransport { network, node };
+            match event {
+                Event::Deliver(_, message) => engines[node].handle_message(*message, &mut apps[node], &mut transport),
+                Event::Timeout(_, timeout) => engines[node].handle_timeout(timeout, &mut apps[node], &mut transport),
+            }
+
+            // Synchro: blocs décidés vérifiés contre le comité de leur round
+            let longest = online.iter().map(|n| apps[*n].blocks.len()).max().unwrap();
+            let lagging: Vec<usize> = online.iter().copied().filter(|n| apps[*n].blocks.len() < longest).collect();
+            for lagging in lagging {
+                let source = online.iter().copied().find(|n| apps[*n].blocks.len() == longest).unwrap();
+                let block = apps[source].blocks[apps[lagging].blocks.len()].clone();
+                assert!(block.is_valid(Some(apps[lagging].last_hash())));
+                assert!(rvs.verify_block_committee(&block, &SEED).is_ok());
+                apps[lagging].blocks.push(block);
+            }
+
+            for n in online.iter().copied() {
+                let next = apps[n].blocks.len() as u64 + 1;
+                if engines[n].height() < next {
+                    let mut transport = SimTransport { network, node: n };
+                    engines[n].start_height(next, SEED, rvs.clone(), &mut apps[n], &mut transport);
+                }
+            }
+        }
+        apps
+    }
+
+    /// Aucune hauteur décidée différemment par deux nœuds
+    fn assert_agreement(apps: &[TestApp]) {
+        for a in apps {
+            for b in apps {
+                for (x, y) in a.blocks.iter().zip(&b.blocks) {
+                    assert_eq!(x.hash, y.hash);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_honest_validators_commit() {
+        let (keypairs, rvs) = validator_set(4);
+        let mut network = SimNetwork::new(4, 1, 0.0, 100);
+        let apps = simulate(&mut network, &keypairs, &rvs, 3);
+
+        assert_agreement(&apps);
+        for block in &apps[0].blocks {
+            assert!(block.validator_signatures.len() >= rvs.required_quorum(block.header.height));
+            assert!(rvs.verify_block_committee(block, &SEED).is_ok());
+        }
+        assert_eq!(apps[0].blocks[0].commit_round, 0);
+    }
+
+    #[test]
+    fn test_absent_proposer_changes_round() {
+        let (keypairs, rvs) = validator_set(4);
+        let proposer = rvs.proposer(&SEED, 1, 0).unwrap();
+        let absent = keypairs.iter().position(|k| *k.public_key() == proposer).unwrap();
+
+        let mut network = SimNetwork::new(4, 2, 0.0, 100);
+        network.offline.insert(absent);
+        let apps = simulate(&mut network, &keypairs, &rvs, 1);
+
+        assert_agreement(&apps);
+        let block = &apps[(absent + 1) % 4].blocks[0];
+        assert!(block.commit_round > 0);
+        assert_ne!(block.header.validator, proposer);
+    }
+
+    #[test]
+    fn test_dropped_and_delayed_messages() {
+        let (keypairs, rvs) = validator_set(4);
+        for seed in 0..2 {
+            // 10% de pertes, délais jusqu'à la moitié du délai de proposition
+            let mut network = SimNetwork::new(4, seed, 0.1, 1500);
+            let apps = simulate(&mut network, &keypairs, &rvs, 3);
+            assert_agreement(&apps);
+        }
+    }
+
+    /// Transport qui mémorise les messages émis
+    #[derive(Default)]
+    struct Recorder {
+        sent: Vec<ConsensusMessage>,
+    }
+
+    impl Transport for Recorder {
+        fn broadcast(&mut self, message: ConsensusMessage) {
+            self.sent.push(message);
+        }
+
+        fn schedule_timeout(&mut self, _: Timeout, _: u64) {}
+    }
+
+    #[test]
+    fn test_equivocating_vote_yields_evidence() {
+        let (keypairs, rvs) = validator_set(4);
+        let mut app = TestApp::default();
+        let mut transport = Recorder::default();
+        let mut engine = BftEngine::new(keypairs[0].clone(), TimeoutConfig::default());
+        engine.start_height(1, SEED, rvs.clone(), &mut app, &mut transport);
+
+        let other = &keypairs[1];
+        let selection = rvs.prove_selection(other.public_key(), other.private_key(), &SEED, 1, 0).unwrap();
+        let first = Vote::new(VoteKind::Prevote, 1, 0, Some([1u8; 32]), &selection, other);
+        let second = Vote::new(VoteKind::Prevote, 1, 0, None, &selection, other);
+        for vote in [first.clone(), second.clone(), second.clone()] {
+            engine.handle_message(ConsensusMessage::Vote(vote), &mut app, &mut transport);
+        }
+
+        // Le premier vote reste compté; la preuve n'est produite qu'une fois
+        assert_eq!(engine.count(0, VoteKind::Prevote, Some([1u8; 32])), 1);
+        let evidence = engine.take_evidence();
+        assert_eq!(evidence, vec![DoubleSignEvidence::new(first, second).unwrap()]);
+        assert!(engine.take_evidence().is_empty());
+    }
+
+    #[test]
+    fn test_unverified_votes_do_not_block_round_catch_up() {
+        let (keypairs, rvs) = validator_set(4);
+        let mut app = TestApp::default();
+        let mut transport = Recorder::default();
+        let mut engine = BftEngine::new(keypairs[0].clone(), TimeoutConfig::default());
+        engine.start_height(1, SEED, rvs.clone(), &mut app, &mut transport);
+        let vote = |keypair: &KeyPair, round| {
+            let selection = rvs.prove_selection(keypair.public_key(), keypair.private_key(), &SEED, 1, round).unwrap();
+            Vote::new(VoteKind::Prevote, 1, round, None, &selection, keypair)
+        };
+
+        // Vote falsifié pour un round lointain: aucune entrée créée
+        let mut forged = vote(&keypairs[1], u32::MAX);
+        forged.signature = [0u8; 64];
+        let outsider = KeyPair::from_seed(&[99u8; 32]);
+        let selection = SelectionProof { validator: *outsider.public_key(), proof: forged.selection_proof };
+        let stranger = Vote::new(VoteKind::Prevote, 1, u32::MAX, None, &selection, &outsider);
+        for message in [forged, stranger] {
+            engine.handle_message(ConsensusMessage::Vote(message), &mut app, &mut transport);
+        }
+        assert!(engine.votes.keys().all(|(round, _)| *round != u32::MAX));
+
+        // Un membre seul au round 9 ne masque pas les deux membres du round 2
+        // (seuil de rattrapage: borne 4 - quorum 3 + 1)
+        for message in [vote(&keypairs[1], 9), vote(&keypairs[2], 2), vote(&keypairs[3], 2)] {
+            engine.handle_message(ConsensusMessage::Vote(message), &mut app, &mut transport);
+        }
+        assert_eq!(engine.round(), 2);
+    }
+
+    #[test]
+    fn test_future_messages_are_bounded() {
+        let (keypairs, rvs) = validator_set(4);
+        let mut app = TestApp::default();
+        let mut transport = Recorder::default();
+        let mut engine = BftEngine::new(keypairs[0].clone(), TimeoutConfig::default());
+        engine.start_height(1, SEED, rvs.clone(), &mut app, &mut transport);
+
+        let member = &keypairs[1];
+        let selection = rvs.prove_selection(member.public_key(), member.private_key(), &SEED, 2, 0).unwrap();
+        for round in 0..2 * FUTURE_MESSAGES_PER_SENDER as u32 {
+            let vote = Vote::new(VoteKind::Prevote, 2, round, None, &selection, member);
+            engine.handle_message(ConsensusMessage::Vote(vote), &mut app, &mut transport);
+        }
+        assert_eq!(engine.future.len(), FUTURE_MESSAGES_PER_SENDER);
+
+        // Validateur hors de l'ensemble ou signature invalide: ignoré
+        let outsider = KeyPair::from_seed(&[99u8; 32]);
+        let selection = SelectionProof { validator: *outsider.public_key(), proof: selection.proof };
+        let vote = Vote::new(VoteKind::Prevote, 2, 0, None, &selection, &outsider);
+        engine.handle_message(ConsensusMessage::Vote(vote), &mut app, &mut transport);
+        let member = &keypairs[2];
+        let selection = rvs.prove_selection(member.public_key(), member.private_key(), &SEED, 2, 0).unwrap();
+        let mut forged = Vote::new(VoteKind::Prevote, 2, 0, None, &selection, member);
+        forged.signature = [0u8; 64];
+        engine.handle_message(ConsensusMessage::Vote(forged), &mut app, &mut transport);
+        assert_eq!(engine.future.len(), FUTURE_MESSAGES_PER_SENDER);
+    }
+
+    #[test]
+    fn test_locked_validator_rejects_other_block() {
+        let (keypairs, rvs) = validator_set(4);
+        let keypair_of = |key: PublicKey| keypairs.iter().find(|k| *k.public_key() == key).unwrap();
+        let vote = |keypair: &KeyPair, kind, round, hash| {
+            let selection = rvs.prove_selection(keypair.public_key(), keypair.private_key(), &SEED, 1, round).unwrap();
+            ConsensusMessage::Vote(Vote::new(kind, 1, round, hash, &selection, keypair))
+        };
+
+        // Le moteur testé n'est proposeur ni au round 0 ni au round 1
+        let proposers = [rvs.proposer(&SEED, 1, 0).unwrap(), rvs.proposer(&SEED, 1, 1).unwrap()];
+        let me = keypairs.iter().find(|k| !proposers.contains(k.public_key())).unwrap();
+        let others: Vec<&KeyPair> = keypairs.iter().filter(|k| k.public_key() != me.public_key()).collect();
+
+        let mut app = TestApp::default();
+        let mut transport = Recorder::default();
+        let mut engine = BftEngine::new(me.clone(), TimeoutConfig::default());
+        engine.start_height(1, SEED, rvs.clone(), &mut app, &mut transport);
+
+        // Round 0: polka pour le bloc A, le moteur se verrouille
+        let block_a = app.build_block(1, &proposers[0]);
+        let hash_a = block_a.get_hash().unwrap();
+        let proposal = Proposal::new(1, 0, None, block_a, keypair_of(proposers[0]));
+        engine.handle_message(ConsensusMessage::Proposal(proposal), &mut app, &mut transport);
+        for other in &others[..2] {
+            engine.handle_message(vote(other, VoteKind::Prevote, 0, Some(hash_a)), &mut app, &mut transport);
+        }
+        assert_eq!(engine.locked_round(), Some(0));
+        assert_eq!(engine.step(), Step::Precommit);
+
+        // Les précommits n'aboutissent pas: round 1
+        for other in &others[..2] {
+            engine.handle_message(vote(other, VoteKind::Precommit, 0, None), &mut app, &mut transport);
+        }
+        engine.handle_timeout(Timeout { height: 1, round: 0, step: Step::Precommit }, &mut app, &mut transport);
+        assert_eq!(engine.round(), 1);
+
+        // Un autre bloc sans polka: prevote nil
+        let mut block_b = Block::new(1, [0u8; 32], Vec::new(), proposers[1]);
+        block_b.header.timestamp += 1;
+        let hash_b = block_b.calculate_hash();
+        let proposal = Proposal::new(1, 1, None, block_b, keypair_of(proposers[1]));
+        engine.handle_message(ConsensusMessage::Proposal(proposal), &mut app, &mut transport);
+
+        let prevote = transport.sent.iter().rev().find_map(|m| match m {
+            ConsensusMessage::Vote(v) if v.kind == VoteKind::Prevote && v.round == 1 => Some(v.block_hash),
+            _ => None,
+        });
+        assert_eq!(prevote, Some(None));
+        assert_ne!(Some(hash_a), Some(hash_b));
+    }
+}
@@ -0,0 +1,273 @@
+//! Preuves d'équivocation et sanction des validateurs
+//!
+//! Un validateur équivoque s'il signe deux votes différents pour la même
+//! hauteur, le même round et la même étape: deux blocs, ou un bloc et nil.
+//! Le précommit d'un bloc étant aussi la signature de comité portée par le
+//! bloc décidé, un bloc et un précommit contraire suffisent.
+//!
+//! Les deux votes signés forment une preuve vérifiable par tout nœud sans
+//! autre contexte. Incluse dans une transaction `Evidence`, elle est
+//! enregistrée dans l'état de la chaîne: le fautif est exclu de la sélection
+//! pendant `RVSConfig::jail_epochs` époques et sa réputation retombe au
+//! minimum. Les frais étant brûlés, il n'y a pas de récompense à confisquer.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::PublicKey;
+use crate::consensus::bft::{Vote, VoteKind};
+use crate::consensus::rvs::{hex_bytes, RVS};
+
+/// Erreurs de validation d'une preuve
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EvidenceError {
+    #[error("votes do not conflict")]
+    NotConflicting,
+
+    #[error("invalid vote signature")]
+    InvalidSignature,
+
+    #[error("evidence height is not final")]
+    FutureHeight,
+
+    #[error("evidence is too old")]
+    Expired,
+
+    #[error("offender is not a validator")]
+    UnknownValidator,
+
+    #[error("offence already punished")]
+    AlreadyPunished,
+}
+
+/// Preuve de double signature: deux votes contradictoires d'un validateur
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DoubleSignEvidence {
+    pub first: Vote,
+    pub second: Vote,
+}
+
+impl DoubleSignEvidence {
+    /// Assembler une preuve à partir de deux votes
+    ///
+    /// Les votes sont ordonnés par signature: deux nœuds témoins de la même
+    /// faute produisent la même preuve. `None` si les votes ne sont pas
+    /// contradictoires.
+    pub fn new(a: Vote, b: Vote) -> Option<Self> {
+        let (first, second) = if a.signature <= b.signature { (a, b) } else { (b, a) };
+        let evidence = Self { first, second };
+        evidence.is_conflicting().then_some(evidence)
+    }
+
+    /// Validateur fautif (clé ayant signé les deux votes)
+    pub fn offender(&self) -> PublicKey {
+        self.first.validator
+    }
+
+    /// Hauteur de la faute
+    pub fn height(&self) -> u64 {
+        self.first.height
+    }
+
+    /// Même signataire et même étape, mais valeurs différentes
+    fn is_conflicting(&self) -> bool {
+        let (a, b) = (&self.first, &self.second);
+        a.validator == b.validator
+            && a.kind == b.kind
+            && a.height == b.height
+            && a.round == b.round
+            && a.block_hash != b.block_hash
+    }
+
+    /// Vérifier que les votes sont contradictoires et signés par le fautif
+    pub fn verify(&self) -> Result<(), EvidenceError> {
+        if !self.is_conflicting() {
+            return Err(EvidenceError::NotConflicting);
+        }
+        if !self.first.verify_signature() || !self.second.verify_signature() {
+            return Err(EvidenceError::InvalidSignature);
+        }
+        Ok(())
+    }
+}
+
+/// Faute sanctionnée
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Offence {
+    /// Clé ayant signé les votes contradictoires
+    #[serde(with = "hex_bytes")]
+    pub signer: PublicKey,
+    /// Clé du validateur sanctionné (clé courante du compte du signataire)
+    #[serde(with = "hex_bytes")]
+    pub validator: PublicKey,
+    pub height: u64,
+    pub round: u32,
+    pub kind: VoteKind,
+    /// Bloc ayant inclus la preuve
+    pub reported_at: u64,
+    /// Hauteur à partir de laquelle le validateur est de nouveau sélectionnable
+    pub jailed_until: u64,
+}
+
+/// Registre des fautes sanctionnées (état de la chaîne)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlashingRegistry {
+    offences: Vec<Offence>,
+}
+
+impl SlashingRegistry {
+    /// Créer un registre vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enregistrer la faute prouvée par une preuve déjà vérifiée
+    ///
+    /// Une faute (signataire, hauteur, round, étape) n'est sanctionnée
+    /// qu'une fois, quels que soient les votes présentés.
+    pub fn record(
+        &mut self,
+        evidence: &DoubleSignEvidence,
+        validator: PublicKey,
+        reported_at: u64,
+        jailed_until: u64,
+    ) -> Result<&Offence, EvidenceError> {
+        let vote = &evidence.first;
+        if self.offences.iter().any(|o| {
+            o.signer == vote.validator && o.height == vote.height && o.round == vote.round && o.kind == vote.kind
+        }) {
+            return Err(EvidenceError::AlreadyPunished);
+        }
+
+        self.offences.push(Offence {
+            signer: vote.validator,
+            validator,
+            height: vote.height,
+            round: vote.round,
+            kind: vote.kind,
+            reported_at,
+            jailed_until,
+        });
+        Ok(self.offences.last().expect("Offence just recorded"))
+    }
+
+    /// Fautes sanctionnées par un bloc
+    pub fn offences_at(&self, block_height: u64) -> impl Iterator<Item = &Offence> {
+        self.offences.iter().filter(move |o| o.reported_at == block_height)
+    }
+
+    /// Fautes d'un validateur (clé signataire ou sanctionnée)
+    pub fn offences_of<'a>(&'a self, validator: &'a PublicKey) -> impl Iterator<Item = &'a Offence> {
+        self.offences.iter().filter(move |o| &o.signer == validator || &o.validator == validator)
+    }
+
+    /// Toutes les fautes sanctionnées
+    pub fn offences(&self) -> &[Offence] {
+        &self.offences
+    }
+}
+
+impl RVS {
+    /// Vérifier une preuve incluse dans un bloc et calculer la fin de
+    /// l'exclusion du validateur
+    ///
+    /// La faute doit précéder le bloc et dater de moins de
+    /// `evidence_max_age_epochs` époques. L'exclusion court jusqu'au début
+    /// de la `jail_epochs`-ième époque suivant celle du bloc.
+    pub fn check_evidence(
+        &self,
+        evidence: &DoubleSignEvidence,
+        validator: &PublicKey,
+        block_height: u64,
+    ) -> Result<u64, EvidenceError> {
+        evidence.verify()?;
+
+        let config = self.config();
+        let epoch_length = config.epoch_length.max(1);
+        let height = evidence.height();
+        if height >= block_height {
+            return Err(EvidenceError::FutureHeight);
+        }
+        if block_height - height > config.evidence_max_age_epochs * epoch_length {
+            return Err(EvidenceError::Expired);
+        }
+        if !self.is_validator(validator) {
+            return Err(EvidenceError::UnknownValidator);
+        }
+        // Le signataire devait appartenir à l'ensemble de l'époque de la faute
+        if let Some(set) = self.validator_set(height) {
+            if set.get(&evidence.offender()).is_none() {
+                return Err(EvidenceError::UnknownValidator);
+            }
+        }
+
+        Ok((self.epoch(block_height) + config.jail_epochs) * epoch_length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consensus::rvs::{RVSConfig, SelectionProof};
+    use crate::crypto::keys::KeyPair;
+
+    fn vote(keypair: &KeyPair, round: u32, block_hash: Option<[u8; 32]>) -> Vote {
+        let selection = SelectionProof { validator: *keypair.public_key(), proof: [0u8; 80] };
+        Vote::new(VoteKind::Precommit, 10, round, block_hash, &selection, keypair)
+    }
+
+    #[test]
+    fn test_double_sign_evidence() {
+        let keypair = KeyPair::new();
+        let a = vote(&keypair, 0, Some([1u8; 32]));
+        let b = vote(&keypair, 0, Some([2u8; 32]));
+
+        let evidence = DoubleSignEvidence::new(a.clone(), b.clone()).unwrap();
+        assert_eq!(evidence, DoubleSignEvidence::new(b.clone(), a.clone()).unwrap());
+        assert_eq!(evidence.offender(), *keypair.public_key());
+        assert_eq!(evidence.verify(), Ok(()));
+
+        // Un bloc et nil sont contradictoires; deux rounds ou deux votants ne le sont pas
+        assert!(DoubleSignEvidence::new(a.clone(), vote(&keypair, 0, None)).is_some());
+        assert!(DoubleSignEvidence::new(a.clone(), vote(&keypair, 1, Some([2u8; 32]))).is_none());
+        assert!(DoubleSignEvidence::new(a.clone(), vote(&KeyPair::new(), 0, Some([2u8; 32]))).is_none());
+
+        let mut forged = evidence.clone();
+        forged.second.block_hash = Some([3u8; 32]);
+        assert_eq!(forged.verify(), Err(EvidenceError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_check_evidence_and_jail() {
+        let config = RVSConfig { epoch_length: 10, jail_epochs: 2, evidence_max_age_epochs: 1, ..Default::default() };
+        let mut rvs = RVS::new(config);
+        let keypair = KeyPair::new();
+        let offender = *keypair.public_key();
+        let evidence = DoubleSignEvidence::new(vote(&keypair, 0, Some([1u8; 32])), vote(&keypair, 0, None)).unwrap();
+
+        assert_eq!(rvs.check_evidence(&evidence, &offender, 12), Err(EvidenceError::UnknownValidator));
+        rvs.register_validator(offender, offender);
+        assert_eq!(rvs.check_evidence(&evidence, &offender, 10), Err(EvidenceError::FutureHeight));
+        assert_eq!(rvs.check_evidence(&evidence, &offender, 21), Err(EvidenceError::Expired));
+        let until = rvs.check_evidence(&evidence, &offender, 12).unwrap();
+        assert_eq!(until, 30);
+
+        let mut registry = SlashingRegistry::new();
+        registry.record(&evidence, offender, 12, until).unwrap();
+        assert_eq!(registry.record(&evidence, offender, 13, until), Err(EvidenceError::AlreadyPunished));
+        assert_eq!(registry.offences_at(12).count(), 1);
+
+        rvs.jail_validator(&offender, until);
+        assert_eq!(rvs.active_validators_count(), 0);
+        // L'exclusion prend effet à l'époque suivante
+        assert!(rvs.selection_threshold(&offender, 5).is_some());
+        rvs.begin_epoch(1);
+        assert!(rvs.selection_threshold(&offender, 10).is_none());
+        // Un validateur exclu ne peut pas se réenregistrer
+        rvs.register_validator(offender, offender);
+        assert_eq!(rvs.jailed_until(&offender), Some(30));
+
+        assert!(rvs.release_validators(29).is_empty());
+        assert_eq!(rvs.release_validators(30), vec![offender]);
+        assert_eq!(rvs.validator(&offender).unwrap().reputation, crate::consensus::rvs::MIN_REPUTATION);
+    }
+}
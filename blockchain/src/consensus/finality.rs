@@ -0,0 +1,108 @@
+//! Certificats de finalité
+//!
+//! Un bloc décidé porte les précommits d'un quorum du comité de son round:
+//! détachés du bloc, ils forment un certificat vérifiable par un client qui
+//! ne connaît que le calendrier de l'époque (ensemble de validateurs figé,
+//! graine et quorum), sans rejouer la chaîne.
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{Hash, PublicKey};
+use crate::block::{Block, ValidatorSignature};
+use crate::consensus::bft::{vote_signing_hash, VoteKind};
+use crate::consensus::rvs::{hex_bytes, EpochSchedule, SelectionProof};
+use crate::crypto::verify_signature;
+
+/// Erreurs de vérification d'un certificat ou d'un checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FinalityError {
+    #[error("height {0} is outside the epoch schedule")]
+    WrongEpoch(u64),
+
+    #[error("certificate does not match the checkpoint")]
+    Mismatch,
+
+    #[error("validator {0} is not in the committee")]
+    NotInCommittee(String),
+
+    #[error("invalid signature from validator {0}")]
+    InvalidSignature(String),
+
+    #[error("duplicate signature from validator {0}")]
+    DuplicateSigner(String),
+
+    #[error("not enough signatures: {have} of {need}")]
+    NoQuorum { have: usize, need: usize },
+}
+
+/// Certificat de finalité d'un bloc: précommits d'un quorum de son comité
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinalityCertificate {
+    pub height: u64,
+    /// Round de validation du bloc
+    pub round: u32,
+    #[serde(with = "hex_bytes")]
+    pub block_hash: Hash,
+    /// Précommits des membres du comité, avec leurs preuves de sélection
+    pub signatures: Vec<ValidatorSignature>,
+}
+
+impl FinalityCertificate {
+    /// Extraire le certificat d'un bloc décidé
+    pub fn from_block(block: &Block, block_hash: Hash) -> Self {
+        Self {
+            height: block.header.height,
+            round: block.commit_round,
+            block_hash,
+            signatures: block.validator_signatures.clone(),
+        }
+    }
+
+    /// Message signé par les membres du comité (précommit du bloc)
+    pub fn signing_hash(&self) -> Hash {
+        vote_signing_hash(VoteKind::Precommit, self.height, self.round, Some(&self.block_hash))
+    }
+
+    /// Le validateur a-t-il signé le bloc ?
+    pub fn is_signer(&self, validator: &PublicKey) -> bool {
+        self.signatures.iter().any(|vs| &vs.validator == validator)
+    }
+
+    /// Vérifier le certificat contre le calendrier de l'époque du bloc
+    ///
+    /// Chaque signataire doit prouver sa sélection dans le comité du round
+    /// et avoir signé le précommit du bloc; les signataires distincts
+    /// doivent atteindre le quorum.
+    pub fn verify(&self, schedule: &EpochSchedule) -> Result<(), FinalityError> {
+        if !schedule.contains(self.height) {
+            return Err(FinalityError::WrongEpoch(self.height));
+        }
+
+        let signing_hash = self.signing_hash();
+        let mut signers = HashSet::new();
+        for vs in &self.signatures {
+            let validator = hex::encode(vs.validator);
+            if !signers.insert(vs.validator) {
+                return Err(FinalityError::DuplicateSigner(validator));
+            }
+            let proof = SelectionProof { validator: vs.validator, proof: vs.selection_proof };
+            if !schedule.verify_selection_proof(&proof, self.height, self.round) {
+                return Err(FinalityError::NotInCommittee(validator));
+            }
+            if !verify_signature(&signing_hash, &vs.signature, &vs.validator) {
+                return Err(FinalityError::InvalidSignature(validator));
+            }
+        }
+        check_quorum(signers.len(), schedule)
+    }
+}
+
+/// Quorum de l'époque, d'au moins une signature
+pub(crate) fn check_quorum(have: usize, schedule: &EpochSchedule) -> Result<(), FinalityError> {
+    let need = schedule.required_quorum.max(1);
+    if have < need {
+        return Err(FinalityError::NoQuorum { have, need });
+    }
+    Ok(())
+}
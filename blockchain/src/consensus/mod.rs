@@ -1,11 +1,17 @@
 //! Consensus RVS (Random Validator Selection) pour Mahala
 //!
 //! Sélection aléatoire de validateurs avec VRF (Verifiable Random Function)
-//! pour garantir la décentralisation et la sécurité
+//! (ECVRF, RFC 9381) pour garantir la décentralisation et la sécurité
 
+pub mod bft;
+pub mod evidence;
+pub mod finality;
 pub mod rvs;
 pub mod vrf;
 
+pub use bft::{BftEngine, ConsensusApp, ConsensusMessage, Transport};
+pub use evidence::{DoubleSignEvidence, SlashingRegistry};
+pub use finality::FinalityCertificate;
 pub use rvs::RVS;
-pub use vrf::VRF;
+pub use vrf::{VRF, VRFHash, VRFOutput, VRFProof};
 
@@ -2,10 +2,28 @@
 //!
 //! Sélection aléatoire de validateurs toutes les 5 secondes
 //! avec quorum de 67% pour valider les blocs
+//!
+//! La sélection est privée et vérifiable: chaque validateur évalue sa VRF
+//! sur la graine de l'époque, la hauteur et le round BFT du bloc. Il fait
+//! partie du comité si la sortie est sous son seuil, et publie alors sa
+//! preuve; tout nœud vérifie la preuve avec la clé publique du validateur.
+//! Un nouveau round tire un nouveau comité, ce qui débloque une hauteur dont
+//! le comité est trop petit pour le quorum.
+//!
+//! La taille du comité n'est pas fixe: le quorum est calculé sur une borne
+//! haute de cette taille (`ValidatorSet::committee_bound`), et non sur la
+//! seule taille attendue. La taille attendue est relevée au-dessus de la
+//! taille configurée jusqu'à ce que ce quorum reste sous la taille attendue
+//! d'au moins un écart-type (`ValidatorSet::committee_size`): la plupart des
+//! rounds tirent un comité capable de décider.
 
-use crate::{PublicKey, Hash, Timestamp};
-use crate::consensus::vrf::VRF;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::{PublicKey, PrivateKey, Hash, Timestamp};
+use crate::block::Block;
+use crate::consensus::vrf::{VRFProof, VRF};
+use crate::crypto::hash_data;
+use crate::storage::snapshot::sorted_map;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 /// Configuration du consensus RVS
 #[derive(Debug, Clone)]
@@ -13,11 +31,38 @@ pub struct RVSConfig {
     /// Intervalle entre les blocs (en secondes)
     pub block_interval: u64,
     
-    /// Nombre de validateurs à sélectionner
+    /// Taille minimale du comité attendu (relevée pour que le quorum reste
+    /// atteignable, voir `ValidatorSet::committee_size`)
     pub validator_count: usize,
     
     /// Quorum requis (en pourcentage, 67 par défaut)
     pub quorum_percentage: u8,
+    
+    /// Durée d'une époque (en blocs)
+    pub epoch_length: u64,
+    
+    /// Durée d'exclusion d'un validateur sanctionné (en époques)
+    pub jail_epochs: u64,
+    
+    /// Âge maximal d'une preuve d'équivocation (en époques)
+    pub evidence_max_age_epochs: u64,
+    
+    /// Rounds manqués consécutivement comme proposeur avant désactivation
+    pub max_missed_rounds: u32,
+    
+    /// Durée sans activité (signature, bloc ou heartbeat) avant
+    /// désactivation (en époques)
+    pub liveness_epochs: u64,
+    
+    /// Gain de réputation par bloc signé ou produit (sur `REPUTATION_SCALE`)
+    pub reputation_reward: u32,
+    
+    /// Perte de réputation par round manqué comme proposeur
+    pub reputation_penalty: u32,
+    
+    /// Part de l'écart à la réputation neutre résorbée à chaque époque
+    /// (en pourcentage)
+    pub reputation_decay_percent: u32,
 }
 
 impl Default for RVSConfig {
@@ -26,31 +71,253 @@ impl Default for RVSConfig {
             block_interval: 5, // 5 secondes
             validator_count: 10, // 10 validateurs par bloc
             quorum_percentage: 67,
+            epoch_length: 720, // 1 heure
+            jail_epochs: 24, // 1 jour
+            evidence_max_age_epochs: 24,
+            max_missed_rounds: 5,
+            liveness_epochs: 24,
+            reputation_reward: 10, // +0,1%
+            reputation_penalty: 500, // -5%
+            reputation_decay_percent: 10,
         }
     }
 }
 
 /// Gestionnaire du consensus RVS
+///
+/// La configuration n'est pas sérialisée avec l'état: c'est un paramètre
+/// du nœud, comme à la genèse.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RVS {
+    #[serde(skip)]
     config: RVSConfig,
-    /// Registre des validateurs actifs (wallet -> clé publique)
+    /// Registre courant des validateurs, actifs ou non (clé publique ->
+    /// informations), modifié par les blocs appliqués
+    #[serde(with = "sorted_map")]
     validators: HashMap<PublicKey, ValidatorInfo>,
+    /// Ensembles de validateurs figés, par époque
+    schedule: BTreeMap<u64, ValidatorSet>,
+    /// Taille attendue du comité de chaque ensemble figé, calculée une fois
+    /// par époque (dérivée de l'ensemble et de la configuration)
+    #[serde(skip)]
+    committee_sizes: BTreeMap<u64, usize>,
+}
+
+/// Ensemble des validateurs d'une époque
+///
+/// Figé au dernier bloc de l'époque précédente: les enregistrements,
+/// désactivations et sanctions appliqués pendant une époque ne prennent
+/// effet qu'à la suivante. Comités, proposeurs et quorums d'une hauteur sont
+/// calculés sur l'ensemble de son époque, identique pour tous les nœuds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ValidatorSet {
+    /// Validateurs actifs, triés par clé
+    validators: Vec<ValidatorInfo>,
+}
+
+impl ValidatorSet {
+    /// Nombre de validateurs
+    pub fn len(&self) -> usize {
+        self.validators.len()
+    }
+
+    /// L'ensemble est-il vide ?
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// Informations figées d'un validateur de l'ensemble
+    pub fn get(&self, public_key: &PublicKey) -> Option<&ValidatorInfo> {
+        self.validators
+            .binary_search_by_key(public_key, |info| info.public_key)
+            .ok()
+            .map(|index| &self.validators[index])
+    }
+
+    /// Validateurs de l'ensemble, triés par clé
+    pub fn iter(&self) -> impl Iterator<Item = &ValidatorInfo> {
+        self.validators.iter()
+    }
+
+    /// Seuil de sélection d'un validateur de l'ensemble pour une taille de
+    /// comité attendue
+    ///
+    /// Le seuil de base donne un comité de `expected_committee_size` membres
+    /// en moyenne; il est ajusté par la réputation figée avec l'ensemble.
+    pub fn selection_threshold(&self, public_key: &PublicKey, expected_committee_size: usize) -> Option<u64> {
+        self.get(public_key).map(|info| self.threshold(info, expected_committee_size))
+    }
+
+    /// Borne haute de la taille du comité pour une taille attendue
+    ///
+    /// La borne ajoute `COMMITTEE_BOUND_SIGMAS` écarts-types à la moyenne,
+    /// sans dépasser la taille de l'ensemble. Calcul entier en virgule fixe
+    /// sur 32 bits, identique sur tous les nœuds.
+    pub fn committee_bound(&self, expected_committee_size: usize) -> usize {
+        let (mean, sigma) = self.committee_distribution(expected_committee_size);
+        self.bound_of(mean, sigma)
+    }
+
+    /// Taille attendue du comité pour une taille configurée et un quorum
+    /// (en pourcentage de la borne haute)
+    ///
+    /// Plus petite taille, au moins `target`, dont le quorum ne dépasse pas
+    /// la taille attendue moins un écart-type: un comité tiré atteint alors
+    /// le quorum dans la plupart des rounds, alors que le quorum calculé sur
+    /// la borne d'une taille trop petite dépasse le comité moyen. À défaut,
+    /// tout l'ensemble est attendu.
+    pub fn committee_size(&self, target: usize, quorum_percentage: u8) -> usize {
+        (target.min(self.len())..self.len())
+            .find(|&size| {
+                let (mean, sigma) = self.committee_distribution(size);
+                let quorum = quorum_of(self.bound_of(mean, sigma), quorum_percentage) as u128;
+                quorum * FIXED_ONE + sigma <= mean
+            })
+            .unwrap_or(self.len())
+    }
+
+    fn bound_of(&self, mean: u128, sigma: u128) -> usize {
+        let bound = mean + COMMITTEE_BOUND_SIGMAS * sigma;
+        (bound.div_ceil(FIXED_ONE) as usize).min(self.len())
+    }
+
+    /// Moyenne et écart-type de la taille du comité pour une taille
+    /// attendue, en virgule fixe sur 32 bits
+    ///
+    /// La taille du comité est une somme d'épreuves indépendantes, une par
+    /// validateur avec la probabilité de son seuil.
+    fn committee_distribution(&self, expected_committee_size: usize) -> (u128, u128) {
+        let (mut mean, mut variance) = (0u128, 0u128);
+        for info in &self.validators {
+            // Probabilité que la sortie VRF soit sous le seuil
+            let p = (self.threshold(info, expected_committee_size) as u128 + 1) >> 32;
+            mean += p;
+            variance += p * (FIXED_ONE - p);
+        }
+        (mean, variance.isqrt())
+    }
+
+    fn threshold(&self, info: &ValidatorInfo, expected_committee_size: usize) -> u64 {
+        let base = u64::MAX as u128 * expected_committee_size as u128 / self.len() as u128;
+        let threshold = base * info.reputation as u128 / REPUTATION_SCALE as u128;
+        // Une réputation > 1 ne dépasse pas u64::MAX
+        threshold.min(u64::MAX as u128) as u64
+    }
+}
+
+/// Unité de la virgule fixe des calculs de taille de comité
+const FIXED_ONE: u128 = 1 << 32;
+
+/// Quorum d'un comité de taille bornée (pourcentage arrondi au supérieur)
+fn quorum_of(bound: usize, quorum_percentage: u8) -> usize {
+    (bound * quorum_percentage as usize).div_ceil(100)
+}
+
+/// Écarts-types ajoutés à la taille attendue du comité pour sa borne haute
+/// (dépassée dans moins de 1 % des rounds)
+pub const COMMITTEE_BOUND_SIGMAS: u128 = 3;
+
+/// Réputation neutre: la réputation est un entier sur cette échelle
+/// (10 000 = 1,0), pour que tous les nœuds calculent les mêmes seuils
+pub const REPUTATION_SCALE: u32 = 10_000;
+
+/// Réputation minimale (et réputation d'un validateur sanctionné)
+pub const MIN_REPUTATION: u32 = REPUTATION_SCALE / 10;
+
+/// Réputation maximale
+pub const MAX_REPUTATION: u32 = 2 * REPUTATION_SCALE;
+
+/// Statistiques de participation d'un validateur
+///
+/// Seule la participation visible dans les blocs est comptée: la sélection
+/// d'un membre de comité qui ne signe pas reste privée (VRF), et n'est pas
+/// une absence.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorStats {
+    /// Blocs signés ou produits et rounds de proposeur
+    pub times_selected: u64,
+    
+    /// Blocs signés ou produits (une fois par bloc)
+    pub blocks_signed: u64,
+    
+    /// Rounds manqués comme proposeur
+    pub absences: u64,
+}
+
+/// Statut d'un validateur
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidatorStatus {
+    /// Sélectionnable
+    Active,
+    /// Désactivé faute d'activité; un nouvel enregistrement le réactive
+    Inactive,
+    /// Exclu après une équivocation jusqu'à une hauteur
+    Jailed { until: u64 },
 }
 
 /// Informations sur un validateur
-#[derive(Debug, Clone)]
+///
+/// Partie de l'état de la chaîne: seules les transactions et les blocs
+/// appliqués les modifient, de façon identique sur tous les nœuds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidatorInfo {
     /// Clé publique du validateur
+    #[serde(with = "hex_bytes")]
     pub public_key: PublicKey,
     
-    /// Adresse du wallet
+    /// Adresse du wallet (identité du compte)
+    #[serde(with = "hex_bytes")]
     pub wallet_address: PublicKey,
     
-    /// Score de réputation (basé sur l'historique)
-    pub reputation: f64,
+    /// Score de réputation (basé sur l'historique, sur `REPUTATION_SCALE`)
+    pub reputation: u32,
+    
+    /// Participation constatée dans les blocs appliqués
+    pub stats: ValidatorStats,
+    
+    /// Statut (actif, désactivé, exclu)
+    pub status: ValidatorStatus,
     
-    /// Timestamp de dernière activité
-    pub last_active: Timestamp,
+    /// Hauteur d'enregistrement
+    pub registered_at: u64,
+    
+    /// Hauteur de dernière activité (signature, bloc produit ou heartbeat)
+    pub last_active: u64,
+    
+    /// Rounds manqués consécutivement comme proposeur
+    pub missed_rounds: u32,
+}
+
+impl ValidatorInfo {
+    /// Le validateur est-il sélectionnable ?
+    pub fn is_active(&self) -> bool {
+        self.status == ValidatorStatus::Active
+    }
+}
+
+/// Opération portée par une transaction `Validator` (`from == to`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ValidatorOperation {
+    /// Devenir validateur (ou se réactiver) avec la clé de contrôle du compte;
+    /// réservé aux membres
+    Register,
+    /// Quitter l'ensemble des validateurs
+    Unregister,
+    /// Signaler son activité
+    Heartbeat,
+}
+
+/// Preuve de sélection publiée par un validateur
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelectionProof {
+    /// Clé publique du validateur (sérialisée en hex)
+    #[serde(with = "hex_bytes")]
+    pub validator: PublicKey,
+    
+    /// Preuve VRF sur le message de sélection (sérialisée en hex, 80 bytes)
+    #[serde(with = "hex_bytes")]
+    pub proof: VRFProof,
 }
 
 /// Résultat de la sélection de validateurs
@@ -59,107 +326,638 @@ pub struct ValidatorSelection {
     /// Validateurs sélectionnés pour ce bloc
     pub selected_validators: Vec<PublicKey>,
     
-    /// Hash du bloc précédent utilisé pour la sélection
-    pub previous_block_hash: Hash,
+    /// Preuves des validateurs sélectionnés (même ordre)
+    pub proofs: Vec<SelectionProof>,
+    
+    /// Graine de l'époque utilisée pour la sélection
+    pub seed: Hash,
+    
+    /// Hauteur du bloc
+    pub height: u64,
     
-    /// Timestamp de la sélection
+    /// Round BFT
+    pub round: u32,
+    
+    /// Timestamp du bloc de la sélection
     pub timestamp: Timestamp,
 }
 
+/// Calendrier d'une époque: ensemble figé, graine et paramètres de comité
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochSchedule {
+    pub epoch: u64,
+    /// Première hauteur de l'époque
+    pub start_height: u64,
+    /// Dernière hauteur de l'époque (bloc d'ancrage de la suivante)
+    pub end_height: u64,
+    #[serde(with = "hex_bytes")]
+    pub seed: Hash,
+    pub validators: ValidatorSet,
+    pub expected_committee_size: usize,
+    pub required_quorum: usize,
+}
+
+impl EpochSchedule {
+    /// La hauteur appartient-elle à l'époque ?
+    pub fn contains(&self, height: u64) -> bool {
+        (self.start_height..=self.end_height).contains(&height)
+    }
+
+    /// Vérifier une preuve de sélection pour une hauteur de l'époque et un
+    /// round, sans autre état que le calendrier
+    pub fn verify_selection_proof(&self, proof: &SelectionProof, height: u64, round: u32) -> bool {
+        let Some(threshold) = self.validators.selection_threshold(&proof.validator, self.expected_committee_size) else {
+            return false;
+        };
+        let message = RVS::selection_message(&self.seed, height, round);
+        self.contains(height)
+            && VRF::verify_proof(&proof.validator, &message, &proof.proof)
+                .is_some_and(|output| VRF::output_to_number(&output) <= threshold)
+    }
+}
+
+/// Helper pour sérialiser/désérialiser les arrays en hex
+pub(crate) mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        hex::encode(bytes).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let hex_str = String::deserialize(deserializer)?;
+        let bytes = hex::decode(&hex_str).map_err(serde::de::Error::custom)?;
+        bytes.try_into().map_err(|_| serde::de::Error::custom("Invalid length"))
+    }
+
+    /// Variante pour un array optionnel (`null` si absent)
+    pub mod option {
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        pub fn serialize<S, const N: usize>(bytes: &Option<[u8; N]>, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            bytes.map(hex::encode).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D, const N: usize>(deserializer: D) -> Result<Option<[u8; N]>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            Option::<String>::deserialize(deserializer)?
+                .map(|hex_str| {
+                    let bytes = hex::decode(&hex_str).map_err(serde::de::Error::custom)?;
+                    bytes.try_into().map_err(|_| serde::de::Error::custom("Invalid length"))
+                })
+                .transpose()
+        }
+    }
+}
+
 impl RVS {
     /// Créer un nouveau gestionnaire RVS
     pub fn new(config: RVSConfig) -> Self {
         Self {
             config,
             validators: HashMap::new(),
+            schedule: BTreeMap::new(),
+            committee_sizes: BTreeMap::new(),
         }
     }
 
-    /// Enregistrer un validateur
+    /// Configuration du consensus
+    pub fn config(&self) -> &RVSConfig {
+        &self.config
+    }
+
+    /// Remplacer la configuration (état restauré depuis un instantané)
+    pub(crate) fn set_config(&mut self, config: RVSConfig) {
+        self.config = config;
+        self.committee_sizes = self.schedule.iter()
+            .map(|(epoch, set)| (*epoch, self.committee_size(set)))
+            .collect();
+    }
+
+    /// Enregistrer un validateur initial (genèse), membre de l'ensemble de
+    /// l'époque 0
+    ///
+    /// Sans effet pour un validateur déjà enregistré.
     pub fn register_validator(&mut self, public_key: PublicKey, wallet_address: PublicKey) {
-        self.validators.insert(public_key, ValidatorInfo {
-            public_key,
-            wallet_address,
-            reputation: 1.0,
-            last_active: chrono::Utc::now().timestamp(),
-        });
+        let _ = self.register_validator_at(public_key, wallet_address, 0);
+        self.freeze(0);
+    }
+
+    /// Figer l'ensemble des validateurs d'une époque à partir du registre
+    /// courant
+    ///
+    /// Appelé au dernier bloc de l'époque précédente, après la décroissance
+    /// des réputations. Les ensembles sont
+    /// conservés tant qu'une preuve d'équivocation sur leur époque reste
+    /// recevable.
+    pub fn begin_epoch(&mut self, epoch: u64) {
+        self.decay_reputation();
+        self.freeze(epoch);
+        let oldest = epoch.saturating_sub(self.config.evidence_max_age_epochs + 1);
+        self.schedule = self.schedule.split_off(&oldest);
+        self.committee_sizes = self.committee_sizes.split_off(&oldest);
+    }
+
+    /// Figer l'ensemble d'une époque et la taille attendue de ses comités
+    fn freeze(&mut self, epoch: u64) {
+        let set = self.snapshot();
+        self.committee_sizes.insert(epoch, self.committee_size(&set));
+        self.schedule.insert(epoch, set);
+    }
+
+    fn committee_size(&self, set: &ValidatorSet) -> usize {
+        set.committee_size(self.config.validator_count, self.config.quorum_percentage)
+    }
+
+    /// Ensemble des validateurs d'une époque (`None` s'il n'est pas figé)
+    pub fn epoch_validators(&self, epoch: u64) -> Option<&ValidatorSet> {
+        self.schedule.get(&epoch)
+    }
+
+    /// Ensemble des validateurs de l'époque d'une hauteur
+    pub fn validator_set(&self, height: u64) -> Option<&ValidatorSet> {
+        self.epoch_validators(self.epoch(height))
+    }
+
+    /// Calendrier d'une époque dont l'ensemble est figé, pour sa graine
+    pub fn epoch_schedule(&self, epoch: u64, seed: Hash) -> Option<EpochSchedule> {
+        let validators = self.epoch_validators(epoch)?.clone();
+        let start_height = epoch * self.config.epoch_length.max(1);
+        Some(EpochSchedule {
+            epoch,
+            start_height,
+            end_height: self.epoch_anchor_height(epoch + 1),
+            seed,
+            validators,
+            expected_committee_size: self.expected_committee_size(start_height),
+            required_quorum: self.required_quorum(start_height),
+        })
+    }
+
+    /// Validateurs actifs du registre courant
+    fn snapshot(&self) -> ValidatorSet {
+        let mut validators: Vec<ValidatorInfo> = self.active().cloned().collect();
+        validators.sort_by_key(|info| info.public_key);
+        ValidatorSet { validators }
+    }
+
+    /// Enregistrer ou réactiver un validateur à une hauteur
+    ///
+    /// Un validateur désactivé conserve sa réputation; un validateur exclu
+    /// ne revient qu'à la fin de sa sanction.
+    pub fn register_validator_at(
+        &mut self,
+        public_key: PublicKey,
+        wallet_address: PublicKey,
+        height: u64,
+    ) -> Result<(), String> {
+        match self.validators.get_mut(&public_key) {
+            None => {
+                self.validators.insert(public_key, ValidatorInfo {
+                    public_key,
+                    wallet_address,
+                    reputation: REPUTATION_SCALE,
+                    stats: ValidatorStats::default(),
+                    status: ValidatorStatus::Active,
+                    registered_at: height,
+                    last_active: height,
+                    missed_rounds: 0,
+                });
+                Ok(())
+            }
+            Some(info) => match info.status {
+                ValidatorStatus::Active => Err("Validator already registered".to_string()),
+                ValidatorStatus::Jailed { until } => Err(format!("Validator is jailed until height {}", until)),
+                ValidatorStatus::Inactive => {
+                    info.status = ValidatorStatus::Active;
+                    info.last_active = height;
+                    info.missed_rounds = 0;
+                    Ok(())
+                }
+            },
+        }
     }
 
     /// Désenregistrer un validateur
-    pub fn unregister_validator(&mut self, public_key: &PublicKey) {
-        self.validators.remove(public_key);
+    ///
+    /// Un validateur exclu reste enregistré jusqu'à la fin de sa sanction.
+    pub fn unregister_validator(&mut self, public_key: &PublicKey) -> Result<(), String> {
+        match self.validators.get(public_key).map(|info| info.status) {
+            None => Err("Not a validator".to_string()),
+            Some(ValidatorStatus::Jailed { until }) => Err(format!("Validator is jailed until height {}", until)),
+            Some(_) => {
+                self.validators.remove(public_key);
+                Ok(())
+            }
+        }
     }
 
-    /// Sélectionner les validateurs pour le prochain bloc
-    pub fn select_validators(&self, previous_block_hash: Hash) -> ValidatorSelection {
-        let mut candidates: Vec<(PublicKey, u64)> = Vec::new();
-        
-        // Utiliser le hash du bloc précédent comme seed pour la sélection
-        let seed = &previous_block_hash;
-        
-        // Calculer un score VRF pour chaque validateur
-        for (pub_key, info) in &self.validators {
-            // Créer un message unique pour ce validateur
-            let mut message = Vec::with_capacity(32 + 32);
-            message.extend_from_slice(seed);
-            message.extend_from_slice(pub_key);
-            
-            // Générer une sortie VRF (simulée avec hash)
-            // Dans une vraie implémentation, chaque validateur générerait sa propre VRF
-            let vrf_output = crate::crypto::hash_data(&message);
-            let score = VRF::output_to_number(&vrf_output);
-            
-            // Ajuster le score avec la réputation
-            let adjusted_score = (score as f64 * info.reputation) as u64;
-            
-            candidates.push((*pub_key, adjusted_score));
+    /// Enregistrer le heartbeat d'un validateur actif
+    pub fn heartbeat(&mut self, public_key: &PublicKey, height: u64) -> Result<(), String> {
+        let info = self.validators.get_mut(public_key)
+            .filter(|info| info.is_active())
+            .ok_or("Not an active validator")?;
+        info.last_active = height;
+        Ok(())
+    }
+
+    /// Suivre l'activité des validateurs après un bloc
+    ///
+    /// `active` regroupe le producteur et les signataires du bloc,
+    /// `missed_proposers` les proposeurs absents des rounds sans décision. Un
+    /// validateur qui manque `max_missed_rounds` rounds d'affilée, ou reste
+    /// inactif pendant `liveness_epochs` époques, est désactivé; le dernier
+    /// validateur actif ne l'est jamais. Retourne les clés désactivées, triées.
+    pub fn record_liveness(
+        &mut self,
+        height: u64,
+        active: &[PublicKey],
+        missed_proposers: &[PublicKey],
+    ) -> Vec<PublicKey> {
+        for key in missed_proposers {
+            if let Some(info) = self.validators.get_mut(key) {
+                info.missed_rounds += 1;
+            }
         }
-        
-        // Trier par score (décroissant)
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
-        
-        // Sélectionner les N meilleurs
-        let selected: Vec<PublicKey> = candidates
-            .iter()
-            .take(self.config.validator_count)
-            .map(|(key, _)| *key)
+        for key in active {
+            if let Some(info) = self.validators.get_mut(key) {
+                info.last_active = height;
+                info.missed_rounds = 0;
+            }
+        }
+
+        let window = self.config.liveness_epochs * self.config.epoch_length.max(1);
+        let mut deactivated: Vec<PublicKey> = self.validators.values()
+            .filter(|info| {
+                info.is_active()
+                    && (info.missed_rounds >= self.config.max_missed_rounds
+                        || height.saturating_sub(info.last_active) > window)
+            })
+            .map(|info| info.public_key)
             .collect();
-        
+        deactivated.sort();
+        if deactivated.len() >= self.active_validators_count() {
+            deactivated.pop();
+        }
+        for key in &deactivated {
+            if let Some(info) = self.validators.get_mut(key) {
+                info.status = ValidatorStatus::Inactive;
+            }
+        }
+        deactivated
+    }
+
+    /// Reporter un validateur sur la nouvelle clé de son compte après une
+    /// rotation (réputation conservée)
+    pub fn rotate_validator_key(&mut self, old_key: &PublicKey, new_key: PublicKey) {
+        if let Some(mut info) = self.validators.remove(old_key) {
+            info.public_key = new_key;
+            self.validators.insert(new_key, info);
+        }
+    }
+
+    /// Exclure un validateur de la sélection jusqu'à une hauteur
+    ///
+    /// Sa réputation retombe au minimum. Une nouvelle sanction d'un
+    /// validateur déjà exclu prolonge l'exclusion si elle finit plus tard.
+    pub fn jail_validator(&mut self, public_key: &PublicKey, until_height: u64) {
+        if let Some(info) = self.validators.get_mut(public_key) {
+            let until = match info.status {
+                ValidatorStatus::Jailed { until } => until.max(until_height),
+                _ => until_height,
+            };
+            info.status = ValidatorStatus::Jailed { until };
+            info.reputation = MIN_REPUTATION;
+        }
+    }
+
+    /// Réintégrer les validateurs dont l'exclusion a pris fin à une hauteur
+    ///
+    /// Retourne leurs clés, triées.
+    pub fn release_validators(&mut self, height: u64) -> Vec<PublicKey> {
+        let mut released = Vec::new();
+        for info in self.validators.values_mut() {
+            if matches!(info.status, ValidatorStatus::Jailed { until } if until <= height) {
+                info.status = ValidatorStatus::Active;
+                info.last_active = height;
+                info.missed_rounds = 0;
+                released.push(info.public_key);
+            }
+        }
+        released.sort();
+        released
+    }
+
+    /// Hauteur de fin d'exclusion d'un validateur sanctionné
+    pub fn jailed_until(&self, public_key: &PublicKey) -> Option<u64> {
+        match self.validators.get(public_key)?.status {
+            ValidatorStatus::Jailed { until } => Some(until),
+            _ => None,
+        }
+    }
+
+    /// Le validateur est-il enregistré (actif ou non) ?
+    pub fn is_validator(&self, public_key: &PublicKey) -> bool {
+        self.validators.contains_key(public_key)
+    }
+
+    /// Informations sur un validateur
+    pub fn validator(&self, public_key: &PublicKey) -> Option<&ValidatorInfo> {
+        self.validators.get(public_key)
+    }
+
+    /// Tous les validateurs enregistrés, triés par clé
+    pub fn validators(&self) -> Vec<&ValidatorInfo> {
+        let mut validators: Vec<&ValidatorInfo> = self.validators.values().collect();
+        validators.sort_by_key(|info| info.public_key);
+        validators
+    }
+
+    /// Validateurs actifs
+    fn active(&self) -> impl Iterator<Item = &ValidatorInfo> {
+        self.validators.values().filter(|info| info.is_active())
+    }
+
+    /// Époque d'une hauteur de bloc
+    pub fn epoch(&self, height: u64) -> u64 {
+        height / self.config.epoch_length.max(1)
+    }
+
+    /// Hauteur du bloc dont le hash ancre la graine d'une époque
+    /// (dernier bloc de l'époque précédente, genesis pour l'époque 0)
+    pub fn epoch_anchor_height(&self, epoch: u64) -> u64 {
+        (epoch * self.config.epoch_length.max(1)).saturating_sub(1)
+    }
+
+    /// Graine d'une époque, dérivée du hash du bloc d'ancrage
+    ///
+    /// Le bloc d'ancrage porte un quorum de précommits: il est final dès son
+    /// ajout, et la graine ne peut plus changer une fois l'époque ouverte.
+    pub fn epoch_seed(epoch: u64, anchor_hash: &Hash) -> Hash {
+        let mut data = Vec::with_capacity(16 + 8 + 32);
+        data.extend_from_slice(b"mahala-rvs-epoch");
+        data.extend_from_slice(&epoch.to_le_bytes());
+        data.extend_from_slice(anchor_hash);
+        hash_data(&data)
+    }
+
+    /// Message évalué par la VRF pour le comité d'une hauteur et d'un round
+    pub fn selection_message(seed: &Hash, height: u64, round: u32) -> Vec<u8> {
+        let mut message = Vec::with_capacity(10 + 32 + 8 + 4);
+        message.extend_from_slice(b"mahala-rvs");
+        message.extend_from_slice(seed);
+        message.extend_from_slice(&height.to_le_bytes());
+        message.extend_from_slice(&round.to_le_bytes());
+        message
+    }
+
+    /// Proposeur d'un round: tirage public dans l'ensemble de l'époque
+    ///
+    /// Le proposeur n'a pas besoin d'être membre du comité; s'il est absent,
+    /// le round suivant désigne un autre proposeur.
+    pub fn proposer(&self, seed: &Hash, height: u64, round: u32) -> Option<PublicKey> {
+        let set = self.validator_set(height).filter(|set| !set.is_empty())?;
+
+        let mut data = Vec::with_capacity(19 + 32 + 8 + 4);
+        data.extend_from_slice(b"mahala-rvs-proposer");
+        data.extend_from_slice(seed);
+        data.extend_from_slice(&height.to_le_bytes());
+        data.extend_from_slice(&round.to_le_bytes());
+        let draw = VRF::output_to_number(&hash_data(&data));
+        Some(set.validators[(draw % set.len() as u64) as usize].public_key)
+    }
+
+    /// Taille attendue du comité d'une hauteur
+    pub fn expected_committee_size(&self, height: u64) -> usize {
+        let epoch = self.epoch(height);
+        match self.committee_sizes.get(&epoch) {
+            Some(size) => *size,
+            None => self.epoch_validators(epoch).map_or(0, |set| self.committee_size(set)),
+        }
+    }
+
+    /// Seuil de sélection d'un validateur de l'ensemble d'une hauteur
+    ///
+    /// Un validateur est sélectionné si sa sortie VRF (en nombre) est
+    /// inférieure ou égale au seuil (`ValidatorSet::selection_threshold`).
+    pub fn selection_threshold(&self, public_key: &PublicKey, height: u64) -> Option<u64> {
+        self.validator_set(height)?
+            .selection_threshold(public_key, self.expected_committee_size(height))
+    }
+
+    /// Évaluer sa propre sélection pour une hauteur et un round
+    ///
+    /// Retourne la preuve à publier si le validateur est sélectionné.
+    pub fn prove_selection(
+        &self,
+        public_key: &PublicKey,
+        private_key: &PrivateKey,
+        seed: &Hash,
+        height: u64,
+        round: u32,
+    ) -> Option<SelectionProof> {
+        let threshold = self.selection_threshold(public_key, height)?;
+        let output = VRF::generate(private_key, &Self::selection_message(seed, height, round));
+        (VRF::output_to_number(&output.output) <= threshold).then_some(SelectionProof {
+            validator: *public_key,
+            proof: output.proof,
+        })
+    }
+
+    /// Vérifier la preuve de sélection d'un validateur pour une hauteur et
+    /// un round
+    pub fn verify_selection_proof(
+        &self,
+        proof: &SelectionProof,
+        seed: &Hash,
+        height: u64,
+        round: u32,
+    ) -> bool {
+        let Some(threshold) = self.selection_threshold(&proof.validator, height) else {
+            return false;
+        };
+        let message = Self::selection_message(seed, height, round);
+        VRF::verify_proof(&proof.validator, &message, &proof.proof)
+            .is_some_and(|output| VRF::output_to_number(&output) <= threshold)
+    }
+
+    /// Constituer le comité d'une hauteur et d'un round à partir des
+    /// preuves publiées
+    ///
+    /// Les preuves invalides ou en double sont ignorées. La sélection porte
+    /// le timestamp du bloc de la hauteur, pour que tous les nœuds
+    /// reconstituent la même.
+    pub fn select_validators(
+        &self,
+        seed: Hash,
+        height: u64,
+        round: u32,
+        timestamp: Timestamp,
+        claims: impl IntoIterator<Item = SelectionProof>,
+    ) -> ValidatorSelection {
+        let mut proofs: Vec<SelectionProof> = Vec::new();
+        for claim in claims {
+            if !proofs.iter().any(|p| p.validator == claim.validator)
+                && self.verify_selection_proof(&claim, &seed, height, round)
+            {
+                proofs.push(claim);
+            }
+        }
+        proofs.sort_by_key(|p| p.validator);
+
         ValidatorSelection {
-            selected_validators: selected,
-            previous_block_hash,
-            timestamp: chrono::Utc::now().timestamp(),
+            selected_validators: proofs.iter().map(|p| p.validator).collect(),
+            proofs,
+            seed,
+            height,
+            round,
+            timestamp,
         }
     }
 
+    /// Vérifier une sélection annoncée par un autre nœud
+    pub fn verify_selection(&self, selection: &ValidatorSelection) -> bool {
+        selection.selected_validators.len() == selection.proofs.len()
+            && selection.proofs.iter().zip(&selection.selected_validators).enumerate().all(|(i, (proof, key))| {
+                &proof.validator == key
+                    && !selection.selected_validators[..i].contains(key)
+                    && self.verify_selection_proof(proof, &selection.seed, selection.height, selection.round)
+            })
+    }
+
     /// Vérifier si un validateur est sélectionné
     pub fn is_validator_selected(&self, public_key: &PublicKey, selection: &ValidatorSelection) -> bool {
         selection.selected_validators.contains(public_key)
     }
 
-    /// Calculer le quorum requis
-    pub fn required_quorum(&self) -> usize {
-        let total = self.validators.len();
-        (total * self.config.quorum_percentage as usize + 99) / 100
+    /// Borne haute de la taille du comité d'une hauteur
+    pub fn committee_bound(&self, height: u64) -> usize {
+        self.validator_set(height)
+            .map_or(0, |set| set.committee_bound(self.expected_committee_size(height)))
     }
 
-    /// Mettre à jour la réputation d'un validateur
-    pub fn update_reputation(&mut self, public_key: &PublicKey, success: bool) {
-        if let Some(info) = self.validators.get_mut(public_key) {
-            if success {
-                // Augmenter légèrement la réputation
-                info.reputation = (info.reputation * 1.01).min(2.0);
-            } else {
-                // Diminuer la réputation
-                info.reputation = (info.reputation * 0.95).max(0.1);
+    /// Calculer le quorum requis d'une hauteur (pourcentage de la borne
+    /// haute du comité)
+    ///
+    /// La taille du comité varie d'un round à l'autre autour de la taille
+    /// attendue. Rapporté à la borne, le quorum dépasse la moitié du comité
+    /// tiré: deux quorums contradictoires d'un même round partagent un
+    /// membre, qui a donc équivoqué. La taille attendue est choisie pour que
+    /// ce quorum reste sous le comité moyen.
+    pub fn required_quorum(&self, height: u64) -> usize {
+        quorum_of(self.committee_bound(height), self.config.quorum_percentage)
+    }
+
+    /// Vérifier les signatures de validateurs d'un bloc contre le comité de
+    /// sa hauteur
+    ///
+    /// Chaque signataire doit prouver sa sélection pour la graine, la hauteur
+    /// et le round de validation du bloc; le nombre de signataires doit
+    /// atteindre le quorum. Les signatures elles-mêmes sont vérifiées par
+    /// `Block::verify`.
+    pub fn verify_block_committee(&self, block: &Block, seed: &Hash) -> Result<(), String> {
+        let height = block.header.height;
+        for vs in &block.validator_signatures {
+            let proof = SelectionProof { validator: vs.validator, proof: vs.selection_proof };
+            if !self.verify_selection_proof(&proof, seed, height, block.commit_round) {
+                return Err(format!(
+                    "Validator {} is not in the committee for height {} round {}",
+                    hex::encode(vs.validator),
+                    height,
+                    block.commit_round
+                ));
             }
-            info.last_active = chrono::Utc::now().timestamp();
         }
+
+        if block.validator_signatures.len() < self.required_quorum(height) {
+            return Err("Block does not have required quorum".to_string());
+        }
+        Ok(())
+    }
+
+    /// Proposeurs absents des rounds sans décision d'un bloc
+    ///
+    /// Un proposeur dont la proposition signée est portée par le bloc
+    /// (`Block::proposal_witnesses`) n'est pas absent: son round a échoué
+    /// pour une autre raison (comité sous le quorum, votes perdus). Une trace
+    /// invalide ou en double rend le bloc invalide.
+    pub fn missed_proposers(&self, block: &Block, seed: &Hash) -> Result<Vec<PublicKey>, String> {
+        let height = block.header.height;
+        let mut proposed = HashSet::new();
+        for witness in &block.proposal_witnesses {
+            let valid = witness.round < block.commit_round
+                && self.proposer(seed, height, witness.round)
+                    .is_some_and(|proposer| witness.verify(height, &proposer));
+            if !valid || !proposed.insert(witness.round) {
+                return Err(format!("Invalid proposal witness for round {}", witness.round));
+            }
+        }
+        let rounds = self.validator_set(height).map_or(0, ValidatorSet::len);
+        Ok((0..block.commit_round)
+            .take(rounds)
+            .filter(|round| !proposed.contains(round))
+            .filter_map(|round| self.proposer(seed, height, round))
+            .collect())
     }
 
-    /// Obtenir le nombre de validateurs actifs
+    /// Comptabiliser la participation constatée dans un bloc appliqué
+    ///
+    /// `signed` regroupe le producteur et les signataires du bloc, chacun
+    /// une fois; `missed_proposers` les proposeurs absents des rounds sans
+    /// décision (`RVS::missed_proposers`). La
+    /// sélection d'un membre de comité qui ne signe pas reste privée: seuls
+    /// les rounds de proposeur, tirés publiquement, comptent comme absences.
+    /// La réputation d'un validateur exclu reste au minimum.
+    pub fn record_participation(&mut self, signed: &[PublicKey], missed_proposers: &[PublicKey]) {
+        let (reward, penalty) = (self.config.reputation_reward, self.config.reputation_penalty);
+        for key in signed {
+            if let Some(info) = self.validators.get_mut(key) {
+                info.stats.times_selected += 1;
+                info.stats.blocks_signed += 1;
+                if !matches!(info.status, ValidatorStatus::Jailed { .. }) {
+                    info.reputation = info.reputation.saturating_add(reward).min(MAX_REPUTATION);
+                }
+            }
+        }
+        for key in missed_proposers {
+            if let Some(info) = self.validators.get_mut(key) {
+                info.stats.times_selected += 1;
+                info.stats.absences += 1;
+                info.reputation = info.reputation.saturating_sub(penalty).max(MIN_REPUTATION);
+            }
+        }
+    }
+
+    /// Rapprocher les réputations de la valeur neutre
+    ///
+    /// L'écart est réduit de `reputation_decay_percent` % à chaque époque:
+    /// une réputation reflète surtout le comportement récent. Les validateurs
+    /// exclus gardent la réputation minimale jusqu'à leur réintégration.
+    fn decay_reputation(&mut self) {
+        let percent = self.config.reputation_decay_percent.min(100) as i64;
+        for info in self.validators.values_mut() {
+            if matches!(info.status, ValidatorStatus::Jailed { .. }) {
+                continue;
+            }
+            let gap = info.reputation as i64 - REPUTATION_SCALE as i64;
+            info.reputation = (info.reputation as i64 - gap * percent / 100) as u32;
+        }
+    }
+
+    /// Obtenir le nombre de validateurs actifs du registre courant
     pub fn active_validators_count(&self) -> usize {
-        self.validators.len()
+        self.active().count()
     }
 }
 
@@ -183,23 +981,144 @@ mod tests {
         assert_eq!(rvs.active_validators_count(), 1);
     }
 
+    #[test]
+    fn test_rotate_validator_key() {
+        let mut rvs = RVS::new(RVSConfig::default());
+        let old_key = KeyPair::new();
+        let new_key = KeyPair::new();
+        rvs.register_validator(*old_key.public_key(), *old_key.public_key());
+        rvs.record_participation(&[*old_key.public_key()], &[]);
+
+        rvs.rotate_validator_key(old_key.public_key(), *new_key.public_key());
+        assert_eq!(rvs.active_validators_count(), 1);
+        let info = &rvs.validators[new_key.public_key()];
+        assert_eq!(info.public_key, *new_key.public_key());
+        assert_eq!(info.wallet_address, *old_key.public_key());
+        assert!(info.reputation > REPUTATION_SCALE);
+        assert_eq!(info.stats.blocks_signed, 1);
+    }
+
+    #[test]
+    fn test_missed_rounds_deactivate_validator() {
+        let mut rvs = RVS::new(RVSConfig { max_missed_rounds: 2, ..Default::default() });
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        rvs.register_validator(a, a);
+        rvs.register_validator(b, b);
+
+        // Un bloc produit ou signé remet le compteur à zéro
+        assert!(rvs.record_liveness(1, &[b], &[a]).is_empty());
+        assert!(rvs.record_liveness(2, &[a], &[b]).is_empty());
+        assert!(rvs.record_liveness(3, &[b], &[a]).is_empty());
+        assert_eq!(rvs.record_liveness(4, &[b], &[a]), vec![a]);
+        assert_eq!(rvs.active_validators_count(), 1);
+        rvs.begin_epoch(1);
+        assert!(rvs.selection_threshold(&a, 720).is_none());
+        assert_eq!(rvs.proposer(&[0u8; 32], 720, 0), Some(b));
+
+        // Le dernier validateur actif n'est jamais désactivé
+        assert!(rvs.record_liveness(5, &[], &[b, b]).is_empty());
+        assert!(rvs.validators[&b].is_active());
+
+        // Réactivation par un nouvel enregistrement, réputation conservée
+        rvs.record_participation(&[], &[a]);
+        assert!(rvs.register_validator_at(b, b, 6).is_err());
+        rvs.register_validator_at(a, a, 6).unwrap();
+        assert!(rvs.validators[&a].is_active());
+        assert!(rvs.validators[&a].reputation < REPUTATION_SCALE);
+    }
+
     #[test]
     fn test_select_validators() {
         let mut rvs = RVS::new(RVSConfig {
-            validator_count: 3,
+            validator_count: 10,
             ..Default::default()
         });
         
-        // Enregistrer 5 validateurs
-        for _ in 0..5 {
-            let keypair = KeyPair::new();
+        // Enregistrer 100 validateurs (clés déterministes)
+        let keypairs: Vec<KeyPair> = (0..100u8).map(|i| KeyPair::from_seed(&[i; 32])).collect();
+        for keypair in &keypairs {
+            rvs.register_validator(*keypair.public_key(), *keypair.public_key());
+        }
+        
+        let seed = RVS::epoch_seed(0, &crate::crypto::hash_data(b"genesis"));
+        let claims: Vec<SelectionProof> = keypairs
+            .iter()
+            .filter_map(|k| rvs.prove_selection(k.public_key(), k.private_key(), &seed, 1, 0))
+            .collect();
+        
+        // Comité d'au moins 10 membres en moyenne, sous sa borne haute
+        let expected = rvs.expected_committee_size(1);
+        assert!(expected >= 10);
+        assert!((expected / 2..=rvs.committee_bound(1)).contains(&claims.len()));
+        let timestamp = 1_700_000_000;
+        let selection = rvs.select_validators(seed, 1, 0, timestamp, claims.clone());
+        assert_eq!(selection.selected_validators.len(), claims.len());
+        assert_eq!(selection.timestamp, timestamp);
+        let again = rvs.select_validators(seed, 1, 0, timestamp, claims.iter().rev().cloned());
+        assert_eq!(again.selected_validators, selection.selected_validators);
+        assert!(rvs.verify_selection(&selection));
+        
+        // Une preuve pour une autre hauteur, un autre round ou un autre
+        // validateur est refusée
+        assert!(!rvs.verify_selection_proof(&claims[0], &seed, 2, 0));
+        assert!(!rvs.verify_selection_proof(&claims[0], &seed, 1, 1));
+        let mut forged = selection.clone();
+        forged.proofs[0].validator = *keypairs.iter()
+            .find(|k| !selection.selected_validators.contains(k.public_key()))
+            .unwrap()
+            .public_key();
+        forged.selected_validators[0] = forged.proofs[0].validator;
+        assert!(!rvs.verify_selection(&forged));
+    }
+
+    #[test]
+    fn test_small_validator_set_all_selected() {
+        let mut rvs = RVS::new(RVSConfig {
+            validator_count: 3,
+            ..Default::default()
+        });
+        let keypairs: Vec<KeyPair> = (0..3).map(|_| KeyPair::new()).collect();
+        for keypair in &keypairs {
             rvs.register_validator(*keypair.public_key(), *keypair.public_key());
         }
         
-        let previous_hash = crate::crypto::hash_data(b"test");
-        let selection = rvs.select_validators(previous_hash);
+        let seed = RVS::epoch_seed(0, &[0u8; 32]);
+        for keypair in &keypairs {
+            assert!(rvs.prove_selection(keypair.public_key(), keypair.private_key(), &seed, 1, 0).is_some());
+        }
+        // Une clé non enregistrée n'est jamais sélectionnée
+        let outsider = KeyPair::new();
+        assert!(rvs.prove_selection(outsider.public_key(), outsider.private_key(), &seed, 1, 0).is_none());
+    }
+
+    #[test]
+    fn test_epochs() {
+        let rvs = RVS::new(RVSConfig {
+            epoch_length: 10,
+            ..Default::default()
+        });
+        assert_eq!(rvs.epoch(9), 0);
+        assert_eq!(rvs.epoch(10), 1);
+        assert_eq!(rvs.epoch_anchor_height(0), 0);
+        assert_eq!(rvs.epoch_anchor_height(2), 19);
+        assert_ne!(RVS::epoch_seed(1, &[0u8; 32]), RVS::epoch_seed(2, &[0u8; 32]));
+    }
+
+    #[test]
+    fn test_proposer_rotates_with_rounds() {
+        let mut rvs = RVS::new(RVSConfig::default());
+        let seed = RVS::epoch_seed(0, &[0u8; 32]);
+        assert!(rvs.proposer(&seed, 1, 0).is_none());
         
-        assert_eq!(selection.selected_validators.len(), 3);
+        let keys: Vec<PublicKey> = (0..5u8).map(|i| *KeyPair::from_seed(&[i; 32]).public_key()).collect();
+        for key in &keys {
+            rvs.register_validator(*key, *key);
+        }
+        // Tirage public et déterministe, qui change d'un round à l'autre
+        let proposers: Vec<PublicKey> = (0..20).map(|round| rvs.proposer(&seed, 1, round).unwrap()).collect();
+        assert!(proposers.iter().all(|p| keys.contains(p)));
+        assert_eq!(rvs.proposer(&seed, 1, 3), Some(proposers[3]));
+        assert!(proposers.iter().any(|p| *p != proposers[0]));
     }
 
     #[test]
@@ -212,7 +1131,135 @@ mod tests {
             rvs.register_validator(*keypair.public_key(), *keypair.public_key());
         }
         
-        assert_eq!(rvs.required_quorum(), 7);
+        assert_eq!(rvs.required_quorum(1), 7);
+    }
+
+    #[test]
+    fn test_quorum_holds_against_committee_variance() {
+        let mut rvs = RVS::new(RVSConfig { validator_count: 10, ..Default::default() });
+        let keypairs: Vec<KeyPair> = (0..60u8).map(|i| KeyPair::from_seed(&[i; 32])).collect();
+        for keypair in &keypairs {
+            rvs.register_validator(*keypair.public_key(), *keypair.public_key());
+        }
+
+        // Ensemble plus grand que le comité: la borne dépasse la taille
+        // attendue, relevée pour que le quorum reste sous le comité moyen
+        let expected = rvs.expected_committee_size(1);
+        assert_eq!(expected, 37);
+        assert_eq!(rvs.committee_bound(1), 49);
+        let quorum = rvs.required_quorum(1);
+        assert_eq!(quorum, 33);
+
+        // Le comité tiré varie, mais deux quorums d'un round se recoupent
+        // toujours, ce qu'un quorum sur la seule taille attendue ne garantit pas
+        let seed = RVS::epoch_seed(0, &[0u8; 32]);
+        let sizes: Vec<usize> = (0..30)
+            .map(|round| {
+                keypairs.iter()
+                    .filter(|k| rvs.prove_selection(k.public_key(), k.private_key(), &seed, 1, round).is_some())
+                    .count()
+            })
+            .collect();
+        assert!(sizes.iter().any(|size| *size > expected));
+        assert!(sizes.iter().all(|size| *size < 2 * quorum));
+
+        // Ensemble entièrement sélectionné: la borne est sa taille
+        let mut small = RVS::new(RVSConfig { validator_count: 10, ..Default::default() });
+        for keypair in &keypairs[..4] {
+            small.register_validator(*keypair.public_key(), *keypair.public_key());
+        }
+        assert_eq!(small.committee_bound(1), 4);
+        assert_eq!(small.required_quorum(1), 3);
+    }
+
+    #[test]
+    fn test_most_rounds_reach_quorum() {
+        let mut rvs = RVS::new(RVSConfig::default());
+        let keypairs: Vec<KeyPair> = (0..40u8).map(|i| KeyPair::from_seed(&[i; 32])).collect();
+        for keypair in &keypairs {
+            rvs.register_validator(*keypair.public_key(), *keypair.public_key());
+        }
+        let quorum = rvs.required_quorum(1);
+        assert!(quorum <= rvs.expected_committee_size(1));
+
+        // Paramètres par défaut: un comité entièrement honnête décide dans la
+        // plupart des rounds
+        let seed = RVS::epoch_seed(0, &[0u8; 32]);
+        let decided = (0..20)
+            .filter(|&round| {
+                keypairs.iter()
+                    .filter(|k| rvs.prove_selection(k.public_key(), k.private_key(), &seed, 1, round).is_some())
+                    .count() >= quorum
+            })
+            .count();
+        assert!(decided >= 16, "{}/20 rounds reach quorum", decided);
+    }
+
+    #[test]
+    fn test_reputation_follows_participation_and_decays() {
+        let mut rvs = RVS::new(RVSConfig {
+            reputation_reward: 100,
+            reputation_penalty: 500,
+            reputation_decay_percent: 50,
+            ..Default::default()
+        });
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        rvs.register_validator(a, a);
+        rvs.register_validator(b, b);
+
+        for _ in 0..3 {
+            rvs.record_participation(&[a], &[b]);
+        }
+        let (info_a, info_b) = (rvs.validator(&a).unwrap(), rvs.validator(&b).unwrap());
+        assert_eq!(info_a.reputation, REPUTATION_SCALE + 300);
+        assert_eq!(info_b.reputation, REPUTATION_SCALE - 1500);
+        assert_eq!(info_a.stats, ValidatorStats { times_selected: 3, blocks_signed: 3, absences: 0 });
+        assert_eq!(info_b.stats, ValidatorStats { times_selected: 3, blocks_signed: 0, absences: 3 });
+        // Le seuil de l'époque en cours reste celui de l'ensemble figé
+        assert_eq!(rvs.selection_threshold(&a, 0), rvs.selection_threshold(&b, 0));
+
+        // La réputation figée pour l'époque suivante a décru vers la valeur neutre
+        rvs.begin_epoch(1);
+        assert_eq!(rvs.validator(&a).unwrap().reputation, REPUTATION_SCALE + 150);
+        assert_eq!(rvs.validator(&b).unwrap().reputation, REPUTATION_SCALE - 750);
+        let threshold = |key| rvs.selection_threshold(key, 720).unwrap();
+        assert!(threshold(&a) > threshold(&b));
+
+        // Bornes
+        for _ in 0..100 {
+            rvs.record_participation(&[], &[b]);
+        }
+        assert_eq!(rvs.validator(&b).unwrap().reputation, MIN_REPUTATION);
+    }
+
+    #[test]
+    fn test_validator_set_changes_at_epoch_boundary() {
+        let mut rvs = RVS::new(RVSConfig { epoch_length: 10, evidence_max_age_epochs: 1, ..Default::default() });
+        let (a, b) = ([1u8; 32], [2u8; 32]);
+        rvs.register_validator(a, a);
+
+        // Enregistré pendant l'époque 0, sélectionnable à partir de l'époque 1
+        rvs.register_validator_at(b, b, 4).unwrap();
+        assert_eq!(rvs.active_validators_count(), 2);
+        assert_eq!(rvs.expected_committee_size(9), 1);
+        assert!(rvs.selection_threshold(&b, 9).is_none());
+        assert!(rvs.validator_set(10).is_none());
+
+        rvs.begin_epoch(1);
+        assert_eq!(rvs.expected_committee_size(10), 2);
+        assert!(rvs.selection_threshold(&b, 10).is_some());
+        assert_eq!(rvs.epoch_validators(0).unwrap().len(), 1);
+
+        // Désinscrit pendant l'époque 1, encore membre de son ensemble
+        rvs.unregister_validator(&b).unwrap();
+        assert!(rvs.validator_set(15).unwrap().get(&b).is_some());
+        rvs.begin_epoch(2);
+        assert!(rvs.validator_set(20).unwrap().get(&b).is_none());
+
+        // Seuls les ensembles encore utiles aux preuves sont conservés
+        rvs.begin_epoch(3);
+        assert!(rvs.epoch_validators(0).is_none());
+        assert!(rvs.epoch_validators(1).is_some());
     }
 }
 
@@ -1,62 +1,101 @@
 //! VRF (Verifiable Random Function) pour sélection aléatoire vérifiable
 //!
-//! Utilise Ed25519 avec une transformation pour créer une VRF
+//! ECVRF-EDWARDS25519-SHA512-TAI (RFC 9381, suite 0x03) avec les clés
+//! Ed25519 des wallets. Seul le détenteur de la clé privée peut calculer la
+//! preuve `pi` pour un message `alpha`; n'importe qui peut vérifier la
+//! preuve avec la clé publique et en déduire la sortie `beta`, unique pour
+//! (clé, message).
 
-use crate::{PublicKey, PrivateKey, Hash};
-use crate::crypto::hash_data;
-use ed25519_dalek::{SigningKey, Signer};
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Digest, Sha512};
+use crate::{PublicKey, PrivateKey};
+
+/// Identifiant de la suite ECVRF-EDWARDS25519-SHA512-TAI
+const SUITE: u8 = 0x03;
+
+/// Taille d'une preuve: Gamma (32) || c (16) || s (32)
+pub const PROOF_LEN: usize = 80;
+
+/// Taille d'une sortie VRF (SHA-512)
+pub const OUTPUT_LEN: usize = 64;
+
+/// Preuve VRF (`pi`)
+pub type VRFProof = [u8; PROOF_LEN];
+
+/// Sortie VRF (`beta`)
+pub type VRFHash = [u8; OUTPUT_LEN];
 
 /// Sortie d'une VRF
 #[derive(Debug, Clone)]
 pub struct VRFOutput {
     /// Valeur aléatoire générée
-    pub output: Hash,
+    pub output: VRFHash,
     /// Preuve de la génération
-    pub proof: Vec<u8>,
+    pub proof: VRFProof,
 }
 
-/// VRF basée sur Ed25519
+/// VRF basée sur Ed25519 (ECVRF, RFC 9381)
 pub struct VRF;
 
 impl VRF {
-    /// Générer une sortie VRF depuis une clé privée et un message
+    /// Générer une sortie VRF et sa preuve depuis une clé privée et un message
     pub fn generate(private_key: &PrivateKey, message: &[u8]) -> VRFOutput {
-        // Combiner la clé privée et le message
-        let mut input = Vec::with_capacity(32 + message.len());
-        input.extend_from_slice(private_key);
-        input.extend_from_slice(message);
-        
-        // Hasher pour obtenir la sortie
-        let output = hash_data(&input);
-        
-        // Créer une preuve en signant le message avec la clé privée
-        let signing_key = SigningKey::from_bytes(private_key);
-        let signature = signing_key.sign(message);
-        let proof = signature.to_bytes().to_vec();
-        
+        let proof = Self::prove(private_key, message);
+        let output = Self::proof_to_hash(&proof).expect("Generated proof is always well formed");
         VRFOutput { output, proof }
     }
 
-    /// Vérifier une sortie VRF avec une clé publique
-    pub fn verify(public_key: &PublicKey, message: &[u8], _output: &Hash, proof: &[u8]) -> bool {
-        // Recalculer la sortie attendue
-        // Note: Pour une vraie VRF, on devrait pouvoir vérifier sans la clé privée
-        // Ici on utilise une approche simplifiée pour la performance mobile
-        
-        // Vérifier que la preuve est valide
-        use crate::crypto::verify_signature;
-        if !verify_signature(message, &proof[..64].try_into().unwrap_or([0u8; 64]), public_key) {
-            return false;
+    /// Calculer la preuve `pi` pour le message `alpha` (ECVRF_prove)
+    pub fn prove(private_key: &PrivateKey, alpha: &[u8]) -> VRFProof {
+        let (x, prefix) = expand_secret_key(private_key);
+        let y = EdwardsPoint::mul_base(&x);
+        let public_key = y.compress().to_bytes();
+
+        let h = encode_to_curve(&public_key, alpha);
+        let h_string = h.compress().to_bytes();
+        let gamma = x * h;
+        let k = nonce(&prefix, &h_string);
+        let c = challenge(&[&y, &h, &gamma, &EdwardsPoint::mul_base(&k), &(k * h)]);
+        let s = k + c * x;
+
+        let mut proof = [0u8; PROOF_LEN];
+        proof[..32].copy_from_slice(gamma.compress().as_bytes());
+        proof[32..48].copy_from_slice(&c.as_bytes()[..16]);
+        proof[48..].copy_from_slice(s.as_bytes());
+        proof
+    }
+
+    /// Sortie `beta` d'une preuve (ECVRF_proof_to_hash), sans vérification
+    pub fn proof_to_hash(proof: &VRFProof) -> Option<VRFHash> {
+        let (gamma, _, _) = decode_proof(proof)?;
+        Some(gamma_to_hash(&gamma))
+    }
+
+    /// Vérifier une preuve et retourner la sortie `beta` (ECVRF_verify)
+    ///
+    /// La clé publique est validée (encodage canonique, ordre non petit).
+    pub fn verify_proof(public_key: &PublicKey, alpha: &[u8], proof: &VRFProof) -> Option<VRFHash> {
+        let y = decode_point(public_key).filter(|y| !y.is_small_order())?;
+        let (gamma, c, s) = decode_proof(proof)?;
+
+        let h = encode_to_curve(public_key, alpha);
+        let u = EdwardsPoint::mul_base(&s) - c * y;
+        let v = s * h - c * gamma;
+
+        if challenge(&[&y, &h, &gamma, &u, &v]) != c {
+            return None;
         }
-        
-        // Pour une vraie VRF, on devrait pouvoir recalculer output depuis proof
-        // Ici on accepte si la signature est valide
-        // (Dans une implémentation complète, on utiliserait une vraie VRF comme ECVRF)
-        true
+        Some(gamma_to_hash(&gamma))
+    }
+
+    /// Vérifier qu'une sortie VRF correspond à la preuve et à la clé publique
+    pub fn verify(public_key: &PublicKey, message: &[u8], output: &VRFHash, proof: &VRFProof) -> bool {
+        Self::verify_proof(public_key, message, proof).is_some_and(|beta| &beta == output)
     }
 
     /// Convertir la sortie VRF en nombre pour sélection
-    pub fn output_to_number(output: &Hash) -> u64 {
+    pub fn output_to_number(output: &[u8]) -> u64 {
         // Prendre les 8 premiers bytes et les convertir en u64
         let mut bytes = [0u8; 8];
         bytes.copy_from_slice(&output[..8]);
@@ -64,25 +103,134 @@ impl VRF {
     }
 }
 
+/// Scalaire secret et préfixe de nonce (RFC 8032, section 5.1.5)
+fn expand_secret_key(private_key: &PrivateKey) -> (Scalar, [u8; 32]) {
+    let digest = Sha512::digest(private_key);
+    let mut scalar_bytes = [0u8; 32];
+    scalar_bytes.copy_from_slice(&digest[..32]);
+    scalar_bytes[0] &= 248;
+    scalar_bytes[31] &= 127;
+    scalar_bytes[31] |= 64;
+
+    let mut prefix = [0u8; 32];
+    prefix.copy_from_slice(&digest[32..]);
+    (Scalar::from_bytes_mod_order(scalar_bytes), prefix)
+}
+
+/// Décoder un point selon RFC 8032 (encodage canonique uniquement)
+fn decode_point(bytes: &[u8; 32]) -> Option<EdwardsPoint> {
+    let point = CompressedEdwardsY(*bytes).decompress()?;
+    // Rejette y >= p et le "zéro négatif", acceptés par `decompress`
+    (point.compress().as_bytes() == bytes).then_some(point)
+}
+
+/// Hacher le message sur la courbe (ECVRF_encode_to_curve_try_and_increment)
+fn encode_to_curve(public_key: &PublicKey, alpha: &[u8]) -> EdwardsPoint {
+    for ctr in 0..=u8::MAX {
+        let digest = Sha512::new()
+            .chain_update([SUITE, 0x01])
+            .chain_update(public_key)
+            .chain_update(alpha)
+            .chain_update([ctr, 0x00])
+            .finalize();
+        let mut candidate = [0u8; 32];
+        candidate.copy_from_slice(&digest[..32]);
+        if let Some(point) = decode_point(&candidate) {
+            return point.mul_by_cofactor();
+        }
+    }
+    // Probabilité d'échec 2^-256: ne se produit pas en pratique
+    unreachable!("ECVRF encode_to_curve failed after 256 attempts")
+}
+
+/// Nonce déterministe (RFC 8032, section 5.1.6)
+fn nonce(prefix: &[u8; 32], h_string: &[u8; 32]) -> Scalar {
+    let digest = Sha512::new().chain_update(prefix).chain_update(h_string).finalize();
+    Scalar::from_bytes_mod_order_wide(&digest.into())
+}
+
+/// Défi: 16 premiers octets de SHA-512(suite || 0x02 || points || 0x00)
+fn challenge(points: &[&EdwardsPoint]) -> Scalar {
+    let mut hasher = Sha512::new().chain_update([SUITE, 0x02]);
+    for point in points {
+        hasher.update(point.compress().as_bytes());
+    }
+    let digest = hasher.chain_update([0x00]).finalize();
+
+    let mut c = [0u8; 32];
+    c[..16].copy_from_slice(&digest[..16]);
+    Scalar::from_bytes_mod_order(c)
+}
+
+/// Décoder une preuve en (Gamma, c, s); `s` doit être réduit modulo l
+fn decode_proof(proof: &VRFProof) -> Option<(EdwardsPoint, Scalar, Scalar)> {
+    let gamma = decode_point(proof[..32].try_into().expect("32-byte slice"))?;
+
+    let mut c = [0u8; 32];
+    c[..16].copy_from_slice(&proof[32..48]);
+    let c = Scalar::from_bytes_mod_order(c);
+
+    let s_bytes: [u8; 32] = proof[48..].try_into().expect("32-byte slice");
+    let s = Option::<Scalar>::from(Scalar::from_canonical_bytes(s_bytes))?;
+    Some((gamma, c, s))
+}
+
+/// beta = SHA-512(suite || 0x03 || cofacteur * Gamma || 0x00)
+fn gamma_to_hash(gamma: &EdwardsPoint) -> VRFHash {
+    Sha512::new()
+        .chain_update([SUITE, 0x03])
+        .chain_update(gamma.mul_by_cofactor().compress().as_bytes())
+        .chain_update([0x00])
+        .finalize()
+        .into()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::crypto::keys::KeyPair;
 
+    fn from_hex<const N: usize>(value: &str) -> [u8; N] {
+        hex::decode(value).unwrap().try_into().unwrap()
+    }
+
+    /// Vecteurs de test de la RFC 9381 (annexe B.3, exemples 16 et 17)
     #[test]
-    fn test_vrf_generate() {
-        let keypair = KeyPair::new();
-        let message = b"test message";
-        
-        let vrf_output = VRF::generate(keypair.private_key(), message);
-        assert_eq!(vrf_output.output.len(), 32);
+    fn test_rfc9381_vectors() {
+        let vectors = [
+            (
+                "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+                "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+                "",
+                "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f26f8a57ccaed74ee1b190bed1f479d9727d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805",
+                "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae",
+            ),
+            (
+                "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+                "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+                "72",
+                "f3141cd382dc42909d19ec5110469e4feae18300e94f304590abdced48aed5933bf0864a62558b3ed7f2fea45c92a465301b3bbf5e3e54ddf2d935be3b67926da3ef39226bbc355bdc9850112c8f4b02",
+                "eb4440665d3891d668e7e0fcaf587f1b4bd7fbfe99d0eb2211ccec90496310eb5e33821bc613efb94db5e5b54c70a848a0bef4553a41befc57663b56373a5031",
+            ),
+        ];
+
+        for (secret, public, alpha, pi, beta) in vectors {
+            let keypair = KeyPair::from_seed(&from_hex(secret));
+            assert_eq!(keypair.public_key(), &from_hex::<32>(public));
+
+            let alpha = hex::decode(alpha).unwrap();
+            let proof = VRF::prove(keypair.private_key(), &alpha);
+            assert_eq!(hex::encode(proof), pi);
+            assert_eq!(hex::encode(VRF::proof_to_hash(&proof).unwrap()), beta);
+            assert_eq!(VRF::verify_proof(keypair.public_key(), &alpha, &proof), Some(from_hex(beta)));
+        }
     }
 
     #[test]
     fn test_vrf_verify() {
         let keypair = KeyPair::new();
         let message = b"test message";
-        
+
         let vrf_output = VRF::generate(keypair.private_key(), message);
         assert!(VRF::verify(
             keypair.public_key(),
@@ -90,18 +238,34 @@ mod tests {
             &vrf_output.output,
             &vrf_output.proof
         ));
+
+        // Une autre sortie, un autre message ou une autre clé sont refusés
+        assert!(!VRF::verify(keypair.public_key(), message, &[0u8; OUTPUT_LEN], &vrf_output.proof));
+        assert!(VRF::verify_proof(keypair.public_key(), b"other message", &vrf_output.proof).is_none());
+        assert!(VRF::verify_proof(KeyPair::new().public_key(), message, &vrf_output.proof).is_none());
+
+        let mut tampered = vrf_output.proof;
+        tampered[40] ^= 1;
+        assert!(VRF::verify_proof(keypair.public_key(), message, &tampered).is_none());
+    }
+
+    #[test]
+    fn test_vrf_rejects_small_order_key() {
+        let keypair = KeyPair::new();
+        let proof = VRF::prove(keypair.private_key(), b"alpha");
+        let identity = EdwardsPoint::default().compress().to_bytes();
+        assert!(VRF::verify_proof(&identity, b"alpha", &proof).is_none());
     }
 
     #[test]
     fn test_vrf_output_to_number() {
         let keypair = KeyPair::new();
         let message = b"test";
-        
+
         let vrf_output = VRF::generate(keypair.private_key(), message);
         let number = VRF::output_to_number(&vrf_output.output);
-        
+
         // Le nombre devrait être dans une plage raisonnable
         assert!(number < u64::MAX);
     }
 }
-
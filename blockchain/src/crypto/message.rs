@@ -0,0 +1,318 @@
+//! Signature de messages hors chaîne ("Sign-in with Mahala")
+//!
+//! Une clé peut signer un message arbitraire sans risque qu'il soit rejoué
+//! comme transaction: le message est préfixé par un domaine de séparation
+//! et sa longueur avant signature (`sign_message` / `verify_message`).
+//!
+//! Au-dessus, `SignInMessage` est le défi standard par lequel un service
+//! (serveur Luanti, boutique Dolibarr) vérifie qu'un joueur ou un client
+//! contrôle une adresse. Le message est un texte lisible, affiché tel quel
+//! dans l'application avant signature:
+//!
+//! ```text
+//! shop.example.org wants you to sign in with your Mahala account:
+//! mhl1...
+//!
+//! Connexion à la boutique
+//!
+//! URI: https://shop.example.org/login
+//! Version: 1
+//! Nonce: 5f2b9c0e7a1d4e63
+//! Issued At: 2024-05-01T12:00:00Z
+//! Expiration Time: 2024-05-01T12:05:00Z
+//! ```
+//!
+//! La ligne de déclaration est facultative. Le service fournit le domaine,
+//! l'URI, le nonce et l'expiration; la vérification contrôle le domaine et
+//! le nonce attendus, la fenêtre de validité et la signature de l'adresse.
+
+use std::fmt;
+use chrono::{DateTime, SecondsFormat, Utc};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use thiserror::Error;
+use crate::{PrivateKey, PublicKey, Signature, Timestamp};
+use crate::crypto::{sign, verify_signature};
+use crate::wallet::{decode_address, encode_address, Network};
+
+/// Domaine de séparation des messages signés hors chaîne
+const MESSAGE_DOMAIN: &[u8] = b"\x19Mahala Signed Message:\n";
+
+/// Version du format `SignInMessage`
+pub const SIGN_IN_VERSION: u32 = 1;
+
+/// Longueur minimale d'un nonce
+pub const MIN_NONCE_LENGTH: usize = 8;
+
+/// Tolérance d'horloge pour la date d'émission (secondes)
+pub const CLOCK_SKEW_SECS: i64 = 60;
+
+const HEADER_SUFFIX: &str = " wants you to sign in with your Mahala account:";
+
+/// Erreurs de message signé
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum MessageError {
+    #[error("Malformed message: {0}")]
+    Malformed(String),
+    #[error("Invalid nonce")]
+    InvalidNonce,
+    #[error("Domain mismatch (expected {expected})")]
+    DomainMismatch { expected: String },
+    #[error("Nonce mismatch")]
+    NonceMismatch,
+    #[error("Message is not valid yet")]
+    NotYetValid,
+    #[error("Message has expired")]
+    Expired,
+    #[error("Invalid signature")]
+    InvalidSignature,
+}
+
+/// Octets signés: domaine || longueur décimale || '\n' || message
+fn message_signing_bytes(message: &[u8]) -> Vec<u8> {
+    let length = message.len().to_string();
+    let mut data = Vec::with_capacity(MESSAGE_DOMAIN.len() + length.len() + 1 + message.len());
+    data.extend_from_slice(MESSAGE_DOMAIN);
+    data.extend_from_slice(length.as_bytes());
+    data.push(b'\n');
+    data.extend_from_slice(message);
+    data
+}
+
+/// Signer un message hors chaîne
+pub fn sign_message(message: &[u8], private_key: &PrivateKey) -> Signature {
+    sign(&message_signing_bytes(message), private_key)
+}
+
+/// Vérifier la signature d'un message hors chaîne
+pub fn verify_message(message: &[u8], signature: &Signature, public_key: &PublicKey) -> bool {
+    verify_signature(&message_signing_bytes(message), signature, public_key)
+}
+
+/// Générer un nonce aléatoire (16 caractères hexadécimaux)
+pub fn generate_nonce() -> String {
+    let mut nonce = [0u8; 8];
+    OsRng.fill_bytes(&mut nonce);
+    hex::encode(nonce)
+}
+
+/// Défi de connexion signé par le détenteur d'une adresse
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignInMessage {
+    /// Domaine (origine) du service demandeur
+    pub domain: String,
+    /// Adresse qui se connecte
+    pub address: PublicKey,
+    /// Réseau de l'adresse
+    pub network: Network,
+    /// Déclaration lisible (une ligne)
+    pub statement: Option<String>,
+    /// URI de la ressource demandant la connexion
+    pub uri: String,
+    /// Nonce fourni par le service (anti-rejeu)
+    pub nonce: String,
+    /// Date d'émission
+    pub issued_at: Timestamp,
+    /// Date d'expiration
+    pub expires_at: Timestamp,
+}
+
+impl SignInMessage {
+    /// Signer le texte canonique du message
+    pub fn sign(&self, private_key: &PrivateKey) -> Signature {
+        sign_message(self.to_string().as_bytes(), private_key)
+    }
+
+    /// Analyser le texte d'un message
+    pub fn parse(text: &str) -> Result<Self, MessageError> {
+        let malformed = |reason: &str| MessageError::Malformed(reason.to_string());
+        let mut lines = text.split('\n');
+        let mut next = || lines.next().ok_or_else(|| malformed("truncated message"));
+
+        let domain = next()?
+            .strip_suffix(HEADER_SUFFIX)
+            .filter(|domain| !domain.is_empty())
+            .ok_or_else(|| malformed("invalid header"))?
+            .to_string();
+        let (network, address) = decode_address(next()?)
+            .map_err(|e| MessageError::Malformed(format!("invalid address: {}", e)))?;
+        if !next()?.is_empty() {
+            return Err(malformed("missing blank line after address"));
+        }
+
+        let mut line = next()?;
+        let mut statement = None;
+        if !line.starts_with("URI: ") {
+            statement = Some(line.to_string());
+            if !next()?.is_empty() {
+                return Err(malformed("missing blank line after statement"));
+            }
+            line = next()?;
+        }
+
+        let uri = field(line, "URI")?.to_string();
+        let version = field(next()?, "Version")?;
+        if version != SIGN_IN_VERSION.to_string() {
+            return Err(MessageError::Malformed(format!("unsupported version {}", version)));
+        }
+        let nonce = field(next()?, "Nonce")?.to_string();
+        let issued_at = parse_time(field(next()?, "Issued At")?)?;
+        let expires_at = parse_time(field(next()?, "Expiration Time")?)?;
+        if lines.next().is_some() {
+            return Err(malformed("unexpected trailing content"));
+        }
+
+        let message = Self { domain, address, network, statement, uri, nonce, issued_at, expires_at };
+        message.validate()?;
+        Ok(message)
+    }
+
+    /// Vérifier la signature et le défi attendu par le service
+    pub fn verify(
+        &self,
+        signature: &Signature,
+        expected_domain: &str,
+        expected_nonce: &str,
+        now: Timestamp,
+    ) -> Result<(), MessageError> {
+        self.validate()?;
+        if self.domain != expected_domain {
+            return Err(MessageError::DomainMismatch { expected: expected_domain.to_string() });
+        }
+        if self.nonce != expected_nonce {
+            return Err(MessageError::NonceMismatch);
+        }
+        if self.issued_at > now + CLOCK_SKEW_SECS {
+            return Err(MessageError::NotYetValid);
+        }
+        if self.expires_at <= now {
+            return Err(MessageError::Expired);
+        }
+        if !verify_message(self.to_string().as_bytes(), signature, &self.address) {
+            return Err(MessageError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// Vérifier la forme des champs (le texte doit rester analysable)
+    fn validate(&self) -> Result<(), MessageError> {
+        let single_line = |value: &str| !value.is_empty() && !value.contains(['\n', '\r']);
+        if !single_line(&self.domain) || self.domain.contains(' ') {
+            return Err(MessageError::Malformed("invalid domain".to_string()));
+        }
+        if !single_line(&self.uri) {
+            return Err(MessageError::Malformed("invalid URI".to_string()));
+        }
+        if let Some(statement) = &self.statement {
+            if !single_line(statement) || statement.starts_with("URI: ") {
+                return Err(MessageError::Malformed("invalid statement".to_string()));
+            }
+        }
+        if self.nonce.len() < MIN_NONCE_LENGTH || !self.nonce.bytes().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(MessageError::InvalidNonce);
+        }
+        if self.expires_at <= self.issued_at {
+            return Err(MessageError::Malformed("expiration before issuance".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SignInMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}{}", self.domain, HEADER_SUFFIX)?;
+        writeln!(f, "{}", encode_address(&self.address, self.network))?;
+        writeln!(f)?;
+        if let Some(statement) = &self.statement {
+            writeln!(f, "{}", statement)?;
+            writeln!(f)?;
+        }
+        writeln!(f, "URI: {}", self.uri)?;
+        writeln!(f, "Version: {}", SIGN_IN_VERSION)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        writeln!(f, "Issued At: {}", format_time(self.issued_at))?;
+        write!(f, "Expiration Time: {}", format_time(self.expires_at))
+    }
+}
+
+fn field<'a>(line: &'a str, name: &str) -> Result<&'a str, MessageError> {
+    line.strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix(": "))
+        .ok_or_else(|| MessageError::Malformed(format!("missing {}", name)))
+}
+
+fn format_time(timestamp: Timestamp) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn parse_time(value: &str) -> Result<Timestamp, MessageError> {
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.timestamp())
+        .map_err(|_| MessageError::Malformed(format!("invalid timestamp {}", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::keys::KeyPair;
+
+    fn message(keypair: &KeyPair) -> SignInMessage {
+        SignInMessage {
+            domain: "shop.example.org".to_string(),
+            address: *keypair.public_key(),
+            network: Network::Mainnet,
+            statement: Some("Connexion à la boutique".to_string()),
+            uri: "https://shop.example.org/login".to_string(),
+            nonce: "5f2b9c0e7a1d4e63".to_string(),
+            issued_at: 1_714_564_800,
+            expires_at: 1_714_565_100,
+        }
+    }
+
+    #[test]
+    fn test_sign_in_round_trip() {
+        let keypair = KeyPair::new();
+        let message = message(&keypair);
+        let text = message.to_string();
+        assert!(text.contains("Issued At: 2024-05-01T12:00:00Z"));
+        assert_eq!(SignInMessage::parse(&text).unwrap(), message);
+
+        let without_statement = SignInMessage { statement: None, ..message.clone() };
+        assert_eq!(SignInMessage::parse(&without_statement.to_string()).unwrap(), without_statement);
+
+        let signature = message.sign(keypair.private_key());
+        let parsed = SignInMessage::parse(&text).unwrap();
+        assert_eq!(parsed.verify(&signature, "shop.example.org", &message.nonce, 1_714_564_900), Ok(()));
+    }
+
+    #[test]
+    fn test_sign_in_rejections() {
+        let keypair = KeyPair::new();
+        let message = message(&keypair);
+        let signature = message.sign(keypair.private_key());
+        let now = 1_714_564_900;
+
+        assert!(matches!(
+            message.verify(&signature, "evil.example.org", &message.nonce, now),
+            Err(MessageError::DomainMismatch { .. })
+        ));
+        assert_eq!(message.verify(&signature, &message.domain, "0000000000000000", now), Err(MessageError::NonceMismatch));
+        assert_eq!(message.verify(&signature, &message.domain, &message.nonce, 1_714_565_100), Err(MessageError::Expired));
+        assert_eq!(message.verify(&signature, &message.domain, &message.nonce, 1_714_564_000), Err(MessageError::NotYetValid));
+
+        let other = SignInMessage { address: *KeyPair::new().public_key(), ..message.clone() };
+        assert_eq!(other.verify(&signature, &message.domain, &message.nonce, now), Err(MessageError::InvalidSignature));
+    }
+
+    #[test]
+    fn test_message_signature_is_domain_separated() {
+        let keypair = KeyPair::new();
+        let data = [7u8; 32];
+        let signature = sign_message(&data, keypair.private_key());
+        assert!(verify_message(&data, &signature, keypair.public_key()));
+        // Une signature de message n'est pas une signature de transaction (hash brut)
+        assert!(!verify_signature(&data, &signature, keypair.public_key()));
+    }
+}
@@ -1,14 +1,20 @@
 //! Module cryptographique pour Mahala
 //!
 //! Utilise Ed25519 pour les signatures et Blake3 pour le hashing
-//! pour une performance optimale sur mobile.
+//! pour une performance optimale sur mobile. Les messages hors chaîne
+//! (connexion à un service) sont signés avec séparation de domaine (voir
+//! `message`).
 
 pub mod keys;
 pub mod signatures;
 pub mod hash;
+pub mod message;
 
 pub use keys::{KeyPair, generate_keypair};
-pub use signatures::{sign, verify_signature};
+pub use signatures::{sign, verify_batch, verify_signature};
+#[cfg(feature = "parallel")]
+pub use signatures::verify_batch_parallel;
+pub use message::{sign_message, verify_message, MessageError, SignInMessage};
 pub use hash::{hash_data, hash_to_string};
 
 
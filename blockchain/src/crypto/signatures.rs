@@ -23,11 +23,63 @@ pub fn verify_signature(data: &[u8], signature: &MahalaSignature, public_key: &P
     verifying_key.verify(data, &sig).is_ok()
 }
 
+/// Nombre de signatures par lot en vérification parallèle
+#[cfg(feature = "parallel")]
+const PARALLEL_BATCH_SIZE: usize = 64;
+
+/// Vérifier un lot de signatures `(message, signature, clé publique)`
+///
+/// Nettement plus rapide que des vérifications une à une; le lot échoue si
+/// une seule signature est invalide, sans dire laquelle.
+pub fn verify_batch(items: &[(&[u8], MahalaSignature, PublicKey)]) -> bool {
+    let mut messages = Vec::with_capacity(items.len());
+    let mut signatures = Vec::with_capacity(items.len());
+    let mut verifying_keys = Vec::with_capacity(items.len());
+
+    for (message, signature, public_key) in items {
+        let Ok(verifying_key) = VerifyingKey::from_bytes(public_key) else {
+            return false;
+        };
+        messages.push(*message);
+        signatures.push(Signature::from_bytes(signature));
+        verifying_keys.push(verifying_key);
+    }
+
+    ed25519_dalek::verify_batch(&messages, &signatures, &verifying_keys).is_ok()
+}
+
+/// Vérifier un lot de signatures sur plusieurs threads (nœuds complets)
+#[cfg(feature = "parallel")]
+pub fn verify_batch_parallel(items: &[(&[u8], MahalaSignature, PublicKey)]) -> bool {
+    use rayon::prelude::*;
+
+    items.par_chunks(PARALLEL_BATCH_SIZE).all(verify_batch)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::crypto::keys::KeyPair;
 
+    #[test]
+    fn test_verify_batch() {
+        let keypairs: Vec<KeyPair> = (0..8).map(|_| KeyPair::new()).collect();
+        let messages: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 32]).collect();
+        let mut items: Vec<(&[u8], MahalaSignature, PublicKey)> = keypairs
+            .iter()
+            .zip(&messages)
+            .map(|(keypair, message)| {
+                (message.as_slice(), sign(message, keypair.private_key()), *keypair.public_key())
+            })
+            .collect();
+
+        assert!(verify_batch(&items));
+        assert!(verify_batch(&[]));
+
+        items[5].0 = b"tampered";
+        assert!(!verify_batch(&items));
+    }
+
     #[test]
     fn test_sign_and_verify() {
         let keypair = KeyPair::new();
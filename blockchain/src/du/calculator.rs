@@ -19,6 +19,8 @@ pub struct DUConfig {
     pub semester_days: i64,
     
     /// Date de création de la monnaie (genesis)
+    ///
+    /// Paramètre de consensus: identique sur tous les nœuds.
     pub genesis_date: DateTime<Utc>,
 }
 
@@ -27,7 +29,8 @@ impl Default for DUConfig {
         Self {
             growth_rate_per_semester: 0.0488, // 4.88%
             semester_days: 183,
-            genesis_date: Utc::now(), // À ajuster selon le lancement réel
+            // 2024-01-01 00:00 UTC, à ajuster selon le lancement réel
+            genesis_date: DateTime::from_timestamp(1_704_067_200, 0).expect("Valid genesis date"),
         }
     }
 }
@@ -3,6 +3,7 @@
 //! Une blockchain légère optimisée pour mobile avec consensus RVS
 //! et Dividende Universel conforme à la TRM.
 
+pub mod account;
 pub mod block;
 pub mod chain;
 pub mod consensus;
@@ -12,6 +13,7 @@ pub mod storage;
 pub mod transaction;
 pub mod wallet;
 pub mod nft;
+pub mod payments;
 
 pub use block::Block;
 pub use chain::Blockchain;
@@ -4,8 +4,10 @@
 
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
-use crate::{PublicKey, Amount};
+use crate::{Hash, PublicKey, Amount, Timestamp};
 use crate::crypto::hash_data;
+use crate::nft::schema::validate_attributes;
+use crate::nft::events::{NFTEvent, NFTEventContext, NFTEventKind, NFTEventLog};
 use thiserror::Error;
 
 /// Type de NFT
@@ -40,13 +42,50 @@ pub struct NFTMetadata {
     pub nft_type: NFTType,
     /// URL du média (IPFS hash ou URL)
     pub media_url: String,
+    /// Hash Blake3 du contenu du média (engagement sur le fichier, en hex)
+    ///
+    /// Absent des métadonnées antérieures: le hash nul est alors refusé au
+    /// mint par `validate`.
+    #[serde(default, with = "crate::consensus::rvs::hex_bytes")]
+    pub media_hash: Hash,
     /// URL de la miniature
     pub thumbnail_url: String,
-    /// Attributs additionnels
+    /// Hash Blake3 de la miniature (optionnel, en hex)
+    #[serde(default, with = "crate::consensus::rvs::hex_bytes::option")]
+    pub thumbnail_hash: Option<Hash>,
+    /// Attributs additionnels (validés selon le schéma du type)
     #[serde(default)]
     pub attributes: HashMap<String, String>,
 }
 
+impl NFTMetadata {
+    /// Valider les métadonnées (nom, engagement média, schéma d'attributs)
+    pub fn validate(&self) -> Result<(), NFTError> {
+        if self.name.trim().is_empty() {
+            return Err(NFTError::InvalidMetadata("name must not be empty".to_string()));
+        }
+
+        if self.media_hash == [0u8; 32] {
+            return Err(NFTError::InvalidMetadata("missing media hash".to_string()));
+        }
+
+        validate_attributes(&self.nft_type, &self.attributes)
+            .map_err(NFTError::InvalidMetadata)
+    }
+
+    /// Vérifier qu'un fichier média téléchargé correspond au hash engagé
+    pub fn verify_media(&self, data: &[u8]) -> bool {
+        hash_data(data) == self.media_hash
+    }
+
+    /// Vérifier qu'une miniature téléchargée correspond au hash engagé
+    ///
+    /// Sans hash de miniature, aucune miniature ne peut être vérifiée.
+    pub fn verify_thumbnail(&self, data: &[u8]) -> bool {
+        self.thumbnail_hash == Some(hash_data(data))
+    }
+}
+
 /// NFT
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NFT {
@@ -77,6 +116,54 @@ pub struct NFTListing {
     pub listed_at: i64,
     /// Date d'expiration (optionnel)
     pub expires_at: Option<i64>,
+    /// Commission de la place de marché via laquelle le NFT est listé
+    #[serde(default)]
+    pub marketplace_fee: Option<MarketplaceFee>,
+}
+
+/// Commission prélevée par une place de marché (opérateur ou front-end)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct MarketplaceFee {
+    /// Bénéficiaire de la commission
+    #[serde(deserialize_with = "crate::wallet::address::deserialize")]
+    pub recipient: PublicKey,
+    /// Taux en points de base (1/100 de pourcent)
+    pub rate_bps: u16,
+}
+
+impl MarketplaceFee {
+    /// Montant de la commission pour un paiement donné
+    pub fn amount(&self, payment: Amount) -> Amount {
+        payment * self.rate_bps as f64 / 10_000.0
+    }
+}
+
+/// Plafond par défaut de la commission de marché (5%)
+pub const DEFAULT_MAX_MARKETPLACE_FEE_BPS: u16 = 500;
+
+/// Royalties maximales du créateur (en pourcentage)
+pub const MAX_ROYALTY_PERCENTAGE: u8 = 10;
+
+/// Plafond configurable de la commission de marché: avec les royalties
+/// maximales, la somme prélevée ne dépasse jamais le paiement
+pub const MAX_MARKETPLACE_FEE_BPS: u16 = 10_000 - MAX_ROYALTY_PERCENTAGE as u16 * 100;
+
+/// Configuration des commissions de marché (règle de consensus)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceConfig {
+    /// Commission maximale autorisée, en points de base
+    pub max_fee_bps: u16,
+    /// Commission de l'opérateur appliquée aux listings sans place de marché
+    pub default_fee: Option<MarketplaceFee>,
+}
+
+impl Default for MarketplaceConfig {
+    fn default() -> Self {
+        Self {
+            max_fee_bps: DEFAULT_MAX_MARKETPLACE_FEE_BPS,
+            default_fee: None,
+        }
+    }
 }
 
 /// Résultat d'un transfert NFT
@@ -96,8 +183,57 @@ pub struct NFTTransferResult {
     pub seller_receives: Amount,
     /// Montant reçu par le créateur (royalties)
     pub creator_receives: Amount,
+    /// Bénéficiaire de la commission de marché
+    pub marketplace: Option<PublicKey>,
+    /// Montant reçu par la place de marché (commission)
+    pub marketplace_receives: Amount,
+}
+
+/// Opération NFT portée par une transaction de type `TransactionType::NFT`
+///
+/// Sérialisée en JSON dans `TransactionMetadata::extra_data`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum NFTOperation {
+    /// Minter un NFT (l'expéditeur devient créateur et propriétaire)
+    Mint {
+        metadata: NFTMetadata,
+        royalty_percentage: u8,
+    },
+    /// Mettre en vente un NFT (éventuellement via une place de marché)
+    List {
+        nft_id: String,
+        price: Amount,
+        duration_days: Option<u64>,
+        #[serde(default)]
+        marketplace_fee: Option<MarketplaceFee>,
+    },
+    /// Retirer un NFT de la vente
+    Delist { nft_id: String },
+    /// Acheter un NFT listé (le montant de la transaction est le paiement)
+    Buy { nft_id: String },
+    /// Transférer un NFT au destinataire de la transaction
+    Transfer { nft_id: String },
+    /// Détruire un NFT
+    Burn { nft_id: String },
 }
 
+impl NFTOperation {
+    /// NFT existant visé par l'opération (`None` pour un mint)
+    pub fn nft_id(&self) -> Option<&str> {
+        match self {
+            Self::Mint { .. } => None,
+            Self::List { nft_id, .. }
+            | Self::Delist { nft_id }
+            | Self::Buy { nft_id }
+            | Self::Transfer { nft_id }
+            | Self::Burn { nft_id } => Some(nft_id),
+        }
+    }
+}
+
+/// NFT et listing d'un identifiant, conservés pour annuler un bloc
+pub(crate) type NFTEntry = (Option<NFT>, Option<NFTListing>);
+
 /// Erreurs du contrat NFT
 #[derive(Debug, Error)]
 pub enum NFTError {
@@ -115,14 +251,28 @@ pub enum NFTError {
     ListingExpired,
     #[error("Insufficient payment")]
     InsufficientPayment,
+    #[error("Invalid metadata: {0}")]
+    InvalidMetadata(String),
+    #[error("Marketplace fee too high (max {0} bps)")]
+    MarketplaceFeeTooHigh(u16),
+    #[error("Royalty and marketplace fee exceed the payment")]
+    FeesExceedPayment,
 }
 
 /// Contrat NFT
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NFTContract {
     /// NFTs indexés par ID
     nfts: HashMap<String, NFT>,
     /// Listings actifs
     listings: HashMap<String, NFTListing>,
+    /// Journal de provenance
+    events: NFTEventLog,
+    /// Contexte de la transaction en cours (défini par la blockchain)
+    #[serde(skip)]
+    context: Option<NFTEventContext>,
+    /// Configuration des commissions de marché
+    marketplace_config: MarketplaceConfig,
 }
 
 impl NFTContract {
@@ -131,9 +281,76 @@ impl NFTContract {
         Self {
             nfts: HashMap::new(),
             listings: HashMap::new(),
+            events: NFTEventLog::new(),
+            context: None,
+            marketplace_config: MarketplaceConfig::default(),
         }
     }
 
+    /// Définir la configuration des commissions de marché
+    ///
+    /// Le plafond ne dépasse pas `MAX_MARKETPLACE_FEE_BPS`.
+    pub fn set_marketplace_config(&mut self, config: MarketplaceConfig) -> Result<(), NFTError> {
+        if config.max_fee_bps > MAX_MARKETPLACE_FEE_BPS {
+            return Err(NFTError::MarketplaceFeeTooHigh(MAX_MARKETPLACE_FEE_BPS));
+        }
+        if let Some(fee) = &config.default_fee {
+            if fee.rate_bps > config.max_fee_bps {
+                return Err(NFTError::MarketplaceFeeTooHigh(config.max_fee_bps));
+            }
+        }
+        self.marketplace_config = config;
+        Ok(())
+    }
+
+    /// Obtenir la configuration des commissions de marché
+    pub fn marketplace_config(&self) -> &MarketplaceConfig {
+        &self.marketplace_config
+    }
+
+    /// Définir le contexte (bloc, transaction) des prochaines opérations
+    ///
+    /// Les événements enregistrés portent ce contexte, et l'horloge du
+    /// contrat devient celle du bloc pour rester déterministe.
+    pub fn set_context(&mut self, context: NFTEventContext) {
+        self.context = Some(context);
+    }
+
+    /// Effacer le contexte courant
+    pub fn clear_context(&mut self) {
+        self.context = None;
+    }
+
+    /// Horloge du contrat: timestamp du bloc si défini, sinon l'heure locale
+    fn now(&self) -> Timestamp {
+        self.context
+            .map(|ctx| ctx.timestamp)
+            .unwrap_or_else(|| chrono::Utc::now().timestamp())
+    }
+
+    /// Enregistrer un événement avec le contexte courant
+    fn record_event(
+        &mut self,
+        kind: NFTEventKind,
+        nft_id: &str,
+        from: Option<PublicKey>,
+        to: Option<PublicKey>,
+        price: Option<Amount>,
+    ) {
+        let context = self.context.unwrap_or_default();
+        let timestamp = self.now();
+        self.events.record(NFTEvent {
+            kind,
+            nft_id: nft_id.to_string(),
+            from,
+            to,
+            price,
+            block_height: context.block_height,
+            tx_hash: context.tx_hash,
+            timestamp,
+        });
+    }
+
     /// Minter un nouveau NFT
     pub fn mint(
         &mut self,
@@ -141,23 +358,26 @@ impl NFTContract {
         metadata: NFTMetadata,
         royalty_percentage: u8,
     ) -> Result<String, NFTError> {
-        if royalty_percentage > 10 {
+        if royalty_percentage > MAX_ROYALTY_PERCENTAGE {
             return Err(NFTError::RoyaltyTooHigh);
         }
 
+        metadata.validate()?;
+
         // Générer un ID unique
-        let nft_id = Self::generate_nft_id(&creator, &metadata);
+        let nft_id = self.generate_nft_id(&creator, &metadata);
         
         let nft = NFT {
             id: nft_id.clone(),
             owner: creator,
             creator,
             metadata,
-            created_at: chrono::Utc::now().timestamp(),
+            created_at: self.now(),
             royalty_percentage,
         };
 
         self.nfts.insert(nft_id.clone(), nft);
+        self.record_event(NFTEventKind::Mint, &nft_id, None, Some(creator), None);
         Ok(nft_id)
     }
 
@@ -168,10 +388,26 @@ impl NFTContract {
         seller: &PublicKey,
         price: Amount,
         duration_days: Option<u64>,
+    ) -> Result<(), NFTError> {
+        self.list_for_sale_via(nft_id, seller, price, duration_days, None)
+    }
+
+    /// Lister un NFT à la vente via une place de marché prélevant une commission
+    pub fn list_for_sale_via(
+        &mut self,
+        nft_id: &str,
+        seller: &PublicKey,
+        price: Amount,
+        duration_days: Option<u64>,
+        marketplace_fee: Option<MarketplaceFee>,
     ) -> Result<(), NFTError> {
         let nft = self.nfts.get(nft_id)
             .ok_or(NFTError::NotFound)?;
 
+        if let Some(fee) = &marketplace_fee {
+            self.check_marketplace_fee(fee)?;
+        }
+
         if &nft.owner != seller {
             return Err(NFTError::NotOwner);
         }
@@ -180,19 +416,34 @@ impl NFTContract {
             return Err(NFTError::AlreadyListed);
         }
 
-        let expires_at = duration_days.map(|days| {
-            chrono::Utc::now().timestamp() + (days as i64 * 86400)
-        });
+        let now = self.now();
+        let expires_at = duration_days.map(|days| now + (days as i64 * 86400));
 
         let listing = NFTListing {
             nft_id: nft_id.to_string(),
             seller: *seller,
             price,
-            listed_at: chrono::Utc::now().timestamp(),
+            listed_at: now,
             expires_at,
+            marketplace_fee,
         };
 
         self.listings.insert(nft_id.to_string(), listing);
+        self.record_event(NFTEventKind::List, nft_id, Some(*seller), None, Some(price));
+        Ok(())
+    }
+
+    /// Retirer un NFT de la vente
+    pub fn delist(&mut self, nft_id: &str, seller: &PublicKey) -> Result<(), NFTError> {
+        let listing = self.listings.get(nft_id)
+            .ok_or(NFTError::NotListed)?;
+
+        if &listing.seller != seller {
+            return Err(NFTError::NotOwner);
+        }
+
+        self.listings.remove(nft_id);
+        self.record_event(NFTEventKind::Delist, nft_id, Some(*seller), None, None);
         Ok(())
     }
 
@@ -208,7 +459,7 @@ impl NFTContract {
 
         // Vérifier expiration
         if let Some(expires_at) = listing.expires_at {
-            if chrono::Utc::now().timestamp() > expires_at {
+            if self.now() > expires_at {
                 self.listings.remove(nft_id);
                 return Err(NFTError::ListingExpired);
             }
@@ -219,12 +470,25 @@ impl NFTContract {
             return Err(NFTError::InsufficientPayment);
         }
 
+        // Commission du listing, sinon celle de l'opérateur (plafond revérifié)
+        let marketplace_fee = listing.marketplace_fee
+            .or(self.marketplace_config.default_fee);
+        if let Some(fee) = &marketplace_fee {
+            self.check_marketplace_fee(fee)?;
+        }
+
         let nft = self.nfts.get_mut(nft_id)
             .ok_or(NFTError::NotFound)?;
 
-        // Calculer royalties
+        // Calculer royalties et commission
         let royalty_amount = (payment * nft.royalty_percentage as f64) / 100.0;
-        let seller_amount = payment - royalty_amount;
+        let marketplace_amount = marketplace_fee
+            .map(|fee| fee.amount(payment))
+            .unwrap_or(0.0);
+        if royalty_amount + marketplace_amount > payment {
+            return Err(NFTError::FeesExceedPayment);
+        }
+        let seller_amount = payment - royalty_amount - marketplace_amount;
 
         // Transférer NFT
         nft.owner = *buyer;
@@ -233,6 +497,7 @@ impl NFTContract {
         let seller = listing.seller;
         let creator = nft.creator;
         self.listings.remove(nft_id);
+        self.record_event(NFTEventKind::Sale, nft_id, Some(seller), Some(*buyer), Some(payment));
 
         Ok(NFTTransferResult {
             nft_id: nft_id.to_string(),
@@ -242,9 +507,19 @@ impl NFTContract {
             total_paid: payment,
             seller_receives: seller_amount,
             creator_receives: royalty_amount,
+            marketplace: marketplace_fee.map(|fee| fee.recipient),
+            marketplace_receives: marketplace_amount,
         })
     }
 
+    /// Vérifier qu'une commission respecte le plafond de consensus
+    fn check_marketplace_fee(&self, fee: &MarketplaceFee) -> Result<(), NFTError> {
+        if fee.rate_bps > self.marketplace_config.max_fee_bps {
+            return Err(NFTError::MarketplaceFeeTooHigh(self.marketplace_config.max_fee_bps));
+        }
+        Ok(())
+    }
+
     /// Transférer un NFT (gratuit)
     pub fn transfer(
         &mut self,
@@ -263,6 +538,22 @@ impl NFTContract {
         self.listings.remove(nft_id);
 
         nft.owner = *to;
+        self.record_event(NFTEventKind::Transfer, nft_id, Some(*from), Some(*to), None);
+        Ok(())
+    }
+
+    /// Détruire un NFT (propriétaire uniquement)
+    pub fn burn(&mut self, nft_id: &str, owner: &PublicKey) -> Result<(), NFTError> {
+        let nft = self.nfts.get(nft_id)
+            .ok_or(NFTError::NotFound)?;
+
+        if &nft.owner != owner {
+            return Err(NFTError::NotOwner);
+        }
+
+        self.listings.remove(nft_id);
+        self.nfts.remove(nft_id);
+        self.record_event(NFTEventKind::Burn, nft_id, Some(*owner), None, None);
         Ok(())
     }
 
@@ -275,7 +566,7 @@ impl NFTContract {
 
     /// Obtenir les listings actifs
     pub fn get_active_listings(&self) -> Vec<(&NFT, &NFTListing)> {
-        let now = chrono::Utc::now().timestamp();
+        let now = self.now();
 
         self.listings.values()
             .filter(|listing| {
@@ -293,13 +584,69 @@ impl NFTContract {
         self.nfts.get(nft_id)
     }
 
+    /// Obtenir le listing d'un NFT
+    pub fn get_listing(&self, nft_id: &str) -> Option<&NFTListing> {
+        self.listings.get(nft_id)
+    }
+
+    /// Historique de provenance d'un NFT
+    pub fn history(&self, nft_id: &str) -> Vec<&NFTEvent> {
+        self.events.history(nft_id)
+    }
+
+    /// Événements NFT impliquant une adresse
+    pub fn events_for_address(&self, address: &PublicKey) -> Vec<&NFTEvent> {
+        self.events.by_address(address)
+    }
+
+    /// Journal complet des événements
+    pub fn events(&self) -> &NFTEventLog {
+        &self.events
+    }
+
+    /// NFT et listing d'un identifiant, tels quels
+    pub(crate) fn entry(&self, nft_id: &str) -> NFTEntry {
+        (self.nfts.get(nft_id).cloned(), self.listings.get(nft_id).cloned())
+    }
+
+    /// Remettre le NFT et le listing d'un identifiant
+    pub(crate) fn restore(&mut self, nft_id: &str, (nft, listing): NFTEntry) {
+        match nft {
+            Some(nft) => self.nfts.insert(nft_id.to_string(), nft),
+            None => self.nfts.remove(nft_id),
+        };
+        match listing {
+            Some(listing) => self.listings.insert(nft_id.to_string(), listing),
+            None => self.listings.remove(nft_id),
+        };
+    }
+
+    /// Annuler les opérations enregistrées après les `events_len` premiers
+    /// événements
+    ///
+    /// Les entrées journalisées (`entry`) sont remises; les NFTs mintés
+    /// depuis sont retirés, même si une opération suivante les a journalisés.
+    pub(crate) fn undo(&mut self, events_len: usize, mut entries: HashMap<String, NFTEntry>) {
+        for event in self.events.truncate(events_len) {
+            if event.kind == NFTEventKind::Mint {
+                entries.insert(event.nft_id, (None, None));
+            }
+        }
+        for (nft_id, entry) in entries {
+            self.restore(&nft_id, entry);
+        }
+    }
+
     /// Générer un ID unique pour un NFT
-    fn generate_nft_id(creator: &PublicKey, metadata: &NFTMetadata) -> String {
+    fn generate_nft_id(&self, creator: &PublicKey, metadata: &NFTMetadata) -> String {
         let mut data = Vec::new();
         data.extend_from_slice(creator);
         data.extend_from_slice(metadata.name.as_bytes());
-        let timestamp = chrono::Utc::now().timestamp();
-        data.extend_from_slice(&timestamp.to_le_bytes());
+        data.extend_from_slice(&self.now().to_le_bytes());
+        // Le hash de la transaction distingue deux mints identiques dans le même bloc
+        if let Some(context) = &self.context {
+            data.extend_from_slice(&context.tx_hash);
+        }
         
         let hash = hash_data(&data);
         hex::encode(&hash[..16]) // Utiliser les 16 premiers bytes pour l'ID
@@ -327,7 +674,9 @@ mod tests {
             description: "A test NFT".to_string(),
             nft_type: NFTType::Art,
             media_url: "ipfs://test".to_string(),
+            media_hash: hash_data(b"media"),
             thumbnail_url: "ipfs://thumb".to_string(),
+            thumbnail_hash: None,
             attributes: HashMap::new(),
         };
 
@@ -346,7 +695,9 @@ mod tests {
             description: "A test NFT".to_string(),
             nft_type: NFTType::Art,
             media_url: "ipfs://test".to_string(),
+            media_hash: hash_data(b"media"),
             thumbnail_url: "ipfs://thumb".to_string(),
+            thumbnail_hash: None,
             attributes: HashMap::new(),
         };
 
@@ -363,5 +714,257 @@ mod tests {
         assert_eq!(transfer.seller_receives, 95.0); // 100 - 5% royalties
         assert_eq!(transfer.creator_receives, 5.0);
     }
+
+    #[test]
+    fn test_nft_provenance_history() {
+        let mut contract = NFTContract::new();
+        let creator = KeyPair::new();
+        let buyer = KeyPair::new();
+        let friend = KeyPair::new();
+
+        let metadata = NFTMetadata {
+            name: "Provenance".to_string(),
+            description: "Historique".to_string(),
+            nft_type: NFTType::Art,
+            media_url: "ipfs://test".to_string(),
+            media_hash: hash_data(b"media"),
+            thumbnail_url: "ipfs://thumb".to_string(),
+            thumbnail_hash: None,
+            attributes: HashMap::new(),
+        };
+
+        contract.set_context(NFTEventContext { block_height: 1, tx_hash: [1u8; 32], timestamp: 1000 });
+        let nft_id = contract.mint(*creator.public_key(), metadata, 5).unwrap();
+
+        contract.set_context(NFTEventContext { block_height: 2, tx_hash: [2u8; 32], timestamp: 1005 });
+        contract.list_for_sale(&nft_id, creator.public_key(), 50.0, None).unwrap();
+        contract.delist(&nft_id, creator.public_key()).unwrap();
+        contract.list_for_sale(&nft_id, creator.public_key(), 80.0, Some(1)).unwrap();
+
+        contract.set_context(NFTEventContext { block_height: 3, tx_hash: [3u8; 32], timestamp: 1010 });
+        contract.buy_nft(&nft_id, buyer.public_key(), 80.0).unwrap();
+        contract.transfer(&nft_id, buyer.public_key(), friend.public_key()).unwrap();
+        contract.burn(&nft_id, friend.public_key()).unwrap();
+
+        let kinds: Vec<NFTEventKind> = contract.history(&nft_id).iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![
+            NFTEventKind::Mint,
+            NFTEventKind::List,
+            NFTEventKind::Delist,
+            NFTEventKind::List,
+            NFTEventKind::Sale,
+            NFTEventKind::Transfer,
+            NFTEventKind::Burn,
+        ]);
+
+        let sale = contract.history(&nft_id)[4];
+        assert_eq!(sale.price, Some(80.0));
+        assert_eq!(sale.block_height, 3);
+        assert_eq!(sale.tx_hash, [3u8; 32]);
+
+        // L'acheteur apparaît dans la vente et le transfert
+        assert_eq!(contract.events_for_address(buyer.public_key()).len(), 2);
+        assert!(contract.get_nft(&nft_id).is_none());
+    }
+
+    #[test]
+    fn test_nft_marketplace_commission() {
+        let mut contract = NFTContract::new();
+        let creator = KeyPair::new();
+        let buyer = KeyPair::new();
+        let front_end = KeyPair::new();
+        let operator = KeyPair::new();
+
+        contract.set_marketplace_config(MarketplaceConfig {
+            max_fee_bps: 500,
+            default_fee: Some(MarketplaceFee { recipient: *operator.public_key(), rate_bps: 200 }),
+        }).unwrap();
+
+        let metadata = NFTMetadata {
+            name: "Tableau".to_string(),
+            description: "Tableau numérique".to_string(),
+            nft_type: NFTType::Art,
+            media_url: "ipfs://tableau".to_string(),
+            media_hash: hash_data(b"tableau"),
+            thumbnail_url: "ipfs://tableau-thumb".to_string(),
+            thumbnail_hash: None,
+            attributes: HashMap::new(),
+        };
+        let nft_id = contract.mint(*creator.public_key(), metadata.clone(), 5).unwrap();
+
+        // Commission au-delà du plafond refusée
+        let greedy = MarketplaceFee { recipient: *front_end.public_key(), rate_bps: 1000 };
+        assert!(matches!(
+            contract.list_for_sale_via(&nft_id, creator.public_key(), 100.0, None, Some(greedy)),
+            Err(NFTError::MarketplaceFeeTooHigh(500))
+        ));
+
+        // Listing via un front-end: sa commission remplace celle de l'opérateur
+        let fee = MarketplaceFee { recipient: *front_end.public_key(), rate_bps: 250 };
+        contract.list_for_sale_via(&nft_id, creator.public_key(), 100.0, None, Some(fee)).unwrap();
+        let result = contract.buy_nft(&nft_id, buyer.public_key(), 100.0).unwrap();
+        assert_eq!(result.marketplace, Some(*front_end.public_key()));
+        assert_eq!(result.marketplace_receives, 2.5);
+        assert_eq!(result.creator_receives, 5.0);
+        assert_eq!(result.seller_receives, 92.5);
+
+        // Listing direct: commission par défaut de l'opérateur
+        contract.list_for_sale(&nft_id, buyer.public_key(), 100.0, None).unwrap();
+        let result = contract.buy_nft(&nft_id, creator.public_key(), 100.0).unwrap();
+        assert_eq!(result.marketplace, Some(*operator.public_key()));
+        assert_eq!(result.marketplace_receives, 2.0);
+        assert_eq!(result.seller_receives, 93.0);
+    }
+
+    #[test]
+    fn test_nft_undo_restores_touched_entries() {
+        let mut contract = NFTContract::new();
+        let creator = KeyPair::new();
+        let friend = KeyPair::new();
+        let metadata = |name: &str| NFTMetadata {
+            name: name.to_string(),
+            description: "Annulation".to_string(),
+            nft_type: NFTType::Art,
+            media_url: format!("ipfs://{}", name),
+            media_hash: hash_data(name.as_bytes()),
+            thumbnail_url: String::new(),
+            thumbnail_hash: None,
+            attributes: HashMap::new(),
+        };
+        let kept = contract.mint(*creator.public_key(), metadata("kept"), 5).unwrap();
+
+        // Opérations d'un bloc refusé: entrée visée journalisée, puis mint
+        // d'un NFT aussitôt mis en vente
+        let events_len = contract.events().len();
+        let mut entries = HashMap::from([(kept.clone(), contract.entry(&kept))]);
+        contract.list_for_sale(&kept, creator.public_key(), 10.0, None).unwrap();
+        contract.transfer(&kept, creator.public_key(), friend.public_key()).unwrap();
+        contract.set_context(NFTEventContext { block_height: 2, tx_hash: [2u8; 32], timestamp: 10 });
+        let minted = contract.mint(*creator.public_key(), metadata("minted"), 5).unwrap();
+        entries.insert(minted.clone(), contract.entry(&minted));
+        contract.list_for_sale(&minted, creator.public_key(), 10.0, None).unwrap();
+
+        contract.undo(events_len, entries);
+        assert_eq!(contract.get_nft(&kept).unwrap().owner, *creator.public_key());
+        assert!(contract.get_listing(&kept).is_none());
+        assert!(contract.get_nft(&minted).is_none());
+        assert!(contract.get_listing(&minted).is_none());
+        assert_eq!(contract.events().len(), events_len);
+        assert!(contract.events_for_address(friend.public_key()).is_empty());
+    }
+
+    #[test]
+    fn test_nft_fees_never_exceed_payment() {
+        let mut contract = NFTContract::new();
+        let creator = KeyPair::new();
+        let buyer = KeyPair::new();
+        let front_end = KeyPair::new();
+
+        // Plafond configurable limité par les royalties maximales
+        let config = |max_fee_bps| MarketplaceConfig { max_fee_bps, default_fee: None };
+        assert!(matches!(
+            contract.set_marketplace_config(config(MAX_MARKETPLACE_FEE_BPS + 1)),
+            Err(NFTError::MarketplaceFeeTooHigh(MAX_MARKETPLACE_FEE_BPS))
+        ));
+        contract.set_marketplace_config(config(MAX_MARKETPLACE_FEE_BPS)).unwrap();
+
+        let metadata = NFTMetadata {
+            name: "Sculpture".to_string(),
+            description: "Sculpture numérique".to_string(),
+            nft_type: NFTType::Art,
+            media_url: "ipfs://sculpture".to_string(),
+            media_hash: hash_data(b"sculpture"),
+            thumbnail_url: "ipfs://sculpture-thumb".to_string(),
+            thumbnail_hash: None,
+            attributes: HashMap::new(),
+        };
+        let nft_id = contract.mint(*creator.public_key(), metadata, MAX_ROYALTY_PERCENTAGE).unwrap();
+
+        // Configuration hors plafond (état restauré): la vente est refusée
+        // plutôt que de créditer un montant négatif au vendeur
+        contract.marketplace_config = config(10_000);
+        let fee = MarketplaceFee { recipient: *front_end.public_key(), rate_bps: 9_500 };
+        contract.list_for_sale_via(&nft_id, creator.public_key(), 100.0, None, Some(fee)).unwrap();
+        assert!(matches!(
+            contract.buy_nft(&nft_id, buyer.public_key(), 100.0),
+            Err(NFTError::FeesExceedPayment)
+        ));
+        assert_eq!(contract.get_nft(&nft_id).unwrap().owner, *creator.public_key());
+        assert!(contract.get_listing(&nft_id).is_some());
+    }
+
+    #[test]
+    fn test_nft_mint_validates_metadata() {
+        let mut contract = NFTContract::new();
+        let creator = KeyPair::new();
+        let model = b"glTF binary content";
+
+        let mut metadata = NFTMetadata {
+            name: "Fontaine".to_string(),
+            description: "Fontaine 3D".to_string(),
+            nft_type: NFTType::Model3D,
+            media_url: "ipfs://fontaine".to_string(),
+            media_hash: hash_data(model),
+            thumbnail_url: "ipfs://fontaine-thumb".to_string(),
+            thumbnail_hash: None,
+            attributes: HashMap::new(),
+        };
+
+        // Attributs du schéma Model3D manquants
+        assert!(matches!(
+            contract.mint(*creator.public_key(), metadata.clone(), 5),
+            Err(NFTError::InvalidMetadata(_))
+        ));
+
+        metadata.attributes.insert("polygon_count".to_string(), "4200".to_string());
+        metadata.attributes.insert("textures".to_string(), "water.png,stone.png".to_string());
+        let nft_id = contract.mint(*creator.public_key(), metadata, 5).unwrap();
+
+        let nft = contract.get_nft(&nft_id).unwrap();
+        assert!(nft.metadata.verify_media(model));
+        assert!(!nft.metadata.verify_media(b"tampered content"));
+        assert!(!nft.metadata.verify_thumbnail(b"any thumbnail"));
+    }
+
+    #[test]
+    fn test_nft_metadata_serde() {
+        let mut contract = NFTContract::new();
+        let creator = KeyPair::new();
+
+        // Les hashes sont sérialisés en hex
+        let metadata = NFTMetadata {
+            name: "Statue".to_string(),
+            description: "Statue 3D".to_string(),
+            nft_type: NFTType::Art,
+            media_url: "ipfs://statue".to_string(),
+            media_hash: hash_data(b"media"),
+            thumbnail_url: "ipfs://statue-thumb".to_string(),
+            thumbnail_hash: Some(hash_data(b"thumb")),
+            attributes: HashMap::new(),
+        };
+        let json = serde_json::to_value(&metadata).unwrap();
+        assert_eq!(json["media_hash"], hex::encode(hash_data(b"media")));
+        assert_eq!(json["thumbnail_hash"], hex::encode(hash_data(b"thumb")));
+        let decoded: NFTMetadata = serde_json::from_value(json).unwrap();
+        assert!(decoded.verify_media(b"media"));
+        assert!(decoded.verify_thumbnail(b"thumb"));
+
+        // Des métadonnées antérieures sans hash se désérialisent, mais ne
+        // peuvent plus être mintées
+        let legacy = r#"{
+            "name": "NFT Example",
+            "description": "Un NFT d'exemple",
+            "nft_type": "Art",
+            "media_url": "https://example.com/image.png",
+            "thumbnail_url": "https://example.com/thumb.png"
+        }"#;
+        let legacy: NFTMetadata = serde_json::from_str(legacy).unwrap();
+        assert_eq!(legacy.media_hash, [0u8; 32]);
+        assert_eq!(legacy.thumbnail_hash, None);
+        assert!(matches!(
+            contract.mint(*creator.public_key(), legacy, 5),
+            Err(NFTError::InvalidMetadata(_))
+        ));
+    }
 }
 
@@ -0,0 +1,228 @@
+//! Journal des événements NFT (provenance)
+//!
+//! Historique append-only des mint, mises en vente, retraits, ventes,
+//! transferts et destructions, avec la hauteur de bloc et le hash de
+//! la transaction à l'origine de chaque événement.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use crate::{Hash, PublicKey, Amount, Timestamp};
+
+/// Type d'événement NFT
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NFTEventKind {
+    /// Création du NFT
+    Mint,
+    /// Mise en vente
+    List,
+    /// Retrait de la vente
+    Delist,
+    /// Vente sur le marché
+    Sale,
+    /// Transfert gratuit
+    Transfer,
+    /// Destruction du NFT
+    Burn,
+}
+
+/// Contexte d'exécution d'une opération NFT (bloc et transaction en cours)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct NFTEventContext {
+    /// Hauteur du bloc contenant l'opération
+    pub block_height: u64,
+    /// Hash de la transaction à l'origine de l'opération
+    pub tx_hash: Hash,
+    /// Timestamp du bloc
+    pub timestamp: Timestamp,
+}
+
+/// Événement NFT
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NFTEvent {
+    /// Type d'événement
+    pub kind: NFTEventKind,
+    /// ID du NFT concerné
+    pub nft_id: String,
+    /// Adresse d'origine (propriétaire ou vendeur)
+    pub from: Option<PublicKey>,
+    /// Adresse de destination (nouveau propriétaire)
+    pub to: Option<PublicKey>,
+    /// Prix (mise en vente ou vente)
+    pub price: Option<Amount>,
+    /// Hauteur du bloc
+    pub block_height: u64,
+    /// Hash de la transaction
+    pub tx_hash: Hash,
+    /// Timestamp de l'événement
+    pub timestamp: Timestamp,
+}
+
+/// Journal append-only des événements NFT, indexé par NFT et par adresse
+///
+/// Seuls les événements sont sérialisés; les index sont reconstruits.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(from = "Vec<NFTEvent>")]
+pub struct NFTEventLog {
+    /// Événements dans l'ordre d'insertion
+    events: Vec<NFTEvent>,
+    /// Index des événements par ID de NFT
+    by_nft: HashMap<String, Vec<usize>>,
+    /// Index des événements par adresse (from ou to)
+    by_address: HashMap<PublicKey, Vec<usize>>,
+}
+
+impl NFTEventLog {
+    /// Créer un journal vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ajouter un événement au journal
+    pub fn record(&mut self, event: NFTEvent) {
+        let index = self.events.len();
+
+        self.by_nft.entry(event.nft_id.clone()).or_default().push(index);
+
+        for address in [event.from, event.to].into_iter().flatten() {
+            let entries = self.by_address.entry(address).or_default();
+            // Éviter les doublons quand from == to
+            if entries.last() != Some(&index) {
+                entries.push(index);
+            }
+        }
+
+        self.events.push(event);
+    }
+
+    /// Historique complet d'un NFT (du plus ancien au plus récent)
+    pub fn history(&self, nft_id: &str) -> Vec<&NFTEvent> {
+        self.by_nft.get(nft_id)
+            .map(|indices| indices.iter().map(|&i| &self.events[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Événements impliquant une adresse (du plus ancien au plus récent)
+    pub fn by_address(&self, address: &PublicKey) -> Vec<&NFTEvent> {
+        self.by_address.get(address)
+            .map(|indices| indices.iter().map(|&i| &self.events[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Tous les événements
+    pub fn events(&self) -> &[NFTEvent] {
+        &self.events
+    }
+
+    /// Nombre d'événements enregistrés
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Le journal est-il vide ?
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Retirer les événements enregistrés après les `len` premiers (bloc
+    /// refusé) et les retourner, du plus ancien au plus récent
+    pub(crate) fn truncate(&mut self, len: usize) -> Vec<NFTEvent> {
+        let removed = self.events.split_off(len.min(self.events.len()));
+        for (offset, event) in removed.iter().enumerate().rev() {
+            let index = len + offset;
+            unindex(&mut self.by_nft, event.nft_id.clone(), index);
+            for address in [event.from, event.to].into_iter().flatten() {
+                unindex(&mut self.by_address, address, index);
+            }
+        }
+        removed
+    }
+}
+
+/// Retirer l'index d'un événement (le dernier de sa clé)
+fn unindex<K: std::hash::Hash + Eq>(index: &mut HashMap<K, Vec<usize>>, key: K, event: usize) {
+    if let Some(entries) = index.get_mut(&key) {
+        if entries.last() == Some(&event) {
+            entries.pop();
+        }
+        if entries.is_empty() {
+            index.remove(&key);
+        }
+    }
+}
+
+impl From<Vec<NFTEvent>> for NFTEventLog {
+    fn from(events: Vec<NFTEvent>) -> Self {
+        let mut log = Self::new();
+        for event in events {
+            log.record(event);
+        }
+        log
+    }
+}
+
+impl Serialize for NFTEventLog {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.events.serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: NFTEventKind, nft_id: &str, from: Option<PublicKey>, to: Option<PublicKey>) -> NFTEvent {
+        NFTEvent {
+            kind,
+            nft_id: nft_id.to_string(),
+            from,
+            to,
+            price: None,
+            block_height: 1,
+            tx_hash: [0u8; 32],
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_history_by_nft() {
+        let mut log = NFTEventLog::new();
+        log.record(event(NFTEventKind::Mint, "a", None, Some([1u8; 32])));
+        log.record(event(NFTEventKind::Mint, "b", None, Some([1u8; 32])));
+        log.record(event(NFTEventKind::Transfer, "a", Some([1u8; 32]), Some([2u8; 32])));
+
+        let history = log.history("a");
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].kind, NFTEventKind::Mint);
+        assert_eq!(history[1].kind, NFTEventKind::Transfer);
+        assert!(log.history("unknown").is_empty());
+    }
+
+    #[test]
+    fn test_history_by_address() {
+        let mut log = NFTEventLog::new();
+        log.record(event(NFTEventKind::Mint, "a", None, Some([1u8; 32])));
+        log.record(event(NFTEventKind::Transfer, "a", Some([1u8; 32]), Some([2u8; 32])));
+        log.record(event(NFTEventKind::Transfer, "a", Some([2u8; 32]), Some([2u8; 32])));
+
+        assert_eq!(log.by_address(&[1u8; 32]).len(), 2);
+        // L'auto-transfert ne doit apparaître qu'une fois
+        assert_eq!(log.by_address(&[2u8; 32]).len(), 2);
+        assert_eq!(log.len(), 3);
+    }
+
+    #[test]
+    fn test_truncate_restores_indexes() {
+        let mut log = NFTEventLog::new();
+        log.record(event(NFTEventKind::Mint, "a", None, Some([1u8; 32])));
+        log.record(event(NFTEventKind::Mint, "b", None, Some([1u8; 32])));
+        log.record(event(NFTEventKind::Transfer, "a", Some([1u8; 32]), Some([2u8; 32])));
+
+        let removed = log.truncate(1);
+        assert_eq!(removed.len(), 2);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log.history("a").len(), 1);
+        assert!(log.history("b").is_empty());
+        assert_eq!(log.by_address(&[1u8; 32]).len(), 1);
+        assert!(log.by_address(&[2u8; 32]).is_empty());
+    }
+}
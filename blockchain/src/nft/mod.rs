@@ -3,6 +3,10 @@
 //! Permet de créer, transférer et vendre des NFT sur la blockchain
 
 pub mod contract;
+pub mod events;
+pub mod schema;
 
-pub use contract::{NFTContract, NFT, NFTMetadata, NFTType};
+pub use contract::{NFTContract, NFT, NFTMetadata, NFTType, NFTOperation, MarketplaceFee, MarketplaceConfig};
+pub use events::{NFTEvent, NFTEventKind, NFTEventLog, NFTEventContext};
+pub use schema::{AttributeKind, AttributeSpec, validate_attributes};
 
@@ -0,0 +1,204 @@
+//! Schémas d'attributs typés par type de NFT
+//!
+//! Les attributs restent des paires clé/valeur textuelles dans `NFTMetadata`,
+//! mais chaque `NFTType` déclare les attributs attendus et leur format,
+//! vérifiés au moment du mint.
+
+use std::collections::HashMap;
+use crate::nft::contract::NFTType;
+
+/// Format attendu pour la valeur d'un attribut
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeKind {
+    /// Texte libre non vide
+    Text,
+    /// Entier dans un intervalle inclusif
+    Integer { min: i64, max: i64 },
+    /// Liste non vide d'éléments séparés par des virgules
+    List,
+    /// Coordonnées entières "x,y,z"
+    Coordinates,
+    /// Dimensions entières strictement positives "x,y,z"
+    Dimensions,
+}
+
+/// Définition d'un attribut dans un schéma
+#[derive(Debug, Clone, Copy)]
+pub struct AttributeSpec {
+    /// Nom de l'attribut
+    pub name: &'static str,
+    /// Format de la valeur
+    pub kind: AttributeKind,
+    /// L'attribut est-il obligatoire ?
+    pub required: bool,
+}
+
+const fn required(name: &'static str, kind: AttributeKind) -> AttributeSpec {
+    AttributeSpec { name, kind, required: true }
+}
+
+const fn optional(name: &'static str, kind: AttributeKind) -> AttributeSpec {
+    AttributeSpec { name, kind, required: false }
+}
+
+/// Nombre maximal de polygones d'un modèle 3D (rendu mobile)
+pub const MAX_POLYGON_COUNT: i64 = 1_000_000;
+
+/// Taille maximale d'un terrain virtuel par axe (en nœuds Luanti)
+pub const MAX_LAND_SIZE: i64 = 1_000;
+
+const MODEL_3D: &[AttributeSpec] = &[
+    required("polygon_count", AttributeKind::Integer { min: 1, max: MAX_POLYGON_COUNT }),
+    required("textures", AttributeKind::List),
+    optional("format", AttributeKind::Text),
+];
+
+const VIRTUAL_LAND: &[AttributeSpec] = &[
+    required("coordinates", AttributeKind::Coordinates),
+    required("size", AttributeKind::Dimensions),
+    optional("world", AttributeKind::Text),
+];
+
+const MEDIA: &[AttributeSpec] = &[
+    optional("duration_seconds", AttributeKind::Integer { min: 1, max: i64::MAX }),
+    optional("format", AttributeKind::Text),
+];
+
+const RENTAL_CONTRACT: &[AttributeSpec] = &[
+    required("property_id", AttributeKind::Text),
+    required("period_days", AttributeKind::Integer { min: 1, max: 3650 }),
+];
+
+const SERVICE_TICKET: &[AttributeSpec] = &[
+    required("service", AttributeKind::Text),
+    optional("valid_until", AttributeKind::Integer { min: 0, max: i64::MAX }),
+];
+
+const PRODUCT_VOUCHER: &[AttributeSpec] = &[
+    required("product_ref", AttributeKind::Text),
+    optional("quantity", AttributeKind::Integer { min: 1, max: i64::MAX }),
+];
+
+impl NFTType {
+    /// Schéma d'attributs associé à ce type de NFT
+    pub fn attribute_schema(&self) -> &'static [AttributeSpec] {
+        match self {
+            NFTType::Art => &[],
+            NFTType::Music | NFTType::Video => MEDIA,
+            NFTType::Model3D => MODEL_3D,
+            NFTType::VirtualLand => VIRTUAL_LAND,
+            NFTType::RentalContract => RENTAL_CONTRACT,
+            NFTType::ServiceTicket => SERVICE_TICKET,
+            NFTType::ProductVoucher => PRODUCT_VOUCHER,
+        }
+    }
+}
+
+/// Valider des attributs contre le schéma d'un type de NFT
+///
+/// Les attributs hors schéma sont autorisés et laissés libres.
+pub fn validate_attributes(
+    nft_type: &NFTType,
+    attributes: &HashMap<String, String>,
+) -> Result<(), String> {
+    for spec in nft_type.attribute_schema() {
+        match attributes.get(spec.name) {
+            Some(value) => validate_value(spec.kind, value)
+                .map_err(|e| format!("attribute '{}': {}", spec.name, e))?,
+            None if spec.required => {
+                return Err(format!("missing required attribute '{}'", spec.name));
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+/// Valider une valeur selon son format
+fn validate_value(kind: AttributeKind, value: &str) -> Result<(), String> {
+    match kind {
+        AttributeKind::Text => {
+            if value.trim().is_empty() {
+                return Err("must not be empty".to_string());
+            }
+        }
+        AttributeKind::Integer { min, max } => {
+            let n = parse_integer(value)?;
+            if !(min..=max).contains(&n) {
+                return Err(format!("must be between {} and {}", min, max));
+            }
+        }
+        AttributeKind::List => {
+            if value.split(',').any(|item| item.trim().is_empty()) {
+                return Err("must be a comma-separated list of non-empty items".to_string());
+            }
+        }
+        AttributeKind::Coordinates => {
+            parse_triplet(value)?;
+        }
+        AttributeKind::Dimensions => {
+            let dims = parse_triplet(value)?;
+            if dims.iter().any(|d| !(1..=MAX_LAND_SIZE).contains(d)) {
+                return Err(format!("each dimension must be between 1 and {}", MAX_LAND_SIZE));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_integer(value: &str) -> Result<i64, String> {
+    value.trim().parse::<i64>()
+        .map_err(|_| format!("'{}' is not an integer", value))
+}
+
+/// Parser un triplet "x,y,z" d'entiers
+fn parse_triplet(value: &str) -> Result<[i64; 3], String> {
+    let parts: Vec<&str> = value.split(',').collect();
+    if parts.len() != 3 {
+        return Err(format!("'{}' must have the form x,y,z", value));
+    }
+    Ok([
+        parse_integer(parts[0])?,
+        parse_integer(parts[1])?,
+        parse_integer(parts[2])?,
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attrs(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_model3d_schema() {
+        let valid = attrs(&[("polygon_count", "12000"), ("textures", "wood.png,stone.png")]);
+        assert!(validate_attributes(&NFTType::Model3D, &valid).is_ok());
+
+        let missing = attrs(&[("polygon_count", "12000")]);
+        assert!(validate_attributes(&NFTType::Model3D, &missing).is_err());
+
+        let too_many = attrs(&[("polygon_count", "5000000"), ("textures", "a.png")]);
+        assert!(validate_attributes(&NFTType::Model3D, &too_many).is_err());
+    }
+
+    #[test]
+    fn test_virtual_land_schema() {
+        let valid = attrs(&[("coordinates", "-120,8,340"), ("size", "16,32,16")]);
+        assert!(validate_attributes(&NFTType::VirtualLand, &valid).is_ok());
+
+        let bad_coords = attrs(&[("coordinates", "12,8"), ("size", "16,32,16")]);
+        assert!(validate_attributes(&NFTType::VirtualLand, &bad_coords).is_err());
+
+        let empty_size = attrs(&[("coordinates", "0,0,0"), ("size", "16,0,16")]);
+        assert!(validate_attributes(&NFTType::VirtualLand, &empty_size).is_err());
+    }
+
+    #[test]
+    fn test_art_accepts_free_attributes() {
+        let free = attrs(&[("artist_note", "hello")]);
+        assert!(validate_attributes(&NFTType::Art, &free).is_ok());
+    }
+}
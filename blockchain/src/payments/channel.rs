@@ -0,0 +1,500 @@
+//! Canaux de paiement unidirectionnels (micropaiements en jeu)
+//!
+//! Le payeur bloque un dépôt on-chain puis signe hors chaîne des mises à
+//! jour cumulatives (« montant total dû au bénéficiaire ») à chaque
+//! téléportation ou minute de location. Seule la clôture repasse par la
+//! chaîne:
+//!
+//! - le bénéficiaire clôture à tout moment avec la dernière mise à jour
+//!   signée: règlement immédiat;
+//! - le payeur peut clôturer unilatéralement: le règlement n'a lieu qu'après
+//!   la période de contestation, pendant laquelle le bénéficiaire peut encore
+//!   présenter une mise à jour plus récente.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{Hash, PublicKey, PrivateKey, Signature, Amount, Timestamp};
+use crate::crypto::{hash_data, sign, verify_signature};
+use crate::storage::snapshot::sorted_map;
+
+/// Période de contestation par défaut (1 jour)
+pub const DEFAULT_DISPUTE_PERIOD_SECS: i64 = 86400;
+
+/// Période de contestation minimale (1 heure)
+pub const MIN_DISPUTE_PERIOD_SECS: i64 = 3600;
+
+/// Période de contestation maximale (30 jours)
+pub const MAX_DISPUTE_PERIOD_SECS: i64 = 30 * 86400;
+
+/// Domaine de signature des mises à jour (évite toute confusion avec une transaction)
+const UPDATE_DOMAIN: &[u8] = b"mahala-channel-update";
+
+/// État d'un canal
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ChannelStatus {
+    /// Canal ouvert, mises à jour hors chaîne en cours
+    Open,
+    /// Clôture unilatérale du payeur, période de contestation en cours
+    Closing,
+    /// Canal réglé
+    Closed,
+}
+
+/// Canal de paiement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentChannel {
+    /// ID du canal (hash de la transaction d'ouverture)
+    pub id: Hash,
+    /// Payeur (signe les mises à jour)
+    pub payer: PublicKey,
+    /// Bénéficiaire
+    pub payee: PublicKey,
+    /// Dépôt bloqué
+    pub deposit: Amount,
+    /// Période de contestation (secondes)
+    pub dispute_period_secs: i64,
+    /// Date d'ouverture (timestamp du bloc)
+    pub opened_at: Timestamp,
+    /// État courant
+    pub status: ChannelStatus,
+    /// Montant dû au bénéficiaire selon la dernière mise à jour présentée
+    pub paid: Amount,
+    /// Nonce de la dernière mise à jour présentée
+    pub nonce: u64,
+    /// Date de règlement d'une clôture unilatérale
+    pub settle_at: Option<Timestamp>,
+}
+
+/// Conditions d'un canal choisies à l'ouverture
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelTerms {
+    /// Période de contestation (défaut: 1 jour)
+    #[serde(default)]
+    pub dispute_period_secs: Option<i64>,
+}
+
+/// Mise à jour de solde hors chaîne
+///
+/// `paid` est cumulatif: chaque mise à jour remplace les précédentes et
+/// porte un nonce strictement croissant.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct BalanceUpdate {
+    /// ID du canal
+    pub channel_id: Hash,
+    /// Numéro de séquence
+    pub nonce: u64,
+    /// Montant total dû au bénéficiaire
+    pub paid: Amount,
+}
+
+impl BalanceUpdate {
+    /// Hash signé par le payeur
+    pub fn signing_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(UPDATE_DOMAIN.len() + 48);
+        data.extend_from_slice(UPDATE_DOMAIN);
+        data.extend_from_slice(&self.channel_id);
+        data.extend_from_slice(&self.nonce.to_le_bytes());
+        data.extend_from_slice(&self.paid.to_bits().to_le_bytes());
+        hash_data(&data)
+    }
+
+    /// Signer la mise à jour avec la clé du payeur
+    pub fn sign(self, private_key: &PrivateKey) -> SignedBalanceUpdate {
+        let signature = sign(&self.signing_hash(), private_key);
+        SignedBalanceUpdate {
+            update: self,
+            signature: hex::encode(signature),
+        }
+    }
+}
+
+/// Mise à jour signée par le payeur (signature en hex)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignedBalanceUpdate {
+    /// Mise à jour
+    pub update: BalanceUpdate,
+    /// Signature Ed25519 du payeur (hex)
+    pub signature: String,
+}
+
+impl SignedBalanceUpdate {
+    /// Vérifier la signature contre la clé du payeur
+    pub fn verify(&self, payer: &PublicKey) -> bool {
+        let Ok(bytes) = hex::decode(&self.signature) else {
+            return false;
+        };
+        let Ok(signature) = Signature::try_from(bytes) else {
+            return false;
+        };
+        verify_signature(&self.update.signing_hash(), &signature, payer)
+    }
+}
+
+/// Opération de canal portée par une transaction `PaymentChannel`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ChannelOperation {
+    /// Ouvrir un canal (payeur): le montant de la transaction est le dépôt
+    /// et `to` le bénéficiaire
+    Open(ChannelTerms),
+    /// Compléter le dépôt (payeur, canal ouvert)
+    TopUp { channel_id: Hash },
+    /// Clôturer avec la dernière mise à jour (bénéficiaire): règlement immédiat
+    Close { update: SignedBalanceUpdate },
+    /// Clôture unilatérale (payeur), avec éventuellement sa dernière mise à jour
+    StartClose { channel_id: Hash, update: Option<SignedBalanceUpdate> },
+}
+
+/// Règlement d'un canal
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelSettlement {
+    /// Bénéficiaire et montant reçu
+    pub payee: (PublicKey, Amount),
+    /// Payeur et reliquat du dépôt restitué
+    pub payer: (PublicKey, Amount),
+}
+
+/// Erreurs de canal
+#[derive(Debug, Error)]
+pub enum ChannelError {
+    #[error("Channel not found")]
+    NotFound,
+    #[error("Channel already exists")]
+    AlreadyExists,
+    #[error("Not authorized for this channel")]
+    NotAuthorized,
+    #[error("Invalid channel state: {0:?}")]
+    InvalidState(ChannelStatus),
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid dispute period")]
+    InvalidDisputePeriod,
+    #[error("Invalid balance update signature")]
+    InvalidSignature,
+    #[error("Balance update is for another channel")]
+    WrongChannel,
+    #[error("Stale balance update (nonce {0})")]
+    StaleUpdate(u64),
+    #[error("Balance update exceeds deposit")]
+    ExceedsDeposit,
+    #[error("Payer and payee must differ")]
+    SelfPayment,
+}
+
+impl PaymentChannel {
+    /// Valider une mise à jour signée pour ce canal
+    ///
+    /// Une mise à jour est acceptée si elle est signée par la clé courante du
+    /// payeur (`payer_key`, qui diffère de `payer` après une rotation), reste
+    /// dans le dépôt et ne recule ni le nonce ni le montant dû.
+    pub fn check_update(&self, signed: &SignedBalanceUpdate, payer_key: &PublicKey) -> Result<(), ChannelError> {
+        let update = &signed.update;
+        if update.channel_id != self.id {
+            return Err(ChannelError::WrongChannel);
+        }
+        if !signed.verify(payer_key) {
+            return Err(ChannelError::InvalidSignature);
+        }
+        if !(0.0..=self.deposit).contains(&update.paid) {
+            return Err(ChannelError::ExceedsDeposit);
+        }
+        if update.nonce < self.nonce || update.paid < self.paid {
+            return Err(ChannelError::StaleUpdate(update.nonce));
+        }
+        Ok(())
+    }
+
+    /// Répartition du dépôt selon le dernier montant dû
+    fn settle(&mut self) -> ChannelSettlement {
+        self.status = ChannelStatus::Closed;
+        ChannelSettlement {
+            payee: (self.payee, self.paid),
+            payer: (self.payer, self.deposit - self.paid),
+        }
+    }
+}
+
+/// Registre des canaux de paiement
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChannelRegistry {
+    #[serde(with = "sorted_map")]
+    channels: HashMap<Hash, PaymentChannel>,
+}
+
+impl ChannelRegistry {
+    /// Créer un registre vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ouvrir un canal
+    pub fn open(
+        &mut self,
+        id: Hash,
+        payer: PublicKey,
+        payee: PublicKey,
+        deposit: Amount,
+        terms: ChannelTerms,
+        now: Timestamp,
+    ) -> Result<&PaymentChannel, ChannelError> {
+        if self.channels.contains_key(&id) {
+            return Err(ChannelError::AlreadyExists);
+        }
+
+        if payer == payee {
+            return Err(ChannelError::SelfPayment);
+        }
+
+        if deposit <= 0.0 {
+            return Err(ChannelError::InvalidAmount);
+        }
+
+        let dispute_period = terms.dispute_period_secs.unwrap_or(DEFAULT_DISPUTE_PERIOD_SECS);
+        if !(MIN_DISPUTE_PERIOD_SECS..=MAX_DISPUTE_PERIOD_SECS).contains(&dispute_period) {
+            return Err(ChannelError::InvalidDisputePeriod);
+        }
+
+        let channel = PaymentChannel {
+            id,
+            payer,
+            payee,
+            deposit,
+            dispute_period_secs: dispute_period,
+            opened_at: now,
+            status: ChannelStatus::Open,
+            paid: 0.0,
+            nonce: 0,
+            settle_at: None,
+        };
+
+        Ok(self.channels.entry(id).or_insert(channel))
+    }
+
+    /// Compléter le dépôt d'un canal ouvert
+    pub fn top_up(&mut self, id: &Hash, caller: &PublicKey, amount: Amount) -> Result<(), ChannelError> {
+        let channel = self.channels.get_mut(id)
+            .ok_or(ChannelError::NotFound)?;
+
+        if caller != &channel.payer {
+            return Err(ChannelError::NotAuthorized);
+        }
+
+        if channel.status != ChannelStatus::Open {
+            return Err(ChannelError::InvalidState(channel.status));
+        }
+
+        if amount <= 0.0 {
+            return Err(ChannelError::InvalidAmount);
+        }
+
+        channel.deposit += amount;
+        Ok(())
+    }
+
+    /// Clôturer par le bénéficiaire avec une mise à jour signée (règlement immédiat)
+    ///
+    /// Possible aussi pendant la période de contestation d'une clôture unilatérale.
+    pub fn close(
+        &mut self,
+        caller: &PublicKey,
+        signed: &SignedBalanceUpdate,
+        payer_key: &PublicKey,
+    ) -> Result<ChannelSettlement, ChannelError> {
+        let channel = self.channels.get_mut(&signed.update.channel_id)
+            .ok_or(ChannelError::NotFound)?;
+
+        if caller != &channel.payee {
+            return Err(ChannelError::NotAuthorized);
+        }
+
+        if channel.status == ChannelStatus::Closed {
+            return Err(ChannelError::InvalidState(channel.status));
+        }
+
+        channel.check_update(signed, payer_key)?;
+        channel.paid = signed.update.paid;
+        channel.nonce = signed.update.nonce;
+        Ok(channel.settle())
+    }
+
+    /// Démarrer une clôture unilatérale par le payeur
+    pub fn start_close(
+        &mut self,
+        id: &Hash,
+        caller: &PublicKey,
+        signed: Option<&SignedBalanceUpdate>,
+        payer_key: &PublicKey,
+        now: Timestamp,
+    ) -> Result<Timestamp, ChannelError> {
+        let channel = self.channels.get_mut(id)
+            .ok_or(ChannelError::NotFound)?;
+
+        if caller != &channel.payer {
+            return Err(ChannelError::NotAuthorized);
+        }
+
+        if channel.status != ChannelStatus::Open {
+            return Err(ChannelError::InvalidState(channel.status));
+        }
+
+        if let Some(signed) = signed {
+            channel.check_update(signed, payer_key)?;
+            channel.paid = signed.update.paid;
+            channel.nonce = signed.update.nonce;
+        }
+
+        let settle_at = now + channel.dispute_period_secs;
+        channel.status = ChannelStatus::Closing;
+        channel.settle_at = Some(settle_at);
+        Ok(settle_at)
+    }
+
+    /// Régler les clôtures unilatérales dont la période de contestation est écoulée
+    ///
+    /// Appelé à chaque application de bloc; l'ordre est déterministe (par ID).
+    pub fn settle_expired(&mut self, now: Timestamp) -> Vec<(Hash, ChannelSettlement)> {
+        let mut expired: Vec<&mut PaymentChannel> = self.channels
+            .values_mut()
+            .filter(|c| c.status == ChannelStatus::Closing && c.settle_at.is_some_and(|t| now >= t))
+            .collect();
+        expired.sort_by_key(|c| c.id);
+
+        expired
+            .into_iter()
+            .map(|channel| (channel.id, channel.settle()))
+            .collect()
+    }
+
+    /// Obtenir un canal
+    pub fn get(&self, id: &Hash) -> Option<&PaymentChannel> {
+        self.channels.get(id)
+    }
+
+    /// Canaux impliquant une adresse (payeur ou bénéficiaire)
+    pub fn by_participant(&self, address: &PublicKey) -> Vec<&PaymentChannel> {
+        let mut channels: Vec<&PaymentChannel> = self.channels
+            .values()
+            .filter(|c| &c.payer == address || &c.payee == address)
+            .collect();
+        channels.sort_by_key(|c| (c.opened_at, c.id));
+        channels
+    }
+}
+
+/// Côté payeur d'un canal: produit les mises à jour signées successives
+#[derive(Debug, Clone)]
+pub struct ChannelSender {
+    channel_id: Hash,
+    deposit: Amount,
+    nonce: u64,
+    paid: Amount,
+}
+
+impl ChannelSender {
+    /// Suivre un canal ouvert avec son dépôt
+    pub fn new(channel_id: Hash, deposit: Amount) -> Self {
+        Self { channel_id, deposit, nonce: 0, paid: 0.0 }
+    }
+
+    /// Payer `amount` de plus et signer la nouvelle mise à jour
+    pub fn pay(&mut self, amount: Amount, private_key: &PrivateKey) -> Result<SignedBalanceUpdate, ChannelError> {
+        if amount <= 0.0 {
+            return Err(ChannelError::InvalidAmount);
+        }
+        if self.paid + amount > self.deposit {
+            return Err(ChannelError::ExceedsDeposit);
+        }
+
+        self.nonce += 1;
+        self.paid += amount;
+        Ok(BalanceUpdate {
+            channel_id: self.channel_id,
+            nonce: self.nonce,
+            paid: self.paid,
+        }
+        .sign(private_key))
+    }
+
+    /// Montant total déjà payé
+    pub fn paid(&self) -> Amount {
+        self.paid
+    }
+
+    /// Montant encore disponible dans le canal
+    pub fn remaining(&self) -> Amount {
+        self.deposit - self.paid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+
+    const PAYEE: PublicKey = [2u8; 32];
+
+    fn open(registry: &mut ChannelRegistry, payer: &KeyPair) -> Hash {
+        let id = [9u8; 32];
+        registry.open(id, *payer.public_key(), PAYEE, 10.0, ChannelTerms::default(), 1000).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_sender_updates_and_cooperative_close() {
+        let payer = KeyPair::new();
+        let mut registry = ChannelRegistry::new();
+        let id = open(&mut registry, &payer);
+
+        let mut sender = ChannelSender::new(id, 10.0);
+        let first = sender.pay(0.5, payer.private_key()).unwrap();
+        let latest = sender.pay(1.5, payer.private_key()).unwrap();
+        assert!(sender.pay(9.0, payer.private_key()).is_err());
+        assert!(registry.get(&id).unwrap().check_update(&first, payer.public_key()).is_ok());
+
+        // Une mise à jour signée par un autre que le payeur est refusée
+        let forged = BalanceUpdate { channel_id: id, nonce: 3, paid: 10.0 }.sign(KeyPair::new().private_key());
+        assert!(matches!(registry.close(&PAYEE, &forged, payer.public_key()), Err(ChannelError::InvalidSignature)));
+        assert!(matches!(registry.close(payer.public_key(), &latest, payer.public_key()), Err(ChannelError::NotAuthorized)));
+
+        let settlement = registry.close(&PAYEE, &latest, payer.public_key()).unwrap();
+        assert_eq!(settlement.payee, (PAYEE, 2.0));
+        assert_eq!(settlement.payer, (*payer.public_key(), 8.0));
+        assert_eq!(registry.get(&id).unwrap().status, ChannelStatus::Closed);
+    }
+
+    #[test]
+    fn test_unilateral_close_and_challenge() {
+        let payer = KeyPair::new();
+        let mut registry = ChannelRegistry::new();
+        let id = open(&mut registry, &payer);
+
+        let mut sender = ChannelSender::new(id, 10.0);
+        let stale = sender.pay(1.0, payer.private_key()).unwrap();
+        let latest = sender.pay(3.0, payer.private_key()).unwrap();
+
+        // Le payeur clôture avec une mise à jour périmée
+        let settle_at = registry.start_close(&id, payer.public_key(), Some(&stale), payer.public_key(), 2000).unwrap();
+        assert_eq!(settle_at, 2000 + DEFAULT_DISPUTE_PERIOD_SECS);
+        assert!(registry.settle_expired(settle_at - 1).is_empty());
+
+        // Le bénéficiaire conteste avec la plus récente pendant la période
+        let settlement = registry.close(&PAYEE, &latest, payer.public_key()).unwrap();
+        assert_eq!(settlement.payee, (PAYEE, 4.0));
+        assert!(registry.settle_expired(settle_at).is_empty());
+    }
+
+    #[test]
+    fn test_unilateral_close_settles_after_dispute_period() {
+        let payer = KeyPair::new();
+        let mut registry = ChannelRegistry::new();
+        let id = open(&mut registry, &payer);
+
+        let settle_at = registry.start_close(&id, payer.public_key(), None, payer.public_key(), 2000).unwrap();
+        assert!(registry.top_up(&id, payer.public_key(), 5.0).is_err());
+
+        let settled = registry.settle_expired(settle_at);
+        assert_eq!(settled.len(), 1);
+        assert_eq!(settled[0].1.payer, (*payer.public_key(), 10.0));
+        assert_eq!(registry.get(&id).unwrap().status, ChannelStatus::Closed);
+    }
+}
@@ -0,0 +1,376 @@
+//! Séquestre (escrow) pour les achats en boutique
+//!
+//! Le paiement de l'acheteur est bloqué jusqu'à confirmation de livraison,
+//! expiration du délai, remboursement par le marchand ou décision d'un
+//! arbitre en cas de litige. Chaque transition est appliquée par la chaîne.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{Hash, PublicKey, Amount, Timestamp};
+use crate::storage::snapshot::sorted_map;
+
+/// Délai de libération par défaut (14 jours)
+pub const DEFAULT_ESCROW_TIMEOUT_SECS: i64 = 14 * 86400;
+
+/// Délai de libération minimal (1 heure)
+pub const MIN_ESCROW_TIMEOUT_SECS: i64 = 3600;
+
+/// Délai de libération maximal (90 jours)
+pub const MAX_ESCROW_TIMEOUT_SECS: i64 = 90 * 86400;
+
+/// État d'un séquestre
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EscrowStatus {
+    /// Fonds bloqués, en attente de livraison
+    Funded,
+    /// Litige ouvert, en attente de l'arbitre
+    Disputed,
+    /// Fonds versés au marchand (confirmation ou expiration)
+    Released,
+    /// Fonds rendus à l'acheteur par le marchand
+    Refunded,
+    /// Litige tranché par l'arbitre
+    Resolved,
+}
+
+impl EscrowStatus {
+    /// Le séquestre est-il clôturé ?
+    pub fn is_final(&self) -> bool {
+        matches!(self, Self::Released | Self::Refunded | Self::Resolved)
+    }
+}
+
+/// Séquestre d'un achat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Escrow {
+    /// ID du séquestre (hash de la transaction d'ouverture)
+    pub id: Hash,
+    /// Acheteur
+    pub buyer: PublicKey,
+    /// Marchand
+    pub merchant: PublicKey,
+    /// Arbitre désigné pour les litiges
+    #[serde(deserialize_with = "crate::wallet::address::deserialize")]
+    pub arbiter: PublicKey,
+    /// Montant bloqué
+    pub amount: Amount,
+    /// Référence de commande (ex: commande Dolibarr)
+    pub order_ref: Option<String>,
+    /// Date d'ouverture (timestamp du bloc)
+    pub created_at: Timestamp,
+    /// Date de libération automatique au marchand
+    pub release_at: Timestamp,
+    /// État courant
+    pub status: EscrowStatus,
+    /// Partie ayant ouvert le litige
+    pub disputed_by: Option<PublicKey>,
+}
+
+/// Conditions d'un séquestre choisies à l'ouverture
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscrowTerms {
+    /// Arbitre désigné pour les litiges
+    pub arbiter: PublicKey,
+    /// Délai avant libération automatique (défaut: 14 jours)
+    #[serde(default)]
+    pub timeout_secs: Option<i64>,
+    /// Référence de commande (ex: commande Dolibarr)
+    #[serde(default)]
+    pub order_ref: Option<String>,
+}
+
+/// Opération de séquestre portée par une transaction
+///
+/// `Open` est portée par une transaction `ShopPurchase` (le montant est bloqué
+/// et `to` est le marchand); les autres par une transaction `Escrow`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum EscrowOperation {
+    /// Ouvrir un séquestre
+    Open(EscrowTerms),
+    /// Confirmer la livraison (acheteur)
+    Confirm { escrow_id: Hash },
+    /// Rembourser l'acheteur (marchand)
+    Refund { escrow_id: Hash },
+    /// Ouvrir un litige (acheteur ou marchand)
+    Dispute { escrow_id: Hash },
+    /// Trancher un litige (arbitre): part versée au marchand, le reste à l'acheteur
+    Resolve { escrow_id: Hash, merchant_amount: Amount },
+}
+
+/// Versement issu d'une transition de séquestre
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EscrowPayout {
+    /// Bénéficiaire
+    pub to: PublicKey,
+    /// Montant versé
+    pub amount: Amount,
+}
+
+/// Erreurs de séquestre
+#[derive(Debug, Error)]
+pub enum EscrowError {
+    #[error("Escrow not found")]
+    NotFound,
+    #[error("Escrow already exists")]
+    AlreadyExists,
+    #[error("Not authorized for this escrow")]
+    NotAuthorized,
+    #[error("Invalid escrow state: {0:?}")]
+    InvalidState(EscrowStatus),
+    #[error("Escrow release time has passed")]
+    Expired,
+    #[error("Invalid escrow timeout")]
+    InvalidTimeout,
+    #[error("Invalid arbiter")]
+    InvalidArbiter,
+    #[error("Invalid amount")]
+    InvalidAmount,
+}
+
+/// Registre des séquestres
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EscrowRegistry {
+    #[serde(with = "sorted_map")]
+    escrows: HashMap<Hash, Escrow>,
+}
+
+impl EscrowRegistry {
+    /// Créer un registre vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ouvrir un séquestre
+    pub fn open(
+        &mut self,
+        id: Hash,
+        buyer: PublicKey,
+        merchant: PublicKey,
+        amount: Amount,
+        terms: EscrowTerms,
+        now: Timestamp,
+    ) -> Result<&Escrow, EscrowError> {
+        if self.escrows.contains_key(&id) {
+            return Err(EscrowError::AlreadyExists);
+        }
+
+        if amount <= 0.0 {
+            return Err(EscrowError::InvalidAmount);
+        }
+
+        if terms.arbiter == buyer || terms.arbiter == merchant {
+            return Err(EscrowError::InvalidArbiter);
+        }
+
+        let timeout = terms.timeout_secs.unwrap_or(DEFAULT_ESCROW_TIMEOUT_SECS);
+        if !(MIN_ESCROW_TIMEOUT_SECS..=MAX_ESCROW_TIMEOUT_SECS).contains(&timeout) {
+            return Err(EscrowError::InvalidTimeout);
+        }
+
+        let escrow = Escrow {
+            id,
+            buyer,
+            merchant,
+            arbiter: terms.arbiter,
+            amount,
+            order_ref: terms.order_ref,
+            created_at: now,
+            release_at: now + timeout,
+            status: EscrowStatus::Funded,
+            disputed_by: None,
+        };
+
+        Ok(self.escrows.entry(id).or_insert(escrow))
+    }
+
+    /// Confirmer la livraison: les fonds vont au marchand
+    pub fn confirm(&mut self, id: &Hash, caller: &PublicKey) -> Result<EscrowPayout, EscrowError> {
+        let escrow = self.funded_mut(id)?;
+
+        if caller != &escrow.buyer {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        escrow.status = EscrowStatus::Released;
+        Ok(EscrowPayout { to: escrow.merchant, amount: escrow.amount })
+    }
+
+    /// Rembourser l'acheteur (à l'initiative du marchand, y compris en litige)
+    pub fn refund(&mut self, id: &Hash, caller: &PublicKey) -> Result<EscrowPayout, EscrowError> {
+        let escrow = self.escrows.get_mut(id)
+            .ok_or(EscrowError::NotFound)?;
+
+        if caller != &escrow.merchant {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if escrow.status.is_final() {
+            return Err(EscrowError::InvalidState(escrow.status));
+        }
+
+        escrow.status = EscrowStatus::Refunded;
+        Ok(EscrowPayout { to: escrow.buyer, amount: escrow.amount })
+    }
+
+    /// Ouvrir un litige (avant la libération automatique)
+    pub fn dispute(&mut self, id: &Hash, caller: &PublicKey, now: Timestamp) -> Result<(), EscrowError> {
+        let escrow = self.funded_mut(id)?;
+
+        if caller != &escrow.buyer && caller != &escrow.merchant {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if now >= escrow.release_at {
+            return Err(EscrowError::Expired);
+        }
+
+        escrow.status = EscrowStatus::Disputed;
+        escrow.disputed_by = Some(*caller);
+        Ok(())
+    }
+
+    /// Trancher un litige: l'arbitre répartit les fonds
+    pub fn resolve(
+        &mut self,
+        id: &Hash,
+        caller: &PublicKey,
+        merchant_amount: Amount,
+    ) -> Result<Vec<EscrowPayout>, EscrowError> {
+        let escrow = self.escrows.get_mut(id)
+            .ok_or(EscrowError::NotFound)?;
+
+        if caller != &escrow.arbiter {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if escrow.status != EscrowStatus::Disputed {
+            return Err(EscrowError::InvalidState(escrow.status));
+        }
+
+        if !(0.0..=escrow.amount).contains(&merchant_amount) {
+            return Err(EscrowError::InvalidAmount);
+        }
+
+        escrow.status = EscrowStatus::Resolved;
+
+        let buyer_amount = escrow.amount - merchant_amount;
+        Ok([
+            EscrowPayout { to: escrow.merchant, amount: merchant_amount },
+            EscrowPayout { to: escrow.buyer, amount: buyer_amount },
+        ]
+        .into_iter()
+        .filter(|payout| payout.amount > 0.0)
+        .collect())
+    }
+
+    /// Libérer au marchand les séquestres non contestés arrivés à échéance
+    ///
+    /// Appelé à chaque application de bloc; l'ordre est déterministe (par ID).
+    pub fn release_expired(&mut self, now: Timestamp) -> Vec<(Hash, EscrowPayout)> {
+        let mut expired: Vec<&mut Escrow> = self.escrows
+            .values_mut()
+            .filter(|e| e.status == EscrowStatus::Funded && now >= e.release_at)
+            .collect();
+        expired.sort_by_key(|e| e.id);
+
+        expired
+            .into_iter()
+            .map(|escrow| {
+                escrow.status = EscrowStatus::Released;
+                (escrow.id, EscrowPayout { to: escrow.merchant, amount: escrow.amount })
+            })
+            .collect()
+    }
+
+    /// Obtenir un séquestre
+    pub fn get(&self, id: &Hash) -> Option<&Escrow> {
+        self.escrows.get(id)
+    }
+
+    /// Séquestres impliquant une adresse (acheteur, marchand ou arbitre)
+    pub fn by_participant(&self, address: &PublicKey) -> Vec<&Escrow> {
+        let mut escrows: Vec<&Escrow> = self.escrows
+            .values()
+            .filter(|e| &e.buyer == address || &e.merchant == address || &e.arbiter == address)
+            .collect();
+        escrows.sort_by_key(|e| e.created_at);
+        escrows
+    }
+
+    /// Séquestre en attente de livraison (état `Funded`)
+    fn funded_mut(&mut self, id: &Hash) -> Result<&mut Escrow, EscrowError> {
+        let escrow = self.escrows.get_mut(id)
+            .ok_or(EscrowError::NotFound)?;
+
+        if escrow.status != EscrowStatus::Funded {
+            return Err(EscrowError::InvalidState(escrow.status));
+        }
+
+        Ok(escrow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUYER: PublicKey = [1u8; 32];
+    const MERCHANT: PublicKey = [2u8; 32];
+    const ARBITER: PublicKey = [3u8; 32];
+
+    fn open(registry: &mut EscrowRegistry, id: u8) -> Hash {
+        let id = [id; 32];
+        let terms = EscrowTerms {
+            arbiter: ARBITER,
+            timeout_secs: None,
+            order_ref: Some("CMD-42".to_string()),
+        };
+        registry.open(id, BUYER, MERCHANT, 50.0, terms, 1000).unwrap();
+        id
+    }
+
+    #[test]
+    fn test_confirm_releases_to_merchant() {
+        let mut registry = EscrowRegistry::new();
+        let id = open(&mut registry, 1);
+
+        assert!(matches!(registry.confirm(&id, &MERCHANT), Err(EscrowError::NotAuthorized)));
+        let payout = registry.confirm(&id, &BUYER).unwrap();
+        assert_eq!(payout, EscrowPayout { to: MERCHANT, amount: 50.0 });
+        assert!(registry.refund(&id, &MERCHANT).is_err());
+    }
+
+    #[test]
+    fn test_timeout_release_skips_disputes() {
+        let mut registry = EscrowRegistry::new();
+        let released = open(&mut registry, 1);
+        let disputed = open(&mut registry, 2);
+        registry.dispute(&disputed, &BUYER, 2000).unwrap();
+
+        let release_at = 1000 + DEFAULT_ESCROW_TIMEOUT_SECS;
+        assert!(registry.release_expired(release_at - 1).is_empty());
+
+        let payouts = registry.release_expired(release_at);
+        assert_eq!(payouts, vec![(released, EscrowPayout { to: MERCHANT, amount: 50.0 })]);
+        assert_eq!(registry.get(&disputed).unwrap().status, EscrowStatus::Disputed);
+    }
+
+    #[test]
+    fn test_dispute_resolved_by_arbiter() {
+        let mut registry = EscrowRegistry::new();
+        let id = open(&mut registry, 1);
+        registry.dispute(&id, &MERCHANT, 2000).unwrap();
+
+        assert!(registry.confirm(&id, &BUYER).is_err());
+        assert!(matches!(registry.resolve(&id, &BUYER, 0.0), Err(EscrowError::NotAuthorized)));
+        assert!(matches!(registry.resolve(&id, &ARBITER, 60.0), Err(EscrowError::InvalidAmount)));
+
+        let payouts = registry.resolve(&id, &ARBITER, 20.0).unwrap();
+        assert_eq!(payouts, vec![
+            EscrowPayout { to: MERCHANT, amount: 20.0 },
+            EscrowPayout { to: BUYER, amount: 30.0 },
+        ]);
+        assert_eq!(registry.get(&id).unwrap().status, EscrowStatus::Resolved);
+    }
+}
@@ -0,0 +1,19 @@
+//! Paiements conditionnels pour le commerce Mahala
+//!
+//! Séquestre des achats en boutique (livraison physique via Dolibarr),
+//! ordres permanents (locations, abonnements) et canaux de paiement
+//! (micropaiements en jeu)
+
+pub mod channel;
+pub mod escrow;
+pub mod standing_order;
+
+pub use channel::{
+    BalanceUpdate, ChannelOperation, ChannelRegistry, ChannelSender, ChannelStatus, ChannelTerms,
+    PaymentChannel, SignedBalanceUpdate,
+};
+pub use escrow::{Escrow, EscrowOperation, EscrowRegistry, EscrowStatus, EscrowTerms};
+pub use standing_order::{
+    StandingOrder, StandingOrderEvent, StandingOrderEventKind, StandingOrderOperation,
+    StandingOrderRegistry, StandingOrderStatus, StandingOrderTerms,
+};
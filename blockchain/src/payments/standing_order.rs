@@ -0,0 +1,448 @@
+//! Ordres permanents (paiements récurrents et programmés)
+//!
+//! Le payeur autorise une fois un montant, une période, un nombre
+//! d'échéances ou une date de fin, et un bénéficiaire. La chaîne exécute
+//! chaque échéance de façon déterministe lors de l'application des blocs.
+//! Une échéance impayée faute de fonds est consignée comme événement sans
+//! faire échouer le bloc.
+
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{Hash, PublicKey, Amount, Timestamp};
+use crate::storage::snapshot::sorted_map;
+
+/// Période minimale entre deux échéances (1 heure)
+pub const MIN_STANDING_ORDER_PERIOD_SECS: i64 = 3600;
+
+/// État d'un ordre permanent
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StandingOrderStatus {
+    /// Échéances en cours
+    Active,
+    /// Nombre d'échéances ou date de fin atteint
+    Completed,
+    /// Annulé par le payeur ou le bénéficiaire
+    Cancelled,
+}
+
+/// Ordre permanent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingOrder {
+    /// ID de l'ordre (hash de la transaction de création)
+    pub id: Hash,
+    /// Payeur
+    pub payer: PublicKey,
+    /// Bénéficiaire
+    pub payee: PublicKey,
+    /// Montant de chaque échéance
+    pub amount: Amount,
+    /// Période entre deux échéances (secondes)
+    pub period_secs: i64,
+    /// Nombre maximal d'échéances
+    pub max_payments: Option<u32>,
+    /// Date après laquelle plus aucune échéance n'est exécutée
+    pub end_at: Option<Timestamp>,
+    /// Référence libre (ex: emplacement loué, abonnement Dolibarr)
+    pub reference: Option<String>,
+    /// Date de création (timestamp du bloc)
+    pub created_at: Timestamp,
+    /// Prochaine échéance
+    pub next_due: Timestamp,
+    /// Échéances payées
+    pub payments_made: u32,
+    /// Échéances impayées (fonds insuffisants)
+    pub payments_failed: u32,
+    /// État courant
+    pub status: StandingOrderStatus,
+    /// Partie ayant annulé l'ordre
+    pub cancelled_by: Option<PublicKey>,
+}
+
+impl StandingOrder {
+    /// Nombre d'échéances échues (payées ou non)
+    pub fn payments_due(&self) -> u32 {
+        self.payments_made + self.payments_failed
+    }
+
+    /// Toutes les échéances autorisées ont-elles été atteintes ?
+    fn is_exhausted(&self) -> bool {
+        self.max_payments.is_some_and(|max| self.payments_due() >= max)
+            || self.end_at.is_some_and(|end| self.next_due > end)
+    }
+}
+
+/// Conditions d'un ordre permanent choisies à la création
+///
+/// `max_payments` ou `end_at` doit être renseigné.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingOrderTerms {
+    /// Montant de chaque échéance
+    pub amount: Amount,
+    /// Période entre deux échéances (secondes)
+    pub period_secs: i64,
+    /// Date de la première échéance (défaut: bloc de création)
+    #[serde(default)]
+    pub start_at: Option<Timestamp>,
+    /// Nombre maximal d'échéances
+    #[serde(default)]
+    pub max_payments: Option<u32>,
+    /// Date après laquelle plus aucune échéance n'est exécutée
+    #[serde(default)]
+    pub end_at: Option<Timestamp>,
+    /// Référence libre (ex: emplacement loué, abonnement Dolibarr)
+    #[serde(default)]
+    pub reference: Option<String>,
+}
+
+/// Opération sur un ordre permanent portée par une transaction `StandingOrder`
+///
+/// Pour `Create`, `from` est le payeur et `to` le bénéficiaire; la
+/// transaction elle-même ne transporte aucun montant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StandingOrderOperation {
+    /// Créer un ordre permanent (payeur)
+    Create(StandingOrderTerms),
+    /// Annuler un ordre permanent (payeur ou bénéficiaire)
+    Cancel { order_id: Hash },
+}
+
+/// Type d'événement d'un ordre permanent
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum StandingOrderEventKind {
+    /// Ordre créé
+    Created,
+    /// Échéance payée
+    Paid,
+    /// Échéance impayée faute de fonds
+    InsufficientFunds,
+    /// Dernière échéance atteinte
+    Completed,
+    /// Ordre annulé
+    Cancelled,
+}
+
+/// Événement d'un ordre permanent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingOrderEvent {
+    /// ID de l'ordre
+    pub order_id: Hash,
+    /// Type d'événement
+    pub kind: StandingOrderEventKind,
+    /// Montant de l'échéance (paiement ou impayé)
+    pub amount: Option<Amount>,
+    /// Hauteur du bloc
+    pub block_height: u64,
+    /// Timestamp du bloc
+    pub timestamp: Timestamp,
+}
+
+/// Erreurs d'ordre permanent
+#[derive(Debug, Error)]
+pub enum StandingOrderError {
+    #[error("Standing order not found")]
+    NotFound,
+    #[error("Standing order already exists")]
+    AlreadyExists,
+    #[error("Not authorized for this standing order")]
+    NotAuthorized,
+    #[error("Invalid standing order state: {0:?}")]
+    InvalidState(StandingOrderStatus),
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid period")]
+    InvalidPeriod,
+    #[error("Invalid schedule: {0}")]
+    InvalidSchedule(String),
+    #[error("Payer and payee must differ")]
+    SelfPayment,
+}
+
+/// Registre des ordres permanents et de leurs événements
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StandingOrderRegistry {
+    #[serde(with = "sorted_map")]
+    orders: HashMap<Hash, StandingOrder>,
+    events: Vec<StandingOrderEvent>,
+}
+
+impl StandingOrderRegistry {
+    /// Créer un registre vide
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Créer un ordre permanent
+    pub fn create(
+        &mut self,
+        id: Hash,
+        payer: PublicKey,
+        payee: PublicKey,
+        terms: StandingOrderTerms,
+        block_height: u64,
+        now: Timestamp,
+    ) -> Result<&StandingOrder, StandingOrderError> {
+        if self.orders.contains_key(&id) {
+            return Err(StandingOrderError::AlreadyExists);
+        }
+
+        if payer == payee {
+            return Err(StandingOrderError::SelfPayment);
+        }
+
+        if terms.amount <= 0.0 {
+            return Err(StandingOrderError::InvalidAmount);
+        }
+
+        if terms.period_secs < MIN_STANDING_ORDER_PERIOD_SECS {
+            return Err(StandingOrderError::InvalidPeriod);
+        }
+
+        let start = terms.start_at.unwrap_or(now);
+        if start < now {
+            return Err(StandingOrderError::InvalidSchedule("start is in the past".to_string()));
+        }
+
+        match (terms.max_payments, terms.end_at) {
+            (None, None) => {
+                return Err(StandingOrderError::InvalidSchedule(
+                    "a payment count or an end date is required".to_string(),
+                ));
+            }
+            (Some(0), _) => {
+                return Err(StandingOrderError::InvalidSchedule("payment count must be positive".to_string()));
+            }
+            (_, Some(end)) if end < start => {
+                return Err(StandingOrderError::InvalidSchedule("end is before start".to_string()));
+            }
+            _ => {}
+        }
+
+        let order = StandingOrder {
+            id,
+            payer,
+            payee,
+            amount: terms.amount,
+            period_secs: terms.period_secs,
+            max_payments: terms.max_payments,
+            end_at: terms.end_at,
+            reference: terms.reference,
+            created_at: now,
+            next_due: start,
+            payments_made: 0,
+            payments_failed: 0,
+            status: StandingOrderStatus::Active,
+            cancelled_by: None,
+        };
+
+        self.record(id, StandingOrderEventKind::Created, None, block_height, now);
+        Ok(self.orders.entry(id).or_insert(order))
+    }
+
+    /// Annuler un ordre permanent (payeur ou bénéficiaire)
+    pub fn cancel(
+        &mut self,
+        id: &Hash,
+        caller: &PublicKey,
+        block_height: u64,
+        now: Timestamp,
+    ) -> Result<(), StandingOrderError> {
+        let order = self.orders.get_mut(id)
+            .ok_or(StandingOrderError::NotFound)?;
+
+        if caller != &order.payer && caller != &order.payee {
+            return Err(StandingOrderError::NotAuthorized);
+        }
+
+        if order.status != StandingOrderStatus::Active {
+            return Err(StandingOrderError::InvalidState(order.status));
+        }
+
+        order.status = StandingOrderStatus::Cancelled;
+        order.cancelled_by = Some(*caller);
+        self.record(*id, StandingOrderEventKind::Cancelled, None, block_height, now);
+        Ok(())
+    }
+
+    /// Exécuter les échéances arrivées à terme
+    ///
+    /// Appelé à chaque application de bloc. Les ordres sont traités par
+    /// échéance puis par ID, et les échéances manquées sont rattrapées.
+    /// `pay(payer, payee, amount)` effectue le virement et retourne `false`
+    /// si le payeur n'a pas les fonds; l'échéance est alors consignée comme
+    /// impayée. Retourne le nombre d'échéances payées.
+    pub fn execute_due<F>(&mut self, block_height: u64, now: Timestamp, mut pay: F) -> usize
+    where
+        F: FnMut(&PublicKey, &PublicKey, Amount) -> bool,
+    {
+        let mut due: Vec<(Timestamp, Hash)> = self.orders
+            .values()
+            .filter(|o| o.status == StandingOrderStatus::Active && o.next_due <= now)
+            .map(|o| (o.next_due, o.id))
+            .collect();
+        due.sort();
+
+        let mut paid = 0;
+        for (_, id) in due {
+            let mut events = Vec::new();
+            let order = self.orders.get_mut(&id).expect("due order exists");
+
+            while order.status == StandingOrderStatus::Active && order.next_due <= now {
+                if order.is_exhausted() {
+                    order.status = StandingOrderStatus::Completed;
+                    events.push((StandingOrderEventKind::Completed, None));
+                    break;
+                }
+
+                if pay(&order.payer, &order.payee, order.amount) {
+                    order.payments_made += 1;
+                    paid += 1;
+                    events.push((StandingOrderEventKind::Paid, Some(order.amount)));
+                } else {
+                    order.payments_failed += 1;
+                    events.push((StandingOrderEventKind::InsufficientFunds, Some(order.amount)));
+                }
+                order.next_due += order.period_secs;
+            }
+
+            // Clôturer dès la dernière échéance plutôt qu'à la suivante
+            if order.status == StandingOrderStatus::Active && order.is_exhausted() {
+                order.status = StandingOrderStatus::Completed;
+                events.push((StandingOrderEventKind::Completed, None));
+            }
+
+            for (kind, amount) in events {
+                self.record(id, kind, amount, block_height, now);
+            }
+        }
+
+        paid
+    }
+
+    /// Obtenir un ordre permanent
+    pub fn get(&self, id: &Hash) -> Option<&StandingOrder> {
+        self.orders.get(id)
+    }
+
+    /// Ordres impliquant une adresse (payeur ou bénéficiaire)
+    pub fn by_participant(&self, address: &PublicKey) -> Vec<&StandingOrder> {
+        let mut orders: Vec<&StandingOrder> = self.orders
+            .values()
+            .filter(|o| &o.payer == address || &o.payee == address)
+            .collect();
+        orders.sort_by_key(|o| (o.created_at, o.id));
+        orders
+    }
+
+    /// Événements d'un ordre (du plus ancien au plus récent)
+    pub fn events_for(&self, id: &Hash) -> Vec<&StandingOrderEvent> {
+        self.events.iter().filter(|e| &e.order_id == id).collect()
+    }
+
+    /// Ajouter un événement au journal
+    fn record(
+        &mut self,
+        order_id: Hash,
+        kind: StandingOrderEventKind,
+        amount: Option<Amount>,
+        block_height: u64,
+        timestamp: Timestamp,
+    ) {
+        self.events.push(StandingOrderEvent { order_id, kind, amount, block_height, timestamp });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYER: PublicKey = [1u8; 32];
+    const PAYEE: PublicKey = [2u8; 32];
+    const DAY: i64 = 86400;
+
+    fn terms(max_payments: Option<u32>, end_at: Option<Timestamp>) -> StandingOrderTerms {
+        StandingOrderTerms {
+            amount: 10.0,
+            period_secs: DAY,
+            start_at: None,
+            max_payments,
+            end_at,
+            reference: Some("rental-7".to_string()),
+        }
+    }
+
+    fn kinds(registry: &StandingOrderRegistry, id: &Hash) -> Vec<StandingOrderEventKind> {
+        registry.events_for(id).iter().map(|e| e.kind).collect()
+    }
+
+    #[test]
+    fn test_schedule_validation() {
+        let mut registry = StandingOrderRegistry::new();
+        assert!(matches!(
+            registry.create([1; 32], PAYER, PAYEE, terms(None, None), 1, 0),
+            Err(StandingOrderError::InvalidSchedule(_))
+        ));
+        assert!(matches!(
+            registry.create([1; 32], PAYER, PAYER, terms(Some(3), None), 1, 0),
+            Err(StandingOrderError::SelfPayment)
+        ));
+
+        let mut short = terms(Some(3), None);
+        short.period_secs = 60;
+        assert!(matches!(
+            registry.create([1; 32], PAYER, PAYEE, short, 1, 0),
+            Err(StandingOrderError::InvalidPeriod)
+        ));
+    }
+
+    #[test]
+    fn test_count_limited_order_with_failed_payment() {
+        let mut registry = StandingOrderRegistry::new();
+        let id = [1u8; 32];
+        registry.create(id, PAYER, PAYEE, terms(Some(3), None), 1, 0).unwrap();
+
+        let mut balance = 15.0;
+        let mut pay = |_: &PublicKey, _: &PublicKey, amount: Amount| {
+            if balance < amount {
+                return false;
+            }
+            balance -= amount;
+            true
+        };
+
+        assert_eq!(registry.execute_due(1, 0, &mut pay), 1);
+        assert_eq!(registry.execute_due(2, DAY - 1, &mut pay), 0);
+        // Fonds insuffisants: consigné, l'ordre continue
+        assert_eq!(registry.execute_due(3, DAY, &mut pay), 0);
+        assert_eq!(registry.get(&id).unwrap().status, StandingOrderStatus::Active);
+        assert_eq!(registry.execute_due(4, 2 * DAY, &mut pay), 0);
+
+        let order = registry.get(&id).unwrap();
+        assert_eq!(order.status, StandingOrderStatus::Completed);
+        assert_eq!((order.payments_made, order.payments_failed), (1, 2));
+        assert_eq!(kinds(&registry, &id), vec![
+            StandingOrderEventKind::Created,
+            StandingOrderEventKind::Paid,
+            StandingOrderEventKind::InsufficientFunds,
+            StandingOrderEventKind::InsufficientFunds,
+            StandingOrderEventKind::Completed,
+        ]);
+    }
+
+    #[test]
+    fn test_end_date_catch_up_and_cancel() {
+        let mut registry = StandingOrderRegistry::new();
+        let id = [1u8; 32];
+        registry.create(id, PAYER, PAYEE, terms(None, Some(10 * DAY)), 1, 0).unwrap();
+
+        // Trois échéances rattrapées dans un même bloc
+        assert_eq!(registry.execute_due(2, 2 * DAY, |_, _, _| true), 3);
+        assert_eq!(registry.get(&id).unwrap().next_due, 3 * DAY);
+
+        assert!(matches!(registry.cancel(&id, &[3u8; 32], 3, 2 * DAY), Err(StandingOrderError::NotAuthorized)));
+        registry.cancel(&id, &PAYEE, 3, 2 * DAY).unwrap();
+        assert_eq!(registry.execute_due(4, 5 * DAY, |_, _, _| true), 0);
+        assert_eq!(registry.get(&id).unwrap().cancelled_by, Some(PAYEE));
+        assert!(registry.cancel(&id, &PAYER, 5, 5 * DAY).is_err());
+    }
+}
@@ -2,10 +2,28 @@
 //!
 //! Permet aux clients légers de se synchroniser rapidement en téléchargeant
 //! uniquement les en-têtes de blocs et les checkpoints périodiques
+//!
+//! Un checkpoint porte le certificat de finalité de son bloc et les
+//! signatures, par un quorum du comité ayant décidé ce bloc, de la hauteur,
+//! du hash du bloc et du hash de l'état. Un client qui connaît le calendrier
+//! de l'époque vérifie ainsi le bloc et l'état sans rejouer la chaîne.
 
+use std::collections::HashSet;
 use serde::{Deserialize, Serialize};
-use crate::{Hash, Timestamp};
+use crate::{Hash, PublicKey, Signature, Timestamp};
 use crate::block::Block;
+use crate::consensus::finality::{check_quorum, FinalityCertificate, FinalityError};
+use crate::consensus::rvs::{hex_bytes, EpochSchedule};
+use crate::crypto::{hash_data, verify_signature};
+
+/// Signature d'un checkpoint par un membre du comité de son bloc
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointSignature {
+    #[serde(with = "hex_bytes")]
+    pub validator: PublicKey,
+    #[serde(with = "hex_bytes")]
+    pub signature: Signature,
+}
 
 /// Point de contrôle (checkpoint)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,7 +34,7 @@ pub struct Checkpoint {
     /// Hash du bloc
     pub block_hash: Hash,
     
-    /// Hash de l'état (balances, etc.)
+    /// Hash de l'état: racine des blocs de données de son instantané
     pub state_hash: Hash,
     
     /// Timestamp de création
@@ -24,27 +42,80 @@ pub struct Checkpoint {
     
     /// Période entre checkpoints (en blocs)
     pub checkpoint_interval: u64,
+    
+    /// Certificat de finalité du bloc
+    pub certificate: FinalityCertificate,
+    
+    /// Signatures du checkpoint (hauteur, bloc, état)
+    pub signatures: Vec<CheckpointSignature>,
 }
 
 impl Checkpoint {
     /// Créer un nouveau checkpoint depuis un bloc
+    ///
+    /// Le checkpoint n'est pas encore signé.
     pub fn from_block(block: &Block, state_hash: Hash, interval: u64) -> Self {
+        let block_hash = block.hash.unwrap_or_else(|| block.compute_hash());
         Self {
             height: block.header.height,
-            block_hash: block.hash.unwrap_or_else(|| {
-                let mut b = block.clone();
-                b.calculate_hash();
-                b.hash.unwrap()
-            }),
+            block_hash,
             state_hash,
             timestamp: block.header.timestamp,
             checkpoint_interval: interval,
+            certificate: FinalityCertificate::from_block(block, block_hash),
+            signatures: Vec::new(),
         }
     }
 
-    /// Vérifier si un checkpoint est valide
-    pub fn is_valid(&self) -> bool {
-        self.height > 0 && self.checkpoint_interval > 0
+    /// Message signé par les membres du comité
+    pub fn signing_hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(17 + 8 + 32 + 32);
+        data.extend_from_slice(b"mahala-checkpoint");
+        data.extend_from_slice(&self.height.to_le_bytes());
+        data.extend_from_slice(&self.block_hash);
+        data.extend_from_slice(&self.state_hash);
+        hash_data(&data)
+    }
+
+    /// Ajouter la signature d'un membre du comité ayant décidé le bloc
+    pub fn add_signature(&mut self, validator: PublicKey, signature: Signature) -> Result<(), FinalityError> {
+        if self.signatures.iter().any(|s| s.validator == validator) {
+            return Err(FinalityError::DuplicateSigner(hex::encode(validator)));
+        }
+        self.check_signature(&validator, &signature)?;
+        self.signatures.push(CheckpointSignature { validator, signature });
+        Ok(())
+    }
+
+    /// Vérifier le checkpoint contre le calendrier de l'époque de son bloc
+    ///
+    /// Le certificat doit finaliser le bloc du checkpoint, et un quorum de
+    /// ses signataires doit avoir signé le checkpoint.
+    pub fn verify(&self, schedule: &EpochSchedule) -> Result<(), FinalityError> {
+        if self.certificate.height != self.height || self.certificate.block_hash != self.block_hash {
+            return Err(FinalityError::Mismatch);
+        }
+        self.certificate.verify(schedule)?;
+
+        let mut signers = HashSet::new();
+        for s in &self.signatures {
+            if !signers.insert(s.validator) {
+                return Err(FinalityError::DuplicateSigner(hex::encode(s.validator)));
+            }
+            self.check_signature(&s.validator, &s.signature)?;
+        }
+        check_quorum(signers.len(), schedule)
+    }
+
+    /// Signataire du certificat et signature valide du checkpoint
+    fn check_signature(&self, validator: &PublicKey, signature: &Signature) -> Result<(), FinalityError> {
+        if !self.certificate.is_signer(validator) {
+            return Err(FinalityError::NotInCommittee(hex::encode(validator)));
+        }
+        if !verify_signature(&self.signing_hash(), signature, validator) {
+            return Err(FinalityError::InvalidSignature(hex::encode(validator)));
+        }
+        Ok(())
     }
 
     /// Obtenir le prochain checkpoint attendu
@@ -95,10 +166,28 @@ mod tests {
         );
         
         let state_hash = [1u8; 32];
-        let checkpoint = Checkpoint::from_block(&block, state_hash, 100);
+        let mut checkpoint = Checkpoint::from_block(&block, state_hash, 100);
         
         assert_eq!(checkpoint.height, 100);
-        assert!(checkpoint.is_valid());
+        assert_eq!(checkpoint.next_checkpoint_height(), 200);
+        
+        // Sans certificat ni signatures, le checkpoint n'est pas vérifiable
+        let schedule = EpochSchedule {
+            epoch: 0,
+            start_height: 0,
+            end_height: 719,
+            seed: [0u8; 32],
+            validators: Default::default(),
+            expected_committee_size: 0,
+            required_quorum: 0,
+        };
+        assert_eq!(checkpoint.verify(&schedule), Err(FinalityError::NoQuorum { have: 0, need: 1 }));
+        // Seuls les signataires du certificat peuvent signer le checkpoint
+        let signature = crate::crypto::sign(&checkpoint.signing_hash(), keypair.private_key());
+        assert!(matches!(
+            checkpoint.add_signature(*keypair.public_key(), signature),
+            Err(FinalityError::NotInCommittee(_))
+        ));
     }
 
     #[test]
@@ -15,23 +15,19 @@ pub struct MerkleTree {
 impl MerkleTree {
     /// Créer un arbre de Merkle depuis des transactions
     pub fn from_transactions(transactions: &[Transaction]) -> Self {
-        if transactions.is_empty() {
+        // Hasher chaque transaction
+        Self::from_hashes(transactions.iter().map(Transaction::compute_hash).collect())
+    }
+
+    /// Créer un arbre de Merkle depuis des hash de transactions déjà calculés
+    pub fn from_hashes(leaves: Vec<Hash>) -> Self {
+        if leaves.is_empty() {
             return Self {
                 root: hash_data(b"empty"),
-                leaves: Vec::new(),
+                leaves,
             };
         }
 
-        // Hasher chaque transaction
-        let leaves: Vec<Hash> = transactions
-            .iter()
-            .map(|tx| {
-                let mut tx_copy = tx.clone();
-                tx_copy.calculate_hash();
-                tx_copy.hash.unwrap()
-            })
-            .collect();
-
         let root = Self::build_tree(&leaves);
         
         Self { root, leaves }
@@ -4,7 +4,9 @@
 
 pub mod merkle;
 pub mod checkpoint;
+pub mod snapshot;
 
 pub use merkle::MerkleTree;
 pub use checkpoint::Checkpoint;
+pub use snapshot::{SnapshotManifest, StateSnapshot};
 
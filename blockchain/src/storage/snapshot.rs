@@ -0,0 +1,400 @@
+//! Instantanés de l'état pour le démarrage rapide
+//!
+//! L'état de la chaîne à un checkpoint est découpé en sections (méta,
+//! balances, membres, comptes, NFTs, séquestres, ordres permanents, canaux,
+//! fautes, validateurs), chacune sérialisée de façon canonique puis
+//! découpée en blocs de données d'au plus `SNAPSHOT_CHUNK_SIZE` octets. La
+//! racine de Merkle des hash de ces blocs est le hash d'état du checkpoint:
+//! les signatures du checkpoint certifient chaque bloc de données.
+//!
+//! Un nœud télécharge d'abord le manifeste (checkpoint, blocs de l'époque
+//! depuis son bloc d'ancrage, hash des blocs de données), le vérifie contre
+//! le calendrier de l'époque, puis vérifie chaque bloc de données à sa
+//! réception.
+
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::{Amount, Hash, PublicKey, Timestamp};
+use crate::block::Block;
+use crate::chain::BlockchainState;
+use crate::consensus::finality::FinalityError;
+use crate::consensus::rvs::{EpochSchedule, RVSConfig, RVS};
+use crate::crypto::hash_data;
+use crate::storage::checkpoint::Checkpoint;
+use crate::storage::merkle::MerkleTree;
+
+/// Taille maximale d'un bloc de données (octets)
+pub const SNAPSHOT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Erreurs de vérification ou de restauration d'un instantané
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SnapshotError {
+    #[error("invalid checkpoint: {0}")]
+    Checkpoint(#[from] FinalityError),
+
+    #[error("state root does not match the checkpoint")]
+    StateRoot,
+
+    #[error("epoch blocks do not lead to the checkpoint block")]
+    Blocks,
+
+    #[error("chunk {0} does not match the manifest")]
+    Chunk(usize),
+
+    #[error("expected {expected} chunks, got {got}")]
+    ChunkCount { expected: usize, got: usize },
+
+    #[error("invalid {0:?} section: {1}")]
+    Section(SnapshotSection, String),
+}
+
+/// Section de l'état
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SnapshotSection {
+    Meta,
+    Balances,
+    Members,
+    Accounts,
+    Nfts,
+    Escrows,
+    StandingOrders,
+    Channels,
+    Slashing,
+    Validators,
+}
+
+/// Bloc de données d'une section
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotChunk {
+    pub section: SnapshotSection,
+    /// Rang du bloc dans sa section
+    pub index: u32,
+    #[serde(with = "hex_data")]
+    pub data: Vec<u8>,
+}
+
+impl SnapshotChunk {
+    /// Hash du bloc, feuille de la racine d'état
+    pub fn hash(&self) -> Hash {
+        let mut data = Vec::with_capacity(21 + 1 + 4 + self.data.len());
+        data.extend_from_slice(b"mahala-snapshot-chunk");
+        data.push(self.section as u8);
+        data.extend_from_slice(&self.index.to_le_bytes());
+        data.extend_from_slice(&self.data);
+        hash_data(&data)
+    }
+}
+
+/// Racine d'état: racine de Merkle des hash des blocs de données
+pub fn state_root(chunk_hashes: &[Hash]) -> Hash {
+    MerkleTree::from_hashes(chunk_hashes.to_vec()).root()
+}
+
+/// Manifeste d'un instantané
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Checkpoint final dont l'instantané reproduit l'état
+    pub checkpoint: Checkpoint,
+    /// Blocs de l'époque du checkpoint, de son bloc d'ancrage au bloc du
+    /// checkpoint (graine des comités, sommet de la chaîne)
+    pub blocks: Vec<Block>,
+    /// Hash des blocs de données, dans l'ordre
+    #[serde(with = "hex_hashes")]
+    pub chunks: Vec<Hash>,
+}
+
+impl SnapshotManifest {
+    /// Hauteur de l'instantané
+    pub fn height(&self) -> u64 {
+        self.checkpoint.height
+    }
+
+    /// Vérifier le manifeste contre le calendrier de l'époque du checkpoint
+    ///
+    /// Le checkpoint doit être signé par un quorum, la racine des blocs de
+    /// données doit être son hash d'état, et les blocs de l'époque doivent
+    /// s'enchaîner du bloc d'ancrage de la graine au bloc du checkpoint.
+    pub fn verify(&self, schedule: &EpochSchedule) -> Result<(), SnapshotError> {
+        self.checkpoint.verify(schedule)?;
+        if state_root(&self.chunks) != self.checkpoint.state_hash {
+            return Err(SnapshotError::StateRoot);
+        }
+
+        let anchor = self.blocks.first().ok_or(SnapshotError::Blocks)?;
+        if anchor.header.height != schedule.start_height.saturating_sub(1)
+            || RVS::epoch_seed(schedule.epoch, &anchor.compute_hash()) != schedule.seed
+        {
+            return Err(SnapshotError::Blocks);
+        }
+        let mut previous: Option<(u64, Hash)> = None;
+        for block in &self.blocks {
+            if let Some((height, _)) = previous {
+                if block.header.height != height + 1 {
+                    return Err(SnapshotError::Blocks);
+                }
+            }
+            let mut block = block.clone();
+            let hash = block.calculate_hash();
+            if block.verify(previous.map(|(_, hash)| hash)).is_none() {
+                return Err(SnapshotError::Blocks);
+            }
+            previous = Some((block.header.height, hash));
+        }
+        if previous != Some((self.checkpoint.height, self.checkpoint.block_hash)) {
+            return Err(SnapshotError::Blocks);
+        }
+        Ok(())
+    }
+
+    /// Vérifier un bloc de données reçu contre le manifeste
+    pub fn verify_chunk(&self, index: usize, chunk: &SnapshotChunk) -> Result<(), SnapshotError> {
+        match self.chunks.get(index) {
+            Some(hash) if *hash == chunk.hash() => Ok(()),
+            _ => Err(SnapshotError::Chunk(index)),
+        }
+    }
+}
+
+/// Instantané complet: manifeste et blocs de données
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub manifest: SnapshotManifest,
+    pub chunks: Vec<SnapshotChunk>,
+}
+
+impl StateSnapshot {
+    /// Vérifier le manifeste et chacun des blocs de données
+    pub fn verify(&self, schedule: &EpochSchedule) -> Result<(), SnapshotError> {
+        self.manifest.verify(schedule)?;
+        if self.chunks.len() != self.manifest.chunks.len() {
+            return Err(SnapshotError::ChunkCount {
+                expected: self.manifest.chunks.len(),
+                got: self.chunks.len(),
+            });
+        }
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            self.manifest.verify_chunk(index, chunk)?;
+        }
+        Ok(())
+    }
+
+    /// Calendrier de l'époque du checkpoint décrit par l'instantané lui-même
+    ///
+    /// Sans valeur de confiance: un instantané forgé décrit ses propres
+    /// validateurs. Le hash du bloc du checkpoint doit être comparé à une
+    /// valeur connue avant de s'y fier.
+    pub fn epoch_schedule(&self, config: RVSConfig) -> Result<EpochSchedule, SnapshotError> {
+        let state = BlockchainState::from_snapshot_chunks(&self.chunks, config)?;
+        let consensus = &state.consensus;
+        let epoch = consensus.epoch(self.manifest.height());
+        let anchor = self.manifest.blocks.first().ok_or(SnapshotError::Blocks)?;
+        consensus.epoch_schedule(epoch, RVS::epoch_seed(epoch, &anchor.compute_hash()))
+            .ok_or_else(|| SnapshotError::Section(SnapshotSection::Validators, format!("no validator set for epoch {}", epoch)))
+    }
+}
+
+/// Données globales de l'état
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SnapshotMeta {
+    member_count: u64,
+    total_mass: Amount,
+    last_du_distribution: Timestamp,
+}
+
+impl BlockchainState {
+    /// Découper l'état en blocs de données
+    ///
+    /// Les collections sont triées par clé: deux nœuds au même état
+    /// produisent les mêmes blocs, donc la même racine.
+    pub fn snapshot_chunks(&self) -> Vec<SnapshotChunk> {
+        let mut balances: Vec<(&PublicKey, &Amount)> = self.balances.iter().collect();
+        balances.sort_by_key(|(address, _)| **address);
+        let mut members: Vec<&PublicKey> = self.members.iter().collect();
+        members.sort();
+        let meta = SnapshotMeta {
+            member_count: self.member_count,
+            total_mass: self.total_mass,
+            last_du_distribution: self.last_du_distribution,
+        };
+
+        let sections = [
+            (SnapshotSection::Meta, canonical_json(&meta)),
+            (SnapshotSection::Balances, canonical_json(&balances)),
+            (SnapshotSection::Members, canonical_json(&members)),
+            (SnapshotSection::Accounts, canonical_json(&self.accounts)),
+            (SnapshotSection::Nfts, canonical_json(&self.nfts)),
+            (SnapshotSection::Escrows, canonical_json(&self.escrows)),
+            (SnapshotSection::StandingOrders, canonical_json(&self.standing_orders)),
+            (SnapshotSection::Channels, canonical_json(&self.channels)),
+            (SnapshotSection::Slashing, canonical_json(&self.slashing)),
+            (SnapshotSection::Validators, canonical_json(&self.consensus)),
+        ];
+        sections.into_iter()
+            .flat_map(|(section, data)| {
+                data.chunks(SNAPSHOT_CHUNK_SIZE)
+                    .enumerate()
+                    .map(|(index, data)| SnapshotChunk { section, index: index as u32, data: data.to_vec() })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// Racine d'état (hash d'état des checkpoints)
+    pub fn state_root(&self) -> Hash {
+        let hashes: Vec<Hash> = self.snapshot_chunks().iter().map(SnapshotChunk::hash).collect();
+        state_root(&hashes)
+    }
+
+    /// Reconstruire l'état depuis ses blocs de données
+    ///
+    /// La configuration du consensus est celle du nœud, comme à la genèse.
+    pub fn from_snapshot_chunks(chunks: &[SnapshotChunk], config: RVSConfig) -> Result<Self, SnapshotError> {
+        let mut sections: BTreeMap<SnapshotSection, Vec<u8>> = BTreeMap::new();
+        for chunk in chunks {
+            let data = sections.entry(chunk.section).or_default();
+            let expected = data.len().div_ceil(SNAPSHOT_CHUNK_SIZE);
+            if chunk.index as usize != expected || !data.len().is_multiple_of(SNAPSHOT_CHUNK_SIZE) {
+                return Err(SnapshotError::Section(chunk.section, format!("unexpected chunk {}", chunk.index)));
+            }
+            data.extend_from_slice(&chunk.data);
+        }
+
+        let meta: SnapshotMeta = decode(&sections, SnapshotSection::Meta)?;
+        let balances: Vec<(PublicKey, Amount)> = decode(&sections, SnapshotSection::Balances)?;
+        let members: Vec<PublicKey> = decode(&sections, SnapshotSection::Members)?;
+        let mut consensus: RVS = decode(&sections, SnapshotSection::Validators)?;
+        consensus.set_config(config);
+
+        Ok(Self {
+            balances: balances.into_iter().collect(),
+            member_count: meta.member_count,
+            total_mass: meta.total_mass,
+            last_du_distribution: meta.last_du_distribution,
+            nfts: decode(&sections, SnapshotSection::Nfts)?,
+            escrows: decode(&sections, SnapshotSection::Escrows)?,
+            standing_orders: decode(&sections, SnapshotSection::StandingOrders)?,
+            channels: decode(&sections, SnapshotSection::Channels)?,
+            accounts: decode(&sections, SnapshotSection::Accounts)?,
+            slashing: decode(&sections, SnapshotSection::Slashing)?,
+            members: members.into_iter().collect(),
+            consensus,
+        })
+    }
+}
+
+/// Sérialisation canonique: les objets JSON passent par une table triée
+fn canonical_json<T: Serialize>(value: &T) -> Vec<u8> {
+    let value = serde_json::to_value(value).expect("Serialization should never fail");
+    serde_json::to_vec(&value).expect("Serialization should never fail")
+}
+
+/// Décoder une section reconstituée
+fn decode<T: for<'de> Deserialize<'de>>(
+    sections: &BTreeMap<SnapshotSection, Vec<u8>>,
+    section: SnapshotSection,
+) -> Result<T, SnapshotError> {
+    let data = sections.get(&section)
+        .ok_or_else(|| SnapshotError::Section(section, "missing".to_string()))?;
+    serde_json::from_slice(data).map_err(|e| SnapshotError::Section(section, e.to_string()))
+}
+
+/// Sérialiser une table de hachage en liste de paires triées par clé
+///
+/// Rend la sérialisation indépendante de l'ordre de la table, et admet
+/// des clés qui ne sont pas des chaînes (clés publiques, hash).
+pub(crate) mod sorted_map {
+    use std::collections::HashMap;
+    use std::hash::Hash;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S, K, V>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        K: Serialize + Ord,
+        V: Serialize,
+    {
+        let mut entries: Vec<(&K, &V)> = map.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        entries.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, K, V>(deserializer: D) -> Result<HashMap<K, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        K: Deserialize<'de> + Eq + Hash,
+        V: Deserialize<'de>,
+    {
+        Ok(Vec::<(K, V)>::deserialize(deserializer)?.into_iter().collect())
+    }
+}
+
+mod hex_data {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        hex::encode(data).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        hex::decode(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+mod hex_hashes {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use crate::Hash;
+
+    pub fn serialize<S: Serializer>(hashes: &[Hash], serializer: S) -> Result<S::Ok, S::Error> {
+        hashes.iter().map(hex::encode).collect::<Vec<_>>().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Hash>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .into_iter()
+            .map(|h| {
+                hex::decode(&h)
+                    .map_err(serde::de::Error::custom)?
+                    .try_into()
+                    .map_err(|_| serde::de::Error::custom("Invalid length"))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_state_chunks_are_canonical() {
+        let addresses: Vec<PublicKey> = (0..3000u32)
+            .map(|i| hash_data(&i.to_le_bytes()))
+            .collect();
+        let mut a = BlockchainState::default();
+        let mut b = BlockchainState::default();
+        for (i, address) in addresses.iter().enumerate() {
+            a.balances.insert(*address, i as Amount);
+            a.members.insert(*address);
+        }
+        for (i, address) in addresses.iter().enumerate().rev() {
+            b.balances.insert(*address, i as Amount);
+            b.members.insert(*address);
+        }
+
+        // Même état, même racine, quel que soit l'ordre d'insertion
+        let chunks = a.snapshot_chunks();
+        assert_eq!(a.state_root(), b.state_root());
+        assert!(chunks.iter().any(|c| c.section == SnapshotSection::Balances && c.index > 0));
+        assert!(chunks.iter().all(|c| c.data.len() <= SNAPSHOT_CHUNK_SIZE));
+
+        let restored = BlockchainState::from_snapshot_chunks(&chunks, RVSConfig::default()).unwrap();
+        assert_eq!(restored.state_root(), a.state_root());
+        assert_eq!(restored.balances[&addresses[42]], 42.0);
+
+        // Un bloc manquant est détecté
+        let mut missing = chunks.clone();
+        missing.remove(1);
+        assert!(BlockchainState::from_snapshot_chunks(&missing, RVSConfig::default()).is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! Structures et logique des transactions Mahala
 
 use serde::{Deserialize, Serialize};
-use crate::{Hash, PublicKey, Amount, Timestamp};
+use crate::{Hash, PublicKey, Amount, Signature, Timestamp};
 use crate::crypto::{hash_data, sign, verify_signature};
 
 /// Transaction sur la blockchain Mahala
@@ -12,9 +12,11 @@ pub struct Transaction {
     pub hash: Option<Hash>,
     
     /// Adresse expéditeur (clé publique)
+    #[serde(deserialize_with = "crate::wallet::address::deserialize")]
     pub from: PublicKey,
     
     /// Adresse destinataire (clé publique)
+    #[serde(deserialize_with = "crate::wallet::address::deserialize")]
     pub to: PublicKey,
     
     /// Montant en Mahala
@@ -63,6 +65,20 @@ pub enum TransactionType {
     UniversalDividend,
     /// Transaction NFT
     NFT,
+    /// Transition d'un séquestre (confirmation, remboursement, litige, arbitrage)
+    Escrow,
+    /// Création ou annulation d'un ordre permanent (paiements récurrents)
+    StandingOrder,
+    /// Ouverture, recharge ou clôture d'un canal de paiement
+    PaymentChannel,
+    /// Rotation de la clé de contrôle d'un compte
+    KeyRotation,
+    /// Récupération sociale d'un compte (configuration, approbation des gardiens)
+    Recovery,
+    /// Preuve d'équivocation d'un validateur (double signature)
+    Evidence,
+    /// Enregistrement, départ ou heartbeat d'un validateur
+    Validator,
 }
 
 impl Default for TransactionType {
@@ -71,6 +87,46 @@ impl Default for TransactionType {
     }
 }
 
+impl TransactionType {
+    /// Le type porte-t-il une opération sur l'état (payload dans `extra_data`)
+    /// plutôt qu'un simple transfert de fonds ?
+    ///
+    /// Ces transactions peuvent avoir un montant nul et `from == to`.
+    pub fn is_operation(&self) -> bool {
+        matches!(
+            self,
+            Self::NFT
+                | Self::Escrow
+                | Self::StandingOrder
+                | Self::PaymentChannel
+                | Self::KeyRotation
+                | Self::Recovery
+                | Self::Evidence
+                | Self::Validator
+        )
+    }
+}
+
+impl TransactionMetadata {
+    /// Créer des métadonnées portant un payload JSON typé
+    pub fn with_payload<T: Serialize>(transaction_type: TransactionType, payload: &T) -> Self {
+        Self {
+            transaction_type,
+            extra_data: Some(
+                serde_json::to_string(payload).expect("Serialization should never fail"),
+            ),
+        }
+    }
+
+    /// Décoder le payload JSON de `extra_data`
+    pub fn payload<T: serde::de::DeserializeOwned>(&self) -> Result<T, String> {
+        let data = self.extra_data.as_deref()
+            .ok_or("Missing transaction payload")?;
+        serde_json::from_str(data)
+            .map_err(|e| format!("Invalid transaction payload: {}", e))
+    }
+}
+
 impl Transaction {
     /// Créer une nouvelle transaction
     pub fn new(
@@ -96,6 +152,13 @@ impl Transaction {
 
     /// Calculer le hash de la transaction
     pub fn calculate_hash(&mut self) -> Hash {
+        let hash = self.compute_hash();
+        self.hash = Some(hash);
+        hash
+    }
+
+    /// Calculer le hash sans le mémoriser (ni cloner la transaction)
+    pub fn compute_hash(&self) -> Hash {
         // Exclure hash et signature du calcul
         let data = serde_json::to_vec(&TransactionForHash {
             from: &self.from,
@@ -106,9 +169,14 @@ impl Transaction {
             metadata: &self.metadata,
         }).expect("Serialization should never fail");
         
-        let hash = hash_data(&data);
-        self.hash = Some(hash);
-        hash
+        hash_data(&data)
+    }
+
+    /// Identifiant de la transaction (hash déjà calculé, sinon recalculé)
+    ///
+    /// Sert d'ID aux objets créés par une opération (séquestre, canal...).
+    pub fn id(&self) -> Hash {
+        self.hash.unwrap_or_else(|| self.compute_hash())
     }
 
     /// Signer la transaction avec une clé privée
@@ -123,43 +191,50 @@ impl Transaction {
         Ok(())
     }
 
+    /// Signature décodée (absente ou mal formée: `None`)
+    pub fn signature_bytes(&self) -> Option<Signature> {
+        let bytes = hex::decode(self.signature.as_ref()?).ok()?;
+        bytes.try_into().ok()
+    }
+
     /// Vérifier la signature de la transaction
+    ///
+    /// Le hash est toujours recalculé: le hash mémorisé peut ne plus
+    /// correspondre si la transaction a été modifiée.
     pub fn verify_signature(&self) -> bool {
-        let Some(sig_hex) = &self.signature else {
+        let Some(signature) = self.signature_bytes() else {
             return false;
         };
         
-        let signature_bytes = match hex::decode(sig_hex) {
-            Ok(bytes) if bytes.len() == 64 => {
-                let mut sig = [0u8; 64];
-                sig.copy_from_slice(&bytes);
-                sig
-            }
-            _ => return false,
-        };
-        
-        // Recalculer le hash pour vérification
-        let mut tx_copy = self.clone();
-        tx_copy.signature = None; // Exclure la signature du calcul
-        let hash = tx_copy.calculate_hash();
-        
-        verify_signature(&hash, &signature_bytes, &self.from)
+        verify_signature(&self.compute_hash(), &signature, &self.from)
     }
 
     /// Vérifier la validité de la transaction
     pub fn is_valid(&self) -> bool {
-        // Vérifier signature
-        if !self.verify_signature() {
-            return false;
-        }
-        
-        // Vérifier montants positifs
-        if self.amount <= 0.0 || self.fee < 0.0 {
+        self.verify_signature() && self.is_well_formed()
+    }
+
+    /// Vérifier la forme de la transaction (montants, parties), hors signature
+    ///
+    /// Utilisé avec une vérification des signatures par lot.
+    pub fn is_well_formed(&self) -> bool {
+        let tx_type = &self.metadata.transaction_type;
+
+        // Vérifier montants positifs (nul autorisé pour les opérations)
+        let amount_ok = if tx_type.is_operation() {
+            self.amount >= 0.0
+        } else {
+            self.amount > 0.0
+        };
+        if !amount_ok || self.fee < 0.0 {
             return false;
         }
         
-        // Vérifier que from != to (sauf pour DU)
-        if self.from == self.to && self.metadata.transaction_type != TransactionType::UniversalDividend {
+        // Vérifier que from != to (sauf pour DU et les opérations)
+        if self.from == self.to
+            && *tx_type != TransactionType::UniversalDividend
+            && !tx_type.is_operation()
+        {
             return false;
         }
         
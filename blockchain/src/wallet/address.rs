@@ -0,0 +1,435 @@
+//! Format d'adresse lisible avec somme de contrôle (bech32m)
+//!
+//! Une adresse est la clé publique encodée en bech32m (BIP350) avec un
+//! préfixe de réseau: `mhl1…` sur le réseau principal, `tmhl1…` sur le
+//! réseau de test. La somme de contrôle détecte toute faute de frappe d'un
+//! caractère et permet de la localiser. Une adresse saisie est lue pour un
+//! réseau donné: celle d'un autre réseau est refusée. L'ancien format
+//! hexadécimal (64 caractères), sans réseau, reste accepté partout où une
+//! adresse est attendue.
+
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize};
+use thiserror::Error;
+use crate::PublicKey;
+
+/// Alphabet bech32
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Constante de somme de contrôle bech32m
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+/// Générateurs du polynôme BCH
+const GENERATORS: [u32; 5] = [0x3b6a_57b2, 0x2650_8e6d, 0x1ea1_19fa, 0x3d42_33dd, 0x2a14_62b3];
+
+/// Longueur de la somme de contrôle (en caractères)
+const CHECKSUM_LEN: usize = 6;
+
+/// Séparateur entre le préfixe et les données
+const SEPARATOR: char = '1';
+
+/// Nombre de caractères de données pour une clé de 32 octets
+const KEY_DATA_LEN: usize = 52;
+
+/// Réseau auquel une adresse est destinée
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+}
+
+impl Network {
+    /// Préfixe lisible (HRP) du réseau
+    pub fn hrp(self) -> &'static str {
+        match self {
+            Network::Mainnet => "mhl",
+            Network::Testnet => "tmhl",
+        }
+    }
+
+    fn from_hrp(hrp: &str) -> Option<Self> {
+        [Network::Mainnet, Network::Testnet]
+            .into_iter()
+            .find(|network| network.hrp() == hrp)
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            other => Err(format!("Unknown network: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Mainnet => write!(f, "mainnet"),
+            Network::Testnet => write!(f, "testnet"),
+        }
+    }
+}
+
+/// Erreurs de décodage d'adresse
+///
+/// Les positions sont des index de caractères (à partir de 0) dans la
+/// chaîne saisie.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AddressError {
+    #[error("Empty address")]
+    Empty,
+    #[error("Address mixes upper and lower case")]
+    MixedCase,
+    #[error("Missing separator '1'")]
+    MissingSeparator,
+    #[error("Unknown address prefix: {0}")]
+    UnknownPrefix(String),
+    #[error("Address is for {found}, expected {expected}")]
+    WrongNetwork { expected: Network, found: Network },
+    #[error("Invalid character {character:?} at position {position}")]
+    InvalidCharacter { position: usize, character: char },
+    #[error("Invalid address length: {0}")]
+    InvalidLength(usize),
+    #[error("Invalid checksum (likely error positions: {error_positions:?})")]
+    InvalidChecksum { error_positions: Vec<usize> },
+    #[error("Invalid padding")]
+    InvalidPadding,
+    #[error("Invalid hex address")]
+    InvalidHex,
+}
+
+impl AddressError {
+    /// Positions des caractères fautifs, si elles ont pu être déterminées
+    pub fn positions(&self) -> Vec<usize> {
+        match self {
+            AddressError::InvalidCharacter { position, .. } => vec![*position],
+            AddressError::InvalidChecksum { error_positions } => error_positions.clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Encoder une clé publique en adresse bech32m
+pub fn encode_address(public_key: &PublicKey, network: Network) -> String {
+    let hrp = network.hrp();
+    let data = convert_bits_8_to_5(public_key);
+    let checksum = create_checksum(hrp, &data);
+
+    let mut address = String::with_capacity(hrp.len() + 1 + data.len() + CHECKSUM_LEN);
+    address.push_str(hrp);
+    address.push(SEPARATOR);
+    for value in data.iter().chain(checksum.iter()) {
+        address.push(CHARSET[*value as usize] as char);
+    }
+    address
+}
+
+/// Décoder une adresse bech32m (réseau et clé publique)
+pub fn decode_address(address: &str) -> Result<(Network, PublicKey), AddressError> {
+    if address.is_empty() {
+        return Err(AddressError::Empty);
+    }
+
+    if let Some((position, character)) = address
+        .chars()
+        .enumerate()
+        .find(|(_, c)| !c.is_ascii() || !(33..=126).contains(&(*c as u32)))
+    {
+        return Err(AddressError::InvalidCharacter { position, character });
+    }
+
+    let has_lower = address.chars().any(|c| c.is_ascii_lowercase());
+    let has_upper = address.chars().any(|c| c.is_ascii_uppercase());
+    if has_lower && has_upper {
+        return Err(AddressError::MixedCase);
+    }
+    let address = address.to_ascii_lowercase();
+
+    let separator = address.rfind(SEPARATOR).ok_or(AddressError::MissingSeparator)?;
+    let (hrp, data_part) = (&address[..separator], &address[separator + 1..]);
+    let network = Network::from_hrp(hrp)
+        .ok_or_else(|| AddressError::UnknownPrefix(hrp.to_string()))?;
+
+    let data_offset = separator + 1;
+    let values = data_part
+        .bytes()
+        .enumerate()
+        .map(|(i, c)| {
+            CHARSET.iter()
+                .position(|x| *x == c)
+                .map(|v| v as u8)
+                .ok_or(AddressError::InvalidCharacter {
+                    position: data_offset + i,
+                    character: c as char,
+                })
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+
+    if values.len() != KEY_DATA_LEN + CHECKSUM_LEN {
+        return Err(AddressError::InvalidLength(address.len()));
+    }
+
+    if !verify_checksum(hrp, &values) {
+        let error_positions = locate_errors(hrp, &values)
+            .into_iter()
+            .map(|i| data_offset + i)
+            .collect();
+        return Err(AddressError::InvalidChecksum { error_positions });
+    }
+
+    let bytes = convert_bits_5_to_8(&values[..KEY_DATA_LEN])?;
+    let mut public_key = [0u8; 32];
+    public_key.copy_from_slice(&bytes);
+    Ok((network, public_key))
+}
+
+/// Lire une adresse saisie pour un réseau: bech32m du réseau ou
+/// hexadécimal (64 caractères)
+pub fn parse_address(address: &str, network: Network) -> Result<PublicKey, AddressError> {
+    let (found, public_key) = parse_any_network(address)?;
+    match found {
+        Some(found) if found != network => Err(AddressError::WrongNetwork { expected: network, found }),
+        _ => Ok(public_key),
+    }
+}
+
+/// Lire une adresse sans contrôler son réseau (absent pour l'hexadécimal)
+fn parse_any_network(address: &str) -> Result<(Option<Network>, PublicKey), AddressError> {
+    let address = address.trim();
+
+    if address.len() == 64 && address.bytes().all(|c| c.is_ascii_hexdigit()) {
+        let bytes = hex::decode(address).map_err(|_| AddressError::InvalidHex)?;
+        let mut public_key = [0u8; 32];
+        public_key.copy_from_slice(&bytes);
+        return Ok((None, public_key));
+    }
+
+    decode_address(address).map(|(network, public_key)| (Some(network), public_key))
+}
+
+/// Désérialiser une adresse: tableau d'octets, hexadécimal ou bech32m
+///
+/// À utiliser avec `#[serde(deserialize_with = ...)]`; la sérialisation
+/// reste celle de `PublicKey` afin de ne pas changer les hashes. Les
+/// données chargées n'ont pas de réseau: tout préfixe connu est accepté,
+/// le réseau se contrôle à la saisie (`parse_address`).
+pub fn deserialize<'de, D>(deserializer: D) -> Result<PublicKey, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum AddressRepr {
+        Bytes(PublicKey),
+        Text(String),
+    }
+
+    match AddressRepr::deserialize(deserializer)? {
+        AddressRepr::Bytes(public_key) => Ok(public_key),
+        AddressRepr::Text(text) => parse_any_network(&text)
+            .map(|(_, public_key)| public_key)
+            .map_err(serde::de::Error::custom),
+    }
+}
+
+/// Désérialiser une liste d'adresses (chaque élément comme `deserialize`)
+pub fn deserialize_list<'de, D>(deserializer: D) -> Result<Vec<PublicKey>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Address(#[serde(deserialize_with = "deserialize")] PublicKey);
+
+    let addresses = Vec::<Address>::deserialize(deserializer)?;
+    Ok(addresses.into_iter().map(|Address(public_key)| public_key).collect())
+}
+
+fn polymod(values: impl IntoIterator<Item = u8>) -> u32 {
+    values.into_iter().fold(1u32, |checksum, value| {
+        let top = checksum >> 25;
+        let mut checksum = ((checksum & 0x01ff_ffff) << 5) ^ value as u32;
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+        checksum
+    })
+}
+
+fn hrp_expand(hrp: &str) -> Vec<u8> {
+    hrp.bytes()
+        .map(|c| c >> 5)
+        .chain(std::iter::once(0))
+        .chain(hrp.bytes().map(|c| c & 31))
+        .collect()
+}
+
+fn create_checksum(hrp: &str, data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let values = hrp_expand(hrp)
+        .into_iter()
+        .chain(data.iter().copied())
+        .chain([0u8; CHECKSUM_LEN]);
+    let checksum = polymod(values) ^ BECH32M_CONST;
+
+    let mut result = [0u8; CHECKSUM_LEN];
+    for (i, value) in result.iter_mut().enumerate() {
+        *value = ((checksum >> (5 * (CHECKSUM_LEN - 1 - i))) & 31) as u8;
+    }
+    result
+}
+
+fn verify_checksum(hrp: &str, values: &[u8]) -> bool {
+    polymod(hrp_expand(hrp).into_iter().chain(values.iter().copied())) == BECH32M_CONST
+}
+
+/// Chercher les substitutions d'un caractère qui rendent la somme valide
+///
+/// Renvoie les index (dans la partie données) des caractères suspects; vide
+/// si l'erreur porte sur plus d'un caractère.
+fn locate_errors(hrp: &str, values: &[u8]) -> Vec<usize> {
+    let mut candidate = values.to_vec();
+    (0..values.len())
+        .filter(|i| {
+            let original = candidate[*i];
+            let fixable = (0..32u8)
+                .filter(|v| *v != original)
+                .any(|v| {
+                    candidate[*i] = v;
+                    verify_checksum(hrp, &candidate)
+                });
+            candidate[*i] = original;
+            fixable
+        })
+        .collect()
+}
+
+fn convert_bits_8_to_5(bytes: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    for byte in bytes {
+        accumulator = (accumulator << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(((accumulator >> bits) & 31) as u8);
+        }
+    }
+    if bits > 0 {
+        result.push(((accumulator << (5 - bits)) & 31) as u8);
+    }
+    result
+}
+
+fn convert_bits_5_to_8(values: &[u8]) -> Result<Vec<u8>, AddressError> {
+    let mut result = Vec::with_capacity(values.len() * 5 / 8);
+    let mut accumulator = 0u32;
+    let mut bits = 0;
+    for value in values {
+        accumulator = (accumulator << 5) | *value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push(((accumulator >> bits) & 0xff) as u8);
+        }
+    }
+    if bits >= 5 || (accumulator << (8 - bits)) & 0xff != 0 {
+        return Err(AddressError::InvalidPadding);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bech32m_reference_checksums() {
+        // Vecteurs valides de BIP350
+        for valid in ["a1lqfn3a", "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", "?1v759aa"] {
+            let separator = valid.rfind('1').unwrap();
+            let values: Vec<u8> = valid[separator + 1..]
+                .bytes()
+                .map(|c| CHARSET.iter().position(|x| *x == c).unwrap() as u8)
+                .collect();
+            assert!(verify_checksum(&valid[..separator], &values), "{}", valid);
+        }
+    }
+
+    #[test]
+    fn test_roundtrip_and_hex_compatibility() {
+        let public_key = *crate::crypto::keys::KeyPair::new().public_key();
+
+        let mainnet = encode_address(&public_key, Network::Mainnet);
+        let testnet = encode_address(&public_key, Network::Testnet);
+        assert!(mainnet.starts_with("mhl1"));
+        assert!(testnet.starts_with("tmhl1"));
+        assert_eq!(decode_address(&mainnet).unwrap(), (Network::Mainnet, public_key));
+        assert_eq!(decode_address(&testnet.to_uppercase()).unwrap(), (Network::Testnet, public_key));
+
+        assert_eq!(parse_address(&mainnet, Network::Mainnet).unwrap(), public_key);
+        assert_eq!(parse_address(&testnet, Network::Testnet).unwrap(), public_key);
+        assert_eq!(parse_address(&hex::encode(public_key), Network::Testnet).unwrap(), public_key);
+        assert_eq!(parse_address(&hex::encode_upper(public_key), Network::Mainnet).unwrap(), public_key);
+
+        // Une adresse de test n'est pas acceptée sur le réseau principal
+        assert_eq!(
+            parse_address(&testnet, Network::Mainnet),
+            Err(AddressError::WrongNetwork { expected: Network::Mainnet, found: Network::Testnet })
+        );
+        assert_eq!("Testnet".parse::<Network>(), Ok(Network::Testnet));
+    }
+
+    #[test]
+    fn test_typo_is_detected_and_located() {
+        let address = encode_address(&[7u8; 32], Network::Mainnet);
+        let position = 20;
+        let mut typo = address.clone().into_bytes();
+        typo[position] = if typo[position] == b'q' { b'p' } else { b'q' };
+        let typo = String::from_utf8(typo).unwrap();
+
+        match parse_address(&typo, Network::Mainnet) {
+            Err(AddressError::InvalidChecksum { error_positions }) => {
+                assert!(error_positions.contains(&position));
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert_eq!(
+            parse_address("mhl1qqqbqqq", Network::Mainnet),
+            Err(AddressError::InvalidCharacter { position: 7, character: 'b' })
+        );
+        assert_eq!(parse_address("Mhl1qqqq", Network::Mainnet), Err(AddressError::MixedCase));
+        assert!(matches!(parse_address("btc1qqqq", Network::Mainnet), Err(AddressError::UnknownPrefix(_))));
+    }
+
+    #[test]
+    fn test_deserialize_accepts_all_forms() {
+        #[derive(Deserialize)]
+        struct Target {
+            #[serde(deserialize_with = "deserialize")]
+            address: PublicKey,
+        }
+
+        let public_key = [42u8; 32];
+        let forms = [
+            serde_json::json!({ "address": public_key }),
+            serde_json::json!({ "address": hex::encode(public_key) }),
+            serde_json::json!({ "address": encode_address(&public_key, Network::Testnet) }),
+        ];
+        for form in forms {
+            let target: Target = serde_json::from_value(form).unwrap();
+            assert_eq!(target.address, public_key);
+        }
+        assert!(serde_json::from_value::<Target>(serde_json::json!({ "address": "mhl1xyz" })).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+//! Dérivation du wallet à partir de facteurs biométriques
+//!
+//! Schéma v2: la clé n'est plus le hash biométrique lui-même. Trois facteurs
+//! sont combinés par Argon2id (avec un sel propre à l'enrôlement):
+//!
+//! - le hash biométrique fourni par la plateforme;
+//! - un secret aléatoire de 32 octets conservé par l'appareil (Keystore
+//!   Android, Keychain iOS);
+//! - le code PIN de l'utilisateur.
+//!
+//! Le résultat est l'entropie d'une phrase BIP39 de 24 mots, remise à
+//! l'utilisateur à l'enrôlement: c'est le chemin de récupération (perte de
+//! l'appareil, changement de biométrie) via `Wallet::restore`. Changer l'un
+//! des facteurs donne une nouvelle clé.
+//!
+//! L'enregistrement d'enrôlement ne contient aucun secret: version du
+//! schéma, paramètres de dérivation, sel et adresse attendue (qui permet de
+//! détecter un mauvais PIN). Le schéma v1 (hash biométrique utilisé
+//! directement comme seed) n'est conservé que pour migrer les anciens
+//! wallets.
+
+use std::fs;
+use std::path::Path;
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use crate::wallet::keystore::write_private_file;
+use crate::wallet::{KdfParams, Mnemonic, MnemonicLanguage, Wallet, WalletError};
+
+/// Schéma historique: hash biométrique utilisé comme seed
+pub const LEGACY_BIOMETRIC_SCHEME: u32 = 1;
+
+/// Schéma courant: biométrie + secret d'appareil + PIN via Argon2id
+pub const BIOMETRIC_SCHEME: u32 = 2;
+
+/// Longueur minimale du code PIN
+pub const MIN_PIN_LENGTH: usize = 6;
+
+/// Domaine de séparation de la dérivation v2
+const DERIVATION_DOMAIN: &[u8] = b"mahala-biometric-v2";
+
+const SALT_LEN: usize = 16;
+
+/// Facteurs présentés pour dériver le wallet
+pub struct BiometricFactors<'a> {
+    /// Hash biométrique fourni par la plateforme
+    pub biometric_hash: &'a [u8; 32],
+    /// Secret conservé par l'appareil
+    pub device_secret: &'a [u8; 32],
+    /// Code PIN de l'utilisateur
+    pub pin: &'a str,
+}
+
+/// Générer un nouveau secret d'appareil
+pub fn generate_device_secret() -> [u8; 32] {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    secret
+}
+
+/// Enrôlement biométrique (sans secret, stockable en clair)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BiometricEnrollment {
+    /// Version du schéma de dérivation
+    pub version: u32,
+    /// Adresse attendue (hex)
+    pub address: String,
+    /// Paramètres Argon2id/scrypt (v2)
+    #[serde(default)]
+    pub kdf: Option<KdfParams>,
+    /// Sel de dérivation en hex (v2)
+    #[serde(default)]
+    pub salt: Option<String>,
+    /// Langue de la phrase de récupération (v2)
+    #[serde(default)]
+    pub language: Option<MnemonicLanguage>,
+}
+
+/// Résultat d'un enrôlement
+#[derive(Debug, Clone)]
+pub struct EnrolledWallet {
+    /// Wallet dérivé
+    pub wallet: Wallet,
+    /// Enregistrement à conserver sur l'appareil
+    pub enrollment: BiometricEnrollment,
+    /// Phrase de récupération à faire noter à l'utilisateur
+    pub recovery: Mnemonic,
+}
+
+impl BiometricEnrollment {
+    /// Enrôler un nouveau wallet avec le schéma courant
+    pub fn enroll(
+        factors: &BiometricFactors,
+        language: MnemonicLanguage,
+    ) -> Result<EnrolledWallet, WalletError> {
+        Self::enroll_with(factors, language, KdfParams::default())
+    }
+
+    /// Enrôler avec des paramètres de dérivation explicites
+    pub fn enroll_with(
+        factors: &BiometricFactors,
+        language: MnemonicLanguage,
+        kdf: KdfParams,
+    ) -> Result<EnrolledWallet, WalletError> {
+        if factors.pin.chars().count() < MIN_PIN_LENGTH {
+            return Err(WalletError::WeakPin(MIN_PIN_LENGTH));
+        }
+
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let recovery = derive_recovery(factors, &kdf, &salt, language)?;
+        let wallet = Wallet::from_mnemonic(&recovery, "", 0);
+        let enrollment = Self {
+            version: BIOMETRIC_SCHEME,
+            address: hex::encode(wallet.address()),
+            kdf: Some(kdf),
+            salt: Some(hex::encode(salt)),
+            language: Some(language),
+        };
+
+        Ok(EnrolledWallet { wallet, enrollment, recovery })
+    }
+
+    /// Enregistrement d'un wallet créé avec le schéma v1 (à migrer)
+    pub fn legacy(biometric_hash: &[u8; 32]) -> Self {
+        let wallet = Wallet::from_seed(biometric_hash);
+        Self {
+            version: LEGACY_BIOMETRIC_SCHEME,
+            address: hex::encode(wallet.address()),
+            kdf: None,
+            salt: None,
+            language: None,
+        }
+    }
+
+    /// Dériver le wallet enrôlé à partir des facteurs présentés
+    pub fn unlock(&self, factors: &BiometricFactors) -> Result<Wallet, WalletError> {
+        let wallet = match self.version {
+            LEGACY_BIOMETRIC_SCHEME => Wallet::from_seed(factors.biometric_hash),
+            BIOMETRIC_SCHEME => {
+                let (kdf, salt, language) = self.v2_params()?;
+                let recovery = derive_recovery(factors, &kdf, &salt, language)?;
+                Wallet::from_mnemonic(&recovery, "", 0)
+            }
+            version => return Err(WalletError::UnsupportedScheme(version)),
+        };
+
+        if hex::encode(wallet.address()) != self.address {
+            return Err(WalletError::BiometricMismatch);
+        }
+        Ok(wallet)
+    }
+
+    /// Retrouver la phrase de récupération (facteurs requis)
+    pub fn recovery_phrase(&self, factors: &BiometricFactors) -> Result<Mnemonic, WalletError> {
+        if self.version != BIOMETRIC_SCHEME {
+            return Err(WalletError::UnsupportedScheme(self.version));
+        }
+        let (kdf, salt, language) = self.v2_params()?;
+        let recovery = derive_recovery(factors, &kdf, &salt, language)?;
+
+        if hex::encode(Wallet::from_mnemonic(&recovery, "", 0).address()) != self.address {
+            return Err(WalletError::BiometricMismatch);
+        }
+        Ok(recovery)
+    }
+
+    /// L'enrôlement utilise-t-il un schéma obsolète ?
+    ///
+    /// Migrer change l'adresse: les fonds de l'ancien wallet doivent être
+    /// transférés vers le nouveau.
+    pub fn needs_upgrade(&self) -> bool {
+        self.version != BIOMETRIC_SCHEME
+    }
+
+    /// Charger un enrôlement depuis un fichier
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, WalletError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| WalletError::Enrollment(e.to_string()))?;
+        let enrollment: Self = serde_json::from_str(&content)
+            .map_err(|e| WalletError::Enrollment(e.to_string()))?;
+
+        if enrollment.version > BIOMETRIC_SCHEME {
+            return Err(WalletError::UnsupportedScheme(enrollment.version));
+        }
+        Ok(enrollment)
+    }
+
+    /// Sauvegarder l'enrôlement
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), WalletError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| WalletError::Enrollment(e.to_string()))?;
+        write_private_file(path.as_ref(), json.as_bytes())
+            .map_err(|e| WalletError::Enrollment(e.to_string()))
+    }
+
+    fn v2_params(&self) -> Result<(KdfParams, Vec<u8>, MnemonicLanguage), WalletError> {
+        let missing = |field: &str| WalletError::Enrollment(format!("missing {}", field));
+
+        let kdf = self.kdf.ok_or_else(|| missing("kdf"))?;
+        let salt = self.salt.as_deref()
+            .ok_or_else(|| missing("salt"))
+            .and_then(|salt| {
+                hex::decode(salt).map_err(|_| WalletError::Enrollment("invalid salt".to_string()))
+            })?;
+        let language = self.language.ok_or_else(|| missing("language"))?;
+        Ok((kdf, salt, language))
+    }
+}
+
+/// Dérivation v2: Argon2id(domaine || biométrie || secret d'appareil || PIN)
+fn derive_recovery(
+    factors: &BiometricFactors,
+    kdf: &KdfParams,
+    salt: &[u8],
+    language: MnemonicLanguage,
+) -> Result<Mnemonic, WalletError> {
+    let mut input = Vec::with_capacity(DERIVATION_DOMAIN.len() + 64 + factors.pin.len());
+    input.extend_from_slice(DERIVATION_DOMAIN);
+    input.extend_from_slice(factors.biometric_hash);
+    input.extend_from_slice(factors.device_secret);
+    input.extend_from_slice(factors.pin.as_bytes());
+
+    let entropy = kdf.derive_key(&input, salt)
+        .map_err(|e| WalletError::Enrollment(e.to_string()))?;
+    Mnemonic::from_entropy(language, &entropy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAST_ARGON2: KdfParams = KdfParams::Argon2id { m_cost: 64, t_cost: 1, p_cost: 1 };
+
+    #[test]
+    fn test_enroll_unlock_and_recover() {
+        let biometric_hash = [1u8; 32];
+        let device_secret = generate_device_secret();
+        let factors = BiometricFactors { biometric_hash: &biometric_hash, device_secret: &device_secret, pin: "482913" };
+
+        let enrolled = BiometricEnrollment::enroll_with(&factors, MnemonicLanguage::French, FAST_ARGON2).unwrap();
+        assert_ne!(enrolled.wallet.address(), Wallet::from_seed(&biometric_hash).address());
+        assert_eq!(enrolled.recovery.word_count(), 24);
+
+        let unlocked = enrolled.enrollment.unlock(&factors).unwrap();
+        assert_eq!(unlocked.address(), enrolled.wallet.address());
+
+        // Récupération sans l'appareil: la phrase suffit
+        let restored = Wallet::restore(&enrolled.recovery.phrase(), "", 0).unwrap();
+        assert_eq!(restored.address(), enrolled.wallet.address());
+        assert_eq!(enrolled.enrollment.recovery_phrase(&factors).unwrap(), enrolled.recovery);
+    }
+
+    #[test]
+    fn test_each_factor_is_required() {
+        let biometric_hash = [1u8; 32];
+        let device_secret = [2u8; 32];
+        let factors = BiometricFactors { biometric_hash: &biometric_hash, device_secret: &device_secret, pin: "482913" };
+        let enrolled = BiometricEnrollment::enroll_with(&factors, MnemonicLanguage::English, FAST_ARGON2).unwrap();
+        let enrollment = enrolled.enrollment;
+
+        let other = [3u8; 32];
+        for wrong in [
+            BiometricFactors { biometric_hash: &other, ..factors },
+            BiometricFactors { device_secret: &other, ..factors },
+            BiometricFactors { pin: "482914", ..factors },
+        ] {
+            assert!(matches!(enrollment.unlock(&wrong), Err(WalletError::BiometricMismatch)));
+        }
+
+        let weak = BiometricFactors { pin: "1234", ..factors };
+        assert!(matches!(
+            BiometricEnrollment::enroll_with(&weak, MnemonicLanguage::English, FAST_ARGON2),
+            Err(WalletError::WeakPin(_))
+        ));
+    }
+
+    #[test]
+    fn test_legacy_enrollment_needs_upgrade() {
+        let biometric_hash = [9u8; 32];
+        let device_secret = [0u8; 32];
+        let factors = BiometricFactors { biometric_hash: &biometric_hash, device_secret: &device_secret, pin: "" };
+
+        let legacy = BiometricEnrollment::legacy(&biometric_hash);
+        assert!(legacy.needs_upgrade());
+        assert_eq!(
+            legacy.unlock(&factors).unwrap().address(),
+            Wallet::from_seed(&biometric_hash).address()
+        );
+
+        let json = serde_json::to_string(&legacy).unwrap();
+        assert_eq!(serde_json::from_str::<BiometricEnrollment>(&json).unwrap(), legacy);
+    }
+}
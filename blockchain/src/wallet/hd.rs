@@ -0,0 +1,178 @@
+//! Dérivation hiérarchique SLIP-0010 pour Ed25519
+//!
+//! Ed25519 n'autorise que la dérivation durcie: chaque index du chemin est
+//! durci (`'`). Plusieurs comptes sont dérivés d'une même seed BIP39 selon
+//! `m/44'/<MAHALA_COIN_TYPE>'/<compte>'`.
+
+use std::fmt;
+use std::str::FromStr;
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+use crate::crypto::keys::KeyPair;
+use crate::wallet::WalletError;
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// Bit de dérivation durcie
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// Type de monnaie Mahala dans les chemins BIP44
+pub const MAHALA_COIN_TYPE: u32 = 1977;
+
+/// Clé de la dérivation racine (SLIP-0010)
+const ED25519_SEED_KEY: &[u8] = b"ed25519 seed";
+
+/// Chemin de dérivation (indices non durcis, tous dérivés durcis)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath {
+    indices: Vec<u32>,
+}
+
+impl DerivationPath {
+    /// Chemin standard d'un compte Mahala: `m/44'/1977'/<account>'`
+    pub fn account(account: u32) -> Self {
+        Self { indices: vec![44, MAHALA_COIN_TYPE, account] }
+    }
+
+    /// Indices du chemin (sans le bit de durcissement)
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = WalletError;
+
+    /// Parser un chemin `m/44'/1977'/0'` (`'` ou `h` pour durci)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || WalletError::InvalidDerivationPath(s.to_string());
+
+        let mut segments = s.trim().split('/');
+        if segments.next() != Some("m") {
+            return Err(invalid());
+        }
+
+        let indices = segments
+            .map(|segment| {
+                let index = segment
+                    .strip_suffix('\'')
+                    .or_else(|| segment.strip_suffix('h'))
+                    .ok_or_else(invalid)?;
+                index.parse::<u32>()
+                    .ok()
+                    .filter(|i| *i < HARDENED_OFFSET)
+                    .ok_or_else(invalid)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { indices })
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.indices {
+            write!(f, "/{}'", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Clé étendue (clé privée Ed25519 et chain code)
+#[derive(Clone)]
+pub struct ExtendedKey {
+    private_key: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    /// Clé maîtresse dérivée d'une seed (typiquement la seed BIP39 de 64 octets)
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self::from_hmac(ED25519_SEED_KEY, &[seed])
+    }
+
+    /// Dériver l'enfant durci d'index `index`
+    pub fn derive_hardened(&self, index: u32) -> Self {
+        let index = (index | HARDENED_OFFSET).to_be_bytes();
+        Self::from_hmac(&self.chain_code, &[&[0u8], &self.private_key, &index])
+    }
+
+    /// Dériver un chemin complet
+    pub fn derive_path(&self, path: &DerivationPath) -> Self {
+        path.indices()
+            .iter()
+            .fold(self.clone(), |key, index| key.derive_hardened(*index))
+    }
+
+    /// Paire de clés Ed25519 correspondante
+    pub fn keypair(&self) -> KeyPair {
+        KeyPair::from_seed(&self.private_key)
+    }
+
+    /// Chain code
+    pub fn chain_code(&self) -> &[u8; 32] {
+        &self.chain_code
+    }
+
+    fn from_hmac(key: &[u8], parts: &[&[u8]]) -> Self {
+        let mut mac = HmacSha512::new_from_slice(key)
+            .expect("HMAC accepts keys of any length");
+        for part in parts {
+            mac.update(part);
+        }
+        let output = mac.finalize().into_bytes();
+
+        let mut private_key = [0u8; 32];
+        let mut chain_code = [0u8; 32];
+        private_key.copy_from_slice(&output[..32]);
+        chain_code.copy_from_slice(&output[32..]);
+        Self { private_key, chain_code }
+    }
+}
+
+impl fmt::Debug for ExtendedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Ne jamais afficher la clé privée
+        f.debug_struct("ExtendedKey")
+            .field("public_key", &hex::encode(self.keypair().public_key()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slip10_vector_1() {
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let master = ExtendedKey::from_seed(&seed);
+        assert_eq!(hex::encode(master.private_key), "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7");
+        assert_eq!(hex::encode(master.chain_code), "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb");
+        assert_eq!(
+            hex::encode(master.keypair().public_key()),
+            "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed"
+        );
+
+        let child = master.derive_path(&"m/0'".parse().unwrap());
+        assert_eq!(hex::encode(child.private_key), "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3");
+        assert_eq!(hex::encode(child.chain_code), "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69");
+        assert_eq!(
+            hex::encode(child.keypair().public_key()),
+            "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c"
+        );
+    }
+
+    #[test]
+    fn test_derivation_path_parsing() {
+        let path: DerivationPath = "m/44'/1977'/3h".parse().unwrap();
+        assert_eq!(path, DerivationPath::account(3));
+        assert_eq!(path.to_string(), "m/44'/1977'/3'");
+
+        // Ed25519: pas de dérivation non durcie
+        assert!("m/44'/1977'/0".parse::<DerivationPath>().is_err());
+        assert!("44'/0'".parse::<DerivationPath>().is_err());
+        assert!("m/2147483648'".parse::<DerivationPath>().is_err());
+    }
+}
@@ -0,0 +1,340 @@
+//! Keystore chiffré pour les clés de wallet et de validateur
+//!
+//! Fichier JSON versionné: la clé privée Ed25519 est chiffrée avec
+//! ChaCha20-Poly1305 sous une clé dérivée du mot de passe (argon2id par
+//! défaut, scrypt accepté). L'en-tête (version, adresse) est authentifié
+//! comme donnée associée.
+//!
+//! Les anciens fichiers de clé en clair (clé privée en hex) se migrent avec
+//! `Keystore::migrate_plaintext`.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{ChaCha20Poly1305, Key, KeyInit, Nonce};
+use chacha20poly1305::aead::{Aead, Payload};
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::PrivateKey;
+use crate::crypto::keys::KeyPair;
+
+/// Version courante du format
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Chiffrement utilisé par la version courante
+const CIPHER: &str = "chacha20poly1305";
+
+/// Domaine des données associées
+const AAD_DOMAIN: &[u8] = b"mahala-keystore";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Paramètres de dérivation du mot de passe
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "algorithm", rename_all = "lowercase")]
+pub enum KdfParams {
+    /// Argon2id (mémoire en KiB)
+    Argon2id { m_cost: u32, t_cost: u32, p_cost: u32 },
+    /// scrypt (N = 2^log_n)
+    Scrypt { log_n: u8, r: u32, p: u32 },
+}
+
+impl Default for KdfParams {
+    /// Argon2id 19 MiB, 2 passes: supportable sur mobile d'entrée de gamme
+    fn default() -> Self {
+        KdfParams::Argon2id { m_cost: 19 * 1024, t_cost: 2, p_cost: 1 }
+    }
+}
+
+impl KdfParams {
+    /// Dériver une clé de 32 octets
+    pub(crate) fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<[u8; 32], KeystoreError> {
+        let mut key = [0u8; 32];
+        match *self {
+            KdfParams::Argon2id { m_cost, t_cost, p_cost } => {
+                let params = Params::new(m_cost, t_cost, p_cost, Some(key.len()))
+                    .map_err(|e| KeystoreError::Kdf(e.to_string()))?;
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password, salt, &mut key)
+                    .map_err(|e| KeystoreError::Kdf(e.to_string()))?;
+            }
+            KdfParams::Scrypt { log_n, r, p } => {
+                let params = scrypt::Params::new(log_n, r, p, key.len())
+                    .map_err(|e| KeystoreError::Kdf(e.to_string()))?;
+                scrypt::scrypt(password, salt, &params, &mut key)
+                    .map_err(|e| KeystoreError::Kdf(e.to_string()))?;
+            }
+        }
+        Ok(key)
+    }
+}
+
+/// Erreurs de keystore
+#[derive(Debug, Error)]
+pub enum KeystoreError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid keystore format: {0}")]
+    Format(String),
+    #[error("Unsupported keystore version: {0}")]
+    UnsupportedVersion(u32),
+    #[error("Unsupported cipher: {0}")]
+    UnsupportedCipher(String),
+    #[error("Key derivation failed: {0}")]
+    Kdf(String),
+    #[error("Wrong password or corrupted keystore")]
+    InvalidPassword,
+    #[error("Decrypted key does not match keystore address")]
+    AddressMismatch,
+}
+
+/// Keystore chiffré (champs binaires en hex)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    /// Version du format
+    pub version: u32,
+    /// Adresse (clé publique) en hex, lisible sans mot de passe
+    pub address: String,
+    /// Libellé libre (ex: "validateur", "compte 0")
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Paramètres de dérivation du mot de passe
+    pub kdf: KdfParams,
+    /// Sel de la dérivation
+    pub salt: String,
+    /// Algorithme de chiffrement
+    pub cipher: String,
+    /// Nonce du chiffrement
+    pub nonce: String,
+    /// Clé privée chiffrée (avec tag d'authentification)
+    pub ciphertext: String,
+}
+
+impl Keystore {
+    /// Chiffrer une paire de clés avec les paramètres par défaut
+    pub fn encrypt(keypair: &KeyPair, password: &str) -> Result<Self, KeystoreError> {
+        Self::encrypt_with(keypair, password, KdfParams::default())
+    }
+
+    /// Chiffrer une paire de clés avec des paramètres de dérivation choisis
+    pub fn encrypt_with(keypair: &KeyPair, password: &str, kdf: KdfParams) -> Result<Self, KeystoreError> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut salt);
+        OsRng.fill_bytes(&mut nonce);
+
+        let address = hex::encode(keypair.public_key());
+        let key = kdf.derive_key(password.as_bytes(), &salt)?;
+        let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .encrypt(Nonce::from_slice(&nonce), Payload {
+                msg: keypair.private_key(),
+                aad: &associated_data(KEYSTORE_VERSION, &address),
+            })
+            .map_err(|_| KeystoreError::Format("encryption failed".to_string()))?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            address,
+            label: None,
+            kdf,
+            salt: hex::encode(salt),
+            cipher: CIPHER.to_string(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Déchiffrer la paire de clés
+    pub fn decrypt(&self, password: &str) -> Result<KeyPair, KeystoreError> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(self.version));
+        }
+        if self.cipher != CIPHER {
+            return Err(KeystoreError::UnsupportedCipher(self.cipher.clone()));
+        }
+
+        let salt = decode_field("salt", &self.salt)?;
+        let nonce = decode_field("nonce", &self.nonce)?;
+        let ciphertext = decode_field("ciphertext", &self.ciphertext)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(KeystoreError::Format("invalid nonce length".to_string()));
+        }
+
+        let key = self.kdf.derive_key(password.as_bytes(), &salt)?;
+        let plaintext = ChaCha20Poly1305::new(Key::from_slice(&key))
+            .decrypt(Nonce::from_slice(&nonce), Payload {
+                msg: &ciphertext,
+                aad: &associated_data(self.version, &self.address),
+            })
+            .map_err(|_| KeystoreError::InvalidPassword)?;
+
+        let private_key: PrivateKey = plaintext.try_into()
+            .map_err(|_| KeystoreError::Format("invalid key length".to_string()))?;
+        let keypair = KeyPair::from_seed(&private_key);
+        if hex::encode(keypair.public_key()) != self.address {
+            return Err(KeystoreError::AddressMismatch);
+        }
+
+        Ok(keypair)
+    }
+
+    /// Changer le mot de passe (nouveau sel et nouveau nonce)
+    pub fn change_password(&self, old_password: &str, new_password: &str) -> Result<Self, KeystoreError> {
+        let keypair = self.decrypt(old_password)?;
+        let mut keystore = Self::encrypt_with(&keypair, new_password, self.kdf)?;
+        keystore.label = self.label.clone();
+        Ok(keystore)
+    }
+
+    /// Le keystore utilise-t-il des paramètres différents des paramètres courants ?
+    pub fn needs_upgrade(&self) -> bool {
+        self.version != KEYSTORE_VERSION || self.kdf != KdfParams::default()
+    }
+
+    /// Re-chiffrer avec la version et les paramètres courants
+    pub fn upgrade(&self, password: &str) -> Result<Self, KeystoreError> {
+        let keypair = self.decrypt(password)?;
+        let mut keystore = Self::encrypt(&keypair, password)?;
+        keystore.label = self.label.clone();
+        Ok(keystore)
+    }
+
+    /// Migrer un ancien fichier de clé en clair (clé privée en hex)
+    ///
+    /// Le fichier d'origine n'est pas modifié: à l'appelant de le supprimer
+    /// une fois le keystore sauvegardé.
+    pub fn migrate_plaintext(path: impl AsRef<Path>, password: &str) -> Result<Self, KeystoreError> {
+        let content = fs::read_to_string(path)?;
+        let bytes = hex::decode(content.trim())
+            .map_err(|e| KeystoreError::Format(e.to_string()))?;
+        let private_key: PrivateKey = bytes.try_into()
+            .map_err(|_| KeystoreError::Format("legacy key must be 32 bytes".to_string()))?;
+
+        Self::encrypt(&KeyPair::from_seed(&private_key), password)
+    }
+
+    /// Charger un keystore depuis un fichier
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, KeystoreError> {
+        let content = fs::read_to_string(path)?;
+        let keystore: Self = serde_json::from_str(&content)
+            .map_err(|e| KeystoreError::Format(e.to_string()))?;
+
+        if keystore.version > KEYSTORE_VERSION {
+            return Err(KeystoreError::UnsupportedVersion(keystore.version));
+        }
+        Ok(keystore)
+    }
+
+    /// Sauvegarder le keystore (écriture atomique, lisible par le seul propriétaire)
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), KeystoreError> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| KeystoreError::Format(e.to_string()))?;
+
+        write_private_file(path, json.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Écrire un fichier de manière atomique, lisible par le seul propriétaire
+pub(crate) fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)
+}
+
+/// Données associées: version et adresse ne peuvent être modifiées
+fn associated_data(version: u32, address: &str) -> Vec<u8> {
+    let mut aad = AAD_DOMAIN.to_vec();
+    aad.extend_from_slice(&version.to_le_bytes());
+    aad.extend_from_slice(address.as_bytes());
+    aad
+}
+
+fn decode_field(name: &str, value: &str) -> Result<Vec<u8>, KeystoreError> {
+    hex::decode(value).map_err(|_| KeystoreError::Format(format!("invalid {}", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paramètres faibles pour des tests rapides
+    const FAST_ARGON2: KdfParams = KdfParams::Argon2id { m_cost: 64, t_cost: 1, p_cost: 1 };
+    const FAST_SCRYPT: KdfParams = KdfParams::Scrypt { log_n: 4, r: 8, p: 1 };
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mahala-{}-{}.json", name, hex::encode(KeyPair::new().public_key())))
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        for kdf in [FAST_ARGON2, FAST_SCRYPT] {
+            let keypair = KeyPair::new();
+            let keystore = Keystore::encrypt_with(&keypair, "correct horse", kdf).unwrap();
+
+            let decrypted = keystore.decrypt("correct horse").unwrap();
+            assert_eq!(decrypted.private_key(), keypair.private_key());
+            assert!(matches!(keystore.decrypt("wrong"), Err(KeystoreError::InvalidPassword)));
+        }
+    }
+
+    #[test]
+    fn test_tampered_header_is_rejected() {
+        let keystore = Keystore::encrypt_with(&KeyPair::new(), "pw", FAST_ARGON2).unwrap();
+
+        let mut tampered = keystore.clone();
+        tampered.address = hex::encode(KeyPair::new().public_key());
+        assert!(matches!(tampered.decrypt("pw"), Err(KeystoreError::InvalidPassword)));
+
+        let mut future = keystore;
+        future.version = KEYSTORE_VERSION + 1;
+        assert!(matches!(future.decrypt("pw"), Err(KeystoreError::UnsupportedVersion(_))));
+    }
+
+    #[test]
+    fn test_save_load_and_change_password() {
+        let keypair = KeyPair::new();
+        let path = temp_path("keystore");
+        let mut keystore = Keystore::encrypt_with(&keypair, "old", FAST_ARGON2).unwrap();
+        keystore.label = Some("validateur".to_string());
+        keystore.save(&path).unwrap();
+
+        let loaded = Keystore::load(&path).unwrap();
+        let changed = loaded.change_password("old", "new").unwrap();
+        assert_eq!(changed.label.as_deref(), Some("validateur"));
+        assert!(changed.decrypt("old").is_err());
+        assert_eq!(changed.decrypt("new").unwrap().public_key(), keypair.public_key());
+
+        // Les paramètres de test sont plus faibles que les paramètres courants
+        assert!(changed.needs_upgrade());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_migrate_plaintext_key() {
+        let keypair = KeyPair::new();
+        let legacy = temp_path("legacy");
+        fs::write(&legacy, format!("{}\n", hex::encode(keypair.private_key()))).unwrap();
+
+        let keystore = Keystore::migrate_plaintext(&legacy, "pw").unwrap();
+        assert!(!keystore.needs_upgrade());
+        assert_eq!(keystore.address, hex::encode(keypair.public_key()));
+        assert_eq!(keystore.decrypt("pw").unwrap().private_key(), keypair.private_key());
+        fs::remove_file(&legacy).unwrap();
+    }
+}
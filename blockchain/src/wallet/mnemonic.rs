@@ -0,0 +1,152 @@
+//! Phrases mnémoniques BIP39 (sauvegarde et restauration des wallets)
+//!
+//! Listes de mots française et anglaise. La phrase donne une seed de 64
+//! octets (PBKDF2, avec une passphrase optionnelle) à partir de laquelle les
+//! comptes sont dérivés (voir `wallet::hd`).
+
+use rand::RngCore;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use crate::wallet::WalletError;
+
+/// Nombres de mots acceptés (128 à 256 bits d'entropie)
+pub const VALID_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Nombre de mots par défaut (256 bits d'entropie)
+pub const DEFAULT_WORD_COUNT: usize = 24;
+
+/// Langue de la liste de mots
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum MnemonicLanguage {
+    French,
+    English,
+}
+
+impl MnemonicLanguage {
+    fn to_bip39(self) -> bip39::Language {
+        match self {
+            MnemonicLanguage::French => bip39::Language::French,
+            MnemonicLanguage::English => bip39::Language::English,
+        }
+    }
+
+    fn from_bip39(language: bip39::Language) -> Option<Self> {
+        // Comparaison plutôt que `match`: les autres langues dépendent des
+        // features activées ailleurs dans le graphe de dépendances
+        [MnemonicLanguage::French, MnemonicLanguage::English]
+            .into_iter()
+            .find(|supported| supported.to_bip39() == language)
+    }
+}
+
+/// Phrase mnémonique validée (checksum BIP39 correct)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mnemonic {
+    inner: bip39::Mnemonic,
+}
+
+impl Mnemonic {
+    /// Générer une nouvelle phrase aléatoire
+    pub fn generate(language: MnemonicLanguage, word_count: usize) -> Result<Self, WalletError> {
+        if !VALID_WORD_COUNTS.contains(&word_count) {
+            return Err(WalletError::InvalidWordCount(word_count));
+        }
+
+        // 11 bits par mot dont 1 bit de checksum pour 32 bits d'entropie
+        let mut entropy = [0u8; 32];
+        let entropy_len = word_count * 4 / 3;
+        OsRng.fill_bytes(&mut entropy[..entropy_len]);
+
+        Self::from_entropy(language, &entropy[..entropy_len])
+    }
+
+    /// Construire la phrase correspondant à une entropie donnée
+    pub fn from_entropy(language: MnemonicLanguage, entropy: &[u8]) -> Result<Self, WalletError> {
+        bip39::Mnemonic::from_entropy_in(language.to_bip39(), entropy)
+            .map(|inner| Self { inner })
+            .map_err(|e| WalletError::InvalidMnemonic(e.to_string()))
+    }
+
+    /// Valider une phrase saisie par l'utilisateur (langue détectée)
+    ///
+    /// Les espaces superflus et la casse sont ignorés.
+    pub fn parse(phrase: &str) -> Result<Self, WalletError> {
+        let normalized = phrase
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let inner = bip39::Mnemonic::parse(normalized)
+            .map_err(|e| WalletError::InvalidMnemonic(e.to_string()))?;
+
+        if MnemonicLanguage::from_bip39(inner.language()).is_none() {
+            return Err(WalletError::InvalidMnemonic("unsupported language".to_string()));
+        }
+
+        Ok(Self { inner })
+    }
+
+    /// Langue de la phrase
+    pub fn language(&self) -> MnemonicLanguage {
+        MnemonicLanguage::from_bip39(self.inner.language())
+            .expect("only supported languages are constructed")
+    }
+
+    /// Mots de la phrase
+    pub fn words(&self) -> Vec<&'static str> {
+        self.inner.words().collect()
+    }
+
+    /// Nombre de mots
+    pub fn word_count(&self) -> usize {
+        self.inner.word_count()
+    }
+
+    /// Seed BIP39 de 64 octets (passphrase vide si aucune)
+    pub fn to_seed(&self, passphrase: &str) -> [u8; 64] {
+        self.inner.to_seed(passphrase)
+    }
+
+    /// Phrase sous forme de texte (mots séparés par des espaces)
+    pub fn phrase(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bip39_reference_vector() {
+        let mnemonic = Mnemonic::from_entropy(MnemonicLanguage::English, &[0u8; 16]).unwrap();
+        assert_eq!(
+            mnemonic.phrase(),
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        );
+        assert_eq!(
+            hex::encode(mnemonic.to_seed("TREZOR")),
+            "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04"
+        );
+    }
+
+    #[test]
+    fn test_generate_and_parse_french() {
+        let mnemonic = Mnemonic::generate(MnemonicLanguage::French, 12).unwrap();
+        assert_eq!(mnemonic.word_count(), 12);
+
+        let typed = format!("  {}  ", mnemonic.phrase().to_uppercase());
+        let parsed = Mnemonic::parse(&typed).unwrap();
+        assert_eq!(parsed.language(), MnemonicLanguage::French);
+        assert_eq!(parsed.to_seed(""), mnemonic.to_seed(""));
+
+        assert!(Mnemonic::generate(MnemonicLanguage::French, 13).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_checksum() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert!(Mnemonic::parse(phrase).is_err());
+    }
+}
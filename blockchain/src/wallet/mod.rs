@@ -0,0 +1,177 @@
+//! Gestion des wallets Mahala
+//!
+//! Un wallet peut être sauvegardé sous forme de phrase mnémonique BIP39 et
+//! restauré sur un autre appareil; plusieurs comptes sont dérivés de la même
+//! phrase (SLIP-0010). La clé privée est conservée sur disque dans un
+//! keystore chiffré par mot de passe. Sur mobile, le wallet peut aussi être
+//! dérivé de la biométrie combinée à un secret d'appareil et à un PIN
+//! (voir `biometric`).
+
+pub mod address;
+pub mod biometric;
+pub mod hd;
+pub mod keystore;
+pub mod mnemonic;
+
+pub use address::{AddressError, Network, decode_address, encode_address, parse_address};
+pub use biometric::{BiometricEnrollment, BiometricFactors, EnrolledWallet};
+pub use hd::{DerivationPath, ExtendedKey};
+pub use keystore::{KdfParams, Keystore, KeystoreError};
+pub use mnemonic::{Mnemonic, MnemonicLanguage};
+
+use thiserror::Error;
+use crate::PublicKey;
+use crate::crypto::keys::KeyPair;
+
+/// Erreurs de wallet
+#[derive(Debug, Error)]
+pub enum WalletError {
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+    #[error("Invalid word count: {0}")]
+    InvalidWordCount(usize),
+    #[error("Invalid derivation path: {0}")]
+    InvalidDerivationPath(String),
+    #[error("PIN must be at least {0} characters")]
+    WeakPin(usize),
+    #[error("Biometric factors do not match the enrolled wallet")]
+    BiometricMismatch,
+    #[error("Unsupported biometric derivation scheme: {0}")]
+    UnsupportedScheme(u32),
+    #[error("Invalid biometric enrollment: {0}")]
+    Enrollment(String),
+}
+
+/// Adresse d'un wallet (alias pour PublicKey)
+pub type WalletAddress = PublicKey;
+
+/// Wallet Mahala
+#[derive(Debug, Clone)]
+pub struct Wallet {
+    /// Paire de clés
+    pub keypair: KeyPair,
+    
+    /// Adresse du wallet (clé publique)
+    pub address: WalletAddress,
+}
+
+impl Wallet {
+    /// Créer un nouveau wallet
+    pub fn new() -> Self {
+        let keypair = KeyPair::new();
+        Self {
+            address: *keypair.public_key(),
+            keypair,
+        }
+    }
+
+    /// Créer un wallet depuis une seed (déterministe)
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        let keypair = KeyPair::from_seed(seed);
+        Self {
+            address: *keypair.public_key(),
+            keypair,
+        }
+    }
+
+    /// Créer un wallet depuis une paire de clés existante
+    pub fn from_keypair(keypair: KeyPair) -> Self {
+        Self {
+            address: *keypair.public_key(),
+            keypair,
+        }
+    }
+
+    /// Ouvrir un wallet depuis son keystore chiffré
+    pub fn from_keystore(keystore: &Keystore, password: &str) -> Result<Self, KeystoreError> {
+        keystore.decrypt(password).map(Self::from_keypair)
+    }
+
+    /// Chiffrer le wallet dans un keystore
+    pub fn to_keystore(&self, password: &str) -> Result<Keystore, KeystoreError> {
+        Keystore::encrypt(&self.keypair, password)
+    }
+
+    /// Créer le wallet d'un compte dérivé d'une phrase mnémonique
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str, account: u32) -> Self {
+        Self::from_mnemonic_path(mnemonic, passphrase, &DerivationPath::account(account))
+    }
+
+    /// Créer un wallet dérivé d'une phrase mnémonique selon un chemin quelconque
+    pub fn from_mnemonic_path(mnemonic: &Mnemonic, passphrase: &str, path: &DerivationPath) -> Self {
+        let master = ExtendedKey::from_seed(&mnemonic.to_seed(passphrase));
+        let keypair = master.derive_path(path).keypair();
+        Self {
+            address: *keypair.public_key(),
+            keypair,
+        }
+    }
+
+    /// Restaurer un wallet depuis la phrase saisie par l'utilisateur
+    pub fn restore(phrase: &str, passphrase: &str, account: u32) -> Result<Self, WalletError> {
+        let mnemonic = Mnemonic::parse(phrase)?;
+        Ok(Self::from_mnemonic(&mnemonic, passphrase, account))
+    }
+
+    /// Obtenir l'adresse du wallet
+    pub fn address(&self) -> &WalletAddress {
+        &self.address
+    }
+
+    /// Obtenir la clé publique
+    pub fn public_key(&self) -> &PublicKey {
+        &self.address
+    }
+
+    /// Adresse lisible avec somme de contrôle (`mhl1…` / `tmhl1…`)
+    pub fn encoded_address(&self, network: Network) -> String {
+        encode_address(&self.address, network)
+    }
+
+    /// Obtenir la clé privée (attention: sensible!)
+    pub fn private_key(&self) -> &crate::PrivateKey {
+        self.keypair.private_key()
+    }
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_creation() {
+        let wallet = Wallet::new();
+        assert_eq!(wallet.address.len(), 32);
+    }
+
+    #[test]
+    fn test_wallet_from_seed() {
+        let seed = [0u8; 32];
+        let wallet1 = Wallet::from_seed(&seed);
+        let wallet2 = Wallet::from_seed(&seed);
+        
+        assert_eq!(wallet1.address, wallet2.address);
+    }
+
+    #[test]
+    fn test_wallet_restore_accounts() {
+        let mnemonic = Mnemonic::generate(MnemonicLanguage::French, 24).unwrap();
+        let account0 = Wallet::from_mnemonic(&mnemonic, "", 0);
+        let account1 = Wallet::from_mnemonic(&mnemonic, "", 1);
+        assert_ne!(account0.address, account1.address);
+
+        let restored = Wallet::restore(&mnemonic.phrase(), "", 1).unwrap();
+        assert_eq!(restored.address, account1.address);
+
+        // Une passphrase différente donne un autre wallet
+        let other = Wallet::restore(&mnemonic.phrase(), "secret", 0).unwrap();
+        assert_ne!(other.address, account0.address);
+    }
+}
+
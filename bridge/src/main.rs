@@ -14,6 +14,7 @@ use actix_cors::Cors;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use mahala_blockchain::wallet::{self, Network};
 
 use market_maker::{MarketMaker, ExchangeDirection, ExchangeRequest, ExchangeResult};
 use reserves::Reserves;
@@ -33,6 +34,12 @@ async fn main() -> std::io::Result<()> {
     // Créer le gestionnaire de sécurité
     let security = Arc::new(SecurityManager::new(1000.0, 5000.0)); // 1k/jour, 5k/mois
     
+    // Réseau Mahala des adresses acceptées (MAHALA_NETWORK, réseau principal par défaut)
+    let network: Network = match std::env::var("MAHALA_NETWORK") {
+        Ok(value) => value.parse().map_err(|e: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?,
+        Err(_) => Network::Mainnet,
+    };
+    
     println!("Starting Mahala Bridge on 0.0.0.0:8081");
     
     HttpServer::new(move || {
@@ -40,6 +47,7 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(reserves.clone()))
             .app_data(web::Data::new(market_maker.clone()))
             .app_data(web::Data::new(security.clone()))
+            .app_data(web::Data::new(network))
             .wrap(Logger::default())
             .wrap(
                 Cors::default()
@@ -128,9 +136,22 @@ async fn execute_exchange(
     req: web::Json<ExchangeRequest>,
     market_maker: web::Data<Arc<RwLock<MarketMaker>>>,
     security: web::Data<Arc<SecurityManager>>,
+    network: web::Data<Network>,
 ) -> ActixResult<HttpResponse> {
+    // Forme canonique (hex) de l'adresse: les limites s'appliquent par clé,
+    // quelle que soit la forme saisie (bech32m du réseau ou hex)
+    let wallet_address = match wallet::parse_address(&req.wallet_address, **network) {
+        Ok(public_key) => hex::encode(public_key),
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": format!("Invalid wallet address: {}", e),
+                "error_positions": e.positions(),
+            })));
+        }
+    };
+
     // Vérifier les limites de sécurité
-    if let Err(e) = security.check_exchange(&req.wallet_address, req.amount).await {
+    if let Err(e) = security.check_exchange(&wallet_address, req.amount).await {
         return Ok(HttpResponse::BadRequest().json(serde_json::json!({
             "error": e.to_string()
         })));
@@ -30,7 +30,7 @@ class MahalaWallet extends CommonObject
     public $wallet_type;
 
     /**
-     * @var string Adresse du wallet
+     * @var string Adresse du wallet (Mahala: bech32m du réseau du nœud, mhl1… ou tmhl1…)
      */
     public $wallet_address;
 
@@ -73,6 +73,11 @@ class MahalaWallet extends CommonObject
 
         $error = 0;
 
+        // Adresse Mahala validée et enregistrée sous sa forme bech32m
+        if ($this->wallet_type == 'mahala' && $this->normalizeAddress() < 0) {
+            return -1;
+        }
+
         $this->date_creation = dol_now();
         $this->fk_user_creat = $user->id;
 
@@ -146,6 +151,48 @@ class MahalaWallet extends CommonObject
         }
     }
 
+    /**
+     * Valider l'adresse Mahala auprès du nœud et la remplacer par sa forme
+     * bech32m pour le réseau du nœud
+     *
+     * Une adresse hexadécimale (ancien format) est convertie; une adresse
+     * d'un autre réseau ou mal saisie est refusée.
+     *
+     * @return int <0 si KO, >0 si OK
+     */
+    public function normalizeAddress()
+    {
+        global $conf;
+
+        if (empty($conf->global->MAHALA_NODE_URL)) {
+            $this->error = "Mahala node URL not configured";
+            return -1;
+        }
+
+        $url = rtrim($conf->global->MAHALA_NODE_URL, '/');
+        $api_url = $url."/address/".rawurlencode(trim($this->wallet_address));
+
+        $ch = curl_init($api_url);
+        curl_setopt($ch, CURLOPT_RETURNTRANSFER, true);
+        curl_setopt($ch, CURLOPT_TIMEOUT, 10);
+        $response = curl_exec($ch);
+        $http_code = curl_getinfo($ch, CURLINFO_HTTP_CODE);
+        curl_close($ch);
+
+        $data = $response ? json_decode($response, true) : null;
+        if ($http_code == 200 && !empty($data['valid']) && isset($data['address'])) {
+            $this->wallet_address = $data['address'];
+            return 1;
+        }
+
+        if (isset($data['error'])) {
+            $this->error = "Invalid Mahala address: ".$data['error'];
+        } else {
+            $this->error = "Failed to validate address";
+        }
+        return -1;
+    }
+
     /**
      * Synchroniser la balance avec la blockchain
      *
@@ -160,8 +207,19 @@ class MahalaWallet extends CommonObject
             return -1;
         }
 
+        // Wallet enregistré avant les adresses bech32m: convertir l'adresse
+        if ($this->wallet_type == 'mahala' && preg_match('/^[0-9a-fA-F]{64}$/', $this->wallet_address)) {
+            if ($this->normalizeAddress() < 0) {
+                return -1;
+            }
+            $sql = "UPDATE ".MAIN_DB_PREFIX."mahala_wallet SET";
+            $sql .= " wallet_address = '".$this->db->escape($this->wallet_address)."'";
+            $sql .= " WHERE rowid = ".(int) $this->id;
+            $this->db->query($sql);
+        }
+
         $url = rtrim($conf->global->MAHALA_NODE_URL, '/');
-        $api_url = $url."/blockchain/balance/".$this->wallet_address;
+        $api_url = $url."/blockchain/balance/".rawurlencode($this->wallet_address);
 
         // Appel API
         $ch = curl_init($api_url);
@@ -3,7 +3,7 @@ CREATE TABLE IF NOT EXISTS llx_mahala_wallet (
     rowid INTEGER AUTO_INCREMENT PRIMARY KEY,
     fk_soc INTEGER NOT NULL,
     wallet_type VARCHAR(10) NOT NULL DEFAULT 'mahala', -- 'mahala' ou 'june'
-    wallet_address VARCHAR(128) NOT NULL,
+    wallet_address VARCHAR(128) NOT NULL, -- Mahala: adresse bech32m (mhl1…/tmhl1…)
     balance DECIMAL(20, 8) DEFAULT 0.00000000,
     last_sync DATETIME,
     sync_enabled TINYINT(1) DEFAULT 1,
@@ -4,19 +4,38 @@
 //! Utilise des fonctions C simples pour l'interopérabilité
 
 use mahala_light_client::{LightClient, LightClientConfig};
-use mahala_blockchain::PublicKey;
+use mahala_blockchain::wallet::{self, biometric, BiometricFactors, MnemonicLanguage, Network};
+use mahala_blockchain::{PublicKey, Transaction};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
+use std::path::Path;
 
 /// Instance globale du light client (singleton)
 static mut CLIENT: Option<Arc<RwLock<LightClient>>> = None;
 static INIT: std::sync::Once = std::sync::Once::new();
 
-/// Initialiser le light client
+/// Initialiser le light client (réseau principal)
 #[no_mangle]
 pub extern "C" fn init_light_client(full_node_url: *const c_char) -> i32 {
+    unsafe { init_light_client_on_network(full_node_url, 0) }
+}
+
+/// Initialiser le light client pour un réseau
+/// `network`: 0 = réseau principal, 1 = réseau de test (les adresses d'un
+/// autre réseau sont refusées)
+///
+/// # Safety
+///
+/// `full_node_url` doit pointer vers une chaîne C valide (terminée par nul).
+#[no_mangle]
+pub unsafe extern "C" fn init_light_client_on_network(full_node_url: *const c_char, network: i32) -> i32 {
+    let network = match network {
+        0 => Network::Mainnet,
+        1 => Network::Testnet,
+        _ => return -1,
+    };
     let url = unsafe {
         match CStr::from_ptr(full_node_url).to_str() {
             Ok(s) => s.to_string(),
@@ -29,6 +48,7 @@ pub extern "C" fn init_light_client(full_node_url: *const c_char) -> i32 {
             full_node_url: url,
             sync_interval: 10,
             enable_consensus: true,
+            network,
         };
         let client = LightClient::new(config);
         unsafe {
@@ -47,35 +67,226 @@ fn get_client() -> Result<Arc<RwLock<LightClient>>, String> {
     }
 }
 
-/// Créer un wallet depuis un hash biométrique
+/// Réseau du client, pour lire les adresses saisies
+fn client_network() -> Result<Network, String> {
+    Ok(get_client()?.blocking_read().network())
+}
+
+/// Générer un secret d'appareil (32 octets en hex, 65 octets de buffer)
+/// À conserver dans le stockage sécurisé de la plateforme (Keystore, Keychain)
+///
+/// # Safety
+///
+/// `output` doit pointer vers un buffer inscriptible d'au moins
+/// `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn generate_device_secret(output: *mut c_char, output_len: usize) -> i32 {
+    let secret = hex::encode(biometric::generate_device_secret());
+    if write_output(&secret, output, output_len) { 0 } else { -5 }
+}
+
+/// Créer un wallet depuis la biométrie, le secret de l'appareil et un PIN
+/// `biometric_hash_hex` et `device_secret_hex`: 32 octets en hex
+/// `language`: 0 = français, 1 = anglais (phrase de récupération)
+/// Retourne la phrase de récupération dans le buffer fourni
+///
+/// # Safety
+///
+/// `biometric_hash_hex`, `device_secret_hex`, `pin` et `data_dir` doivent
+/// pointer vers des chaînes C valides (terminées par nul); `output` vers un
+/// buffer inscriptible d'au moins `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn create_wallet_from_biometric(
+    biometric_hash_hex: *const c_char,
+    device_secret_hex: *const c_char,
+    pin: *const c_char,
+    data_dir: *const c_char,
+    language: i32,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let Some((biometric_hash, device_secret, pin, data_dir)) =
+        (unsafe { read_biometric_args(biometric_hash_hex, device_secret_hex, pin, data_dir) })
+    else {
+        return -1;
+    };
+    let language = match language {
+        0 => MnemonicLanguage::French,
+        1 => MnemonicLanguage::English,
+        _ => return -1,
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let factors = BiometricFactors {
+        biometric_hash: &biometric_hash,
+        device_secret: &device_secret,
+        pin: &pin,
+    };
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.create_wallet_from_biometric(Path::new(&data_dir), &factors, language).await
+    });
+
+    match result {
+        Ok((_, phrase)) => {
+            if write_output(&phrase, output, output_len) { 0 } else { -5 }
+        }
+        Err(_) => -6,
+    }
+}
+
+/// Déverrouiller le wallet biométrique enrôlé sur l'appareil
 /// Retourne l'adresse en hexadécimal dans le buffer fourni
+///
+/// # Safety
+///
+/// `biometric_hash_hex`, `device_secret_hex`, `pin` et `data_dir` doivent
+/// pointer vers des chaînes C valides (terminées par nul); `output` vers un
+/// buffer inscriptible d'au moins `output_len` octets.
 #[no_mangle]
-pub extern "C" fn create_wallet_from_biometric(
+pub unsafe extern "C" fn unlock_wallet_from_biometric(
     biometric_hash_hex: *const c_char,
+    device_secret_hex: *const c_char,
+    pin: *const c_char,
+    data_dir: *const c_char,
     output: *mut c_char,
     output_len: usize,
 ) -> i32 {
-    let hash_hex = unsafe {
-        match CStr::from_ptr(biometric_hash_hex).to_str() {
-            Ok(s) => s.to_string(),
-            Err(_) => return -1,
+    let Some((biometric_hash, device_secret, pin, data_dir)) =
+        (unsafe { read_biometric_args(biometric_hash_hex, device_secret_hex, pin, data_dir) })
+    else {
+        return -1;
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let factors = BiometricFactors {
+        biometric_hash: &biometric_hash,
+        device_secret: &device_secret,
+        pin: &pin,
+    };
+    let address = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.unlock_wallet_from_biometric(Path::new(&data_dir), &factors).await
+    });
+
+    match address {
+        Ok(addr) => {
+            if write_output(&hex::encode(addr), output, output_len) { 0 } else { -5 }
         }
+        Err(_) => -2,
+    }
+}
+
+/// Lire les arguments communs aux fonctions biométriques
+unsafe fn read_biometric_args(
+    biometric_hash_hex: *const c_char,
+    device_secret_hex: *const c_char,
+    pin: *const c_char,
+    data_dir: *const c_char,
+) -> Option<([u8; 32], [u8; 32], String, String)> {
+    let read = |ptr: *const c_char| CStr::from_ptr(ptr).to_str().ok().map(str::to_string);
+    let decode = |value: String| -> Option<[u8; 32]> {
+        hex::decode(value).ok()?.try_into().ok()
+    };
+
+    Some((
+        decode(read(biometric_hash_hex)?)?,
+        decode(read(device_secret_hex)?)?,
+        read(pin)?,
+        read(data_dir)?,
+    ))
+}
+
+/// Copier une chaîne C (terminée par nul) dans le buffer fourni
+fn write_output(value: &str, output: *mut c_char, output_len: usize) -> bool {
+    let Ok(value) = CString::new(value) else {
+        return false;
+    };
+    let bytes = value.as_bytes_with_nul();
+    if bytes.len() > output_len {
+        return false;
+    }
+
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), output as *mut u8, bytes.len());
+    }
+    true
+}
+
+/// Créer un wallet sauvegardable par phrase mnémonique
+/// `language`: 0 = français, 1 = anglais; `word_count`: 12, 15, 18, 21 ou 24
+/// Retourne la phrase (mots séparés par des espaces) dans le buffer fourni
+///
+/// # Safety
+///
+/// `output` doit pointer vers un buffer inscriptible d'au moins
+/// `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn create_wallet_with_mnemonic(
+    language: i32,
+    word_count: u32,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let language = match language {
+        0 => MnemonicLanguage::French,
+        1 => MnemonicLanguage::English,
+        _ => return -1,
     };
 
-    // Décoder le hash hexadécimal
-    let hash_bytes = match hex::decode(&hash_hex) {
-        Ok(bytes) => bytes,
-        Err(_) => return -2,
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
     };
 
-    if hash_bytes.len() != 32 {
-        return -3;
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.create_wallet_with_mnemonic(language, word_count as usize).await
+    });
+
+    match result {
+        Ok((_, phrase)) => {
+            if write_output(&phrase, output, output_len) { 0 } else { -5 }
+        }
+        Err(_) => -6,
     }
+}
 
-    let mut hash = [0u8; 32];
-    hash.copy_from_slice(&hash_bytes);
+/// Restaurer un wallet depuis sa phrase mnémonique
+/// `passphrase` peut être une chaîne vide; `account` est l'index du compte
+/// Retourne l'adresse en hexadécimal dans le buffer fourni
+///
+/// # Safety
+///
+/// `phrase` et `passphrase` doivent pointer vers des chaînes C valides
+/// (terminées par nul); `output` vers un buffer inscriptible d'au moins
+/// `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn restore_wallet_from_mnemonic(
+    phrase: *const c_char,
+    passphrase: *const c_char,
+    account: u32,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let (phrase, passphrase) = unsafe {
+        match (CStr::from_ptr(phrase).to_str(), CStr::from_ptr(passphrase).to_str()) {
+            (Ok(p), Ok(pp)) => (p.to_string(), pp.to_string()),
+            _ => return -1,
+        }
+    };
 
-    // Utiliser tokio runtime pour les appels async
     let rt = tokio::runtime::Runtime::new().unwrap();
     let client = match get_client() {
         Ok(c) => c,
@@ -84,32 +295,297 @@ pub extern "C" fn create_wallet_from_biometric(
 
     let address = rt.block_on(async {
         let client_guard = client.read().await;
-        client_guard.create_wallet_from_biometric(&hash).await
+        client_guard.restore_wallet_from_mnemonic(&phrase, &passphrase, account).await
     });
 
     match address {
         Ok(addr) => {
-            let addr_hex = hex::encode(addr);
-            let addr_cstr = CString::new(addr_hex).unwrap();
-            let addr_bytes = addr_cstr.as_bytes_with_nul();
-            
-            if addr_bytes.len() > output_len {
-                return -5;
-            }
-
-            unsafe {
-                std::ptr::copy_nonoverlapping(
-                    addr_bytes.as_ptr(),
-                    output as *mut u8,
-                    addr_bytes.len(),
-                );
-            }
-            0
+            if write_output(&hex::encode(addr), output, output_len) { 0 } else { -5 }
         }
+        Err(_) => -2,
+    }
+}
+
+/// Sauvegarder le wallet actif dans un keystore chiffré
+/// `data_dir` est le répertoire privé de l'application
+///
+/// # Safety
+///
+/// `data_dir` et `password` doivent pointer vers des chaînes C valides
+/// (terminées par nul).
+#[no_mangle]
+pub unsafe extern "C" fn save_wallet_keystore(
+    data_dir: *const c_char,
+    password: *const c_char,
+) -> i32 {
+    let (data_dir, password) = unsafe {
+        match (CStr::from_ptr(data_dir).to_str(), CStr::from_ptr(password).to_str()) {
+            (Ok(d), Ok(p)) => (d.to_string(), p.to_string()),
+            _ => return -1,
+        }
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.save_wallet(Path::new(&data_dir), &password).await
+    });
+
+    match result {
+        Ok(()) => 0,
         Err(_) => -6,
     }
 }
 
+/// Déverrouiller le wallet sauvegardé avec son mot de passe
+/// Retourne l'adresse en hexadécimal dans le buffer fourni
+///
+/// # Safety
+///
+/// `data_dir` et `password` doivent pointer vers des chaînes C valides
+/// (terminées par nul); `output` vers un buffer inscriptible d'au moins
+/// `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn unlock_wallet_keystore(
+    data_dir: *const c_char,
+    password: *const c_char,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let (data_dir, password) = unsafe {
+        match (CStr::from_ptr(data_dir).to_str(), CStr::from_ptr(password).to_str()) {
+            (Ok(d), Ok(p)) => (d.to_string(), p.to_string()),
+            _ => return -1,
+        }
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let address = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.unlock_wallet(Path::new(&data_dir), &password).await
+    });
+
+    match address {
+        Ok(addr) => {
+            if write_output(&hex::encode(addr), output, output_len) { 0 } else { -5 }
+        }
+        Err(_) => -2,
+    }
+}
+
+/// Définir les gardiens du compte du wallet actif
+/// `guardians`: adresses (bech32m du réseau du client ou hex) séparées par des virgules
+/// Retourne la transaction signée (JSON) à soumettre au nœud complet
+///
+/// # Safety
+///
+/// `guardians` doit pointer vers une chaîne C valide (terminée par nul);
+/// `output` vers un buffer inscriptible d'au moins `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn configure_recovery(
+    guardians: *const c_char,
+    threshold: u32,
+    delay_secs: i64,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let guardians = unsafe {
+        match CStr::from_ptr(guardians).to_str() {
+            Ok(s) => s.to_string(),
+            Err(_) => return -1,
+        }
+    };
+    let Ok(network) = client_network() else {
+        return -4;
+    };
+    let Ok(guardians) = guardians
+        .split(',')
+        .map(|address| wallet::parse_address(address.trim(), network))
+        .collect::<Result<Vec<PublicKey>, _>>()
+    else {
+        return -1;
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.configure_recovery(guardians, threshold as usize, delay_secs).await
+    });
+
+    write_transaction(result, output, output_len)
+}
+
+/// Préparer une demande de récupération depuis le nouvel appareil
+/// `account`: adresse du compte à récupérer
+/// Retourne la demande (JSON) à transmettre aux gardiens
+///
+/// # Safety
+///
+/// `account` doit pointer vers une chaîne C valide (terminée par nul);
+/// `output` vers un buffer inscriptible d'au moins `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn create_recovery_request(
+    account: *const c_char,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let Some(account) = (unsafe { read_address(account) }) else {
+        return -1;
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.create_recovery_request(&account).await
+    });
+
+    match result {
+        Ok(request) => {
+            if write_output(&request, output, output_len) { 0 } else { -5 }
+        }
+        Err(_) => -6,
+    }
+}
+
+/// Approuver une demande de récupération (wallet actif = gardien)
+/// Retourne la transaction signée (JSON) à soumettre au nœud complet
+///
+/// # Safety
+///
+/// `account` et `request` doivent pointer vers des chaînes C valides
+/// (terminées par nul); `output` vers un buffer inscriptible d'au moins
+/// `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn approve_recovery(
+    account: *const c_char,
+    request: *const c_char,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let Some(account) = (unsafe { read_address(account) }) else {
+        return -1;
+    };
+    let request = unsafe {
+        match CStr::from_ptr(request).to_str() {
+            Ok(s) => s.to_string(),
+            Err(_) => return -1,
+        }
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.approve_recovery(&account, &request).await
+    });
+
+    write_transaction(result, output, output_len)
+}
+
+/// Annuler une récupération en cours sur le compte du wallet actif
+/// Retourne la transaction signée (JSON) à soumettre au nœud complet
+///
+/// # Safety
+///
+/// `output` doit pointer vers un buffer inscriptible d'au moins
+/// `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn cancel_recovery(output: *mut c_char, output_len: usize) -> i32 {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.cancel_recovery().await
+    });
+
+    write_transaction(result, output, output_len)
+}
+
+/// Signer un défi de connexion ("Sign-in with Mahala") avec le wallet actif
+/// `message`: texte complet du défi, affiché à l'utilisateur avant l'appel
+/// Retourne la signature en hexadécimal (129 octets de buffer)
+///
+/// # Safety
+///
+/// `message` doit pointer vers une chaîne C valide (terminée par nul);
+/// `output` vers un buffer inscriptible d'au moins `output_len` octets.
+#[no_mangle]
+pub unsafe extern "C" fn sign_in_message(
+    message: *const c_char,
+    output: *mut c_char,
+    output_len: usize,
+) -> i32 {
+    let message = unsafe {
+        match CStr::from_ptr(message).to_str() {
+            Ok(s) => s.to_string(),
+            Err(_) => return -1,
+        }
+    };
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let client = match get_client() {
+        Ok(c) => c,
+        Err(_) => return -4,
+    };
+
+    let result = rt.block_on(async {
+        let client_guard = client.read().await;
+        client_guard.sign_in(&message).await
+    });
+
+    match result {
+        Ok(signature) => {
+            if write_output(&hex::encode(signature), output, output_len) { 0 } else { -5 }
+        }
+        Err(_) => -6,
+    }
+}
+
+/// Lire une adresse (bech32m du réseau du client ou hex)
+unsafe fn read_address(address: *const c_char) -> Option<PublicKey> {
+    let address = CStr::from_ptr(address).to_str().ok()?;
+    wallet::parse_address(address, client_network().ok()?).ok()
+}
+
+/// Écrire une transaction signée (JSON) dans le buffer fourni
+fn write_transaction(result: Result<Transaction, String>, output: *mut c_char, output_len: usize) -> i32 {
+    let Ok(tx) = result else {
+        return -6;
+    };
+    let Ok(json) = serde_json::to_string(&tx) else {
+        return -6;
+    };
+    if write_output(&json, output, output_len) { 0 } else { -5 }
+}
+
 /// Obtenir la balance
 #[no_mangle]
 pub extern "C" fn get_balance() -> f64 {
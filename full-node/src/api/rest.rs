@@ -3,6 +3,14 @@
 use actix_web::{web, App, HttpServer, HttpResponse, Result as ActixResult};
 use mahala_blockchain::{Blockchain, PublicKey};
 use mahala_blockchain::transaction::Transaction;
+use mahala_blockchain::consensus::rvs::ValidatorInfo;
+use mahala_blockchain::nft::NFTEvent;
+use mahala_blockchain::payments::{
+    Escrow, PaymentChannel, SignedBalanceUpdate, StandingOrder, StandingOrderEvent,
+};
+use mahala_blockchain::crypto::hash::hash_from_string;
+use mahala_blockchain::crypto::SignInMessage;
+use mahala_blockchain::wallet::{self, Network};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -11,16 +19,40 @@ use tokio::sync::RwLock;
 pub fn create_rest_app(
     blockchain: Arc<RwLock<Blockchain>>,
     mempool: Arc<crate::mempool::Mempool>,
+    network: Network,
 ) -> impl Fn() -> App<impl actix_web::dev::ServiceFactory<actix_web::dev::ServiceRequest, Config = (), Error = actix_web::Error, InitError = ()>> {
     move || {
         App::new()
             .app_data(web::Data::new(blockchain.clone()))
             .app_data(web::Data::new(mempool.clone()))
+            .app_data(web::Data::new(network))
             .route("/health", web::get().to(health))
             .route("/blockchain/height", web::get().to(get_height))
             .route("/blockchain/last_block", web::get().to(get_last_block))
             .route("/blockchain/block/{height}", web::get().to(get_block))
+            .route("/blockchain/checkpoint", web::get().to(get_finalized_checkpoint))
+            .route("/blockchain/checkpoint/{height}", web::get().to(get_checkpoint))
+            .route("/blockchain/snapshot", web::get().to(get_snapshot))
+            .route("/blockchain/snapshot/manifest", web::get().to(get_snapshot_manifest))
+            .route("/blockchain/snapshot/chunk/{index}", web::get().to(get_snapshot_chunk))
             .route("/blockchain/balance/{address}", web::get().to(get_balance))
+            .route("/address/{address}", web::get().to(validate_address))
+            .route("/account/{address}", web::get().to(get_account))
+            .route("/nft/{id}", web::get().to(get_nft))
+            .route("/nft/{id}/history", web::get().to(get_nft_history))
+            .route("/nft/address/{address}/history", web::get().to(get_address_nft_history))
+            .route("/escrow/{id}", web::get().to(get_escrow))
+            .route("/escrow/address/{address}", web::get().to(get_address_escrows))
+            .route("/channel/verify", web::post().to(verify_channel_update))
+            .route("/channel/{id}", web::get().to(get_channel))
+            .route("/channel/address/{address}", web::get().to(get_address_channels))
+            .route("/standing_order/{id}", web::get().to(get_standing_order))
+            .route("/standing_order/address/{address}", web::get().to(get_address_standing_orders))
+            .route("/consensus/validators", web::get().to(get_validators))
+            .route("/consensus/validator/{address}", web::get().to(get_validator))
+            .route("/consensus/epoch/{epoch}", web::get().to(get_epoch_schedule))
+            .route("/consensus/proposer/{height}/{round}", web::get().to(get_proposer))
+            .route("/auth/verify", web::post().to(verify_sign_in))
             .route("/transaction/submit", web::post().to(submit_transaction))
             .route("/mempool/size", web::get().to(get_mempool_size))
     }
@@ -83,25 +115,86 @@ async fn get_block(
     }
 }
 
+/// Obtenir le dernier checkpoint final (signé par un quorum)
+async fn get_finalized_checkpoint(
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.finalized_checkpoint() {
+        Some(checkpoint) => Ok(HttpResponse::Ok().json(checkpoint)),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No finalized checkpoint"
+        }))),
+    }
+}
+
+/// Obtenir le checkpoint d'une hauteur, final ou en cours de signature
+async fn get_checkpoint(
+    path: web::Path<u64>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.checkpoint(path.into_inner()) {
+        Some(checkpoint) => Ok(HttpResponse::Ok().json(checkpoint)),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Checkpoint not found"
+        }))),
+    }
+}
+
+/// Exporter l'instantané complet du dernier checkpoint final
+async fn get_snapshot(
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.snapshot() {
+        Some(snapshot) => Ok(HttpResponse::Ok().json(snapshot)),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No finalized snapshot"
+        }))),
+    }
+}
+
+/// Obtenir le manifeste de l'instantané du dernier checkpoint final
+async fn get_snapshot_manifest(
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.snapshot_manifest() {
+        Some(manifest) => Ok(HttpResponse::Ok().json(manifest)),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No finalized snapshot"
+        }))),
+    }
+}
+
+/// Obtenir un bloc de données de l'instantané du dernier checkpoint final
+async fn get_snapshot_chunk(
+    path: web::Path<usize>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.snapshot_chunk(path.into_inner()) {
+        Some(chunk) => Ok(HttpResponse::Ok().json(chunk)),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Snapshot chunk not found"
+        }))),
+    }
+}
+
 /// Obtenir la balance d'une adresse
 async fn get_balance(
     path: web::Path<String>,
     blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
 ) -> ActixResult<HttpResponse> {
     let address_hex = path.into_inner();
     
-    // Décoder l'adresse hex
-    let address_bytes = match hex::decode(&address_hex) {
-        Ok(bytes) if bytes.len() == 32 => {
-            let mut addr = [0u8; 32];
-            addr.copy_from_slice(&bytes);
-            addr
-        }
-        _ => {
-            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "Invalid address format"
-            })));
-        }
+    // Décoder l'adresse (bech32m ou hex)
+    let Some(address_bytes) = parse_address(&address_hex, **network) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid address format"
+        })));
     };
     
     let blockchain_guard = blockchain.read().await;
@@ -113,6 +206,498 @@ async fn get_balance(
     })))
 }
 
+/// Obtenir l'état d'un compte (clé de contrôle, nonce, rotations)
+async fn get_account(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let address_hex = path.into_inner();
+
+    let Some(address) = parse_address(&address_hex, **network) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid address format"
+        })));
+    };
+
+    let blockchain_guard = blockchain.read().await;
+    let accounts = blockchain_guard.accounts();
+    let account = accounts.resolve(&address);
+    let state = accounts.get(&account);
+    let rotations: Vec<_> = accounts.rotations_of(&account)
+        .into_iter()
+        .map(|r| serde_json::json!({
+            "old_key": hex::encode(r.old_key),
+            "new_key": hex::encode(r.new_key),
+            "block_height": r.block_height,
+            "timestamp": r.timestamp
+        }))
+        .collect();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "address": address_hex,
+        "account": hex::encode(account),
+        "controller": hex::encode(accounts.controller(&account)),
+        "nonce": accounts.nonce(&account),
+        "balance": blockchain_guard.get_balance(&account),
+        "rotation_delay_secs": state.map_or(0, |a| a.rotation_delay_secs),
+        "pending_rotation": state.and_then(|a| a.pending_rotation.as_ref()).map(|p| serde_json::json!({
+            "new_key": hex::encode(p.new_key),
+            "requested_at": p.requested_at,
+            "executes_at": p.executes_at
+        })),
+        "pending_delay": state.and_then(|a| a.pending_delay.as_ref()),
+        "recovery": state.and_then(|a| a.recovery.as_ref()).map(|r| serde_json::json!({
+            "guardians": r.guardians.iter().map(hex::encode).collect::<Vec<_>>(),
+            "threshold": r.threshold,
+            "delay_secs": r.delay_secs
+        })),
+        "recovery_approvals": state.map_or(0, |a| a.recovery_approvals.len()),
+        "scheduled_recovery": state.and_then(|a| a.scheduled_recovery.as_ref()).map(|p| serde_json::json!({
+            "new_key": hex::encode(p.new_key),
+            "requested_at": p.requested_at,
+            "executes_at": p.executes_at
+        })),
+        "rotations": rotations
+    })))
+}
+
+/// Valider une adresse pour le réseau du nœud et donner ses différentes
+/// représentations (`address`: forme bech32m du réseau du nœud)
+async fn validate_address(
+    path: web::Path<String>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let address = path.into_inner();
+
+    match wallet::parse_address(&address, **network) {
+        Ok(public_key) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "valid": true,
+            "address": wallet::encode_address(&public_key, **network),
+            "hex": hex::encode(public_key),
+            "mainnet": wallet::encode_address(&public_key, Network::Mainnet),
+            "testnet": wallet::encode_address(&public_key, Network::Testnet),
+            "network": wallet::decode_address(address.trim())
+                .ok()
+                .map(|(network, _)| network.to_string()),
+        }))),
+        Err(e) => Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "valid": false,
+            "error": e.to_string(),
+            "error_positions": e.positions(),
+        }))),
+    }
+}
+
+/// Décoder une adresse (bech32m du réseau du nœud ou hexadécimale)
+fn parse_address(address: &str, network: Network) -> Option<PublicKey> {
+    wallet::parse_address(address, network).ok()
+}
+
+/// Représentation JSON d'un événement NFT (adresses et hash en hex)
+fn nft_event_json(event: &NFTEvent) -> serde_json::Value {
+    serde_json::json!({
+        "kind": event.kind,
+        "nft_id": event.nft_id,
+        "from": event.from.map(hex::encode),
+        "to": event.to.map(hex::encode),
+        "price": event.price,
+        "block_height": event.block_height,
+        "tx_hash": hex::encode(event.tx_hash),
+        "timestamp": event.timestamp,
+    })
+}
+
+/// Obtenir un NFT par ID
+async fn get_nft(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let nft_id = path.into_inner();
+    let blockchain_guard = blockchain.read().await;
+    
+    let Some(nft) = blockchain_guard.nfts().get_nft(&nft_id) else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "NFT not found"
+        })));
+    };
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": nft.id,
+        "owner": hex::encode(nft.owner),
+        "creator": hex::encode(nft.creator),
+        "metadata": nft.metadata,
+        "created_at": nft.created_at,
+        "royalty_percentage": nft.royalty_percentage,
+        "listing": blockchain_guard.nfts().get_listing(&nft_id).map(|listing| serde_json::json!({
+            "seller": hex::encode(listing.seller),
+            "price": listing.price,
+            "expires_at": listing.expires_at,
+            "marketplace_fee": listing.marketplace_fee.map(|fee| serde_json::json!({
+                "recipient": hex::encode(fee.recipient),
+                "rate_bps": fee.rate_bps,
+            })),
+        })),
+    })))
+}
+
+/// Obtenir l'historique de provenance d'un NFT
+async fn get_nft_history(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let nft_id = path.into_inner();
+    let blockchain_guard = blockchain.read().await;
+    let events: Vec<_> = blockchain_guard.nfts()
+        .history(&nft_id)
+        .into_iter()
+        .map(nft_event_json)
+        .collect();
+    
+    // Un NFT brûlé n'existe plus mais garde son historique
+    if events.is_empty() {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "NFT not found"
+        })));
+    }
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "nft_id": nft_id,
+        "events": events
+    })))
+}
+
+/// Obtenir les événements NFT impliquant une adresse
+async fn get_address_nft_history(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let address_hex = path.into_inner();
+    
+    let Some(address) = parse_address(&address_hex, **network) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid address format"
+        })));
+    };
+    
+    let blockchain_guard = blockchain.read().await;
+    let address = blockchain_guard.resolve_account(&address);
+    let events: Vec<_> = blockchain_guard.nfts()
+        .events_for_address(&address)
+        .into_iter()
+        .map(nft_event_json)
+        .collect();
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "address": address_hex,
+        "events": events
+    })))
+}
+
+/// Représentation JSON d'un séquestre (adresses et hash en hex)
+fn escrow_json(escrow: &Escrow) -> serde_json::Value {
+    serde_json::json!({
+        "id": hex::encode(escrow.id),
+        "buyer": hex::encode(escrow.buyer),
+        "merchant": hex::encode(escrow.merchant),
+        "arbiter": hex::encode(escrow.arbiter),
+        "amount": escrow.amount,
+        "order_ref": escrow.order_ref,
+        "created_at": escrow.created_at,
+        "release_at": escrow.release_at,
+        "status": escrow.status,
+        "disputed_by": escrow.disputed_by.map(hex::encode),
+    })
+}
+
+/// Obtenir un séquestre par ID
+async fn get_escrow(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let Ok(escrow_id) = hash_from_string(&path.into_inner()) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid escrow id"
+        })));
+    };
+    
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.escrows().get(&escrow_id) {
+        Some(escrow) => Ok(HttpResponse::Ok().json(escrow_json(escrow))),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Escrow not found"
+        }))),
+    }
+}
+
+/// Obtenir les séquestres impliquant une adresse
+async fn get_address_escrows(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let address_hex = path.into_inner();
+    
+    let Some(address) = parse_address(&address_hex, **network) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid address format"
+        })));
+    };
+    
+    let blockchain_guard = blockchain.read().await;
+    let address = blockchain_guard.resolve_account(&address);
+    let escrows: Vec<_> = blockchain_guard.escrows()
+        .by_participant(&address)
+        .into_iter()
+        .map(escrow_json)
+        .collect();
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "address": address_hex,
+        "escrows": escrows
+    })))
+}
+
+/// Représentation JSON d'un ordre permanent (adresses et hash en hex)
+fn standing_order_json(order: &StandingOrder) -> serde_json::Value {
+    serde_json::json!({
+        "id": hex::encode(order.id),
+        "payer": hex::encode(order.payer),
+        "payee": hex::encode(order.payee),
+        "amount": order.amount,
+        "period_secs": order.period_secs,
+        "max_payments": order.max_payments,
+        "end_at": order.end_at,
+        "reference": order.reference,
+        "created_at": order.created_at,
+        "next_due": order.next_due,
+        "payments_made": order.payments_made,
+        "payments_failed": order.payments_failed,
+        "status": order.status,
+        "cancelled_by": order.cancelled_by.map(hex::encode),
+    })
+}
+
+/// Représentation JSON d'un événement d'ordre permanent
+fn standing_order_event_json(event: &StandingOrderEvent) -> serde_json::Value {
+    serde_json::json!({
+        "kind": event.kind,
+        "amount": event.amount,
+        "block_height": event.block_height,
+        "timestamp": event.timestamp,
+    })
+}
+
+/// Obtenir un ordre permanent et ses événements
+async fn get_standing_order(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let Ok(order_id) = hash_from_string(&path.into_inner()) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid standing order id"
+        })));
+    };
+    
+    let blockchain_guard = blockchain.read().await;
+    let standing_orders = blockchain_guard.standing_orders();
+    match standing_orders.get(&order_id) {
+        Some(order) => {
+            let mut json = standing_order_json(order);
+            json["events"] = standing_orders.events_for(&order_id)
+                .into_iter()
+                .map(standing_order_event_json)
+                .collect();
+            Ok(HttpResponse::Ok().json(json))
+        }
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Standing order not found"
+        }))),
+    }
+}
+
+/// Obtenir les ordres permanents impliquant une adresse
+async fn get_address_standing_orders(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let address_hex = path.into_inner();
+    
+    let Some(address) = parse_address(&address_hex, **network) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid address format"
+        })));
+    };
+    
+    let blockchain_guard = blockchain.read().await;
+    let address = blockchain_guard.resolve_account(&address);
+    let orders: Vec<_> = blockchain_guard.standing_orders()
+        .by_participant(&address)
+        .into_iter()
+        .map(standing_order_json)
+        .collect();
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "address": address_hex,
+        "standing_orders": orders
+    })))
+}
+
+/// Représentation JSON d'un canal de paiement (adresses et hash en hex)
+fn channel_json(channel: &PaymentChannel) -> serde_json::Value {
+    serde_json::json!({
+        "id": hex::encode(channel.id),
+        "payer": hex::encode(channel.payer),
+        "payee": hex::encode(channel.payee),
+        "deposit": channel.deposit,
+        "dispute_period_secs": channel.dispute_period_secs,
+        "opened_at": channel.opened_at,
+        "status": channel.status,
+        "paid": channel.paid,
+        "nonce": channel.nonce,
+        "settle_at": channel.settle_at,
+    })
+}
+
+/// Obtenir un canal de paiement par ID
+async fn get_channel(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let Ok(channel_id) = hash_from_string(&path.into_inner()) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid channel id"
+        })));
+    };
+    
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.channels().get(&channel_id) {
+        Some(channel) => Ok(HttpResponse::Ok().json(channel_json(channel))),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Channel not found"
+        }))),
+    }
+}
+
+/// Obtenir les canaux de paiement impliquant une adresse
+async fn get_address_channels(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let address_hex = path.into_inner();
+    
+    let Some(address) = parse_address(&address_hex, **network) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid address format"
+        })));
+    };
+    
+    let blockchain_guard = blockchain.read().await;
+    let address = blockchain_guard.resolve_account(&address);
+    let channels: Vec<_> = blockchain_guard.channels()
+        .by_participant(&address)
+        .into_iter()
+        .map(channel_json)
+        .collect();
+    
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "address": address_hex,
+        "channels": channels
+    })))
+}
+
+/// Vérifier une mise à jour de canal hors chaîne
+///
+/// Permet au serveur Luanti de valider un paiement sans implémenter Ed25519.
+async fn verify_channel_update(
+    req: web::Json<SignedBalanceUpdate>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let signed = req.into_inner();
+    
+    let blockchain_guard = blockchain.read().await;
+    let Some(channel) = blockchain_guard.channels().get(&signed.update.channel_id) else {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Channel not found"
+        })));
+    };
+    
+    let payer_key = blockchain_guard.accounts().controller(&channel.payer);
+    match channel.check_update(&signed, &payer_key) {
+        Ok(()) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "valid": true,
+            "channel_id": hex::encode(channel.id),
+            "status": channel.status,
+            "nonce": signed.update.nonce,
+            "paid": signed.update.paid,
+            "remaining": channel.deposit - signed.update.paid
+        }))),
+        Err(e) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "valid": false,
+            "error": e.to_string()
+        }))),
+    }
+}
+
+/// Vérifier une connexion "Sign-in with Mahala"
+#[derive(Deserialize)]
+struct VerifySignInRequest {
+    /// Texte complet du défi signé
+    message: String,
+    /// Signature en hexadécimal
+    signature: String,
+    /// Domaine attendu par le service
+    domain: String,
+    /// Nonce émis par le service
+    nonce: String,
+}
+
+/// Permet aux mods Luanti et au module Dolibarr de vérifier qu'un joueur ou
+/// un client contrôle une adresse sans implémenter Ed25519. La clé doit être
+/// la clé de contrôle courante de son compte.
+async fn verify_sign_in(
+    req: web::Json<VerifySignInRequest>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let req = req.into_inner();
+    let invalid = |error: String| Ok(HttpResponse::Ok().json(serde_json::json!({
+        "valid": false,
+        "error": error
+    })));
+    
+    let message = match SignInMessage::parse(&req.message) {
+        Ok(message) => message,
+        Err(e) => return invalid(e.to_string()),
+    };
+    let Some(signature) = hex::decode(&req.signature).ok().and_then(|s| s.try_into().ok()) else {
+        return invalid("Invalid signature format".to_string());
+    };
+    
+    if message.network != **network {
+        return invalid(format!("Address is for {}, expected {}", message.network, **network));
+    }
+    
+    let now = chrono::Utc::now().timestamp();
+    if let Err(e) = message.verify(&signature, &req.domain, &req.nonce, now) {
+        return invalid(e.to_string());
+    }
+    
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.accounts().authorize(&message.address) {
+        Ok(account) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "valid": true,
+            "address": hex::encode(message.address),
+            "account": hex::encode(account),
+            "expires_at": message.expires_at
+        }))),
+        Err(e) => invalid(e.to_string()),
+    }
+}
+
 /// Soumettre une transaction
 #[derive(Deserialize)]
 struct SubmitTransactionRequest {
@@ -144,6 +729,94 @@ async fn submit_transaction(
     }
 }
 
+/// Réponse JSON d'un validateur (réputation et statistiques de participation)
+fn validator_json(info: &ValidatorInfo) -> serde_json::Value {
+    serde_json::json!({
+        "public_key": hex::encode(info.public_key),
+        "wallet_address": hex::encode(info.wallet_address),
+        "status": info.status,
+        "reputation": info.reputation,
+        "registered_at": info.registered_at,
+        "last_active": info.last_active,
+        "missed_rounds": info.missed_rounds,
+        "selected": info.stats.times_selected,
+        "signed": info.stats.blocks_signed,
+        "missed": info.stats.absences,
+    })
+}
+
+/// Obtenir les validateurs enregistrés
+async fn get_validators(
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let blockchain_guard = blockchain.read().await;
+    let validators: Vec<_> = blockchain_guard.consensus().validators()
+        .into_iter()
+        .map(validator_json)
+        .collect();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "validators": validators
+    })))
+}
+
+/// Obtenir un validateur par clé ou par adresse de compte
+async fn get_validator(
+    path: web::Path<String>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+    network: web::Data<Network>,
+) -> ActixResult<HttpResponse> {
+    let Some(address) = parse_address(&path.into_inner(), **network) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid address format"
+        })));
+    };
+
+    let blockchain_guard = blockchain.read().await;
+    let accounts = blockchain_guard.accounts();
+    let controller = accounts.controller(&accounts.resolve(&address));
+    let consensus = blockchain_guard.consensus();
+    match consensus.validator(&address).or_else(|| consensus.validator(&controller)) {
+        Some(info) => Ok(HttpResponse::Ok().json(validator_json(info))),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Validator not found"
+        }))),
+    }
+}
+
+/// Obtenir le calendrier d'une époque (ensemble de validateurs, graine, quorum)
+async fn get_epoch_schedule(
+    path: web::Path<u64>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.epoch_schedule(path.into_inner()) {
+        Some(schedule) => Ok(HttpResponse::Ok().json(schedule)),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Epoch schedule not available"
+        }))),
+    }
+}
+
+/// Obtenir le proposeur attendu d'un round
+async fn get_proposer(
+    path: web::Path<(u64, u32)>,
+    blockchain: web::Data<Arc<RwLock<Blockchain>>>,
+) -> ActixResult<HttpResponse> {
+    let (height, round) = path.into_inner();
+    let blockchain_guard = blockchain.read().await;
+    match blockchain_guard.proposer(height, round) {
+        Some(proposer) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "height": height,
+            "round": round,
+            "epoch": blockchain_guard.consensus().epoch(height),
+            "proposer": hex::encode(proposer)
+        }))),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Proposer not available"
+        }))),
+    }
+}
+
 /// Obtenir la taille du mempool
 async fn get_mempool_size(
     mempool: web::Data<Arc<crate::mempool::Mempool>>,
@@ -1,5 +1,6 @@
 //! Configuration du nœud complet
 
+use mahala_blockchain::wallet::Network;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -23,6 +24,26 @@ pub struct NodeConfig {
     
     /// Activer le mode debug
     pub debug: bool,
+    
+    /// Réseau du nœud ("mainnet" ou "testnet"): les adresses bech32m d'un
+    /// autre réseau sont refusées
+    #[serde(default)]
+    pub network: Network,
+    
+    /// Bénéficiaire de la commission de l'opérateur sur les ventes NFT (bech32m ou hex)
+    #[serde(default)]
+    pub marketplace_fee_recipient: Option<String>,
+    
+    /// Taux de la commission de l'opérateur (points de base)
+    ///
+    /// Paramètre de consensus: doit être identique sur tous les nœuds.
+    #[serde(default)]
+    pub marketplace_fee_bps: u16,
+    
+    /// Keystore chiffré de la clé du validateur
+    /// (défaut: `<data_dir>/validator_keystore.json`)
+    #[serde(default)]
+    pub validator_keystore: Option<PathBuf>,
 }
 
 impl Default for NodeConfig {
@@ -34,6 +55,10 @@ impl Default for NodeConfig {
             data_dir: PathBuf::from("./data"),
             bootstrap_nodes: Vec::new(),
             debug: false,
+            network: Network::Mainnet,
+            marketplace_fee_recipient: None,
+            marketplace_fee_bps: 0,
+            validator_keystore: None,
         }
     }
 }
@@ -48,5 +73,17 @@ impl NodeConfig {
         
         settings.try_deserialize()
     }
+
+    /// Répertoire des instantanés exportés aux checkpoints finals
+    pub fn snapshot_dir(&self) -> PathBuf {
+        self.data_dir.join("snapshots")
+    }
+
+    /// Chemin du keystore du validateur
+    pub fn validator_keystore_path(&self) -> PathBuf {
+        self.validator_keystore
+            .clone()
+            .unwrap_or_else(|| self.data_dir.join("validator_keystore.json"))
+    }
 }
 
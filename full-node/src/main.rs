@@ -7,7 +7,10 @@ mod node;
 mod mempool;
 mod api;
 
+use mahala_blockchain::crypto::hash::hash_from_string;
 use mahala_blockchain::crypto::keys::KeyPair;
+use mahala_blockchain::storage::StateSnapshot;
+use mahala_blockchain::wallet::Keystore;
 use node::FullNode;
 use config::NodeConfig;
 use api::rest::create_rest_app;
@@ -28,20 +31,56 @@ async fn main() -> std::io::Result<()> {
             NodeConfig::default()
         });
     
-    // Générer ou charger la clé du validateur
-    let keypair = KeyPair::new();
+    // `--ephemeral` (développement): clé de validateur jetable, sans keystore
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let ephemeral = args.iter().any(|arg| arg == EPHEMERAL_FLAG);
+    args.retain(|arg| arg != EPHEMERAL_FLAG);
+    
+    // Charger la clé du validateur depuis son keystore chiffré
+    let keypair = if ephemeral {
+        eprintln!("Warning: {} set, using an ephemeral validator key", EPHEMERAL_FLAG);
+        KeyPair::new()
+    } else {
+        match load_validator_key(&config) {
+            Ok(keypair) => keypair,
+            Err(e) => {
+                eprintln!("Error loading validator key: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
     let validator_key = *keypair.public_key();
     
     println!("Validator public key: {}", hex::encode(validator_key));
     
-    // Créer le nœud
-    let node = FullNode::new(config.clone(), validator_key);
+    // Créer le nœud, depuis la genèse ou depuis un instantané
+    // (`mahala-node import-snapshot <fichier> <hash du bloc du checkpoint>`).
+    // Le nœud exporte l'instantané de chaque checkpoint final dans
+    // `<data_dir>/snapshots/`, aussi servi par `GET /blockchain/snapshot`.
+    let node = match args.first().map(String::as_str) {
+        None => FullNode::new(config.clone(), keypair),
+        Some("import-snapshot") => match import_snapshot(&config, keypair, &args[1..]) {
+            Ok(node) => node,
+            Err(e) => {
+                eprintln!("Error importing snapshot: {}", e);
+                std::process::exit(1);
+            }
+        },
+        Some(command) => {
+            eprintln!("Unknown command: {}", command);
+            eprintln!("Usage: mahala-node [--ephemeral] [import-snapshot <file> <checkpoint-block-hash>]");
+            std::process::exit(1);
+        }
+    };
     
     // Démarrer le nœud
-    if let Err(e) = node.start().await {
-        eprintln!("Error starting node: {}", e);
-        std::process::exit(1);
-    }
+    let consensus = match node.start().await {
+        Ok(consensus) => consensus,
+        Err(e) => {
+            eprintln!("Error starting node: {}", e);
+            std::process::exit(1);
+        }
+    };
     
     // Obtenir les références pour l'API
     let blockchain = node.blockchain();
@@ -50,8 +89,8 @@ async fn main() -> std::io::Result<()> {
     // Démarrer l'API REST
     println!("Starting API server on {}:{}", config.bind_address, config.api_port);
     
-    HttpServer::new(move || {
-        let app = create_rest_app(blockchain.clone(), mempool.clone());
+    let server = HttpServer::new(move || {
+        let app = create_rest_app(blockchain.clone(), mempool.clone(), config.network);
         app()
             .wrap(Logger::default())
             .wrap(
@@ -63,6 +102,75 @@ async fn main() -> std::io::Result<()> {
             )
     })
     .bind(format!("{}:{}", config.bind_address, config.api_port))?
-    .run()
-    .await
+    .run();
+    
+    // Le nœud s'arrête proprement (arrêt de l'API, libération de l'état) si
+    // la tâche de consensus se termine
+    let server_handle = server.handle();
+    tokio::pin!(server);
+    tokio::select! {
+        result = &mut server => result,
+        stopped = consensus => {
+            let error = match stopped {
+                Ok(Ok(())) => "consensus stopped".to_string(),
+                Ok(Err(e)) => e,
+                Err(e) => e.to_string(),
+            };
+            eprintln!("Consensus error, shutting down: {}", error);
+            let (_, result) = tokio::join!(server_handle.stop(true), &mut server);
+            result?;
+            Err(std::io::Error::other(error))
+        }
+    }
+}
+
+/// Créer le nœud depuis un instantané exporté par un autre nœud
+///
+/// Le hash du bloc du checkpoint, obtenu d'une source de confiance, ancre la
+/// vérification de l'instantané.
+fn import_snapshot(config: &NodeConfig, keypair: KeyPair, args: &[String]) -> Result<FullNode, String> {
+    let [path, block_hash] = args else {
+        return Err("Usage: mahala-node import-snapshot <file> <checkpoint-block-hash>".to_string());
+    };
+    let block_hash = hash_from_string(block_hash)?;
+    let data = std::fs::read(path).map_err(|e| e.to_string())?;
+    let snapshot: StateSnapshot = serde_json::from_slice(&data).map_err(|e| e.to_string())?;
+    let height = snapshot.manifest.height();
+    
+    let node = FullNode::from_snapshot(config.clone(), keypair, snapshot, block_hash)?;
+    println!("Snapshot imported at height {}", height);
+    Ok(node)
+}
+
+/// Variable d'environnement contenant le mot de passe du keystore
+const KEYSTORE_PASSWORD_ENV: &str = "MAHALA_KEYSTORE_PASSWORD";
+
+/// Option de démarrage avec une clé éphémère (développement)
+const EPHEMERAL_FLAG: &str = "--ephemeral";
+
+/// Charger la clé du validateur
+///
+/// Le keystore (créé par `mahala-keystore new`) et son mot de passe sont
+/// requis; le keystore est re-chiffré si ses paramètres sont obsolètes.
+fn load_validator_key(config: &NodeConfig) -> Result<KeyPair, String> {
+    let path = config.validator_keystore_path();
+    if !path.exists() {
+        return Err(format!(
+            "validator keystore not found: {} (create it with `mahala-keystore new`, or start with {} for development)",
+            path.display(), EPHEMERAL_FLAG
+        ));
+    }
+    let password = std::env::var(KEYSTORE_PASSWORD_ENV).map_err(|_| format!(
+        "{} not set (or start with {} for development)", KEYSTORE_PASSWORD_ENV, EPHEMERAL_FLAG
+    ))?;
+    
+    let keystore = Keystore::load(&path).map_err(|e| e.to_string())?;
+    let keypair = keystore.decrypt(&password).map_err(|e| e.to_string())?;
+    if keystore.needs_upgrade() {
+        keystore.upgrade(&password)
+            .and_then(|upgraded| upgraded.save(&path))
+            .map_err(|e| e.to_string())?;
+        println!("Validator keystore upgraded: {}", path.display());
+    }
+    Ok(keypair)
 }
@@ -77,8 +77,14 @@ impl Mempool {
     }
 
     /// Obtenir les transactions pour un bloc (triées par priorité)
-    pub async fn get_transactions_for_block(&self, max_count: usize) -> Vec<Transaction> {
-        let mempool = self.transactions.read().await;
+    ///
+    /// Appelée par le moteur de consensus lorsqu'il propose, sans attendre le
+    /// verrou: le bloc proposé est vide si le mempool est en cours de
+    /// modification.
+    pub fn get_transactions_for_block(&self, max_count: usize) -> Vec<Transaction> {
+        let Ok(mempool) = self.transactions.try_read() else {
+            return Vec::new();
+        };
         
         // Nettoyer les transactions expirées
         let now = SystemTime::now()
@@ -1,13 +1,25 @@
 //! Nœud complet Mahala
 
-use mahala_blockchain::{Blockchain, Block, PublicKey};
+use mahala_blockchain::{Blockchain, Block, Hash, PublicKey, Transaction};
+use mahala_blockchain::chain::Reorg;
 use mahala_blockchain::du::DUConfig;
+use mahala_blockchain::consensus::{BftEngine, ConsensusApp, ConsensusMessage, Transport};
+use mahala_blockchain::consensus::bft::{Step, Timeout, TimeoutConfig};
+use mahala_blockchain::consensus::DoubleSignEvidence;
 use mahala_blockchain::consensus::rvs::RVSConfig;
+use mahala_blockchain::crypto::keys::KeyPair;
+use mahala_blockchain::crypto::sign;
+use mahala_blockchain::nft::{MarketplaceConfig, MarketplaceFee};
+use mahala_blockchain::storage::StateSnapshot;
+use mahala_blockchain::transaction::{TransactionMetadata, TransactionType};
+use mahala_blockchain::wallet;
 use crate::mempool::Mempool;
 use crate::config::NodeConfig;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{interval, Duration};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, sleep, Duration};
 
 /// Nœud complet de la blockchain Mahala
 pub struct FullNode {
@@ -20,58 +32,188 @@ pub struct FullNode {
     /// Configuration
     config: NodeConfig,
     
-    /// Clé publique du nœud (validateur)
-    validator_key: PublicKey,
+    /// Clés du nœud (validateur)
+    validator: KeyPair,
+    
+    /// Entrée des événements du moteur de consensus
+    consensus_events: mpsc::UnboundedSender<ConsensusEvent>,
+    
+    /// Sortie des événements, reprise par la boucle de consensus au démarrage
+    consensus_receiver: std::sync::Mutex<Option<mpsc::UnboundedReceiver<ConsensusEvent>>>,
+    
+    /// Réorganisations de la chaîne, diffusées aux abonnés
+    reorg_events: broadcast::Sender<Reorg>,
+}
+
+/// Événement traité par le moteur de consensus
+enum ConsensusEvent {
+    Message(ConsensusMessage),
+    Timeout(Timeout),
+}
+
+/// Transport du moteur BFT
+///
+/// Les délais passent par des minuteries tokio. Le nœud n'a pas encore de
+/// réseau P2P: les messages ne sont pas diffusés et seul ce nœud vote.
+struct NodeTransport {
+    events: mpsc::UnboundedSender<ConsensusEvent>,
+}
+
+impl Transport for NodeTransport {
+    fn broadcast(&mut self, _message: ConsensusMessage) {
+        // TODO: Diffuser aux pairs une fois le réseau P2P en place
+    }
+
+    fn schedule_timeout(&mut self, timeout: Timeout, delay_ms: u64) {
+        let events = self.events.clone();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(delay_ms)).await;
+            let _ = events.send(ConsensusEvent::Timeout(timeout));
+        });
+    }
+}
+
+/// Nombre maximal de transactions d'un bloc proposé
+const MAX_BLOCK_TRANSACTIONS: usize = 100;
+
+/// Chaîne vue par le moteur BFT pendant le traitement d'un événement
+struct NodeApp<'a> {
+    blockchain: &'a mut Blockchain,
+    /// Source des transactions d'une proposition
+    mempool: &'a Mempool,
+    /// Transactions du bloc décidé et appliqué
+    committed: Option<Vec<Hash>>,
+    /// Erreur d'application du bloc décidé
+    failure: Option<String>,
+}
+
+impl ConsensusApp for NodeApp<'_> {
+    fn build_block(&mut self, height: u64, proposer: &PublicKey) -> Block {
+        let previous_hash = self.blockchain.last_block_hash().unwrap_or([0u8; 32]);
+        let transactions = self.mempool.get_transactions_for_block(MAX_BLOCK_TRANSACTIONS);
+        let mut block = Block::new(height, previous_hash, transactions, *proposer);
+        block.calculate_hash();
+        block
+    }
+
+    fn validate_block(&mut self, block: &Block) -> bool {
+        match self.blockchain.check_proposal(block) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("Proposition rejetée à la hauteur {}: {}", block.header.height, e);
+                false
+            }
+        }
+    }
+
+    fn commit_block(&mut self, block: Block) {
+        let hashes = block.transactions.iter().map(Transaction::id).collect();
+        match self.blockchain.add_block(block) {
+            Ok(_) => self.committed = Some(hashes),
+            Err(e) => self.failure = Some(e),
+        }
+    }
 }
 
 impl FullNode {
     /// Créer un nouveau nœud complet
-    pub fn new(config: NodeConfig, validator_key: PublicKey) -> Self {
+    pub fn new(config: NodeConfig, validator: KeyPair) -> Self {
         let du_config = DUConfig::default();
         let rvs_config = RVSConfig::default();
-        let blockchain = Arc::new(RwLock::new(Blockchain::new(du_config, rvs_config)));
+        Self::with_blockchain(config, validator, Blockchain::new(du_config, rvs_config))
+    }
+
+    /// Créer un nœud démarrant depuis l'instantané d'un checkpoint final
+    ///
+    /// Faute de client léger intégré, le calendrier de l'époque est celui que
+    /// décrit l'instantané: il n'est retenu que si le bloc du checkpoint est
+    /// celui attendu par l'opérateur. Le nœud reprend ensuite les blocs
+    /// suivants.
+    pub fn from_snapshot(
+        config: NodeConfig,
+        validator: KeyPair,
+        snapshot: StateSnapshot,
+        trusted_block_hash: Hash,
+    ) -> Result<Self, String> {
+        if snapshot.manifest.checkpoint.block_hash != trusted_block_hash {
+            return Err("Snapshot checkpoint does not match the trusted block hash".to_string());
+        }
+        let rvs_config = RVSConfig::default();
+        let schedule = snapshot.epoch_schedule(rvs_config.clone())
+            .map_err(|e| e.to_string())?;
+        let blockchain = Blockchain::from_snapshot(DUConfig::default(), rvs_config, snapshot, &schedule)
+            .map_err(|e| format!("Invalid snapshot: {}", e))?;
+        Ok(Self::with_blockchain(config, validator, blockchain))
+    }
+
+    /// Créer un nœud autour d'une chaîne
+    fn with_blockchain(config: NodeConfig, validator: KeyPair, blockchain: Blockchain) -> Self {
+        let blockchain = Arc::new(RwLock::new(blockchain));
         
         let mempool = Arc::new(Mempool::new(10000, 3600)); // 10k tx max, 1h max age
+        let (consensus_events, consensus_receiver) = mpsc::unbounded_channel();
+        let (reorg_events, _) = broadcast::channel(64);
         
         Self {
             blockchain,
             mempool,
             config,
-            validator_key,
+            validator,
+            consensus_events,
+            consensus_receiver: std::sync::Mutex::new(Some(consensus_receiver)),
+            reorg_events,
+        }
+    }
+
+    /// Commission de l'opérateur configurée pour les ventes NFT
+    fn marketplace_config(&self) -> Result<MarketplaceConfig, String> {
+        let mut config = MarketplaceConfig::default();
+        
+        if let Some(recipient) = &self.config.marketplace_fee_recipient {
+            let recipient = wallet::parse_address(recipient, self.config.network)
+                .map_err(|e| format!("Invalid marketplace fee recipient: {}", e))?;
+            config.default_fee = Some(MarketplaceFee {
+                recipient,
+                rate_bps: self.config.marketplace_fee_bps,
+            });
         }
+        
+        Ok(config)
     }
 
     /// Démarrer le nœud
-    pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
-        // Créer le bloc genesis si nécessaire
+    ///
+    /// Retourne la tâche de consensus, qui se termine sur une erreur
+    /// (bloc décidé inapplicable): le nœud doit alors s'arrêter.
+    pub async fn start(&self) -> Result<JoinHandle<Result<(), String>>, Box<dyn std::error::Error>> {
+        let validator_key = *self.validator.public_key();
+        
+        // Créer le bloc genesis si nécessaire (un nœud démarré depuis un
+        // instantané reprend les paramètres de l'état du checkpoint)
         {
             let mut blockchain = self.blockchain.write().await;
             if blockchain.height() == 0 {
-                blockchain.create_genesis(self.validator_key)?;
+                blockchain.set_marketplace_config(self.marketplace_config()?)?;
+                blockchain.create_genesis(validator_key)?;
+                // Validateur initial de la genèse (seul votant tant que le
+                // réseau P2P manque); les autres s'enregistrent par transaction
+                blockchain.consensus_mut().register_validator(validator_key, validator_key);
             }
         }
 
-        // Démarrer la production de blocs
+        // Démarrer le consensus BFT
+        let events = self.consensus_receiver.lock().unwrap().take()
+            .ok_or("Node already started")?;
+        let transport = NodeTransport { events: self.consensus_events.clone() };
         let blockchain_clone = self.blockchain.clone();
         let mempool_clone = self.mempool.clone();
-        let validator_key = self.validator_key;
+        let validator = self.validator.clone();
+        let reorg_events = self.reorg_events.clone();
+        let snapshot_dir = self.config.snapshot_dir();
         
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(5)); // Bloc toutes les 5 secondes
-            
-            loop {
-                interval.tick().await;
-                
-                // Produire un nouveau bloc
-                if let Err(e) = Self::produce_block(
-                    &blockchain_clone,
-                    &mempool_clone,
-                    validator_key,
-                ).await {
-                    eprintln!("Erreur production bloc: {}", e);
-                }
-            }
-        });
+        let consensus = tokio::spawn(
+            Self::run_consensus(blockchain_clone, mempool_clone, validator, transport, events, reorg_events, snapshot_dir)
+        );
 
         // Nettoyer le mempool périodiquement
         let mempool_clone = self.mempool.clone();
@@ -83,48 +225,206 @@ impl FullNode {
             }
         });
 
-        Ok(())
+        Ok(consensus)
     }
 
-    /// Produire un nouveau bloc
-    async fn produce_block(
-        blockchain: &Arc<RwLock<Blockchain>>,
-        mempool: &Arc<Mempool>,
-        validator_key: PublicKey,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let height;
-        let previous_hash;
+    /// Boucle du moteur BFT: une hauteur après l'autre
+    ///
+    /// Après chaque bloc décidé, le nœud attend l'intervalle entre blocs
+    /// avant de démarrer la hauteur suivante. Retourne une erreur si un bloc
+    /// décidé ne s'applique pas à la chaîne locale.
+    async fn run_consensus(
+        blockchain: Arc<RwLock<Blockchain>>,
+        mempool: Arc<Mempool>,
+        validator: KeyPair,
+        mut transport: NodeTransport,
+        mut events: mpsc::UnboundedReceiver<ConsensusEvent>,
+        reorg_events: broadcast::Sender<Reorg>,
+        snapshot_dir: PathBuf,
+    ) -> Result<(), String> {
+        let mut engine = BftEngine::new(validator.clone(), TimeoutConfig::default());
+        let mut event = None;
         
-        {
-            let blockchain_guard = blockchain.read().await;
-            height = blockchain_guard.height();
-            previous_hash = blockchain_guard.last_block_hash()
-                .ok_or("No previous block")?;
-        }
+        loop {
+            let (committed, block_interval) = {
+                let mut blockchain_guard = blockchain.write().await;
+                let block_interval = blockchain_guard.consensus().config().block_interval;
+                let mut app = NodeApp { blockchain: &mut blockchain_guard, mempool: &mempool, committed: None, failure: None };
+                
+                match event.take() {
+                    Some(ConsensusEvent::Message(message)) => {
+                        engine.handle_message(message, &mut app, &mut transport)
+                    }
+                    Some(ConsensusEvent::Timeout(timeout)) => {
+                        engine.handle_timeout(timeout, &mut app, &mut transport)
+                    }
+                    None => {
+                        if let Err(e) = Self::start_height(&mut engine, &mut app, &mut transport) {
+                            eprintln!("Consensus en attente: {}", e);
+                        }
+                    }
+                }
+                
+                // Un bloc décidé par un quorum que la chaîne locale refuse:
+                // l'état local a divergé du réseau. Sans synchronisation
+                // P2P, le nœud s'arrête plutôt que de voter sur cet état; il
+                // repart d'un instantané (`import-snapshot`).
+                if let Some(e) = app.failure {
+                    return Err(format!("Decided block cannot be applied: {}", e));
+                }
+                (app.committed, block_interval)
+            };
+            
+            // Soumettre les équivocations constatées par le moteur
+            for evidence in engine.take_evidence() {
+                Self::submit_evidence(&mempool, &validator, evidence).await;
+            }
+            Self::handle_reorgs(&blockchain, &mempool, &reorg_events).await;
 
-        // Obtenir les transactions du mempool
-        let transactions = mempool.get_transactions_for_block(100).await;
+            if engine.step() == Step::Commit {
+                // Retirer les transactions du bloc décidé du mempool
+                if let Some(hashes) = committed {
+                    mempool.remove_transactions(&hashes).await;
+                }
+                if Self::sign_checkpoint(&blockchain, &validator).await {
+                    Self::export_snapshot(&blockchain, &snapshot_dir).await;
+                }
+                sleep(Duration::from_secs(block_interval)).await;
+                continue;
+            }
+            
+            match events.recv().await {
+                Some(next) => event = Some(next),
+                None => return Ok(()),
+            }
+        }
+    }
 
-        // Créer le bloc
-        let mut block = Block::new(height, previous_hash, transactions.clone(), validator_key);
-        block.calculate_hash();
+    /// Soumettre une preuve d'équivocation, signée par le validateur local
+    async fn submit_evidence(mempool: &Mempool, validator: &KeyPair, evidence: DoubleSignEvidence) {
+        let offender = hex::encode(evidence.offender());
+        let metadata = TransactionMetadata::with_payload(TransactionType::Evidence, &evidence);
+        let mut tx = Transaction::new(*validator.public_key(), *validator.public_key(), 0.0, 0.0, metadata);
+        if let Err(e) = tx.sign(validator.private_key()) {
+            eprintln!("Erreur signature preuve: {}", e);
+            return;
+        }
+        match mempool.add_transaction(tx).await {
+            Ok(()) => println!("Équivocation de {} signalée", offender),
+            Err(e) => eprintln!("Erreur soumission preuve: {}", e),
+        }
+    }
 
-        // Ajouter le bloc à la chaîne
-        {
+    /// Répercuter les réorganisations de la chaîne
+    ///
+    /// Les transactions des blocs retirés reviennent dans le mempool, celles
+    /// de la nouvelle branche en sortent; les abonnés sont notifiés.
+    async fn handle_reorgs(blockchain: &RwLock<Blockchain>, mempool: &Mempool, reorg_events: &broadcast::Sender<Reorg>) {
+        let (reorgs, included) = {
             let mut blockchain_guard = blockchain.write().await;
-            blockchain_guard.add_block(block)?;
+            let reorgs = blockchain_guard.take_reorgs();
+            let included: Vec<Hash> = reorgs.iter()
+                .flat_map(|reorg| reorg.applied.iter())
+                .filter_map(|hash| blockchain_guard.get_block_by_hash(hash))
+                .flat_map(|block| block.transactions.iter().map(Transaction::id))
+                .collect();
+            (reorgs, included)
+        };
+        
+        mempool.remove_transactions(&included).await;
+        for reorg in reorgs {
+            println!(
+                "Réorganisation depuis la hauteur {}: {} bloc(s) retiré(s), {} appliqué(s)",
+                reorg.fork_height,
+                reorg.reverted.len(),
+                reorg.applied.len()
+            );
+            for tx in reorg.orphaned_transactions.iter().cloned() {
+                // Une transaction déjà présente ou devenue invalide est ignorée
+                let _ = mempool.add_transaction(tx).await;
+            }
+            // Sans abonné, la notification est perdue
+            let _ = reorg_events.send(reorg);
         }
+    }
 
-        // Retirer les transactions du mempool
-        let hashes: Vec<_> = transactions
-            .iter()
-            .filter_map(|tx| tx.hash)
-            .collect();
-        mempool.remove_transactions(&hashes).await;
+    /// Signer le checkpoint du bloc décidé si le validateur local l'a certifié
+    ///
+    /// Retourne `true` si le checkpoint devient final.
+    async fn sign_checkpoint(blockchain: &RwLock<Blockchain>, validator: &KeyPair) -> bool {
+        let mut blockchain_guard = blockchain.write().await;
+        let height = blockchain_guard.height();
+        let Some(checkpoint) = blockchain_guard.last_checkpoint()
+            .filter(|c| c.height + 1 == height && c.certificate.is_signer(validator.public_key()))
+        else {
+            return false;
+        };
+        
+        // TODO: Diffuser la signature aux pairs une fois le réseau P2P en place
+        let checkpoint_height = checkpoint.height;
+        let signature = sign(&checkpoint.signing_hash(), validator.private_key());
+        match blockchain_guard.add_checkpoint_signature(checkpoint_height, *validator.public_key(), signature) {
+            Ok(true) => {
+                println!("Checkpoint {} finalisé", checkpoint_height);
+                true
+            }
+            Ok(false) => false,
+            Err(e) => {
+                eprintln!("Erreur signature checkpoint: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Exporter l'instantané du dernier checkpoint final
+    ///
+    /// Écrit `snapshot-<hauteur>.json` dans le répertoire des instantanés,
+    /// fichier lu par `mahala-node import-snapshot`; seul le plus récent est
+    /// conservé.
+    async fn export_snapshot(blockchain: &RwLock<Blockchain>, dir: &Path) {
+        let Some(snapshot) = blockchain.read().await.snapshot() else {
+            return;
+        };
+        let height = snapshot.manifest.height();
+        let path = dir.join(format!("snapshot-{}.json", height));
+        let result = std::fs::create_dir_all(dir)
+            .and_then(|_| serde_json::to_vec(&snapshot).map_err(std::io::Error::other))
+            .and_then(|data| std::fs::write(&path, data));
+        if let Err(e) = result {
+            eprintln!("Erreur export instantané: {}", e);
+            return;
+        }
+        println!("Instantané exporté: {}", path.display());
+        
+        // Retirer les instantanés précédents
+        let previous = std::fs::read_dir(dir).into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|p| p != &path && p.file_name().is_some_and(|n| n.to_string_lossy().starts_with("snapshot-")));
+        for old in previous {
+            let _ = std::fs::remove_file(old);
+        }
+    }
 
+    /// Démarrer la hauteur suivante de la chaîne
+    ///
+    /// Échoue si le bloc d'ancrage de l'époque manque; la boucle réessaie
+    /// après l'intervalle entre blocs.
+    fn start_height(engine: &mut BftEngine, app: &mut NodeApp<'_>, transport: &mut NodeTransport) -> Result<(), String> {
+        let height = app.blockchain.height();
+        let seed = app.blockchain.selection_seed(height)
+            .ok_or_else(|| format!("missing epoch seed block for height {}", height))?;
+        let validators = app.blockchain.consensus().clone();
+        engine.start_height(height, seed, validators, app, transport);
         Ok(())
     }
 
+    /// S'abonner aux réorganisations de la chaîne
+    pub fn subscribe_reorgs(&self) -> broadcast::Receiver<Reorg> {
+        self.reorg_events.subscribe()
+    }
+
     /// Obtenir la blockchain (pour l'API)
     pub fn blockchain(&self) -> Arc<RwLock<Blockchain>> {
         self.blockchain.clone()
@@ -135,6 +435,11 @@ impl FullNode {
         self.mempool.clone()
     }
 
+    /// Transmettre au moteur BFT un message de consensus reçu d'un pair
+    pub fn handle_consensus_message(&self, message: ConsensusMessage) {
+        let _ = self.consensus_events.send(ConsensusEvent::Message(message));
+    }
+
     /// Ajouter une transaction au mempool
     pub async fn add_transaction(&self, tx: mahala_blockchain::transaction::Transaction) -> Result<(), crate::mempool::MempoolError> {
         self.mempool.add_transaction(tx).await
@@ -9,15 +9,30 @@ pub mod storage;
 pub mod p2p;
 
 pub use sync::SyncManager;
-pub use validator::ValidatorParticipant;
+pub use validator::{ValidatorParticipant, ValidatorStats};
 
-use mahala_blockchain::{Blockchain, PublicKey, Amount};
-use mahala_blockchain::wallet::Wallet;
+use mahala_blockchain::{Blockchain, PublicKey, Amount, Signature, Transaction};
+use mahala_blockchain::account::{RecoveryConfig, RecoveryOperation};
+use mahala_blockchain::crypto::SignInMessage;
+use mahala_blockchain::transaction::{TransactionMetadata, TransactionType};
+use mahala_blockchain::wallet::{BiometricEnrollment, BiometricFactors, Mnemonic, MnemonicLanguage, Network, Wallet};
 use mahala_blockchain::du::DUConfig;
-use mahala_blockchain::consensus::rvs::RVSConfig;
+use mahala_blockchain::consensus::rvs::{RVSConfig, ValidatorOperation};
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Résultat de la migration d'un wallet biométrique historique
+#[derive(Debug, Clone)]
+pub struct BiometricMigration {
+    /// Ancien wallet (à vider vers la nouvelle adresse)
+    pub legacy_wallet: Wallet,
+    /// Adresse du nouveau wallet
+    pub address: PublicKey,
+    /// Phrase de récupération du nouveau wallet
+    pub recovery_phrase: String,
+}
+
 /// Client léger Mahala pour mobile
 pub struct LightClient {
     /// Blockchain locale (synchronisée)
@@ -44,6 +59,9 @@ pub struct LightClientConfig {
     
     /// Activer la participation au consensus
     pub enable_consensus: bool,
+    
+    /// Réseau du client (adresses saisies, défis de connexion)
+    pub network: Network,
 }
 
 impl Default for LightClientConfig {
@@ -52,6 +70,7 @@ impl Default for LightClientConfig {
             full_node_url: "http://localhost:8080".to_string(),
             sync_interval: 10, // 10 secondes
             enable_consensus: true,
+            network: Network::Mainnet,
         }
     }
 }
@@ -73,23 +92,117 @@ impl LightClient {
         }
     }
 
-    /// Créer un wallet depuis un hash biométrique
-    pub async fn create_wallet_from_biometric(&self, biometric_hash: &[u8; 32]) -> Result<PublicKey, String> {
-        // Utiliser le hash biométrique comme seed pour générer le wallet de manière déterministe
-        let wallet = Wallet::from_seed(biometric_hash);
+    /// Réseau du client
+    pub fn network(&self) -> Network {
+        self.config.network
+    }
+
+    /// Créer un wallet depuis la biométrie, le secret de l'appareil et un PIN
+    ///
+    /// Retourne l'adresse et la phrase de récupération à faire noter à
+    /// l'utilisateur. L'enrôlement (sans secret) est sauvegardé dans `data_dir`.
+    pub async fn create_wallet_from_biometric(
+        &self,
+        data_dir: &Path,
+        factors: &BiometricFactors<'_>,
+        language: MnemonicLanguage,
+    ) -> Result<(PublicKey, String), String> {
+        if storage::has_biometric_enrollment(data_dir) {
+            return Err("Biometric wallet already enrolled on this device".to_string());
+        }
+        
+        let enrolled = BiometricEnrollment::enroll(factors, language)
+            .map_err(|e| e.to_string())?;
+        storage::save_biometric_enrollment(data_dir, &enrolled.enrollment)?;
+        
+        Ok((self.install_wallet(enrolled.wallet).await, enrolled.recovery.phrase()))
+    }
+
+    /// Déverrouiller le wallet biométrique enrôlé sur l'appareil
+    pub async fn unlock_wallet_from_biometric(
+        &self,
+        data_dir: &Path,
+        factors: &BiometricFactors<'_>,
+    ) -> Result<PublicKey, String> {
+        let enrollment = storage::load_biometric_enrollment(data_dir)?;
+        let wallet = enrollment.unlock(factors).map_err(|e| e.to_string())?;
+        Ok(self.install_wallet(wallet).await)
+    }
+
+    /// Migrer un wallet biométrique de l'ancien schéma (hash utilisé comme seed)
+    ///
+    /// Le nouveau wallet devient actif; l'ancien est renvoyé pour que
+    /// l'application transfère ses fonds vers la nouvelle adresse.
+    pub async fn migrate_legacy_biometric_wallet(
+        &self,
+        data_dir: &Path,
+        factors: &BiometricFactors<'_>,
+        language: MnemonicLanguage,
+    ) -> Result<BiometricMigration, String> {
+        let legacy_wallet = BiometricEnrollment::legacy(factors.biometric_hash)
+            .unlock(factors)
+            .map_err(|e| e.to_string())?;
+        
+        let enrolled = BiometricEnrollment::enroll(factors, language)
+            .map_err(|e| e.to_string())?;
+        storage::save_biometric_enrollment(data_dir, &enrolled.enrollment)?;
+        
+        Ok(BiometricMigration {
+            legacy_wallet,
+            address: self.install_wallet(enrolled.wallet).await,
+            recovery_phrase: enrolled.recovery.phrase(),
+        })
+    }
+
+    /// Créer un nouveau wallet sauvegardable par phrase mnémonique
+    ///
+    /// Retourne l'adresse du compte 0 et la phrase à faire noter à l'utilisateur.
+    pub async fn create_wallet_with_mnemonic(
+        &self,
+        language: MnemonicLanguage,
+        word_count: usize,
+    ) -> Result<(PublicKey, String), String> {
+        let mnemonic = Mnemonic::generate(language, word_count)
+            .map_err(|e| e.to_string())?;
+        let wallet = Wallet::from_mnemonic(&mnemonic, "", 0);
+        Ok((self.install_wallet(wallet).await, mnemonic.phrase()))
+    }
+
+    /// Restaurer un wallet depuis sa phrase mnémonique (nouvel appareil)
+    pub async fn restore_wallet_from_mnemonic(
+        &self,
+        phrase: &str,
+        passphrase: &str,
+        account: u32,
+    ) -> Result<PublicKey, String> {
+        let wallet = Wallet::restore(phrase, passphrase, account)
+            .map_err(|e| e.to_string())?;
+        Ok(self.install_wallet(wallet).await)
+    }
+
+    /// Sauvegarder le wallet actif dans un keystore chiffré
+    pub async fn save_wallet(&self, data_dir: &Path, password: &str) -> Result<(), String> {
+        let wallet_guard = self.wallet.read().await;
+        let wallet = wallet_guard.as_ref()
+            .ok_or("Wallet not initialized")?;
+        
+        storage::save_wallet(data_dir, wallet, password)
+    }
+
+    /// Déverrouiller le wallet sauvegardé sur l'appareil
+    pub async fn unlock_wallet(&self, data_dir: &Path, password: &str) -> Result<PublicKey, String> {
+        let wallet = storage::load_wallet(data_dir, password)?;
+        Ok(self.install_wallet(wallet).await)
+    }
+
+    /// Enregistrer le wallet actif
+    async fn install_wallet(&self, wallet: Wallet) -> PublicKey {
         let address = *wallet.address();
         
-        // Enregistrer le wallet
         let mut wallet_guard = self.wallet.write().await;
         *wallet_guard = Some(wallet);
         
-        // Enregistrer comme validateur si activé
-        if self.config.enable_consensus {
-            let mut blockchain_guard = self.blockchain.write().await;
-            blockchain_guard.consensus_mut().register_validator(address, address);
-        }
-        
-        Ok(address)
+        address
     }
 
     /// Obtenir la balance du wallet
@@ -102,6 +215,143 @@ impl LightClient {
         Ok(blockchain_guard.get_balance(wallet.address()))
     }
 
+    /// Définir les gardiens du compte du wallet actif
+    ///
+    /// Retourne la transaction signée, à soumettre au nœud complet.
+    pub async fn configure_recovery(
+        &self,
+        guardians: Vec<PublicKey>,
+        threshold: usize,
+        delay_secs: i64,
+    ) -> Result<Transaction, String> {
+        let operation = RecoveryOperation::Configure(RecoveryConfig { guardians, threshold, delay_secs });
+        self.sign_recovery_operation(None, &operation).await
+    }
+
+    /// Préparer une demande de récupération depuis un nouvel appareil
+    ///
+    /// Le wallet actif (créé sur le nouvel appareil) prouve qu'il détient
+    /// sa clé; la demande JSON retournée est transmise aux gardiens.
+    pub async fn create_recovery_request(&self, account: &PublicKey) -> Result<String, String> {
+        let wallet_guard = self.wallet.read().await;
+        let wallet = wallet_guard.as_ref()
+            .ok_or("Wallet not initialized")?;
+        
+        let request = RecoveryOperation::approve(account, &wallet.keypair);
+        serde_json::to_string(&request).map_err(|e| e.to_string())
+    }
+
+    /// Approuver, en tant que gardien, une demande de récupération
+    pub async fn approve_recovery(&self, account: &PublicKey, request: &str) -> Result<Transaction, String> {
+        let operation: RecoveryOperation = serde_json::from_str(request)
+            .map_err(|e| format!("Invalid recovery request: {}", e))?;
+        if !matches!(operation, RecoveryOperation::Approve { .. }) {
+            return Err("Invalid recovery request: not an approval".to_string());
+        }
+        self.sign_recovery_operation(Some(account), &operation).await
+    }
+
+    /// Annuler une récupération en cours sur le compte du wallet actif
+    pub async fn cancel_recovery(&self) -> Result<Transaction, String> {
+        self.sign_recovery_operation(None, &RecoveryOperation::Cancel).await
+    }
+
+    /// Signer une opération de récupération avec le wallet actif
+    /// (`to`: compte à récupérer, ou le compte du wallet)
+    async fn sign_recovery_operation(
+        &self,
+        to: Option<&PublicKey>,
+        operation: &RecoveryOperation,
+    ) -> Result<Transaction, String> {
+        let wallet_guard = self.wallet.read().await;
+        let wallet = wallet_guard.as_ref()
+            .ok_or("Wallet not initialized")?;
+        
+        let mut tx = Transaction::new(
+            *wallet.address(),
+            *to.unwrap_or(wallet.address()),
+            0.0,
+            0.0,
+            TransactionMetadata::with_payload(TransactionType::Recovery, operation),
+        );
+        tx.sign(wallet.private_key())?;
+        Ok(tx)
+    }
+
+    /// S'enregistrer comme validateur (réservé aux membres)
+    ///
+    /// Retourne la transaction signée, à soumettre au nœud complet.
+    pub async fn register_as_validator(&self) -> Result<Transaction, String> {
+        if !self.config.enable_consensus {
+            return Err("Consensus participation is disabled".to_string());
+        }
+        self.sign_validator_operation(&ValidatorOperation::Register).await
+    }
+
+    /// Quitter l'ensemble des validateurs
+    pub async fn unregister_as_validator(&self) -> Result<Transaction, String> {
+        self.sign_validator_operation(&ValidatorOperation::Unregister).await
+    }
+
+    /// Signaler l'activité du validateur (à soumettre périodiquement quand il
+    /// n'a pas signé de bloc récemment)
+    pub async fn send_heartbeat(&self) -> Result<Transaction, String> {
+        self.sign_validator_operation(&ValidatorOperation::Heartbeat).await
+    }
+
+    /// Statistiques de participation du wallet actif, telles qu'enregistrées
+    /// dans l'état de la chaîne (`None` s'il n'est pas validateur)
+    pub async fn get_validator_stats(&self) -> Result<Option<ValidatorStats>, String> {
+        let wallet_guard = self.wallet.read().await;
+        let wallet = wallet_guard.as_ref()
+            .ok_or("Wallet not initialized")?;
+        
+        let blockchain_guard = self.blockchain.read().await;
+        Ok(blockchain_guard.consensus().validator(wallet.address()).map(|info| info.stats))
+    }
+
+    /// Signer une opération de validateur avec le wallet actif
+    async fn sign_validator_operation(&self, operation: &ValidatorOperation) -> Result<Transaction, String> {
+        let wallet_guard = self.wallet.read().await;
+        let wallet = wallet_guard.as_ref()
+            .ok_or("Wallet not initialized")?;
+        
+        let mut tx = Transaction::new(
+            *wallet.address(),
+            *wallet.address(),
+            0.0,
+            0.0,
+            TransactionMetadata::with_payload(TransactionType::Validator, operation),
+        );
+        tx.sign(wallet.private_key())?;
+        Ok(tx)
+    }
+
+    /// Signer un défi de connexion ("Sign-in with Mahala") avec le wallet actif
+    ///
+    /// Le message doit désigner l'adresse du wallet sur le réseau du client
+    /// et ne pas avoir expiré.
+    /// Retourne la signature, à renvoyer au service.
+    pub async fn sign_in(&self, message: &str) -> Result<Signature, String> {
+        let message = SignInMessage::parse(message).map_err(|e| e.to_string())?;
+        
+        let wallet_guard = self.wallet.read().await;
+        let wallet = wallet_guard.as_ref()
+            .ok_or("Wallet not initialized")?;
+        
+        if &message.address != wallet.address() {
+            return Err("Sign-in message is for another address".to_string());
+        }
+        if message.network != self.config.network {
+            return Err(format!("Sign-in message is for {}, expected {}", message.network, self.config.network));
+        }
+        if message.expires_at <= chrono::Utc::now().timestamp() {
+            return Err("Sign-in message has expired".to_string());
+        }
+        
+        Ok(message.sign(wallet.private_key()))
+    }
+
     /// Synchroniser avec le réseau
     pub async fn sync(&self) -> Result<(), String> {
         // TODO: Implémenter la synchronisation avec les nœuds complets
@@ -117,14 +367,14 @@ impl LightClient {
         let wallet = wallet_guard.as_ref()
             .ok_or("Wallet not initialized")?;
         
-        let last_hash = blockchain_guard.last_block_hash()
+        // Évaluer la VRF du wallet sur la graine de l'époque du prochain bloc
+        let height = blockchain_guard.height();
+        let seed = blockchain_guard.selection_seed(height)
             .ok_or("No blocks in chain")?;
         
-        let selection = blockchain_guard.consensus().select_validators(last_hash);
-        
-        // Vérifier si ce wallet est sélectionné
         let is_selected = blockchain_guard.consensus()
-            .is_validator_selected(wallet.address(), &selection);
+            .prove_selection(wallet.address(), wallet.private_key(), &seed, height, 0)
+            .is_some();
         
         Ok(is_selected)
     }
@@ -5,7 +5,15 @@
 
 use mahala_blockchain::{Hash, PublicKey, Amount};
 use mahala_blockchain::storage::checkpoint::Checkpoint;
+use mahala_blockchain::wallet::{BiometricEnrollment, Keystore, Wallet};
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Nom du keystore chiffré du wallet dans le répertoire de l'application
+pub const WALLET_KEYSTORE_FILE: &str = "wallet_keystore.json";
+
+/// Nom de l'enrôlement biométrique dans le répertoire de l'application
+pub const BIOMETRIC_ENROLLMENT_FILE: &str = "biometric_enrollment.json";
 
 /// État local du light client
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -51,3 +59,58 @@ impl LightClientState {
     }
 }
 
+/// Chemin du keystore du wallet dans un répertoire de données
+pub fn wallet_keystore_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(WALLET_KEYSTORE_FILE)
+}
+
+/// Un wallet chiffré est-il présent sur l'appareil ?
+pub fn has_wallet(data_dir: &Path) -> bool {
+    wallet_keystore_path(data_dir).exists()
+}
+
+/// Chiffrer et sauvegarder le wallet
+pub fn save_wallet(data_dir: &Path, wallet: &Wallet, password: &str) -> Result<(), String> {
+    std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+    wallet.to_keystore(password)
+        .and_then(|keystore| keystore.save(wallet_keystore_path(data_dir)))
+        .map_err(|e| e.to_string())
+}
+
+/// Charger et déchiffrer le wallet
+///
+/// Un keystore aux paramètres obsolètes est re-chiffré au passage.
+pub fn load_wallet(data_dir: &Path, password: &str) -> Result<Wallet, String> {
+    let path = wallet_keystore_path(data_dir);
+    let keystore = Keystore::load(&path).map_err(|e| e.to_string())?;
+    let wallet = Wallet::from_keystore(&keystore, password).map_err(|e| e.to_string())?;
+    
+    if keystore.needs_upgrade() {
+        keystore.upgrade(password)
+            .and_then(|upgraded| upgraded.save(&path))
+            .map_err(|e| e.to_string())?;
+    }
+    
+    Ok(wallet)
+}
+
+/// Chemin de l'enrôlement biométrique dans un répertoire de données
+pub fn biometric_enrollment_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(BIOMETRIC_ENROLLMENT_FILE)
+}
+
+/// Un wallet biométrique est-il enrôlé sur l'appareil ?
+pub fn has_biometric_enrollment(data_dir: &Path) -> bool {
+    biometric_enrollment_path(data_dir).exists()
+}
+
+/// Sauvegarder l'enrôlement biométrique
+pub fn save_biometric_enrollment(data_dir: &Path, enrollment: &BiometricEnrollment) -> Result<(), String> {
+    std::fs::create_dir_all(data_dir).map_err(|e| e.to_string())?;
+    enrollment.save(biometric_enrollment_path(data_dir)).map_err(|e| e.to_string())
+}
+
+/// Charger l'enrôlement biométrique
+pub fn load_biometric_enrollment(data_dir: &Path) -> Result<BiometricEnrollment, String> {
+    BiometricEnrollment::load(biometric_enrollment_path(data_dir)).map_err(|e| e.to_string())
+}
@@ -4,7 +4,9 @@
 //! pour une synchronisation rapide et légère
 
 use mahala_blockchain::{Hash, Block};
+use mahala_blockchain::consensus::rvs::EpochSchedule;
 use mahala_blockchain::storage::checkpoint::Checkpoint;
+use mahala_blockchain::storage::SnapshotManifest;
 use thiserror::Error;
 
 /// Gestionnaire de synchronisation
@@ -21,8 +23,8 @@ pub enum SyncError {
     #[error("Network error: {0}")]
     Network(String),
     
-    #[error("Invalid checkpoint")]
-    InvalidCheckpoint,
+    #[error("Invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
     
     #[error("Sync failed: {0}")]
     Failed(String),
@@ -38,11 +40,18 @@ impl SyncManager {
     }
 
     /// Synchroniser depuis un checkpoint
+    ///
+    /// Le checkpoint téléchargé n'est retenu que s'il est signé par un quorum
+    /// du calendrier de son époque, connu du client.
     pub async fn sync_from_checkpoint(
         &mut self,
         checkpoint: Checkpoint,
+        schedule: &EpochSchedule,
         _full_node_url: &str,
     ) -> Result<Vec<Block>, SyncError> {
+        checkpoint.verify(schedule)
+            .map_err(|e| SyncError::InvalidCheckpoint(e.to_string()))?;
+        
         // TODO: Implémenter la synchronisation réelle avec le nœud complet
         // Pour l'instant, on simule
         
@@ -52,6 +61,27 @@ impl SyncManager {
         Ok(Vec::new())
     }
 
+    /// Synchroniser depuis le manifeste d'un instantané
+    ///
+    /// Le manifeste (`GET /blockchain/snapshot/manifest` d'un nœud complet)
+    /// porte le checkpoint, la racine d'état de ses blocs de données et les
+    /// blocs de l'époque depuis son bloc d'ancrage. Il n'est retenu que s'il
+    /// se vérifie contre le calendrier de l'époque; les blocs de données
+    /// téléchargés ensuite se vérifient avec `SnapshotManifest::verify_chunk`.
+    pub fn sync_from_snapshot(
+        &mut self,
+        manifest: SnapshotManifest,
+        schedule: &EpochSchedule,
+    ) -> Result<Vec<Block>, SyncError> {
+        manifest.verify(schedule)
+            .map_err(|e| SyncError::InvalidCheckpoint(e.to_string()))?;
+        
+        self.synced_height = manifest.height();
+        self.last_checkpoint = Some(manifest.checkpoint);
+        
+        Ok(manifest.blocks)
+    }
+
     /// Synchroniser les nouveaux blocs
     pub async fn sync_new_blocks(
         &mut self,
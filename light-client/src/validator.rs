@@ -5,6 +5,9 @@
 
 use mahala_blockchain::{PublicKey, Hash, Signature};
 use mahala_blockchain::block::Block;
+use mahala_blockchain::consensus::{VRFOutput, VRF};
+use mahala_blockchain::consensus::rvs::SelectionProof;
+pub use mahala_blockchain::consensus::rvs::ValidatorStats;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -17,19 +20,6 @@ pub struct ValidatorParticipant {
     pub stats: ValidatorStats,
 }
 
-/// Statistiques du validateur
-#[derive(Debug, Clone, Default)]
-pub struct ValidatorStats {
-    /// Nombre de fois sélectionné
-    pub times_selected: u64,
-    
-    /// Nombre de blocs signés
-    pub blocks_signed: u64,
-    
-    /// Nombre de fois absent (sélectionné mais pas signé)
-    pub absences: u64,
-}
-
 impl ValidatorParticipant {
     /// Créer un nouveau participant
     pub fn new() -> Self {
@@ -45,32 +35,51 @@ impl ValidatorParticipant {
     }
 
     /// Signer un bloc si sélectionné
+    ///
+    /// `selection` est la preuve obtenue par `RVS::prove_selection` pour la
+    /// hauteur et le round de validation du bloc; elle accompagne la signature.
     pub async fn sign_block_if_selected(
         &mut self,
         block: &mut Block,
         private_key: &mahala_blockchain::PrivateKey,
+        selection: Option<&SelectionProof>,
     ) -> Result<bool, String> {
         let Some(public_key) = self.public_key else {
             return Ok(false);
         };
 
         // Vérifier si ce validateur est dans la sélection
-        // (Cette vérification devrait être faite avant d'appeler cette fonction)
+        let Some(selection) = selection.filter(|s| s.validator == public_key) else {
+            return Ok(false);
+        };
         
         // Calculer le hash du bloc
         let hash = block.calculate_hash();
         
-        // Signer le hash
+        // Signer le précommit du bloc (hauteur, round, hash)
         use mahala_blockchain::crypto::sign;
-        let signature = sign(&hash, private_key);
+        let signature = sign(&block.commit_signing_hash(&hash), private_key);
         
         // Ajouter la signature au bloc
-        block.add_validator_signature(public_key, signature);
+        block.add_validator_signature(public_key, signature, selection.proof);
         
         self.stats.blocks_signed += 1;
         Ok(true)
     }
 
+    /// Calculer la sortie VRF du validateur et sa preuve pour un message
+    ///
+    /// La preuve est publiée avec la sortie; tout nœud peut la vérifier avec
+    /// la clé publique (`VRF::verify`).
+    pub fn evaluate_vrf(
+        &self,
+        private_key: &mahala_blockchain::PrivateKey,
+        message: &[u8],
+    ) -> Option<VRFOutput> {
+        self.public_key?;
+        Some(VRF::generate(private_key, message))
+    }
+
     /// Mettre à jour les statistiques
     pub fn update_stats(&mut self, selected: bool, signed: bool) {
         if selected {
@@ -27,6 +27,62 @@ local function fetch_nft_from_blockchain(nft_id)
     }
 end
 
+-- Libellés des événements de provenance
+local EVENT_LABELS = {
+    Mint = "Création",
+    List = "Mise en vente",
+    Delist = "Retrait de la vente",
+    Sale = "Vente",
+    Transfer = "Transfert",
+    Burn = "Destruction"
+}
+
+-- Raccourcir une adresse pour l'affichage
+local function short_address(address)
+    if not address then
+        return "-"
+    end
+    return string.sub(address, 1, 8) .. "..."
+end
+
+-- Récupérer l'historique de provenance d'un NFT
+local function fetch_nft_history(nft_id, callback)
+    http.fetch({
+        url = MAHALA_API .. "/nft/" .. nft_id .. "/history",
+        method = "GET",
+        timeout = 10
+    }, function(result)
+        if result.succeeded and result.code == 200 then
+            local data = minetest.parse_json(result.data)
+            callback(data and data.events)
+        else
+            callback(nil)
+        end
+    end)
+end
+
+-- Afficher la provenance d'un NFT
+local function show_nft_history(player_name, nft_id, events)
+    local formspec = "size[10,8]" ..
+        "label[0.5,0.5;Provenance du NFT " .. minetest.formspec_escape(nft_id) .. "]"
+    
+    local rows = {}
+    for _, event in ipairs(events) do
+        local row = "#" .. event.block_height .. " " ..
+            (EVENT_LABELS[event.kind] or event.kind) .. " " ..
+            short_address(event.from) .. " -> " .. short_address(event.to)
+        if event.price then
+            row = row .. " (" .. event.price .. " MHL)"
+        end
+        table.insert(rows, minetest.formspec_escape(row))
+    end
+    
+    formspec = formspec ..
+        "textlist[0.5,1.2;9,6.5;history;" .. table.concat(rows, ",") .. "]"
+    
+    minetest.show_formspec(player_name, "mahala:nft_history_" .. nft_id, formspec)
+end
+
 -- Afficher un NFT dans le monde
 local function display_nft_in_world(pos, nft_id)
     local nft = fetch_nft_from_blockchain(nft_id)
@@ -130,6 +186,27 @@ minetest.register_chatcommand("place_nft", {
     end
 })
 
+-- Commande pour afficher la provenance d'un NFT
+minetest.register_chatcommand("nft_history", {
+    params = "<nft_id>",
+    description = "Afficher l'historique de provenance d'un NFT",
+    func = function(name, param)
+        if param == "" then
+            return false, "Usage: /nft_history <nft_id>"
+        end
+        
+        fetch_nft_history(param, function(events)
+            if events then
+                show_nft_history(name, param, events)
+            else
+                minetest.chat_send_player(name, "NFT introuvable: " .. param)
+            end
+        end)
+        
+        return true, "Récupération de l'historique..."
+    end
+})
+
 -- Commande pour lister ses NFT
 minetest.register_chatcommand("my_nfts", {
     description = "Lister vos NFT",
@@ -58,6 +58,39 @@ local function get_world_wallet(world_name)
     return minetest.settings:get("mahala_wallet_address") or ""
 end
 
+-- Valider une adresse Mahala (bech32m "mhl1..." ou hex) auprès du nœud
+-- Retourne la forme hex canonique, ou nil et un message d'erreur
+local function validate_wallet_address(address)
+    local response = http.fetch({
+        url = MAHALA_API .. "/address/" .. address,
+        method = "GET",
+        timeout = 5
+    })
+    
+    if response and response.data then
+        local data = minetest.parse_json(response.data)
+        if data and data.valid then
+            return data.hex
+        elseif data and data.error then
+            local positions = data.error_positions or {}
+            if #positions > 0 then
+                local marks = {}
+                for _, pos in ipairs(positions) do
+                    table.insert(marks, tostring(pos + 1))
+                end
+                return nil, "Adresse invalide (caractère(s) suspect(s) en position " .. table.concat(marks, ", ") .. ")"
+            end
+            return nil, "Adresse invalide: " .. data.error
+        end
+    end
+    
+    -- Nœud injoignable: seul le format hex peut être vérifié localement
+    if string.len(address) == 64 and string.match(address, "^[0-9a-fA-F]+$") then
+        return string.lower(address)
+    end
+    return nil, "Impossible de valider l'adresse (nœud Mahala injoignable)"
+end
+
 -- Vérifier la balance Mahala
 local function check_mahala_balance(wallet_address)
     local url = MAHALA_API .. "/blockchain/balance/" .. wallet_address
@@ -262,13 +295,14 @@ minetest.register_chatcommand("wallet", {
             end
         end
         
-        -- Valider le format de l'adresse (hex 64 caractères)
-        if string.len(param) ~= 64 or not string.match(param, "^[0-9a-fA-F]+$") then
-            return false, "Adresse invalide. Format attendu: 64 caractères hexadécimaux"
+        -- Valider l'adresse (somme de contrôle bech32m ou hex 64 caractères)
+        local address, err = validate_wallet_address(param)
+        if not address then
+            return false, err
         end
         
-        set_player_wallet(name, param)
-        return true, "Wallet configuré: " .. string.sub(param, 1, 20) .. "..."
+        set_player_wallet(name, address)
+        return true, "Wallet configuré: " .. string.sub(address, 1, 20) .. "..."
     end
 })
 